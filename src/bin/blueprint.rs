@@ -0,0 +1,224 @@
+//! Command-line tools for `.gmb` blueprint files.
+//!
+//! To let git merge blueprints feature by feature, add to `.gitattributes`:
+//!
+//! ```text
+//! *.gmb merge=grackle
+//! ```
+//!
+//! and register the driver:
+//!
+//! ```text
+//! git config merge.grackle.name "Grackle blueprint merge"
+//! git config merge.grackle.driver "blueprint merge %O %A %B"
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use clap::{Arg, ArgAction, Command};
+use grackle::editor::balance;
+use grackle::editor::diff;
+use grackle::editor::logic::check_connections;
+use grackle::editor::map_naming::{check_file_name, map_name, suggested_file_name};
+use grackle::constants::MAP_BLUEPRINT_EXTENSION;
+use grackle::editor::save::{self, LoadedBlueprint};
+
+fn main() -> ExitCode {
+    let matches = Command::new("blueprint")
+        .about("Inspect, merge and analyze Grackle blueprint files.")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("diff")
+                .about("List features added, removed or modified between two blueprints.")
+                .arg(Arg::new("old").required(true))
+                .arg(Arg::new("new").required(true))
+        )
+        .subcommand(
+            Command::new("merge")
+                .about("Three-way merge; writes the result over OURS. Usable as a git merge driver.")
+                .arg(Arg::new("base").required(true).help("Common ancestor (%O)"))
+                .arg(Arg::new("ours").required(true).help("Current version, overwritten with the result (%A)"))
+                .arg(Arg::new("theirs").required(true).help("Other branch's version (%B)"))
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Write the result here instead of over OURS.")
+                )
+        )
+        .subcommand(
+            Command::new("balance")
+                .about("Report per-team travel times from spawns to the game mode's objectives and chokepoints, and the pickups in each team's half.")
+                .arg(Arg::new("file").required(true))
+                .arg(
+                    Arg::new("run-speed")
+                        .long("run-speed")
+                        .value_parser(clap::value_parser!(f32))
+                        .default_value("8")
+                        .help("Player run speed in units per second.")
+                )
+                .arg(
+                    Arg::new("max-asymmetry")
+                        .long("max-asymmetry")
+                        .value_parser(clap::value_parser!(f32))
+                        .help("Fail when the teams' travel times differ by more than this many seconds.")
+                )
+        )
+        .subcommand(
+            Command::new("names")
+                .about("Check that every blueprint in a directory is named <prefix>_<name>.gmb for its game mode.")
+                .arg(Arg::new("dir").required(true))
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .action(ArgAction::SetTrue)
+                        .help("Rename misnamed files to the suggested name.")
+                )
+        )
+        .subcommand(
+            Command::new("logic")
+                .about("List the map's output-to-input wiring and any connections that cannot fire.")
+                .arg(Arg::new("file").required(true))
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        Some(("diff", args)) => run_diff(
+            args.get_one::<String>("old").unwrap(),
+            args.get_one::<String>("new").unwrap(),
+        ),
+        Some(("merge", args)) => run_merge(
+            args.get_one::<String>("base").unwrap(),
+            args.get_one::<String>("ours").unwrap(),
+            args.get_one::<String>("theirs").unwrap(),
+            args.get_one::<String>("output"),
+        ),
+        Some(("balance", args)) => run_balance(
+            args.get_one::<String>("file").unwrap(),
+            *args.get_one::<f32>("run-speed").unwrap(),
+            args.get_one::<f32>("max-asymmetry").copied(),
+        ),
+        Some(("names", args)) => run_names(
+            args.get_one::<String>("dir").unwrap(),
+            args.get_flag("fix"),
+        ),
+        Some(("logic", args)) => run_logic(args.get_one::<String>("file").unwrap()),
+        _ => unreachable!("subcommand is required"),
+    };
+
+    result.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        ExitCode::from(2)
+    })
+}
+
+fn load(path: &str) -> Result<LoadedBlueprint, String> {
+    save::load_read_only(Path::new(path)).map_err(|e| format!("Failed to load {}: {}", path, e))
+}
+
+/// Exit status follows `diff(1)`: 0 when identical, 1 when different.
+fn run_diff(old: &str, new: &str) -> Result<ExitCode, String> {
+    let changes = diff::diff(&load(old)?, &load(new)?);
+    if changes.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    print!("{}", changes);
+    Ok(ExitCode::from(1))
+}
+
+/// Exit status is 0 for a clean merge and 1 when conflicts were resolved in favour of ours.
+fn run_merge(base: &str, ours: &str, theirs: &str, output: Option<&String>) -> Result<ExitCode, String> {
+    let result = diff::merge(&load(base)?, &load(ours)?, &load(theirs)?);
+    let output = output.map(PathBuf::from).unwrap_or_else(|| PathBuf::from(ours));
+    // Neither side's baked navmesh matches the merged geometry, so bake it afresh.
+    let timeline = &result.blueprint.timeline;
+    save::save(&output, timeline, &result.blueprint.metadata, &save::bake_navmesh(timeline))
+        .map_err(|e| format!("Failed to write {:?}: {}", output, e))?;
+    if result.dropped_history > 0 {
+        eprintln!("Dropped {} undo step(s) from ours; the merged blueprint starts with no undo history.", result.dropped_history);
+    }
+
+    if result.conflicts.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    eprintln!("{} conflict(s), kept ours:", result.conflicts.len());
+    for conflict in &result.conflicts {
+        eprintln!("  {}", conflict);
+    }
+    Ok(ExitCode::from(1))
+}
+
+/// Exit status is 1 when the largest asymmetry exceeds `max_asymmetry` seconds.
+fn run_balance(file: &str, run_speed: f32, max_asymmetry: Option<f32>) -> Result<ExitCode, String> {
+    let blueprint = load(file)?;
+    let report = balance::analyze_timeline(&blueprint.timeline, &blueprint.metadata, run_speed);
+    print!("{}", report);
+    match max_asymmetry {
+        Some(limit) if report.max_asymmetry_seconds() > limit => {
+            eprintln!("asymmetry of {:.1} s exceeds {:.1} s", report.max_asymmetry_seconds(), limit);
+            Ok(ExitCode::from(1))
+        }
+        _ => Ok(ExitCode::SUCCESS),
+    }
+}
+
+/// Exit status is 1 when any blueprint is still misnamed or failed to load.
+fn run_names(dir: &str, fix: bool) -> Result<ExitCode, String> {
+    let dir = Path::new(dir);
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == MAP_BLUEPRINT_EXTENSION))
+        .collect();
+    paths.sort();
+
+    let mut problems = 0;
+    for path in paths {
+        let blueprint = match save::load_read_only(&path) {
+            Ok(blueprint) => blueprint,
+            Err(e) => {
+                println!("{}: failed to load: {}", path.display(), e);
+                problems += 1;
+                continue;
+            }
+        };
+        let game_mode = blueprint.metadata.game_mode;
+        let Some(issue) = check_file_name(&path, game_mode) else { continue };
+        let suggested = dir.join(suggested_file_name(game_mode, map_name(&path).unwrap_or_default()));
+        if !fix {
+            println!("{}: {}; suggest {}", path.display(), issue, suggested.display());
+            problems += 1;
+        } else if suggested.exists() {
+            println!("{}: {}; not renamed, {} already exists", path.display(), issue, suggested.display());
+            problems += 1;
+        } else {
+            std::fs::rename(&path, &suggested)
+                .map_err(|e| format!("Failed to rename {}: {}", path.display(), e))?;
+            println!("{}: {}; renamed to {}", path.display(), issue, suggested.display());
+        }
+    }
+
+    Ok(if problems == 0 { ExitCode::SUCCESS } else { ExitCode::from(1) })
+}
+
+/// Exit status is 1 when any connection is broken.
+fn run_logic(file: &str) -> Result<ExitCode, String> {
+    let blueprint = load(file)?;
+    let timeline = &blueprint.timeline;
+    let type_key = |id| timeline.get_feature(&id).map_or("?", |f| f.object().type_key());
+    let (wiring, issues) = check_connections(timeline);
+    for (source, c) in &wiring {
+        println!(
+            "#{} {} {} -> #{} {} {} after {} s",
+            source, type_key(*source), c.output, c.target, type_key(c.target), c.input, c.delay,
+        );
+    }
+    if issues.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    eprintln!("{} broken connection(s):", issues.len());
+    for issue in &issues {
+        eprintln!("  {}", issue);
+    }
+    Ok(ExitCode::from(1))
+}
@@ -1,3 +1,3 @@
-pub const SCHEMA_VERSION: u64 = 3;
+pub const SCHEMA_VERSION: u64 = 11;
 pub const MAP_BLUEPRINT_EXTENSION: &str = "gmb";
 pub const MAP_BACKUP_EXTENSION: &str = "bak";
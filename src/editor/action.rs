@@ -4,7 +4,23 @@ use serde::{Deserialize, Serialize};
 use crate::editor::editable::{Feature, FeatureId, FeatureTrait, PointRef};
 use crate::editor::editor_room::EditorRoom;
 use crate::editor::global_point::GlobalPoint;
+use crate::editor::grackle_area_light::GrackleAreaLight;
 use crate::editor::grackle_point_light::GracklePointLight;
+use crate::editor::grackle_spot_light::GrackleSpotLight;
+use crate::editor::grackle_sun::GrackleSun;
+use crate::editor::jump_pad::JumpPad;
+use crate::editor::liquid_volume::{LiquidVolume, VolumeKind};
+use crate::editor::logic::LogicConnection;
+use crate::editor::logic_entity::{LogicEntity, LogicKind};
+use crate::editor::map_marker::{MapMarker, MarkerKind, Team};
+use crate::editor::pickup::{Pickup, PickupKind};
+use crate::editor::prop::Prop;
+use crate::editor::stairs::Stairs;
+use crate::editor::symmetry::SymmetrySettings;
+use crate::editor::dimension::Dimension;
+use crate::editor::note::{Note, NoteStatus};
+use crate::editor::teleporter::Teleporter;
+use crate::editor::trigger_volume::TriggerVolume;
 use crate::common::cuboid::GrackleCuboid;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
@@ -14,10 +30,37 @@ pub enum FeatureData {
     },
     PointLight {
         location: PointRef,
+        color: Vec3,
         intensity: f32,
         radius: f32,
         range: f32,
     },
+    SpotLight {
+        location: PointRef,
+        target: PointRef,
+        color: Vec3,
+        intensity: f32,
+        radius: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+    Sun {
+        location: PointRef,
+        color: Vec3,
+        illuminance: f32,
+        azimuth: f32,
+        elevation: f32,
+    },
+    AreaLight {
+        location: PointRef,
+        target: PointRef,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+        width: f32,
+        height: f32,
+    },
     Room {
         min: PointRef,
         max: PointRef,
@@ -26,6 +69,80 @@ pub enum FeatureData {
         min: Vec3,
         max: Vec3,
     },
+    Marker {
+        location: PointRef,
+        kind: MarkerKind,
+        team: Team,
+        connections: Vec<LogicConnection>,
+    },
+    Trigger {
+        min: PointRef,
+        max: PointRef,
+        start_enabled: bool,
+        once: bool,
+        connections: Vec<LogicConnection>,
+    },
+    Logic {
+        location: PointRef,
+        kind: LogicKind,
+        value: f32,
+        start_enabled: bool,
+        connections: Vec<LogicConnection>,
+    },
+    Teleporter {
+        min: PointRef,
+        max: PointRef,
+        destination: PointRef,
+        yaw: f32,
+    },
+    JumpPad {
+        min: PointRef,
+        max: PointRef,
+        apex: PointRef,
+    },
+    Pickup {
+        location: PointRef,
+        kind: PickupKind,
+        item: String,
+        respawn: f32,
+        team: Team,
+    },
+    Prop {
+        location: PointRef,
+        model: String,
+        rotation: Vec3,
+        scale: Vec3,
+        collision: bool,
+    },
+    Liquid {
+        min: PointRef,
+        max: PointRef,
+        kind: VolumeKind,
+        damage: f32,
+        speed_scale: f32,
+        buoyancy: f32,
+        tint: Vec3,
+        opacity: f32,
+    },
+    Stairs {
+        start: PointRef,
+        end: PointRef,
+        width: f32,
+        step_height: f32,
+        ramp: bool,
+    },
+    Dimension {
+        start: PointRef,
+        end: PointRef,
+        note: String,
+    },
+    Note {
+        location: PointRef,
+        text: String,
+        author: String,
+        status: NoteStatus,
+        color: Vec3,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
@@ -44,15 +161,63 @@ impl FeatureSnapshot {
         }
     }
 
+    /// Snapshot for data that has no live feature yet; parents are derived from its point references.
+    pub fn from_data(data: FeatureData, order_index: usize) -> Self {
+        let mut snapshot = Self { data, parents: vec![], order_index };
+        snapshot.parents = snapshot.blank_object().parent_ids();
+        snapshot
+    }
+
     pub fn blank_object(&self) -> Box<dyn FeatureTrait> {
         let mut object: Box<dyn FeatureTrait> = match &self.data {
             FeatureData::GlobalPoint { .. } => Box::new(GlobalPoint::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0))),
             FeatureData::PointLight { .. } => Box::new(GracklePointLight::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0))),
+            FeatureData::SpotLight { .. } => Box::new(GrackleSpotLight::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
+            FeatureData::Sun { .. } => Box::new(GrackleSun::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0))),
+            FeatureData::AreaLight { .. } => Box::new(GrackleAreaLight::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
             FeatureData::Room { .. } => Box::new(EditorRoom::from_point_refs(
                 PointRef::absolute(0.0, 0.0, 0.0),
                 PointRef::absolute(0.0, 0.0, 0.0),
             )),
             FeatureData::Cuboid { .. } => Box::new(GrackleCuboid::new(Vec3::ZERO, Vec3::ZERO)),
+            FeatureData::Marker { .. } => Box::new(MapMarker::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0), MarkerKind::Spawn, Team::Neutral)),
+            FeatureData::Trigger { .. } => Box::new(TriggerVolume::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
+            FeatureData::Logic { .. } => Box::new(LogicEntity::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0), LogicKind::Relay)),
+            FeatureData::Teleporter { .. } => Box::new(Teleporter::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
+            FeatureData::JumpPad { .. } => Box::new(JumpPad::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
+            FeatureData::Pickup { .. } => Box::new(Pickup::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0), PickupKind::HealthSmall)),
+            FeatureData::Prop { .. } => Box::new(Prop::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0), "")),
+            FeatureData::Liquid { .. } => Box::new(LiquidVolume::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+                VolumeKind::Water,
+            )),
+            FeatureData::Stairs { .. } => Box::new(Stairs::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
+            FeatureData::Dimension { .. } => Box::new(Dimension::from_point_refs(
+                PointRef::absolute(0.0, 0.0, 0.0),
+                PointRef::absolute(0.0, 0.0, 0.0),
+            )),
+            FeatureData::Note { .. } => Box::new(Note::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0), "")),
         };
 
         object.apply_snapshot(&self.data);
@@ -67,17 +232,39 @@ pub struct FeatureDelta {
     pub after: Option<FeatureSnapshot>,
 }
 
+/// Symmetry settings from before and after an action that changed them.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SymmetryChange {
+    pub before: SymmetrySettings,
+    pub after: SymmetrySettings,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Action {
     pub deltas: Vec<FeatureDelta>,
+    pub symmetry: Option<SymmetryChange>,
 }
 
-fn feature_data_kind(data: &FeatureData) -> &'static str {
+pub(crate) fn feature_data_kind(data: &FeatureData) -> &'static str {
     match data {
         FeatureData::GlobalPoint { .. } => "Global Point",
         FeatureData::PointLight { .. } => "Point Light",
+        FeatureData::SpotLight { .. } => "Spot Light",
+        FeatureData::Sun { .. } => "Sun",
+        FeatureData::AreaLight { .. } => "Area Light",
         FeatureData::Room { .. } => "Room",
         FeatureData::Cuboid { .. } => "Cuboid",
+        FeatureData::Marker { .. } => "Marker",
+        FeatureData::Trigger { .. } => "Trigger",
+        FeatureData::Logic { .. } => "Logic",
+        FeatureData::Teleporter { .. } => "Teleporter",
+        FeatureData::JumpPad { .. } => "Jump Pad",
+        FeatureData::Pickup { .. } => "Pickup",
+        FeatureData::Prop { .. } => "Prop",
+        FeatureData::Liquid { .. } => "Liquid",
+        FeatureData::Stairs { .. } => "Stairs",
+        FeatureData::Dimension { .. } => "Dimension",
+        FeatureData::Note { .. } => "Note",
     }
 }
 
@@ -107,40 +294,45 @@ impl FeatureDelta {
 
 impl Action {
     pub fn label(&self) -> String {
-        if self.deltas.len() == 1 {
+        if self.symmetry.is_some() {
+            "Symmetry settings".to_owned()
+        } else if self.deltas.len() == 1 {
             self.deltas[0].label()
         } else {
             format!("{} changes", self.deltas.len())
         }
     }
 
-    /// If `next` is a single-delta edit to the same feature and its `before` snapshot matches
+    /// If `next` is an edit to the same feature and its `before` snapshot matches
     /// this action's `after` (including create-then-modify: create `after` == modify `before`),
     /// folds `next` into `self` by updating `self`'s `after` only. Returns `true` when merged.
     ///
     /// Does not merge create-then-delete into a single delta (would be ambiguous for undo).
+    ///
+    /// Multi-delta actions (e.g. an edit plus its mirrored counterpart) coalesce when every delta
+    /// lines up pairwise under the same rule.
     pub fn try_coalesce_incoming(&mut self, next: &Action) -> bool {
-        if self.deltas.len() != 1 || next.deltas.len() != 1 {
+        if self.symmetry.is_some() || next.symmetry.is_some() {
             return false;
         }
-        let a = &mut self.deltas[0];
-        let b = &next.deltas[0];
-        if a.feature_id != b.feature_id {
+        if self.deltas.is_empty() || self.deltas.len() != next.deltas.len() {
             return false;
         }
-        let Some(before_b) = &b.before else {
-            return false;
-        };
-        let Some(after_a) = &a.after else {
-            return false;
-        };
-        if after_a != before_b {
+        let lines_up = self.deltas.iter().zip(next.deltas.iter()).all(|(a, b)| {
+            if a.feature_id != b.feature_id {
+                return false;
+            }
+            let (Some(before_b), Some(after_a)) = (&b.before, &a.after) else {
+                return false;
+            };
+            after_a == before_b && !(a.before.is_none() && b.after.is_none())
+        });
+        if !lines_up {
             return false;
         }
-        if a.before.is_none() && b.after.is_none() {
-            return false;
+        for (a, b) in self.deltas.iter_mut().zip(next.deltas.iter()) {
+            a.after = b.after.clone();
         }
-        a.after = b.after.clone();
         true
     }
 }
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use crate::editor::editable::{AxisRef, FeatureId, FeatureTimeline, PointRef};
+use crate::editor::transform::{retarget_point_ref, CapturedSelection};
+use crate::get;
+
+const AXIS_NAMES: [&str; 3] = ["X", "Y", "Z"];
+
+/// Which side of the active feature's extent the others line up with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignEdge {
+    Min,
+    Center,
+    Max,
+}
+
+impl AlignEdge {
+    fn of(&self, (min, max): (Vec3, Vec3), axis: usize) -> f32 {
+        match self {
+            AlignEdge::Min => min[axis],
+            AlignEdge::Center => (min[axis] + max[axis]) / 2.0,
+            AlignEdge::Max => max[axis],
+        }
+    }
+
+    /// The point a relative result references on the active feature.
+    fn point_key(&self) -> &'static str {
+        match self {
+            AlignEdge::Min => "min",
+            AlignEdge::Center => "",
+            AlignEdge::Max => "max",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignError {
+    /// The operation needs at least this many features selected.
+    TooFewSelected(usize),
+    NotARoom,
+}
+
+impl AlignError {
+    pub fn message(&self) -> String {
+        match self {
+            AlignError::TooFewSelected(count) => get!("editor.align.too_few", "count", count),
+            AlignError::NotARoom => get!("editor.align.not_a_room"),
+        }
+    }
+}
+
+/// Axis and result mode chosen in the panel.
+#[derive(Default)]
+pub struct AlignState {
+    pub axis: usize,
+    /// Reference the anchor feature instead of writing absolute values, so later edits to it carry over.
+    pub relative: bool,
+    pub error: Option<AlignError>,
+}
+
+/// Extent of a feature: its selection bounds, or its position for features without a size.
+fn extent(timeline: &FeatureTimeline, id: FeatureId) -> Option<(Vec3, Vec3)> {
+    let feature = timeline.get_feature(&id)?;
+    feature.object().selection_bounds()
+        .or_else(|| feature.get_point("").ok().map(|p| (p, p)))
+}
+
+/// The active selected features with their extents, the active feature first.
+fn selected_extents(timeline: &FeatureTimeline) -> Vec<(FeatureId, (Vec3, Vec3))> {
+    let active: HashSet<FeatureId> = timeline.active_features().map(|(id, _)| id).collect();
+    timeline.selected_features().into_iter()
+        .filter(|id| active.contains(id))
+        .filter_map(|id| extent(timeline, id).map(|e| (id, e)))
+        .collect()
+}
+
+/// `original` moved to `target`, with `axis` relative to `anchor`'s `key` point at `anchor_point`.
+/// The other axes keep their position; they lose any other reference, as a point has only one.
+fn relative_to_anchor(original: &PointRef, target: Vec3, axis: usize, anchor: FeatureId, key: &str, anchor_point: Vec3) -> PointRef {
+    let mut pr = if original.reference == Some(anchor) && original.point_key == key {
+        original.clone()
+    } else {
+        PointRef {
+            reference: Some(anchor),
+            point_key: key.to_owned(),
+            ..PointRef::absolute(target.x, target.y, target.z)
+        }
+    };
+    let offset = AxisRef::Relative(target[axis] - anchor_point[axis]);
+    match axis {
+        0 => pr.x = offset,
+        1 => pr.y = offset,
+        _ => pr.z = offset,
+    }
+    pr.resolved_reference = None;
+    pr
+}
+
+/// Move each feature in `offsets` that far along `axis`, as one action. With an `anchor`, every
+/// moved feature after it in the timeline references it on that axis; the rest get plain values.
+fn move_along_axis(timeline: &mut FeatureTimeline, axis: usize, offsets: &HashMap<FeatureId, f32>, anchor: Option<(FeatureId, &str)>) {
+    let anchor = anchor.and_then(|(id, key)| {
+        let feature = timeline.get_feature(&id)?;
+        let (key, point) = match feature.get_point(key) {
+            Ok(point) => (key, point),
+            Err(_) => ("", feature.get_point("").ok()?),
+        };
+        Some((id, key, point))
+    });
+    let can_reference: HashSet<FeatureId> = match anchor {
+        Some((anchor_id, ..)) => timeline.feature_order().iter()
+            .skip_while(|id| **id != anchor_id)
+            .skip(1)
+            .copied()
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    let ids: Vec<FeatureId> = offsets.keys().copied().collect();
+    let captured = CapturedSelection::capture(timeline, &ids);
+    captured.apply_with(timeline, |id, original, world, base| {
+        let mut target = world;
+        target[axis] += offsets.get(&id).copied().unwrap_or_default();
+        match anchor {
+            Some((anchor_id, key, point)) if can_reference.contains(&id) => {
+                relative_to_anchor(original, target, axis, anchor_id, key, point)
+            }
+            _ => retarget_point_ref(original, target, base),
+        }
+    });
+    captured.commit(timeline);
+}
+
+/// Line every selected feature up with `edge` of the active feature along `axis`.
+pub fn align(timeline: &mut FeatureTimeline, axis: usize, edge: AlignEdge, relative: bool) -> Result<(), AlignError> {
+    let extents = selected_extents(timeline);
+    let [(active, active_extent), rest @ ..] = extents.as_slice() else { return Err(AlignError::TooFewSelected(2)); };
+    if rest.is_empty() {
+        return Err(AlignError::TooFewSelected(2));
+    }
+    let target = edge.of(*active_extent, axis);
+    let offsets = rest.iter()
+        .map(|(id, e)| (*id, target - edge.of(*e, axis)))
+        .collect();
+    move_along_axis(timeline, axis, &offsets, relative.then_some((*active, edge.point_key())));
+    Ok(())
+}
+
+/// Space the selected features evenly along `axis`, keeping the two outermost in place. Gaps
+/// between neighbours come out equal, so features of different sizes still read as a tidy row.
+pub fn distribute(timeline: &mut FeatureTimeline, axis: usize, relative: bool) -> Result<(), AlignError> {
+    let mut extents = selected_extents(timeline);
+    if extents.len() < 3 {
+        return Err(AlignError::TooFewSelected(3));
+    }
+    let center = |(min, max): (Vec3, Vec3)| (min[axis] + max[axis]) / 2.0;
+    extents.sort_by(|a, b| center(a.1).total_cmp(&center(b.1)));
+
+    let size = |(min, max): (Vec3, Vec3)| max[axis] - min[axis];
+    let (first, last) = (extents[0], extents[extents.len() - 1]);
+    let occupied: f32 = extents.iter().map(|(_, e)| size(*e)).sum();
+    let gap = (last.1.1[axis] - first.1.0[axis] - occupied) / (extents.len() - 1) as f32;
+
+    let mut cursor = first.1.1[axis] + gap;
+    let mut offsets = HashMap::new();
+    for (id, e) in &extents[1..extents.len() - 1] {
+        offsets.insert(*id, cursor - e.0[axis]);
+        cursor += size(*e) + gap;
+    }
+    move_along_axis(timeline, axis, &offsets, relative.then_some((first.0, "")));
+    Ok(())
+}
+
+/// Give every selected room the active room's size along `axis`, growing or shrinking from its min side.
+pub fn match_size(timeline: &mut FeatureTimeline, axis: usize) -> Result<(), AlignError> {
+    let extents = selected_extents(timeline);
+    if extents.len() < 2 {
+        return Err(AlignError::TooFewSelected(2));
+    }
+    let is_room = |id: &FeatureId| timeline.get_feature(id).is_some_and(|f| f.object().type_key() == "editor_room");
+    if !extents.iter().all(|(id, _)| is_room(id)) {
+        return Err(AlignError::NotARoom);
+    }
+
+    let size = |(min, max): (Vec3, Vec3)| max[axis] - min[axis];
+    let target = size(extents[0].1);
+    let bounds: HashMap<FeatureId, (Vec3, Vec3)> = extents[1..].iter().copied().collect();
+    let ids: Vec<FeatureId> = bounds.keys().copied().collect();
+    let captured = CapturedSelection::capture(timeline, &ids);
+    captured.apply_with(timeline, |id, original, world, base| {
+        let mut target_point = world;
+        if let Some(&(min, max)) = bounds.get(&id)
+            && world[axis] - min[axis] > max[axis] - world[axis]
+        {
+            target_point[axis] += target - size((min, max));
+        }
+        retarget_point_ref(original, target_point, base)
+    });
+    captured.commit(timeline);
+    Ok(())
+}
+
+/// Align, distribute and match-size controls for the current selection.
+pub fn align_panel_ui(ui: &mut egui::Ui, state: &mut AlignState, timeline: &mut FeatureTimeline) {
+    let count = selected_extents(timeline).len();
+    ui.label(get!("editor.align.title"));
+    if count < 2 {
+        ui.weak(get!("editor.align.hint"));
+    }
+
+    ui.horizontal(|ui| {
+        for (axis, name) in AXIS_NAMES.iter().enumerate() {
+            ui.selectable_value(&mut state.axis, axis, *name);
+        }
+        ui.checkbox(&mut state.relative, get!("editor.align.relative"));
+    });
+
+    let mut result = None;
+    ui.add_enabled_ui(count >= 2, |ui| {
+        ui.horizontal(|ui| {
+            for (edge, key) in [
+                (AlignEdge::Min, "editor.align.min"),
+                (AlignEdge::Center, "editor.align.center"),
+                (AlignEdge::Max, "editor.align.max"),
+            ] {
+                if ui.button(get!(key)).clicked() {
+                    result = Some(align(timeline, state.axis, edge, state.relative));
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.add_enabled(count >= 3, egui::Button::new(get!("editor.align.distribute"))).clicked() {
+                result = Some(distribute(timeline, state.axis, state.relative));
+            }
+            if ui.button(get!("editor.align.match_size")).clicked() {
+                result = Some(match_size(timeline, state.axis));
+            }
+        });
+    });
+
+    if let Some(result) = result {
+        state.error = result.err();
+    }
+    if let Some(error) = state.error {
+        ui.colored_label(egui::Color32::LIGHT_RED, error.message());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editor_room::EditorRoom;
+    use crate::editor::global_point::GlobalPoint;
+
+    fn add_room(timeline: &mut FeatureTimeline, min: Vec3, max: Vec3) -> FeatureId {
+        timeline.apply_feature(Box::new(EditorRoom::from_point_refs(
+            PointRef::absolute(min.x, min.y, min.z),
+            PointRef::absolute(max.x, max.y, max.z),
+        )))
+    }
+
+    #[test]
+    fn test_align_relative_follows_the_active_feature() {
+        let mut timeline = FeatureTimeline::default();
+        let active = add_room(&mut timeline, Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0));
+        let other = add_room(&mut timeline, Vec3::new(10.0, 1.0, 2.0), Vec3::new(12.0, 2.0, 3.0));
+        let point = timeline.apply_feature(Box::new(GlobalPoint::from_point_ref(PointRef::absolute(20.0, 5.0, 5.0))));
+        let on_other = timeline.apply_feature(Box::new(GlobalPoint::from_point_ref(PointRef::reference_with_offset(other, 0.0, 1.0, 0.0))));
+        timeline.resolve_all_references();
+        timeline.select(Some(active));
+        timeline.toggle_selected(other);
+        timeline.toggle_selected(point);
+        let actions = timeline.applied_actions().len();
+
+        align(&mut timeline, 1, AlignEdge::Max, true).unwrap();
+        assert_eq!(timeline.applied_actions().len(), actions + 1);
+        assert_eq!(extent(&timeline, other), Some((Vec3::new(10.0, 2.0, 2.0), Vec3::new(12.0, 3.0, 3.0))));
+        assert_eq!(timeline.get_feature(&point).unwrap().get_point("").ok(), Some(Vec3::new(20.0, 3.0, 5.0)));
+        assert!(timeline.get_feature(&other).unwrap().parents().contains(&active));
+        // Unselected dependants follow without anything else resolving the timeline.
+        assert_eq!(timeline.get_feature(&on_other).unwrap().get_point("").ok(), Some(Vec3::new(11.0, 3.5, 2.5)));
+
+        timeline.undo_action();
+        timeline.resolve_all_references();
+        assert_eq!(extent(&timeline, other), Some((Vec3::new(10.0, 1.0, 2.0), Vec3::new(12.0, 2.0, 3.0))));
+    }
+
+    #[test]
+    fn test_distribute_and_match_size() {
+        let mut timeline = FeatureTimeline::default();
+        let a = add_room(&mut timeline, Vec3::ZERO, Vec3::new(2.0, 3.0, 2.0));
+        let b = add_room(&mut timeline, Vec3::new(3.0, 0.0, 0.0), Vec3::new(7.0, 3.0, 2.0));
+        let c = add_room(&mut timeline, Vec3::new(20.0, 0.0, 0.0), Vec3::new(22.0, 1.0, 2.0));
+        timeline.resolve_all_references();
+        timeline.select(Some(a));
+        timeline.toggle_selected(b);
+        assert_eq!(distribute(&mut timeline, 0, false), Err(AlignError::TooFewSelected(3)));
+        timeline.toggle_selected(c);
+
+        distribute(&mut timeline, 0, false).unwrap();
+        assert_eq!(extent(&timeline, b), Some((Vec3::new(9.0, 0.0, 0.0), Vec3::new(13.0, 3.0, 2.0))));
+        assert_eq!(extent(&timeline, c), Some((Vec3::new(20.0, 0.0, 0.0), Vec3::new(22.0, 1.0, 2.0))));
+
+        match_size(&mut timeline, 1).unwrap();
+        assert_eq!(extent(&timeline, c), Some((Vec3::new(20.0, 0.0, 0.0), Vec3::new(22.0, 3.0, 2.0))));
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{FeatureId, FeatureTimeline};
+use crate::editor::prop::{PROP_DIR, available_models};
+use crate::get;
+use crate::tool::Tools;
+
+#[derive(Resource, Default)]
+pub struct AssetBrowserState {
+    /// Model the prop tool places, as a path inside [`PROP_DIR`].
+    pub selected: Option<String>,
+    pub filter: String,
+}
+
+/// How a model in the asset pack is used by the map.
+#[derive(Debug, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub props: usize,
+}
+
+/// Usage counts for every available model, and the props whose model is not in the pack.
+pub fn model_usage(available: &[String], props: &[(FeatureId, String)]) -> (Vec<ModelUsage>, Vec<(FeatureId, String)>) {
+    let mut counts: HashMap<&str, usize> = available.iter().map(|m| (m.as_str(), 0)).collect();
+    let mut missing = vec![];
+    for (id, model) in props {
+        match counts.get_mut(model.as_str()) {
+            Some(count) => *count += 1,
+            None => missing.push((*id, model.clone())),
+        }
+    }
+    let usage = available.iter()
+        .map(|m| ModelUsage { model: m.clone(), props: counts[m.as_str()] })
+        .collect();
+    (usage, missing)
+}
+
+fn props_in_timeline(timeline: &FeatureTimeline) -> Vec<(FeatureId, String)> {
+    timeline.active_features()
+        .filter_map(|(id, f)| match f.object().snapshot() {
+            FeatureData::Prop { model, .. } => Some((id, model)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn asset_browser_panel_ui(ui: &mut egui::Ui, state: &mut AssetBrowserState, timeline: &mut FeatureTimeline, next_tool: &mut NextState<Tools>) {
+    let (usage, missing) = model_usage(&available_models(), &props_in_timeline(timeline));
+
+    ui.horizontal(|ui| {
+        ui.label(get!("editor.assets.filter"));
+        ui.text_edit_singleline(&mut state.filter);
+    });
+    if usage.is_empty() {
+        ui.label(get!("editor.assets.empty", "dir", PROP_DIR));
+    }
+
+    let filter = state.filter.to_lowercase();
+    egui::Grid::new("editor_asset_browser_grid")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for entry in usage.iter().filter(|u| u.model.to_lowercase().contains(&filter)) {
+                let selected = state.selected.as_deref() == Some(entry.model.as_str());
+                if ui.selectable_label(selected, &entry.model).clicked() {
+                    state.selected = Some(entry.model.clone());
+                    next_tool.set(Tools::Prop);
+                }
+                ui.label(get!("editor.assets.used", "count", entry.props));
+                ui.end_row();
+            }
+        });
+
+    if let Some(model) = &state.selected {
+        ui.label(get!("editor.assets.placing", "model", model));
+    }
+
+    if !missing.is_empty() {
+        ui.separator();
+        ui.colored_label(egui::Color32::YELLOW, get!("editor.assets.missing"));
+        for (id, model) in missing {
+            if ui.link(format!("{} ({})", model, id)).clicked() {
+                timeline.select(Some(id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_usage_counts_and_missing() {
+        let available = vec!["crate.glb".to_owned(), "trees/pine.gltf".to_owned()];
+        let (a, b, c) = (FeatureId::from_raw(1), FeatureId::from_raw(2), FeatureId::from_raw(3));
+        let props = vec![
+            (a, "crate.glb".to_owned()),
+            (b, "crate.glb".to_owned()),
+            (c, "barrel.glb".to_owned()),
+        ];
+        let (usage, missing) = model_usage(&available, &props);
+        assert_eq!(usage, vec![
+            ModelUsage { model: "crate.glb".to_owned(), props: 2 },
+            ModelUsage { model: "trees/pine.gltf".to_owned(), props: 0 },
+        ]);
+        assert_eq!(missing, vec![(c, "barrel.glb".to_owned())]);
+    }
+}
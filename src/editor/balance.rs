@@ -0,0 +1,841 @@
+use std::fmt::{Display, Formatter};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use strum::IntoEnumIterator;
+use crate::common::mode::GameMode;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{FeatureId, FeatureTimeline};
+use crate::editor::map_marker::{MarkerKind, Team};
+use crate::editor::map_metadata::MapMetadata;
+use crate::editor::pickup::{PickupKind, item_keys};
+use crate::get;
+use crate::tool::navmesh::{build_navmesh, rooms_from_timeline, stairs_from_timeline, NavHull, Navmesh};
+
+/// How far a marker may sit from the nearest walkable polygon and still count as on it.
+const SNAP_DISTANCE: f32 = 2.0;
+/// Pickups whose nearest red and blue spawns are within this distance of each other sit in the middle.
+const MIDDLE_TOLERANCE: f32 = 0.5;
+/// Portals narrower than this are chokepoints wherever a team's route crosses them.
+const NARROW_PORTAL: f32 = 2.0;
+/// Chokepoints closer together than this are one doorway whose floor was split into several polygons.
+const CHOKE_MERGE_DISTANCE: f32 = 1.0;
+
+/// Objective kinds that matter for a game mode.
+pub fn objective_kinds(mode: GameMode) -> &'static [MarkerKind] {
+    match mode {
+        GameMode::CTF => &[MarkerKind::Flag],
+        GameMode::SD => &[MarkerKind::Flag, MarkerKind::ControlPoint],
+        GameMode::Arena | GameMode::PL | GameMode::PLR | GameMode::KOTH | GameMode::CP => &[MarkerKind::ControlPoint],
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacedMarker {
+    pub id: FeatureId,
+    pub kind: MarkerKind,
+    pub team: Team,
+    pub position: Vec3,
+}
+
+/// Every map marker in the timeline.
+pub fn markers_from_timeline(timeline: &FeatureTimeline) -> Vec<PlacedMarker> {
+    timeline.active_features()
+        .filter_map(|(id, f)| match f.object().snapshot() {
+            FeatureData::Marker { kind, team, .. } => Some(PlacedMarker {
+                id,
+                kind,
+                team,
+                position: f.get_point("").ok()?,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacedPickup {
+    pub id: FeatureId,
+    pub kind: PickupKind,
+    pub item: String,
+    pub team: Team,
+    pub position: Vec3,
+}
+
+/// Every pickup in the timeline.
+pub fn pickups_from_timeline(timeline: &FeatureTimeline) -> Vec<PlacedPickup> {
+    timeline.active_features()
+        .filter_map(|(id, f)| match f.object().snapshot() {
+            FeatureData::Pickup { kind, item, team, .. } => Some(PlacedPickup {
+                id,
+                kind,
+                item,
+                team,
+                position: f.get_point("").ok()?,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A walk through the navmesh, from spawn to objective.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TravelPath {
+    pub points: Vec<Vec3>,
+    pub distance: f32,
+}
+
+/// Polygon the point stands on, or the closest one within [`SNAP_DISTANCE`].
+fn locate(navmesh: &Navmesh, point: Vec3) -> Option<usize> {
+    navmesh.polygons.iter().enumerate()
+        .map(|(i, polygon)| {
+            let flat = Vec2::new(point.x, point.z).clamp(polygon.min, polygon.max);
+            (i, Vec3::new(flat.x, polygon.height, flat.y).distance(point))
+        })
+        .filter(|(_, d)| *d <= SNAP_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Shortest walk between two points. Nodes are the endpoints and every portal midpoint; two nodes
+/// are joined by a straight line when they touch the same (convex) polygon.
+pub fn shortest_path(navmesh: &Navmesh, from: Vec3, to: Vec3) -> Option<TravelPath> {
+    let start_polygon = locate(navmesh, from)?;
+    let end_polygon = locate(navmesh, to)?;
+
+    let mut positions = vec![from, to];
+    let mut members: Vec<Vec<usize>> = vec![vec![]; navmesh.polygons.len()];
+    members[start_polygon].push(0);
+    members[end_polygon].push(1);
+    for link in &navmesh.links {
+        let node = positions.len();
+        positions.push((link.portal[0] + link.portal[1]) * 0.5);
+        members[link.from].push(node);
+        members[link.to].push(node);
+    }
+
+    let mut cost = vec![f32::INFINITY; positions.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; positions.len()];
+    let mut done = vec![false; positions.len()];
+    let mut neighbours: Vec<Vec<usize>> = vec![vec![]; positions.len()];
+    for nodes in &members {
+        for &a in nodes {
+            neighbours[a].extend(nodes.iter().filter(|&&b| b != a));
+        }
+    }
+
+    cost[0] = 0.0;
+    while let Some(current) = (0..positions.len())
+        .filter(|&n| !done[n] && cost[n].is_finite())
+        .min_by(|&a, &b| cost[a].total_cmp(&cost[b]))
+    {
+        if current == 1 { break; }
+        done[current] = true;
+        for &next in &neighbours[current] {
+            let candidate = cost[current] + positions[current].distance(positions[next]);
+            if candidate < cost[next] {
+                cost[next] = candidate;
+                previous[next] = Some(current);
+            }
+        }
+    }
+    if !cost[1].is_finite() {
+        return None;
+    }
+
+    let mut points = vec![positions[1]];
+    let mut node = 1;
+    while let Some(prev) = previous[node] {
+        points.push(positions[prev]);
+        node = prev;
+    }
+    points.reverse();
+    Some(TravelPath { points, distance: cost[1] })
+}
+
+/// What a row of the report measures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BalanceTarget {
+    /// A neutral objective both teams race to.
+    Neutral(FeatureId, MarkerKind),
+    /// The nearest objective of this kind owned by the walking team.
+    Own(MarkerKind),
+    /// The nearest objective of this kind owned by the other team.
+    Enemy(MarkerKind),
+}
+
+impl BalanceTarget {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Neutral(id, kind) => get!("editor.balance.neutral", "kind", kind.name(), "id", id),
+            Self::Own(kind) => get!("editor.balance.own", "kind", kind.name()),
+            Self::Enemy(kind) => get!("editor.balance.enemy", "kind", kind.name()),
+        }
+    }
+}
+
+impl Display for BalanceTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Neutral(id, kind) => write!(f, "{} #{}", kind.key(), id),
+            Self::Own(kind) => write!(f, "own {}", kind.key()),
+            Self::Enemy(kind) => write!(f, "enemy {}", kind.key()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BalanceRow {
+    pub target: BalanceTarget,
+    pub red: Option<TravelPath>,
+    pub blue: Option<TravelPath>,
+}
+
+impl BalanceRow {
+    /// Difference between the teams' walks, when both can reach the target.
+    pub fn asymmetry(&self) -> Option<f32> {
+        Some((self.red.as_ref()?.distance - self.blue.as_ref()?.distance).abs())
+    }
+
+    pub fn path(&self, team: Team) -> Option<&TravelPath> {
+        match team {
+            Team::Red => self.red.as_ref(),
+            Team::Blue => self.blue.as_ref(),
+            Team::Neutral => None,
+        }
+    }
+}
+
+/// A portal that funnels a team's routes: either every route to some objective crosses it, or it
+/// is narrower than [`NARROW_PORTAL`] and on one of the team's shortest paths.
+#[derive(Clone, Debug)]
+pub struct ChokeRow {
+    pub portal: [Vec3; 2],
+    pub width: f32,
+    /// Walk from the team's nearest spawn to the middle of the portal.
+    pub red: Option<TravelPath>,
+    pub blue: Option<TravelPath>,
+    pub red_crosses: bool,
+    pub blue_crosses: bool,
+}
+
+impl ChokeRow {
+    pub fn center(&self) -> Vec3 {
+        (self.portal[0] + self.portal[1]) * 0.5
+    }
+
+    /// Difference between the teams' walks to the chokepoint, when both can reach it.
+    pub fn asymmetry(&self) -> Option<f32> {
+        Some((self.red.as_ref()?.distance - self.blue.as_ref()?.distance).abs())
+    }
+
+    pub fn path(&self, team: Team) -> Option<&TravelPath> {
+        match team {
+            Team::Red => self.red.as_ref(),
+            Team::Blue => self.blue.as_ref(),
+            Team::Neutral => None,
+        }
+    }
+
+    pub fn crosses(&self, team: Team) -> bool {
+        match team {
+            Team::Red => self.red_crosses,
+            Team::Blue => self.blue_crosses,
+            Team::Neutral => false,
+        }
+    }
+}
+
+impl Display for ChokeRow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let c = self.center();
+        write!(f, "chokepoint ({:.1}, {:.1}, {:.1}) width {:.1}", c.x, c.y, c.z, self.width)
+    }
+}
+
+/// How many pickups of one kind sit in each team's half of the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PickupRow {
+    pub kind: PickupKind,
+    /// Item prototype key, for weapon pickups.
+    pub item: Option<String>,
+    pub red: usize,
+    pub blue: usize,
+    pub middle: usize,
+}
+
+impl PickupRow {
+    pub fn name(&self) -> String {
+        match &self.item {
+            Some(item) if item_keys().contains(&item.as_str()) => get!(format!("item.name.{}", item)),
+            Some(item) => item.clone(),
+            None => self.kind.name(),
+        }
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.red == self.blue
+    }
+}
+
+impl Display for PickupRow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.item {
+            Some(item) => write!(f, "{} {}", self.kind.key(), item),
+            None => write!(f, "{}", self.kind.key()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BalanceWarning {
+    NoSpawns(Team),
+    NoObjectives(GameMode),
+    OffNavmesh(FeatureId),
+    Unreachable(BalanceTarget, Team),
+    UnevenPickups(PickupRow),
+    UnknownItem(FeatureId, String),
+    /// The teams' routes to their objectives cross a different number of chokepoints.
+    UnevenChokepoints { red: usize, blue: usize },
+}
+
+impl Display for BalanceWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSpawns(team) => write!(f, "{} team has no spawn", team.key()),
+            Self::NoObjectives(mode) => write!(f, "no objectives for game mode `{}`", mode.prefix()),
+            Self::OffNavmesh(id) => write!(f, "marker #{} is not on a walkable floor", id),
+            Self::Unreachable(target, team) => write!(f, "{} team cannot reach {}", team.key(), target),
+            Self::UnevenPickups(row) => write!(f, "{} pickups are uneven: red {}, blue {}", row, row.red, row.blue),
+            Self::UnknownItem(id, item) => write!(f, "pickup #{} gives unknown item `{}`", id, item),
+            Self::UnevenChokepoints { red, blue } => write!(f, "red team crosses {} chokepoint(s), blue team {}", red, blue),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BalanceReport {
+    pub game_mode: GameMode,
+    /// Run speed used to turn distances into travel times.
+    pub run_speed: f32,
+    pub rows: Vec<BalanceRow>,
+    pub pickups: Vec<PickupRow>,
+    pub chokepoints: Vec<ChokeRow>,
+    pub warnings: Vec<BalanceWarning>,
+}
+
+impl BalanceReport {
+    pub fn seconds(&self, distance: f32) -> f32 {
+        if self.run_speed > 0.0 { distance / self.run_speed } else { f32::INFINITY }
+    }
+
+    /// Largest difference in travel time between the teams, in seconds.
+    pub fn max_asymmetry_seconds(&self) -> f32 {
+        self.rows.iter()
+            .filter_map(|row| row.asymmetry())
+            .map(|d| self.seconds(d))
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Display for BalanceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "mode: {} (run speed {} u/s)", self.game_mode.prefix(), self.run_speed)?;
+        let show = |path: &Option<TravelPath>| path.as_ref()
+            .map_or("unreachable".to_owned(), |p| format!("{:.1} u / {:.1} s", p.distance, self.seconds(p.distance)));
+        for row in &self.rows {
+            write!(f, "{}: red {}, blue {}", row.target, show(&row.red), show(&row.blue))?;
+            match row.asymmetry() {
+                Some(d) => writeln!(f, ", asymmetry {:.1} s", self.seconds(d))?,
+                None => writeln!(f)?,
+            }
+        }
+        for choke in &self.chokepoints {
+            let crossed: Vec<&str> = [Team::Red, Team::Blue].into_iter()
+                .filter(|team| choke.crosses(*team))
+                .map(|team| team.key())
+                .collect();
+            write!(f, "{}: red {}, blue {}, crossed by {}", choke, show(&choke.red), show(&choke.blue), crossed.join(" and "))?;
+            match choke.asymmetry() {
+                Some(d) => writeln!(f, ", asymmetry {:.1} s", self.seconds(d))?,
+                None => writeln!(f)?,
+            }
+        }
+        for row in &self.pickups {
+            writeln!(f, "pickups {}: red {}, blue {}, middle {}", row, row.red, row.blue, row.middle)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shortest walk from any of `spawns` to any of `targets`.
+fn nearest(navmesh: &Navmesh, spawns: &[&PlacedMarker], targets: &[&PlacedMarker]) -> Option<TravelPath> {
+    spawns.iter()
+        .flat_map(|s| targets.iter().filter_map(|t| shortest_path(navmesh, s.position, t.position)))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Straight-line distance from `position` to the closest of `spawns`.
+fn distance_to_nearest(position: Vec3, spawns: &[&PlacedMarker]) -> Option<f32> {
+    spawns.iter().map(|s| s.position.distance(position)).min_by(f32::total_cmp)
+}
+
+/// The half of the map a pickup counts toward. Team-restricted pickups belong to their team;
+/// others go to the side with the closer spawn, or the middle when it is a tie or a team has no spawn.
+fn half_of(pickup: &PlacedPickup, red_spawns: &[&PlacedMarker], blue_spawns: &[&PlacedMarker]) -> Team {
+    if pickup.team != Team::Neutral {
+        return pickup.team;
+    }
+    match (distance_to_nearest(pickup.position, red_spawns), distance_to_nearest(pickup.position, blue_spawns)) {
+        (Some(red), Some(blue)) if red + MIDDLE_TOLERANCE < blue => Team::Red,
+        (Some(red), Some(blue)) if blue + MIDDLE_TOLERANCE < red => Team::Blue,
+        _ => Team::Neutral,
+    }
+}
+
+/// Polygons reachable from `starts` without crossing the link at index `skip`.
+fn reachable(navmesh: &Navmesh, starts: &[usize], skip: Option<usize>) -> Vec<bool> {
+    let mut seen = vec![false; navmesh.polygons.len()];
+    let mut stack = starts.to_vec();
+    while let Some(polygon) = stack.pop() {
+        if std::mem::replace(&mut seen[polygon], true) {
+            continue;
+        }
+        for (i, link) in navmesh.links.iter().enumerate() {
+            if Some(i) == skip { continue; }
+            if link.from == polygon && !seen[link.to] {
+                stack.push(link.to);
+            } else if link.to == polygon && !seen[link.from] {
+                stack.push(link.from);
+            }
+        }
+    }
+    seen
+}
+
+/// Links the team cannot avoid on the way to an objective it can reach, or narrow links on
+/// one of its shortest paths.
+fn crossed_links(navmesh: &Navmesh, spawns: &[&PlacedMarker], objectives: &[&PlacedMarker], paths: &[&TravelPath]) -> Vec<bool> {
+    let starts: Vec<usize> = spawns.iter().filter_map(|s| locate(navmesh, s.position)).collect();
+    let targets: Vec<usize> = objectives.iter().filter_map(|o| locate(navmesh, o.position)).collect();
+    let before = reachable(navmesh, &starts, None);
+    let targets: Vec<usize> = targets.into_iter().filter(|&t| before[t]).collect();
+
+    navmesh.links.iter().enumerate()
+        .map(|(i, link)| {
+            let midpoint = (link.portal[0] + link.portal[1]) * 0.5;
+            let narrow = portal_width(link.portal) < NARROW_PORTAL
+                && paths.iter().any(|path| path.points.contains(&midpoint));
+            narrow || {
+                let after = reachable(navmesh, &starts, Some(i));
+                targets.iter().any(|&t| !after[t])
+            }
+        })
+        .collect()
+}
+
+fn portal_width(portal: [Vec3; 2]) -> f32 {
+    let span = (portal[1] - portal[0]).abs();
+    span.x.max(span.z)
+}
+
+/// Chokepoints on either team's routes, with each team's walk from spawn to them.
+fn chokepoints(navmesh: &Navmesh, red_spawns: &[&PlacedMarker], blue_spawns: &[&PlacedMarker], objectives: &[&PlacedMarker], rows: &[BalanceRow]) -> Vec<ChokeRow> {
+    let paths = |team: Team| -> Vec<&TravelPath> { rows.iter().filter_map(|row| row.path(team)).collect() };
+    let red = crossed_links(navmesh, red_spawns, objectives, &paths(Team::Red));
+    let blue = crossed_links(navmesh, blue_spawns, objectives, &paths(Team::Blue));
+
+    let mut chokes: Vec<ChokeRow> = Vec::new();
+    for (i, link) in navmesh.links.iter().enumerate().filter(|(i, _)| red[*i] || blue[*i]) {
+        let width = portal_width(link.portal);
+        let center = (link.portal[0] + link.portal[1]) * 0.5;
+        if let Some(existing) = chokes.iter_mut().find(|c| {
+            c.red_crosses == red[i] && c.blue_crosses == blue[i] && c.center().distance(center) < CHOKE_MERGE_DISTANCE
+        }) {
+            if width < existing.width {
+                existing.portal = link.portal;
+                existing.width = width;
+            }
+            continue;
+        }
+        chokes.push(ChokeRow { portal: link.portal, width, red: None, blue: None, red_crosses: red[i], blue_crosses: blue[i] });
+    }
+    for choke in &mut chokes {
+        let center = choke.center();
+        let walk = |spawns: &[&PlacedMarker]| spawns.iter()
+            .filter_map(|s| shortest_path(navmesh, s.position, center))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance));
+        choke.red = walk(red_spawns);
+        choke.blue = walk(blue_spawns);
+    }
+    chokes
+}
+
+/// Pickup counts per kind (and per item, for weapons) in each team's half.
+pub fn pickup_distribution(pickups: &[PlacedPickup], red_spawns: &[&PlacedMarker], blue_spawns: &[&PlacedMarker]) -> Vec<PickupRow> {
+    let mut rows: Vec<PickupRow> = Vec::new();
+    for pickup in pickups {
+        let item = (pickup.kind == PickupKind::Weapon).then(|| pickup.item.clone());
+        let index = match rows.iter().position(|r| r.kind == pickup.kind && r.item == item) {
+            Some(index) => index,
+            None => {
+                rows.push(PickupRow { kind: pickup.kind, item, red: 0, blue: 0, middle: 0 });
+                rows.len() - 1
+            }
+        };
+        match half_of(pickup, red_spawns, blue_spawns) {
+            Team::Red => rows[index].red += 1,
+            Team::Blue => rows[index].blue += 1,
+            Team::Neutral => rows[index].middle += 1,
+        }
+    }
+    let kind_order = |kind: PickupKind| PickupKind::iter().position(|k| k == kind);
+    rows.sort_by(|a, b| kind_order(a.kind).cmp(&kind_order(b.kind)).then_with(|| a.item.cmp(&b.item)));
+    rows
+}
+
+/// Per-team walks from spawn to each objective the game mode cares about, the chokepoints on
+/// those routes, and how the pickups are split between the teams' halves.
+pub fn analyze(markers: &[PlacedMarker], pickups: &[PlacedPickup], game_mode: GameMode, navmesh: &Navmesh, run_speed: f32) -> BalanceReport {
+    let mut warnings = Vec::new();
+    for marker in markers {
+        if locate(navmesh, marker.position).is_none() {
+            warnings.push(BalanceWarning::OffNavmesh(marker.id));
+        }
+    }
+
+    let spawns = |team: Team| -> Vec<&PlacedMarker> {
+        markers.iter().filter(|m| m.kind == MarkerKind::Spawn && m.team == team).collect()
+    };
+    let (red_spawns, blue_spawns) = (spawns(Team::Red), spawns(Team::Blue));
+    for (team, list) in [(Team::Red, &red_spawns), (Team::Blue, &blue_spawns)] {
+        if list.is_empty() {
+            warnings.push(BalanceWarning::NoSpawns(team));
+        }
+    }
+
+    let kinds = objective_kinds(game_mode);
+    let objectives: Vec<&PlacedMarker> = markers.iter().filter(|m| kinds.contains(&m.kind)).collect();
+    if objectives.is_empty() {
+        warnings.push(BalanceWarning::NoObjectives(game_mode));
+    }
+
+    let mut rows = Vec::new();
+    for objective in objectives.iter().filter(|o| o.team == Team::Neutral) {
+        rows.push(BalanceRow {
+            target: BalanceTarget::Neutral(objective.id, objective.kind),
+            red: nearest(navmesh, &red_spawns, std::slice::from_ref(objective)),
+            blue: nearest(navmesh, &blue_spawns, std::slice::from_ref(objective)),
+        });
+    }
+    for &kind in kinds {
+        let owned_by = |team: Team| -> Vec<&PlacedMarker> {
+            objectives.iter().copied().filter(|o| o.kind == kind && o.team == team).collect()
+        };
+        let (red_owned, blue_owned) = (owned_by(Team::Red), owned_by(Team::Blue));
+        if red_owned.is_empty() && blue_owned.is_empty() {
+            continue;
+        }
+        rows.push(BalanceRow {
+            target: BalanceTarget::Own(kind),
+            red: nearest(navmesh, &red_spawns, &red_owned),
+            blue: nearest(navmesh, &blue_spawns, &blue_owned),
+        });
+        rows.push(BalanceRow {
+            target: BalanceTarget::Enemy(kind),
+            red: nearest(navmesh, &red_spawns, &blue_owned),
+            blue: nearest(navmesh, &blue_spawns, &red_owned),
+        });
+    }
+
+    for row in &rows {
+        for (team, list) in [(Team::Red, &red_spawns), (Team::Blue, &blue_spawns)] {
+            if row.path(team).is_none() && !list.is_empty() {
+                warnings.push(BalanceWarning::Unreachable(row.target, team));
+            }
+        }
+    }
+
+    let chokepoints = chokepoints(navmesh, &red_spawns, &blue_spawns, &objectives, &rows);
+    let crossings = |team: Team| chokepoints.iter().filter(|c| c.crosses(team)).count();
+    let (red_crossings, blue_crossings) = (crossings(Team::Red), crossings(Team::Blue));
+    if red_crossings != blue_crossings {
+        warnings.push(BalanceWarning::UnevenChokepoints { red: red_crossings, blue: blue_crossings });
+    }
+
+    let known_items = item_keys();
+    for pickup in pickups {
+        if pickup.kind == PickupKind::Weapon && !known_items.contains(&pickup.item.as_str()) {
+            warnings.push(BalanceWarning::UnknownItem(pickup.id, pickup.item.clone()));
+        }
+    }
+    let pickups = pickup_distribution(pickups, &red_spawns, &blue_spawns);
+    for row in pickups.iter().filter(|r| !r.is_even()) {
+        warnings.push(BalanceWarning::UnevenPickups(row.clone()));
+    }
+
+    BalanceReport { game_mode, run_speed, rows, pickups, chokepoints, warnings }
+}
+
+/// Builds a navmesh from the timeline's rooms and analyzes its markers.
+pub fn analyze_timeline(timeline: &FeatureTimeline, metadata: &MapMetadata, run_speed: f32) -> BalanceReport {
+    let navmesh = build_navmesh(&rooms_from_timeline(timeline), &stairs_from_timeline(timeline), &NavHull::default());
+    analyze(&markers_from_timeline(timeline), &pickups_from_timeline(timeline), metadata.game_mode, &navmesh, run_speed)
+}
+
+#[derive(Resource, Default)]
+pub struct BalanceState {
+    pub report: Option<BalanceReport>,
+    pub show_paths: bool,
+}
+
+pub fn balance_panel_ui(ui: &mut egui::Ui, state: &mut BalanceState, timeline: &FeatureTimeline, metadata: &MapMetadata, run_speed: f32) {
+    ui.horizontal(|ui| {
+        if ui.button(get!("editor.balance.analyze")).clicked() {
+            state.report = Some(analyze_timeline(timeline, metadata, run_speed));
+        }
+        ui.checkbox(&mut state.show_paths, get!("editor.balance.show_paths"));
+    });
+
+    let Some(report) = &state.report else {
+        ui.label(get!("editor.balance.empty"));
+        return;
+    };
+
+    ui.label(get!("editor.balance.mode", "mode", report.game_mode));
+    let show = |path: Option<&TravelPath>| path
+        .map_or(get!("editor.balance.unreachable"), |p| get!("editor.balance.time", "seconds", format!("{:.1}", report.seconds(p.distance))));
+    egui::Grid::new("balance_grid")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.colored_label(egui::Color32::from_rgb(230, 70, 60), Team::Red.name());
+            ui.colored_label(egui::Color32::from_rgb(70, 120, 230), Team::Blue.name());
+            ui.label(get!("editor.balance.asymmetry"));
+            ui.end_row();
+            for row in &report.rows {
+                ui.label(row.target.name());
+                ui.label(show(row.red.as_ref()));
+                ui.label(show(row.blue.as_ref()));
+                ui.label(row.asymmetry().map_or("-".to_owned(), |d| get!("editor.balance.time", "seconds", format!("{:.1}", report.seconds(d)))));
+                ui.end_row();
+            }
+        });
+
+    if !report.pickups.is_empty() {
+        ui.separator();
+        ui.label(get!("editor.balance.pickups"));
+        egui::Grid::new("balance_pickup_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.colored_label(egui::Color32::from_rgb(230, 70, 60), Team::Red.name());
+                ui.colored_label(egui::Color32::from_rgb(70, 120, 230), Team::Blue.name());
+                ui.label(get!("editor.balance.middle"));
+                ui.end_row();
+                for row in &report.pickups {
+                    ui.label(row.name());
+                    ui.label(row.red.to_string());
+                    ui.label(row.blue.to_string());
+                    ui.label(row.middle.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+
+    if !report.chokepoints.is_empty() {
+        ui.separator();
+        ui.label(get!("editor.balance.chokepoints"));
+        egui::Grid::new("balance_choke_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.colored_label(egui::Color32::from_rgb(230, 70, 60), Team::Red.name());
+                ui.colored_label(egui::Color32::from_rgb(70, 120, 230), Team::Blue.name());
+                ui.label(get!("editor.balance.asymmetry"));
+                ui.end_row();
+                for choke in &report.chokepoints {
+                    let c = choke.center();
+                    ui.label(get!("editor.balance.chokepoint",
+                        "x", format!("{:.1}", c.x), "y", format!("{:.1}", c.y), "z", format!("{:.1}", c.z),
+                        "width", format!("{:.1}", choke.width)));
+                    let cell = |team: Team| {
+                        let time = show(choke.path(team));
+                        if choke.crosses(team) { get!("editor.balance.crossed", "time", time) } else { time }
+                    };
+                    ui.label(cell(Team::Red));
+                    ui.label(cell(Team::Blue));
+                    ui.label(choke.asymmetry().map_or("-".to_owned(), |d| get!("editor.balance.time", "seconds", format!("{:.1}", report.seconds(d)))));
+                    ui.end_row();
+                }
+            });
+    }
+
+    for warning in &report.warnings {
+        ui.colored_label(egui::Color32::YELLOW, warning.to_string());
+    }
+}
+
+pub fn draw_balance_gizmos(state: Res<BalanceState>, mut gizmos: Gizmos) {
+    if !state.show_paths { return; }
+    let Some(report) = &state.report else { return; };
+    let lift = Vec3::Y * 0.1;
+    for row in &report.rows {
+        for team in [Team::Red, Team::Blue] {
+            if let Some(path) = row.path(team) {
+                gizmos.linestrip(path.points.iter().map(|p| *p + lift), team.color());
+            }
+        }
+    }
+    for choke in &report.chokepoints {
+        let [a, b] = choke.portal;
+        gizmos.line(a + lift, b + lift, Color::srgb(1.0, 0.8, 0.1));
+        gizmos.line(choke.center() + lift, choke.center() + Vec3::Y * 2.0, Color::srgb(1.0, 0.8, 0.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::room::Room;
+
+    fn corridor() -> Navmesh {
+        build_navmesh(&[
+            Room::new(Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0)),
+            Room::new(Vec3::new(4.0, 0.0, 1.0), Vec3::new(16.0, 3.0, 3.0)),
+            Room::new(Vec3::new(16.0, 0.0, 0.0), Vec3::new(20.0, 3.0, 4.0)),
+        ], &[], &NavHull::default())
+    }
+
+    fn marker(id: u64, kind: MarkerKind, team: Team, x: f32) -> PlacedMarker {
+        PlacedMarker { id: FeatureId::from_raw(id), kind, team, position: Vec3::new(x, 0.0, 2.0) }
+    }
+
+    #[test]
+    fn test_path_follows_corridor() {
+        let navmesh = corridor();
+        let path = shortest_path(&navmesh, Vec3::new(2.0, 0.0, 2.0), Vec3::new(18.0, 0.0, 2.0)).unwrap();
+        assert!(path.distance >= 16.0 - 1e-3);
+        assert!(path.distance < 17.0);
+        assert_eq!(path.points.first(), Some(&Vec3::new(2.0, 0.0, 2.0)));
+        assert_eq!(path.points.last(), Some(&Vec3::new(18.0, 0.0, 2.0)));
+    }
+
+    #[test]
+    fn test_centered_point_is_symmetric() {
+        let markers = [
+            marker(1, MarkerKind::Spawn, Team::Red, 2.0),
+            marker(2, MarkerKind::Spawn, Team::Blue, 18.0),
+            marker(3, MarkerKind::ControlPoint, Team::Neutral, 10.0),
+        ];
+        let report = analyze(&markers, &[], GameMode::KOTH, &corridor(), 8.0);
+        assert_eq!(report.rows.len(), 1);
+        assert!(report.max_asymmetry_seconds() < 0.01);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_off_center_point_reports_asymmetry() {
+        let markers = [
+            marker(1, MarkerKind::Spawn, Team::Red, 2.0),
+            marker(2, MarkerKind::Spawn, Team::Blue, 18.0),
+            marker(3, MarkerKind::ControlPoint, Team::Neutral, 6.0),
+        ];
+        let report = analyze(&markers, &[], GameMode::KOTH, &corridor(), 8.0);
+        assert!((report.max_asymmetry_seconds() - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_ctf_compares_own_and_enemy_flags() {
+        let markers = [
+            marker(1, MarkerKind::Spawn, Team::Red, 1.0),
+            marker(2, MarkerKind::Spawn, Team::Blue, 19.0),
+            marker(3, MarkerKind::Flag, Team::Red, 3.0),
+            marker(4, MarkerKind::Flag, Team::Blue, 17.0),
+            marker(5, MarkerKind::ControlPoint, Team::Neutral, 10.0),
+        ];
+        let report = analyze(&markers, &[], GameMode::CTF, &corridor(), 8.0);
+        let targets: Vec<BalanceTarget> = report.rows.iter().map(|r| r.target).collect();
+        assert_eq!(targets, vec![BalanceTarget::Own(MarkerKind::Flag), BalanceTarget::Enemy(MarkerKind::Flag)]);
+        assert!(report.rows[1].red.as_ref().unwrap().distance > report.rows[0].red.as_ref().unwrap().distance);
+    }
+
+    #[test]
+    fn test_pickups_split_by_nearest_spawn() {
+        let markers = [
+            marker(1, MarkerKind::Spawn, Team::Red, 2.0),
+            marker(2, MarkerKind::Spawn, Team::Blue, 18.0),
+        ];
+        let pickup = |id: u64, kind: PickupKind, team: Team, x: f32| PlacedPickup {
+            id: FeatureId::from_raw(id), kind, item: "shotgun".to_owned(), team, position: Vec3::new(x, 0.0, 2.0),
+        };
+        let pickups = [
+            pickup(3, PickupKind::HealthSmall, Team::Neutral, 4.0),
+            pickup(4, PickupKind::HealthSmall, Team::Neutral, 16.0),
+            pickup(5, PickupKind::HealthSmall, Team::Neutral, 10.0),
+            pickup(6, PickupKind::Weapon, Team::Blue, 5.0),
+        ];
+        let report = analyze(&markers, &pickups, GameMode::KOTH, &corridor(), 8.0);
+        assert_eq!(report.pickups, vec![
+            PickupRow { kind: PickupKind::HealthSmall, item: None, red: 1, blue: 1, middle: 1 },
+            PickupRow { kind: PickupKind::Weapon, item: Some("shotgun".to_owned()), red: 0, blue: 1, middle: 0 },
+        ]);
+        assert!(report.warnings.iter().any(|w| matches!(w, BalanceWarning::UnevenPickups(row) if row.kind == PickupKind::Weapon)));
+    }
+
+    #[test]
+    fn test_corridor_links_are_chokepoints_for_their_side() {
+        let markers = [
+            marker(1, MarkerKind::Spawn, Team::Red, 2.0),
+            marker(2, MarkerKind::Spawn, Team::Blue, 18.0),
+            marker(3, MarkerKind::ControlPoint, Team::Neutral, 10.0),
+        ];
+        let report = analyze(&markers, &[], GameMode::KOTH, &corridor(), 8.0);
+        assert_eq!(report.chokepoints.len(), 2);
+        let (near_red, near_blue) = (&report.chokepoints[0], &report.chokepoints[1]);
+        assert!(near_red.center().x < near_blue.center().x);
+        assert!(near_red.red_crosses && !near_red.blue_crosses);
+        assert!(near_blue.blue_crosses && !near_blue.red_crosses);
+        assert!(near_red.red.as_ref().unwrap().distance < near_red.blue.as_ref().unwrap().distance);
+        assert!(!report.warnings.iter().any(|w| matches!(w, BalanceWarning::UnevenChokepoints { .. })));
+    }
+
+    #[test]
+    fn test_narrow_route_is_a_chokepoint_without_being_a_bridge() {
+        // Two routes between the end rooms; red's side of the map funnels into a narrow gap.
+        let navmesh = build_navmesh(&[
+            Room::new(Vec3::ZERO, Vec3::new(4.0, 3.0, 8.0)),
+            Room::new(Vec3::new(4.0, 0.0, 0.0), Vec3::new(7.0, 3.0, 1.5)),
+            Room::new(Vec3::new(7.0, 0.0, 0.0), Vec3::new(16.0, 3.0, 3.0)),
+            Room::new(Vec3::new(4.0, 0.0, 5.0), Vec3::new(16.0, 3.0, 8.0)),
+            Room::new(Vec3::new(16.0, 0.0, 0.0), Vec3::new(20.0, 3.0, 8.0)),
+        ], &[], &NavHull::default());
+        let at = |id: u64, kind: MarkerKind, team: Team, x: f32, z: f32| PlacedMarker {
+            id: FeatureId::from_raw(id), kind, team, position: Vec3::new(x, 0.0, z),
+        };
+        let markers = [
+            at(1, MarkerKind::Spawn, Team::Red, 2.0, 1.0),
+            at(2, MarkerKind::Spawn, Team::Blue, 18.0, 6.5),
+            at(3, MarkerKind::ControlPoint, Team::Neutral, 10.0, 1.5),
+        ];
+        let report = analyze(&markers, &[], GameMode::KOTH, &navmesh, 8.0);
+        let gap: Vec<&ChokeRow> = report.chokepoints.iter().filter(|c| c.center().z < 1.5 && c.center().x < 7.5).collect();
+        assert!(!gap.is_empty());
+        assert!(gap.iter().all(|c| c.width < NARROW_PORTAL && c.red_crosses && !c.blue_crosses));
+        assert!(!report.chokepoints.iter().any(|c| c.center().z > 4.0));
+        assert!(report.warnings.iter().any(|w| matches!(w, BalanceWarning::UnevenChokepoints { .. })));
+    }
+
+    #[test]
+    fn test_missing_spawn_warns() {
+        let markers = [marker(3, MarkerKind::ControlPoint, Team::Neutral, 10.0)];
+        let report = analyze(&markers, &[], GameMode::CP, &corridor(), 8.0);
+        assert!(report.warnings.iter().any(|w| matches!(w, BalanceWarning::NoSpawns(Team::Red))));
+        assert!(report.rows[0].red.is_none());
+    }
+}
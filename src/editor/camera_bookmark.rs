@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rusqlite::{Connection, Transaction, params};
+use crate::get;
+
+/// A named view of the map from the free camera, saved with the blueprint so everyone working on
+/// the map can jump to the same spots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl CameraBookmark {
+    pub fn from_transform(name: &str, transform: &Transform) -> Self {
+        Self {
+            name: name.to_owned(),
+            translation: transform.translation,
+            rotation: transform.rotation,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_rotation(self.rotation)
+    }
+
+    pub(crate) fn load_from_connection(conn: &Connection) -> rusqlite::Result<Vec<Self>> {
+        conn.prepare("SELECT name, x, y, z, qx, qy, qz, qw FROM camera_bookmarks ORDER BY position")?
+            .query_map([], |row| Ok(Self {
+                name: row.get(0)?,
+                translation: Vec3::new(row.get(1)?, row.get(2)?, row.get(3)?),
+                rotation: Quat::from_xyzw(row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?).normalize(),
+            }))?
+            .collect()
+    }
+
+    pub(crate) fn insert_rows(bookmarks: &[Self], tx: &Transaction<'_>) -> rusqlite::Result<()> {
+        for (position, bookmark) in bookmarks.iter().enumerate() {
+            let (t, r) = (bookmark.translation, bookmark.rotation);
+            tx.execute(
+                "INSERT INTO camera_bookmarks (position, name, x, y, z, qx, qy, qz, qw) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![position as i64, bookmark.name, t.x, t.y, t.z, r.x, r.y, r.z, r.w],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// What the bookmarks panel asks of the free camera this frame.
+#[derive(Default)]
+pub struct BookmarkRequests {
+    /// Save the current view under this name.
+    pub add: Option<String>,
+    /// Move the camera to this view.
+    pub jump: Option<Transform>,
+}
+
+/// The map's bookmarks, with buttons to jump to, rename and delete them, and to bookmark the current view.
+pub fn camera_bookmarks_panel_ui(ui: &mut egui::Ui, bookmarks: &mut Vec<CameraBookmark>, new_name: &mut String, requests: &mut BookmarkRequests) {
+    ui.label(get!("editor.bookmarks.controls"));
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(new_name).hint_text(get!("editor.bookmarks.name_hint")));
+        if ui.button(get!("editor.bookmarks.add")).clicked() {
+            let name = match new_name.trim() {
+                "" => get!("editor.bookmarks.default_name", "number", bookmarks.len() + 1),
+                name => name.to_owned(),
+            };
+            requests.add = Some(name);
+            new_name.clear();
+        }
+    });
+    if bookmarks.is_empty() {
+        ui.label(get!("editor.bookmarks.empty"));
+        return;
+    }
+
+    let mut delete = None;
+    egui::Grid::new("editor_camera_bookmarks_grid")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, bookmark) in bookmarks.iter_mut().enumerate() {
+                ui.text_edit_singleline(&mut bookmark.name);
+                if ui.button(get!("editor.bookmarks.go")).clicked() {
+                    requests.jump = Some(bookmark.transform());
+                }
+                if ui.small_button("x").clicked() {
+                    delete = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = delete {
+        bookmarks.remove(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::save::run_migrations;
+
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn, 0, false).unwrap();
+        let bookmarks = vec![
+            CameraBookmark::from_transform("Mid", &Transform::from_xyz(1.0, 2.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y)),
+            CameraBookmark::from_transform("Red base", &Transform::from_xyz(-40.0, 5.0, 0.0)),
+        ];
+        let tx = conn.transaction().unwrap();
+        CameraBookmark::insert_rows(&bookmarks, &tx).unwrap();
+        tx.commit().unwrap();
+
+        let loaded = CameraBookmark::load_from_connection(&conn).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].name, "Red base");
+        for (a, b) in loaded.iter().zip(&bookmarks) {
+            assert!(a.translation.abs_diff_eq(b.translation, 1e-5));
+            assert!(a.rotation.abs_diff_eq(b.rotation, 1e-5));
+        }
+    }
+}
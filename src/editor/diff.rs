@@ -0,0 +1,1096 @@
+use std::fmt::{Display, Formatter};
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::Vec3;
+
+use crate::editor::action::{FeatureData, FeatureSnapshot, feature_data_kind};
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTimeline, PointRef};
+use crate::editor::liquid_volume::VolumeKind;
+use crate::editor::logic::{format_connections, parse_connections, remap_connections};
+use crate::editor::logic_entity::LogicKind;
+use crate::editor::map_marker::{MarkerKind, Team};
+use crate::editor::note::NoteStatus;
+use crate::editor::map_metadata::{MapMetadata, format_authors_for_ui};
+use crate::editor::pickup::PickupKind;
+use crate::editor::save::{LoadedBlueprint, snapshot_data_kind};
+
+/// One persisted field of a [`FeatureData`], flattened so snapshots can be compared key by key.
+#[derive(Clone, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    /// Referenced feature and point key of a [`PointRef`].
+    Reference(Option<FeatureId>, String),
+    Axis(AxisRef),
+    Scalar(f32),
+    Vector(Vec3),
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Text(s) => write!(f, "\"{}\"", s),
+            FieldValue::Reference(None, _) => write!(f, "none"),
+            FieldValue::Reference(Some(id), key) if key.is_empty() => write!(f, "#{}", id),
+            FieldValue::Reference(Some(id), key) => write!(f, "#{}.{}", id, key),
+            FieldValue::Axis(AxisRef::Absolute(v)) => write!(f, "{}", v),
+            FieldValue::Axis(AxisRef::Relative(v)) => write!(f, "{:+} (relative)", v),
+            FieldValue::Scalar(v) => write!(f, "{}", v),
+            FieldValue::Vector(v) => write!(f, "({}, {}, {})", v.x, v.y, v.z),
+        }
+    }
+}
+
+/// A single field that differs between two versions. `None` means the field does not exist on that side.
+#[derive(Clone)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<FieldValue>,
+    pub after: Option<FieldValue>,
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |v: &Option<FieldValue>| v.as_ref().map_or("(absent)".to_owned(), |v| v.to_string());
+        write!(f, "{}: {} -> {}", self.field, show(&self.before), show(&self.after))
+    }
+}
+
+pub enum FeatureChange {
+    Added(FeatureSnapshot),
+    Removed(FeatureSnapshot),
+    Modified {
+        before: FeatureSnapshot,
+        after: FeatureSnapshot,
+        fields: Vec<FieldChange>,
+    },
+}
+
+pub struct FeatureDiff {
+    pub feature_id: FeatureId,
+    pub change: FeatureChange,
+}
+
+impl Display for FeatureDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.change {
+            FeatureChange::Added(s) => write!(f, "+ {} #{}", feature_data_kind(&s.data), self.feature_id),
+            FeatureChange::Removed(s) => write!(f, "- {} #{}", feature_data_kind(&s.data), self.feature_id),
+            FeatureChange::Modified { after, fields, .. } => {
+                write!(f, "~ {} #{}", feature_data_kind(&after.data), self.feature_id)?;
+                for field in fields {
+                    write!(f, "\n    {}", field)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Differences between two blueprints, matched up by [`FeatureId`].
+pub struct BlueprintDiff {
+    pub features: Vec<FeatureDiff>,
+    pub metadata: Vec<FieldChange>,
+}
+
+impl BlueprintDiff {
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty() && self.metadata.is_empty()
+    }
+}
+
+impl Display for BlueprintDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.metadata {
+            writeln!(f, "~ metadata {}", change)?;
+        }
+        for feature in &self.features {
+            writeln!(f, "{}", feature)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something a three-way merge could not decide on its own. The merged output keeps "ours".
+pub struct MergeConflict {
+    pub feature_id: Option<FeatureId>,
+    pub reason: String,
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.feature_id {
+            Some(id) => write!(f, "feature #{}: {}", id, self.reason),
+            None => write!(f, "metadata: {}", self.reason),
+        }
+    }
+}
+
+pub struct MergeResult {
+    pub blueprint: LoadedBlueprint,
+    pub conflicts: Vec<MergeConflict>,
+    /// Undo steps saved in ours. Their snapshots predate the merge, so the result starts with an
+    /// empty history instead.
+    pub dropped_history: usize,
+}
+
+/// Flatten the persisted parts of a feature into `(field, value)` pairs. Resolved caches are skipped.
+pub fn data_fields(data: &FeatureData) -> Vec<(String, FieldValue)> {
+    let mut out = vec![("kind".to_owned(), FieldValue::Text(snapshot_data_kind(data).to_owned()))];
+    match data {
+        FeatureData::GlobalPoint { location } => {
+            point_ref_fields(&mut out, "location", location);
+        }
+        FeatureData::PointLight { location, color, intensity, radius, range } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("color".to_owned(), FieldValue::Vector(*color)));
+            out.push(("intensity".to_owned(), FieldValue::Scalar(*intensity)));
+            out.push(("radius".to_owned(), FieldValue::Scalar(*radius)));
+            out.push(("range".to_owned(), FieldValue::Scalar(*range)));
+        }
+        FeatureData::SpotLight { location, target, color, intensity, radius, range, inner_angle, outer_angle } => {
+            point_ref_fields(&mut out, "location", location);
+            point_ref_fields(&mut out, "target", target);
+            out.push(("color".to_owned(), FieldValue::Vector(*color)));
+            out.push(("intensity".to_owned(), FieldValue::Scalar(*intensity)));
+            out.push(("radius".to_owned(), FieldValue::Scalar(*radius)));
+            out.push(("range".to_owned(), FieldValue::Scalar(*range)));
+            out.push(("inner_angle".to_owned(), FieldValue::Scalar(*inner_angle)));
+            out.push(("outer_angle".to_owned(), FieldValue::Scalar(*outer_angle)));
+        }
+        FeatureData::Sun { location, color, illuminance, azimuth, elevation } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("color".to_owned(), FieldValue::Vector(*color)));
+            out.push(("illuminance".to_owned(), FieldValue::Scalar(*illuminance)));
+            out.push(("azimuth".to_owned(), FieldValue::Scalar(*azimuth)));
+            out.push(("elevation".to_owned(), FieldValue::Scalar(*elevation)));
+        }
+        FeatureData::AreaLight { location, target, color, intensity, range, width, height } => {
+            point_ref_fields(&mut out, "location", location);
+            point_ref_fields(&mut out, "target", target);
+            out.push(("color".to_owned(), FieldValue::Vector(*color)));
+            out.push(("intensity".to_owned(), FieldValue::Scalar(*intensity)));
+            out.push(("range".to_owned(), FieldValue::Scalar(*range)));
+            out.push(("width".to_owned(), FieldValue::Scalar(*width)));
+            out.push(("height".to_owned(), FieldValue::Scalar(*height)));
+        }
+        FeatureData::Room { min, max } => {
+            point_ref_fields(&mut out, "min", min);
+            point_ref_fields(&mut out, "max", max);
+        }
+        FeatureData::Cuboid { min, max } => {
+            out.push(("min".to_owned(), FieldValue::Vector(*min)));
+            out.push(("max".to_owned(), FieldValue::Vector(*max)));
+        }
+        FeatureData::Marker { location, kind, team, connections } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("marker_kind".to_owned(), FieldValue::Text(kind.key().to_owned())));
+            out.push(("team".to_owned(), FieldValue::Text(team.key().to_owned())));
+            out.push(("connections".to_owned(), FieldValue::Text(format_connections(connections))));
+        }
+        FeatureData::Trigger { min, max, start_enabled, once, connections } => {
+            point_ref_fields(&mut out, "min", min);
+            point_ref_fields(&mut out, "max", max);
+            out.push(("start_enabled".to_owned(), FieldValue::Scalar(*start_enabled as u8 as f32)));
+            out.push(("once".to_owned(), FieldValue::Scalar(*once as u8 as f32)));
+            out.push(("connections".to_owned(), FieldValue::Text(format_connections(connections))));
+        }
+        FeatureData::Logic { location, kind, value, start_enabled, connections } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("logic_kind".to_owned(), FieldValue::Text(kind.key().to_owned())));
+            out.push(("value".to_owned(), FieldValue::Scalar(*value)));
+            out.push(("start_enabled".to_owned(), FieldValue::Scalar(*start_enabled as u8 as f32)));
+            out.push(("connections".to_owned(), FieldValue::Text(format_connections(connections))));
+        }
+        FeatureData::Teleporter { min, max, destination, yaw } => {
+            point_ref_fields(&mut out, "min", min);
+            point_ref_fields(&mut out, "max", max);
+            point_ref_fields(&mut out, "destination", destination);
+            out.push(("yaw".to_owned(), FieldValue::Scalar(*yaw)));
+        }
+        FeatureData::JumpPad { min, max, apex } => {
+            point_ref_fields(&mut out, "min", min);
+            point_ref_fields(&mut out, "max", max);
+            point_ref_fields(&mut out, "apex", apex);
+        }
+        FeatureData::Pickup { location, kind, item, respawn, team } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("pickup_kind".to_owned(), FieldValue::Text(kind.key().to_owned())));
+            out.push(("item".to_owned(), FieldValue::Text(item.clone())));
+            out.push(("respawn".to_owned(), FieldValue::Scalar(*respawn)));
+            out.push(("team".to_owned(), FieldValue::Text(team.key().to_owned())));
+        }
+        FeatureData::Prop { location, model, rotation, scale, collision } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("model".to_owned(), FieldValue::Text(model.clone())));
+            out.push(("rotation".to_owned(), FieldValue::Vector(*rotation)));
+            out.push(("scale".to_owned(), FieldValue::Vector(*scale)));
+            out.push(("collision".to_owned(), FieldValue::Scalar(*collision as u8 as f32)));
+        }
+        FeatureData::Liquid { min, max, kind, damage, speed_scale, buoyancy, tint, opacity } => {
+            point_ref_fields(&mut out, "min", min);
+            point_ref_fields(&mut out, "max", max);
+            out.push(("volume_kind".to_owned(), FieldValue::Text(kind.key().to_owned())));
+            out.push(("damage".to_owned(), FieldValue::Scalar(*damage)));
+            out.push(("speed_scale".to_owned(), FieldValue::Scalar(*speed_scale)));
+            out.push(("buoyancy".to_owned(), FieldValue::Scalar(*buoyancy)));
+            out.push(("tint".to_owned(), FieldValue::Vector(*tint)));
+            out.push(("opacity".to_owned(), FieldValue::Scalar(*opacity)));
+        }
+        FeatureData::Stairs { start, end, width, step_height, ramp } => {
+            point_ref_fields(&mut out, "start", start);
+            point_ref_fields(&mut out, "end", end);
+            out.push(("width".to_owned(), FieldValue::Scalar(*width)));
+            out.push(("step_height".to_owned(), FieldValue::Scalar(*step_height)));
+            out.push(("ramp".to_owned(), FieldValue::Scalar(*ramp as u8 as f32)));
+        }
+        FeatureData::Dimension { start, end, note } => {
+            point_ref_fields(&mut out, "start", start);
+            point_ref_fields(&mut out, "end", end);
+            out.push(("note".to_owned(), FieldValue::Text(note.clone())));
+        }
+        FeatureData::Note { location, text, author, status, color } => {
+            point_ref_fields(&mut out, "location", location);
+            out.push(("text".to_owned(), FieldValue::Text(text.clone())));
+            out.push(("author".to_owned(), FieldValue::Text(author.clone())));
+            out.push(("status".to_owned(), FieldValue::Text(status.key().to_owned())));
+            out.push(("color".to_owned(), FieldValue::Vector(*color)));
+        }
+    }
+    out
+}
+
+fn point_ref_fields(out: &mut Vec<(String, FieldValue)>, slot: &str, pr: &PointRef) {
+    out.push((format!("{}.reference", slot), FieldValue::Reference(pr.reference, pr.point_key.clone())));
+    out.push((format!("{}.x", slot), FieldValue::Axis(pr.x.clone())));
+    out.push((format!("{}.y", slot), FieldValue::Axis(pr.y.clone())));
+    out.push((format!("{}.z", slot), FieldValue::Axis(pr.z.clone())));
+}
+
+fn data_point_ref_mut<'a>(data: &'a mut FeatureData, slot: &str) -> Option<&'a mut PointRef> {
+    match (data, slot) {
+        (FeatureData::GlobalPoint { location }, "location") => Some(location),
+        (FeatureData::PointLight { location, .. }, "location") => Some(location),
+        (FeatureData::SpotLight { location, .. }, "location") => Some(location),
+        (FeatureData::SpotLight { target, .. }, "target") => Some(target),
+        (FeatureData::Sun { location, .. }, "location") => Some(location),
+        (FeatureData::AreaLight { location, .. }, "location") => Some(location),
+        (FeatureData::AreaLight { target, .. }, "target") => Some(target),
+        (FeatureData::Marker { location, .. }, "location") => Some(location),
+        (FeatureData::Note { location, .. }, "location") => Some(location),
+        (FeatureData::Logic { location, .. }, "location") => Some(location),
+        (FeatureData::Pickup { location, .. }, "location") => Some(location),
+        (FeatureData::Prop { location, .. }, "location") => Some(location),
+        (FeatureData::Room { min, .. }, "min")
+        | (FeatureData::Trigger { min, .. }, "min")
+        | (FeatureData::Teleporter { min, .. }, "min")
+        | (FeatureData::JumpPad { min, .. }, "min")
+        | (FeatureData::Liquid { min, .. }, "min") => Some(min),
+        (FeatureData::Room { max, .. }, "max")
+        | (FeatureData::Trigger { max, .. }, "max")
+        | (FeatureData::Teleporter { max, .. }, "max")
+        | (FeatureData::JumpPad { max, .. }, "max")
+        | (FeatureData::Liquid { max, .. }, "max") => Some(max),
+        (FeatureData::Teleporter { destination, .. }, "destination") => Some(destination),
+        (FeatureData::JumpPad { apex, .. }, "apex") => Some(apex),
+        (FeatureData::Stairs { start, .. }, "start")
+        | (FeatureData::Dimension { start, .. }, "start") => Some(start),
+        (FeatureData::Stairs { end, .. }, "end")
+        | (FeatureData::Dimension { end, .. }, "end") => Some(end),
+        _ => None,
+    }
+}
+
+/// Inverse of [`data_fields`] for a single field. Unknown fields and `kind` are ignored.
+fn set_data_field(data: &mut FeatureData, field: &str, value: &FieldValue) {
+    if let Some((slot, part)) = field.split_once('.') {
+        let Some(pr) = data_point_ref_mut(data, slot) else { return };
+        match (part, value) {
+            ("reference", FieldValue::Reference(reference, point_key)) => {
+                pr.reference = *reference;
+                pr.point_key = point_key.clone();
+            }
+            ("x", FieldValue::Axis(a)) => pr.x = a.clone(),
+            ("y", FieldValue::Axis(a)) => pr.y = a.clone(),
+            ("z", FieldValue::Axis(a)) => pr.z = a.clone(),
+            _ => return,
+        }
+        pr.resolved_reference = None;
+        return;
+    }
+    match (data, field, value) {
+        (FeatureData::PointLight { intensity, .. }, "intensity", FieldValue::Scalar(v)) => *intensity = *v,
+        (FeatureData::PointLight { radius, .. }, "radius", FieldValue::Scalar(v)) => *radius = *v,
+        (FeatureData::PointLight { range, .. }, "range", FieldValue::Scalar(v)) => *range = *v,
+        (FeatureData::PointLight { color, .. }, "color", FieldValue::Vector(v))
+        | (FeatureData::SpotLight { color, .. }, "color", FieldValue::Vector(v))
+        | (FeatureData::Sun { color, .. }, "color", FieldValue::Vector(v))
+        | (FeatureData::AreaLight { color, .. }, "color", FieldValue::Vector(v))
+        | (FeatureData::Note { color, .. }, "color", FieldValue::Vector(v)) => *color = *v,
+        (FeatureData::SpotLight { intensity, .. }, "intensity", FieldValue::Scalar(v))
+        | (FeatureData::AreaLight { intensity, .. }, "intensity", FieldValue::Scalar(v)) => *intensity = *v,
+        (FeatureData::SpotLight { radius, .. }, "radius", FieldValue::Scalar(v)) => *radius = *v,
+        (FeatureData::SpotLight { range, .. }, "range", FieldValue::Scalar(v))
+        | (FeatureData::AreaLight { range, .. }, "range", FieldValue::Scalar(v)) => *range = *v,
+        (FeatureData::SpotLight { inner_angle, .. }, "inner_angle", FieldValue::Scalar(v)) => *inner_angle = *v,
+        (FeatureData::SpotLight { outer_angle, .. }, "outer_angle", FieldValue::Scalar(v)) => *outer_angle = *v,
+        (FeatureData::Sun { illuminance, .. }, "illuminance", FieldValue::Scalar(v)) => *illuminance = *v,
+        (FeatureData::Sun { azimuth, .. }, "azimuth", FieldValue::Scalar(v)) => *azimuth = *v,
+        (FeatureData::Sun { elevation, .. }, "elevation", FieldValue::Scalar(v)) => *elevation = *v,
+        (FeatureData::AreaLight { width, .. }, "width", FieldValue::Scalar(v)) => *width = *v,
+        (FeatureData::AreaLight { height, .. }, "height", FieldValue::Scalar(v)) => *height = *v,
+        (FeatureData::Cuboid { min, .. }, "min", FieldValue::Vector(v)) => *min = *v,
+        (FeatureData::Cuboid { max, .. }, "max", FieldValue::Vector(v)) => *max = *v,
+        (FeatureData::Marker { kind, .. }, "marker_kind", FieldValue::Text(v)) => {
+            *kind = MarkerKind::from_key(v).unwrap_or(*kind);
+        }
+        (FeatureData::Marker { team, .. }, "team", FieldValue::Text(v))
+        | (FeatureData::Pickup { team, .. }, "team", FieldValue::Text(v)) => {
+            *team = Team::from_key(v).unwrap_or(*team);
+        }
+        (FeatureData::Logic { kind, .. }, "logic_kind", FieldValue::Text(v)) => {
+            *kind = LogicKind::from_key(v).unwrap_or(*kind);
+        }
+        (FeatureData::Logic { value: target, .. }, "value", FieldValue::Scalar(v)) => *target = *v,
+        (FeatureData::Trigger { start_enabled, .. }, "start_enabled", FieldValue::Scalar(v))
+        | (FeatureData::Logic { start_enabled, .. }, "start_enabled", FieldValue::Scalar(v)) => *start_enabled = *v != 0.0,
+        (FeatureData::Trigger { once, .. }, "once", FieldValue::Scalar(v)) => *once = *v != 0.0,
+        (FeatureData::Teleporter { yaw, .. }, "yaw", FieldValue::Scalar(v)) => *yaw = *v,
+        (FeatureData::Pickup { kind, .. }, "pickup_kind", FieldValue::Text(v)) => {
+            *kind = PickupKind::from_key(v).unwrap_or(*kind);
+        }
+        (FeatureData::Pickup { item, .. }, "item", FieldValue::Text(v)) => *item = v.clone(),
+        (FeatureData::Pickup { respawn, .. }, "respawn", FieldValue::Scalar(v)) => *respawn = *v,
+        (FeatureData::Prop { model, .. }, "model", FieldValue::Text(v)) => *model = v.clone(),
+        (FeatureData::Prop { rotation, .. }, "rotation", FieldValue::Vector(v)) => *rotation = *v,
+        (FeatureData::Prop { scale, .. }, "scale", FieldValue::Vector(v)) => *scale = *v,
+        (FeatureData::Prop { collision, .. }, "collision", FieldValue::Scalar(v)) => *collision = *v != 0.0,
+        (FeatureData::Liquid { kind, .. }, "volume_kind", FieldValue::Text(v)) => {
+            *kind = VolumeKind::from_key(v).unwrap_or(*kind);
+        }
+        (FeatureData::Liquid { damage, .. }, "damage", FieldValue::Scalar(v)) => *damage = *v,
+        (FeatureData::Liquid { speed_scale, .. }, "speed_scale", FieldValue::Scalar(v)) => *speed_scale = *v,
+        (FeatureData::Liquid { buoyancy, .. }, "buoyancy", FieldValue::Scalar(v)) => *buoyancy = *v,
+        (FeatureData::Liquid { tint, .. }, "tint", FieldValue::Vector(v)) => *tint = *v,
+        (FeatureData::Liquid { opacity, .. }, "opacity", FieldValue::Scalar(v)) => *opacity = *v,
+        (FeatureData::Stairs { width, .. }, "width", FieldValue::Scalar(v)) => *width = *v,
+        (FeatureData::Stairs { step_height, .. }, "step_height", FieldValue::Scalar(v)) => *step_height = *v,
+        (FeatureData::Stairs { ramp, .. }, "ramp", FieldValue::Scalar(v)) => *ramp = *v != 0.0,
+        (FeatureData::Dimension { note, .. }, "note", FieldValue::Text(v)) => *note = v.clone(),
+        (FeatureData::Note { text, .. }, "text", FieldValue::Text(v)) => *text = v.clone(),
+        (FeatureData::Note { author, .. }, "author", FieldValue::Text(v)) => *author = v.clone(),
+        (FeatureData::Note { status, .. }, "status", FieldValue::Text(v)) => {
+            *status = NoteStatus::from_key(v).unwrap_or(*status);
+        }
+        (FeatureData::Marker { connections, .. }, "connections", FieldValue::Text(v))
+        | (FeatureData::Trigger { connections, .. }, "connections", FieldValue::Text(v))
+        | (FeatureData::Logic { connections, .. }, "connections", FieldValue::Text(v)) => *connections = parse_connections(v),
+        _ => {}
+    }
+}
+
+/// Field-level differences between two versions of the same feature.
+pub fn diff_data(before: &FeatureData, after: &FeatureData) -> Vec<FieldChange> {
+    let before_fields = data_fields(before);
+    let after_fields = data_fields(after);
+    let after_map: HashMap<&str, &FieldValue> = after_fields.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    let before_keys: HashSet<&str> = before_fields.iter().map(|(k, _)| k.as_str()).collect();
+
+    let mut changes = Vec::new();
+    for (key, value) in &before_fields {
+        match after_map.get(key.as_str()) {
+            Some(after_value) if *after_value == value => {}
+            after_value => changes.push(FieldChange {
+                field: key.clone(),
+                before: Some(value.clone()),
+                after: after_value.map(|v| (*v).clone()),
+            }),
+        }
+    }
+    for (key, value) in &after_fields {
+        if !before_keys.contains(key.as_str()) {
+            changes.push(FieldChange {
+                field: key.clone(),
+                before: None,
+                after: Some(value.clone()),
+            });
+        }
+    }
+    changes
+}
+
+/// Equality over persisted fields only (ignores cached resolved positions).
+pub(crate) fn same_data(a: &FeatureData, b: &FeatureData) -> bool {
+    data_fields(a) == data_fields(b)
+}
+
+/// Snapshots of the live features, in timeline order. Features past the rollback bar were undone
+/// and are not part of the map, so diffs and merges leave them out.
+fn timeline_snapshots(timeline: &FeatureTimeline) -> Vec<(FeatureId, FeatureSnapshot)> {
+    timeline.active_features().enumerate()
+        .map(|(idx, (id, f))| (id, FeatureSnapshot::from_feature(f, idx)))
+        .collect()
+}
+
+fn metadata_fields(metadata: &MapMetadata) -> Vec<(String, FieldValue)> {
+    let mut fields = vec![
+        ("authors".to_owned(), FieldValue::Text(format_authors_for_ui(&metadata.authors))),
+        ("game_mode".to_owned(), FieldValue::Text(metadata.game_mode.prefix().to_owned())),
+        ("title".to_owned(), FieldValue::Text(metadata.title.clone())),
+        ("description".to_owned(), FieldValue::Text(metadata.description.clone())),
+        ("version".to_owned(), FieldValue::Text(metadata.version.clone())),
+        ("min_players".to_owned(), FieldValue::Scalar(metadata.min_players as f32)),
+        ("max_players".to_owned(), FieldValue::Scalar(metadata.max_players as f32)),
+    ];
+    for (i, entry) in metadata.changelog.iter().enumerate() {
+        fields.push((format!("changelog[{}]", i), FieldValue::Text(format!("{}: {}", entry.version, entry.notes))));
+    }
+    for (key, value) in metadata.custom_values() {
+        fields.push((format!("custom.{}", key), FieldValue::Text(value.to_owned())));
+    }
+    fields.extend(metadata.environment.rows().into_iter().map(|(key, value)| (key.to_owned(), FieldValue::Text(value))));
+    fields
+}
+
+/// Changelog entries and custom values come and go, so fields are matched by name.
+fn diff_metadata(before: &MapMetadata, after: &MapMetadata) -> Vec<FieldChange> {
+    let before = metadata_fields(before);
+    let after = metadata_fields(after);
+    let find = |fields: &[(String, FieldValue)], field: &str| {
+        fields.iter().find(|(f, _)| f == field).map(|(_, v)| v.clone())
+    };
+    let mut changes: Vec<FieldChange> = before.iter()
+        .map(|(field, b)| FieldChange { field: field.clone(), before: Some(b.clone()), after: find(&after, field) })
+        .filter(|change| change.before != change.after)
+        .collect();
+    changes.extend(after.iter()
+        .filter(|(field, _)| find(&before, field).is_none())
+        .map(|(field, a)| FieldChange { field: field.clone(), before: None, after: Some(a.clone()) }));
+    changes
+}
+
+/// Compare two blueprints feature by feature. Features are matched by [`FeatureId`], so a feature
+/// that was deleted and recreated shows up as a removal plus an addition.
+pub fn diff(old: &LoadedBlueprint, new: &LoadedBlueprint) -> BlueprintDiff {
+    let old_snaps = timeline_snapshots(&old.timeline);
+    let new_snaps = timeline_snapshots(&new.timeline);
+    let new_map: HashMap<FeatureId, &FeatureSnapshot> = new_snaps.iter().map(|(id, s)| (*id, s)).collect();
+    let old_ids: HashSet<FeatureId> = old_snaps.iter().map(|(id, _)| *id).collect();
+
+    let mut features = Vec::new();
+    for (id, before) in &old_snaps {
+        match new_map.get(id) {
+            None => features.push(FeatureDiff { feature_id: *id, change: FeatureChange::Removed(before.clone()) }),
+            Some(after) => {
+                let fields = diff_data(&before.data, &after.data);
+                if !fields.is_empty() {
+                    features.push(FeatureDiff {
+                        feature_id: *id,
+                        change: FeatureChange::Modified {
+                            before: before.clone(),
+                            after: (*after).clone(),
+                            fields,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    for (id, after) in &new_snaps {
+        if !old_ids.contains(id) {
+            features.push(FeatureDiff { feature_id: *id, change: FeatureChange::Added(after.clone()) });
+        }
+    }
+    features.sort_by_key(|d| d.feature_id._id());
+
+    BlueprintDiff {
+        features,
+        metadata: diff_metadata(&old.metadata, &new.metadata),
+    }
+}
+
+fn remap_point_ref(pr: &mut PointRef, remap: &HashMap<FeatureId, FeatureId>) {
+    if let Some(new_id) = pr.reference.and_then(|id| remap.get(&id)) {
+        pr.reference = Some(*new_id);
+        pr.resolved_reference = None;
+    }
+}
+
+fn remap_snapshot(snap: &mut FeatureSnapshot, remap: &HashMap<FeatureId, FeatureId>) {
+    for parent in snap.parents.iter_mut() {
+        if let Some(new_id) = remap.get(parent) {
+            *parent = *new_id;
+        }
+    }
+    match &mut snap.data {
+        FeatureData::GlobalPoint { location }
+        | FeatureData::PointLight { location, .. }
+        | FeatureData::Sun { location, .. }
+        | FeatureData::Marker { location, .. }
+        | FeatureData::Logic { location, .. }
+        | FeatureData::Pickup { location, .. }
+        | FeatureData::Prop { location, .. }
+        | FeatureData::Note { location, .. } => {
+            remap_point_ref(location, remap);
+        }
+        FeatureData::SpotLight { location, target, .. } | FeatureData::AreaLight { location, target, .. } => {
+            remap_point_ref(location, remap);
+            remap_point_ref(target, remap);
+        }
+        FeatureData::Room { min, max } | FeatureData::Trigger { min, max, .. } | FeatureData::Liquid { min, max, .. } => {
+            remap_point_ref(min, remap);
+            remap_point_ref(max, remap);
+        }
+        FeatureData::Teleporter { min, max, destination, .. } => {
+            remap_point_ref(min, remap);
+            remap_point_ref(max, remap);
+            remap_point_ref(destination, remap);
+        }
+        FeatureData::JumpPad { min, max, apex } => {
+            remap_point_ref(min, remap);
+            remap_point_ref(max, remap);
+            remap_point_ref(apex, remap);
+        }
+        FeatureData::Stairs { start, end, .. } | FeatureData::Dimension { start, end, .. } => {
+            remap_point_ref(start, remap);
+            remap_point_ref(end, remap);
+        }
+        FeatureData::Cuboid { .. } => {}
+    }
+    if let FeatureData::Marker { connections, .. }
+        | FeatureData::Trigger { connections, .. }
+        | FeatureData::Logic { connections, .. } = &mut snap.data
+    {
+        remap_connections(connections, |id| remap.get(&id).copied());
+    }
+}
+
+fn referenced_ids(data: &FeatureData) -> Vec<FeatureId> {
+    data_fields(data).into_iter()
+        .filter_map(|(_, v)| match v {
+            FieldValue::Reference(id, _) => id,
+            _ => None,
+        })
+        .collect()
+}
+
+/// Three-way merge of a single feature present on all sides, field by field.
+fn merge_feature(
+    id: FeatureId,
+    base: &FeatureSnapshot,
+    ours: &FeatureSnapshot,
+    theirs: &FeatureSnapshot,
+    conflicts: &mut Vec<MergeConflict>,
+) -> FeatureSnapshot {
+    let base_kind = snapshot_data_kind(&base.data);
+    let ours_kind = snapshot_data_kind(&ours.data);
+    let theirs_kind = snapshot_data_kind(&theirs.data);
+    if ours_kind != theirs_kind {
+        if ours_kind == base_kind {
+            return theirs.clone();
+        }
+        if theirs_kind != base_kind {
+            conflicts.push(MergeConflict {
+                feature_id: Some(id),
+                reason: format!("changed to {} in ours but {} in theirs", ours_kind, theirs_kind),
+            });
+        }
+        return ours.clone();
+    }
+
+    let base_fields: HashMap<String, FieldValue> = data_fields(&base.data).into_iter().collect();
+    let theirs_fields: HashMap<String, FieldValue> = data_fields(&theirs.data).into_iter().collect();
+    let mut merged = ours.clone();
+    for (field, ours_value) in data_fields(&ours.data) {
+        let Some(theirs_value) = theirs_fields.get(&field) else { continue };
+        if *theirs_value == ours_value {
+            continue;
+        }
+        match base_fields.get(&field) {
+            Some(base_value) if *base_value == ours_value => {
+                set_data_field(&mut merged.data, &field, theirs_value);
+            }
+            Some(base_value) if base_value == theirs_value => {}
+            _ => conflicts.push(MergeConflict {
+                feature_id: Some(id),
+                reason: format!("{} is {} in ours but {} in theirs", field, ours_value, theirs_value),
+            }),
+        }
+    }
+    merged
+}
+
+fn merge_metadata(
+    base: &MapMetadata,
+    ours: &MapMetadata,
+    theirs: &MapMetadata,
+    conflicts: &mut Vec<MergeConflict>,
+) -> MapMetadata {
+    let mut merged = ours.clone();
+
+    if ours.authors == base.authors {
+        merged.authors = theirs.authors.clone();
+    } else if theirs.authors != base.authors {
+        // Both sides credited someone; keep everyone rather than calling it a conflict.
+        for author in &theirs.authors {
+            if !merged.authors.contains(author) {
+                merged.authors.push(author.clone());
+            }
+        }
+    }
+    merged.normalize_authors_ui_text();
+
+    if ours.game_mode == base.game_mode {
+        merged.game_mode = theirs.game_mode;
+    } else if theirs.game_mode != base.game_mode && theirs.game_mode != ours.game_mode {
+        conflicts.push(MergeConflict {
+            feature_id: None,
+            reason: format!(
+                "game_mode is {} in ours but {} in theirs",
+                ours.game_mode.prefix(),
+                theirs.game_mode.prefix(),
+            ),
+        });
+    }
+
+    merged.title = merge_value("title", &base.title, &ours.title, &theirs.title, conflicts);
+    merged.description = merge_value("description", &base.description, &ours.description, &theirs.description, conflicts);
+    merged.version = merge_value("version", &base.version, &ours.version, &theirs.version, conflicts);
+    merged.min_players = merge_value("min_players", &base.min_players, &ours.min_players, &theirs.min_players, conflicts);
+    merged.max_players = merge_value("max_players", &base.max_players, &ours.max_players, &theirs.max_players, conflicts)
+        .max(merged.min_players);
+
+    // Both sides' new changelog entries are kept, ours first.
+    for entry in &theirs.changelog {
+        if !base.changelog.contains(entry) && !merged.changelog.contains(entry) {
+            merged.changelog.push(entry.clone());
+        }
+    }
+
+    let custom_keys: Vec<&str> = ours.custom_values()
+        .chain(theirs.custom_values())
+        .chain(base.custom_values())
+        .map(|(key, _)| key)
+        .collect();
+    let mut custom = Vec::new();
+    for (i, key) in custom_keys.iter().enumerate() {
+        if custom_keys[..i].contains(key) { continue; }
+        let value = merge_value(
+            &format!("custom.{}", key),
+            &base.custom_value(key),
+            &ours.custom_value(key),
+            &theirs.custom_value(key),
+            conflicts,
+        );
+        if let Some(value) = value {
+            custom.push((key.to_string(), value.to_owned()));
+        }
+    }
+    merged.custom = custom;
+
+    // Environment settings merge independently; a field changed differently on both sides keeps ours.
+    let rows = base.environment.rows().into_iter()
+        .zip(ours.environment.rows())
+        .zip(theirs.environment.rows());
+    for (((key, base_value), (_, our_value)), (_, their_value)) in rows {
+        if our_value == base_value {
+            merged.environment.set_row(key, &their_value);
+        } else if their_value != base_value && their_value != our_value {
+            conflicts.push(MergeConflict {
+                feature_id: None,
+                reason: format!("{} is {} in ours but {} in theirs", key, our_value, their_value),
+            });
+        }
+    }
+
+    merged
+}
+
+/// Three-way merge of one value. When both sides changed it differently the conflict is recorded and ours is kept.
+fn merge_value<T: PartialEq + Clone + std::fmt::Debug>(
+    field: &str,
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    conflicts: &mut Vec<MergeConflict>,
+) -> T {
+    if ours == base {
+        return theirs.clone();
+    }
+    if theirs != base && theirs != ours {
+        conflicts.push(MergeConflict {
+            feature_id: None,
+            reason: format!("{} is {:?} in ours but {:?} in theirs", field, ours, theirs),
+        });
+    }
+    ours.clone()
+}
+
+/// Stable topological sort: keeps the given order except where a feature would precede a parent.
+fn order_parents_first(
+    order: Vec<FeatureId>,
+    snaps: &HashMap<FeatureId, FeatureSnapshot>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<FeatureId> {
+    let present: HashSet<FeatureId> = order.iter().copied().collect();
+    let mut placed: HashSet<FeatureId> = HashSet::new();
+    let mut remaining = order;
+    let mut out = Vec::with_capacity(remaining.len());
+
+    while !remaining.is_empty() {
+        let ready = remaining.iter().position(|id| {
+            referenced_ids(&snaps[id].data).iter()
+                .all(|p| p == id || !present.contains(p) || placed.contains(p))
+        });
+        let Some(idx) = ready else {
+            for id in &remaining {
+                conflicts.push(MergeConflict {
+                    feature_id: Some(*id),
+                    reason: "is part of a reference cycle created by the merge".to_owned(),
+                });
+            }
+            out.append(&mut remaining);
+            break;
+        };
+        let id = remaining.remove(idx);
+        placed.insert(id);
+        out.push(id);
+    }
+    out
+}
+
+/// Three-way merge of blueprints. `base` is the common ancestor of `ours` and `theirs`.
+///
+/// Features are matched by [`FeatureId`]. When both sides created different features under the
+/// same id, the ones from `theirs` are renumbered. Fields changed on both sides to different values
+/// are reported as conflicts and resolved in favour of `ours`. Only live features take part; ones
+/// undone past either side's rollback bar are left out. Undo history is not carried over, and
+/// [`MergeResult::dropped_history`] says how much of ours' was lost.
+pub fn merge(base: &LoadedBlueprint, ours: &LoadedBlueprint, theirs: &LoadedBlueprint) -> MergeResult {
+    let mut conflicts = Vec::new();
+
+    let base_snaps: HashMap<FeatureId, FeatureSnapshot> = timeline_snapshots(&base.timeline).into_iter().collect();
+    let ours_list = timeline_snapshots(&ours.timeline);
+    let ours_snaps: HashMap<FeatureId, FeatureSnapshot> = ours_list.iter().cloned().collect();
+    let mut theirs_list = timeline_snapshots(&theirs.timeline);
+
+    let mut id_counter = base.timeline.id_counter()
+        .max(ours.timeline.id_counter())
+        .max(theirs.timeline.id_counter());
+
+    // Renumber features both sides created independently under the same id.
+    let mut remap: HashMap<FeatureId, FeatureId> = HashMap::new();
+    for (id, snap) in &theirs_list {
+        if base_snaps.contains_key(id) {
+            continue;
+        }
+        if let Some(ours_snap) = ours_snaps.get(id) {
+            if same_data(&ours_snap.data, &snap.data) {
+                continue;
+            }
+            remap.insert(*id, FeatureId::from_raw(id_counter));
+            id_counter += 1;
+        }
+    }
+    for (id, snap) in theirs_list.iter_mut() {
+        if let Some(new_id) = remap.get(id) {
+            *id = *new_id;
+        }
+        remap_snapshot(snap, &remap);
+    }
+    let theirs_snaps: HashMap<FeatureId, FeatureSnapshot> = theirs_list.iter().cloned().collect();
+
+    let mut merged: HashMap<FeatureId, FeatureSnapshot> = HashMap::new();
+    let mut order: Vec<FeatureId> = Vec::new();
+
+    for (id, ours_snap) in &ours_list {
+        match (base_snaps.get(id), theirs_snaps.get(id)) {
+            (Some(base_snap), Some(theirs_snap)) => {
+                merged.insert(*id, merge_feature(*id, base_snap, ours_snap, theirs_snap, &mut conflicts));
+            }
+            (Some(base_snap), None) => {
+                if same_data(&base_snap.data, &ours_snap.data) {
+                    continue;
+                }
+                conflicts.push(MergeConflict {
+                    feature_id: Some(*id),
+                    reason: "modified in ours but deleted in theirs".to_owned(),
+                });
+                merged.insert(*id, ours_snap.clone());
+            }
+            (None, _) => {
+                merged.insert(*id, ours_snap.clone());
+            }
+        }
+        order.push(*id);
+    }
+
+    for (id, theirs_snap) in &theirs_list {
+        if ours_snaps.contains_key(id) {
+            continue;
+        }
+        if let Some(base_snap) = base_snaps.get(id) {
+            if same_data(&base_snap.data, &theirs_snap.data) {
+                continue;
+            }
+            conflicts.push(MergeConflict {
+                feature_id: Some(*id),
+                reason: "deleted in ours but modified in theirs".to_owned(),
+            });
+        }
+        merged.insert(*id, theirs_snap.clone());
+        order.push(*id);
+    }
+
+    // Bring back deleted features that something in the merge still points at.
+    loop {
+        let missing: Vec<(FeatureId, FeatureId)> = order.iter()
+            .flat_map(|id| referenced_ids(&merged[id].data).into_iter().map(move |r| (*id, r)))
+            .filter(|(_, r)| !merged.contains_key(r))
+            .collect();
+        if missing.is_empty() {
+            break;
+        }
+        let mut restored_any = false;
+        for (owner, referenced) in missing {
+            if merged.contains_key(&referenced) {
+                continue;
+            }
+            match base_snaps.get(&referenced) {
+                Some(base_snap) => {
+                    conflicts.push(MergeConflict {
+                        feature_id: Some(referenced),
+                        reason: format!("was deleted but #{} still references it; restored", owner),
+                    });
+                    merged.insert(referenced, base_snap.clone());
+                    order.push(referenced);
+                    restored_any = true;
+                }
+                None => conflicts.push(MergeConflict {
+                    feature_id: Some(owner),
+                    reason: format!("references #{}, which no longer exists", referenced),
+                }),
+            }
+        }
+        if !restored_any {
+            break;
+        }
+    }
+
+    let order = order_parents_first(order, &merged, &mut conflicts);
+
+    let mut features_map: HashMap<FeatureId, Feature> = HashMap::new();
+    for (idx, id) in order.iter().enumerate() {
+        let mut snap = merged.remove(id).expect("merged snapshot for ordered feature");
+        snap.order_index = idx;
+        let object = snap.blank_object();
+        let parents = object.parent_ids();
+        features_map.insert(*id, Feature::new(*id, object, parents));
+    }
+    let rollback_bar = order.len() as u64;
+    let mut timeline = FeatureTimeline::from_parts(features_map, order, id_counter, rollback_bar, vec![]);
+
+    // Symmetry settings follow ours; counterpart links from both sides are kept where they don't clash.
+    let mut symmetry = ours.timeline.symmetry().clone();
+    for (a, b) in theirs.timeline.symmetry().links() {
+        let a = remap.get(&a).copied().unwrap_or(a);
+        let b = remap.get(&b).copied().unwrap_or(b);
+        if symmetry.counterpart(a).is_none() && symmetry.counterpart(b).is_none() {
+            symmetry.link(a, b);
+        }
+    }
+    *timeline.symmetry_mut() = symmetry;
+    timeline.resolve_all_references();
+
+    let metadata = merge_metadata(&base.metadata, &ours.metadata, &theirs.metadata, &mut conflicts);
+
+    MergeResult {
+        blueprint: LoadedBlueprint { timeline, metadata },
+        conflicts,
+        dropped_history: ours.timeline.applied_actions().len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editable::FeatureTrait;
+    use crate::editor::editor_room::EditorRoom;
+    use crate::editor::global_point::GlobalPoint;
+    use crate::editor::grackle_point_light::GracklePointLight;
+    use crate::editor::map_metadata::ChangelogEntry;
+
+    fn blueprint(objects: Vec<(u64, Box<dyn FeatureTrait>)>) -> LoadedBlueprint {
+        let mut features_map = HashMap::new();
+        let mut order = Vec::new();
+        let mut id_counter = 0;
+        for (raw, object) in objects {
+            let id = FeatureId::from_raw(raw);
+            let parents = object.parent_ids();
+            features_map.insert(id, Feature::new(id, object, parents));
+            order.push(id);
+            id_counter = id_counter.max(raw + 1);
+        }
+        let rollback_bar = order.len() as u64;
+        LoadedBlueprint {
+            timeline: FeatureTimeline::from_parts(features_map, order, id_counter, rollback_bar, vec![]),
+            metadata: MapMetadata::default(),
+        }
+    }
+
+    fn point(x: f32) -> Box<dyn FeatureTrait> {
+        Box::new(GlobalPoint::new(x, 0.0, 0.0))
+    }
+
+    fn light(intensity: f32, radius: f32) -> Box<dyn FeatureTrait> {
+        let mut light = GracklePointLight::new(0.0, 0.0, 0.0);
+        light.set_scalar_field("intensity", intensity);
+        light.set_scalar_field("radius", radius);
+        Box::new(light)
+    }
+
+    fn location_x(blueprint: &LoadedBlueprint, raw: u64) -> f32 {
+        let feature = blueprint.timeline.get_feature(&FeatureId::from_raw(raw)).unwrap();
+        match feature.object().snapshot() {
+            FeatureData::GlobalPoint { location } => location.x.value(),
+            _ => panic!("expected a global point"),
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_modified() {
+        let old = blueprint(vec![(0, point(1.0)), (1, point(2.0))]);
+        let new = blueprint(vec![(0, point(5.0)), (2, point(3.0))]);
+        let d = diff(&old, &new);
+        assert_eq!(d.features.len(), 3);
+        let FeatureChange::Modified { fields, .. } = &d.features[0].change else { panic!("expected modified") };
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].field, "location.x");
+        assert!(matches!(d.features[1].change, FeatureChange::Removed(_)));
+        assert!(matches!(d.features[2].change, FeatureChange::Added(_)));
+    }
+
+    #[test]
+    fn diff_of_identical_blueprints_is_empty() {
+        let a = blueprint(vec![(0, point(1.0)), (1, light(800.0, 0.1))]);
+        let b = blueprint(vec![(0, point(1.0)), (1, light(800.0, 0.1))]);
+        assert!(diff(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn merge_takes_non_overlapping_field_edits_from_both_sides() {
+        let base = blueprint(vec![(0, light(800.0, 0.1))]);
+        let ours = blueprint(vec![(0, light(1200.0, 0.1))]);
+        let theirs = blueprint(vec![(0, light(800.0, 0.5))]);
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        let merged = result.blueprint.timeline.get_feature(&FeatureId::from_raw(0)).unwrap();
+        let FeatureData::PointLight { intensity, radius, .. } = merged.object().snapshot() else {
+            panic!("expected a point light");
+        };
+        assert_eq!(intensity, 1200.0);
+        assert_eq!(radius, 0.5);
+    }
+
+    #[test]
+    fn merge_reports_conflicting_field_and_keeps_ours() {
+        let base = blueprint(vec![(0, point(1.0))]);
+        let ours = blueprint(vec![(0, point(2.0))]);
+        let theirs = blueprint(vec![(0, point(3.0))]);
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(location_x(&result.blueprint, 0), 2.0);
+    }
+
+    #[test]
+    fn merge_renumbers_colliding_additions_and_their_references() {
+        let base = blueprint(vec![(0, point(0.0))]);
+        let ours = blueprint(vec![(0, point(0.0)), (1, point(10.0))]);
+        let theirs = blueprint(vec![
+            (0, point(0.0)),
+            (1, point(20.0)),
+            (2, Box::new(EditorRoom::from_points(FeatureId::from_raw(0), FeatureId::from_raw(1)))),
+        ]);
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        let timeline = &result.blueprint.timeline;
+        assert_eq!(timeline.feature_order().len(), 4);
+        assert_eq!(location_x(&result.blueprint, 1), 10.0);
+        assert_eq!(location_x(&result.blueprint, 3), 20.0);
+        let room = timeline.get_feature(&FeatureId::from_raw(2)).unwrap();
+        assert_eq!(room.parents().len(), 2);
+        assert!(room.parents().contains(&FeatureId::from_raw(3)));
+        assert_eq!(timeline.id_counter(), 4);
+    }
+
+    #[test]
+    fn merge_restores_deleted_feature_still_referenced() {
+        let base = blueprint(vec![(0, point(0.0)), (1, point(1.0))]);
+        let ours = blueprint(vec![(0, point(0.0))]);
+        let theirs = blueprint(vec![
+            (0, point(0.0)),
+            (1, point(1.0)),
+            (2, Box::new(EditorRoom::from_points(FeatureId::from_raw(0), FeatureId::from_raw(1)))),
+        ]);
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let order = result.blueprint.timeline.feature_order();
+        let pos = |raw| order.iter().position(|id| *id == FeatureId::from_raw(raw)).unwrap();
+        assert!(pos(1) < pos(2));
+    }
+
+    #[test]
+    fn merge_leaves_out_features_past_the_rollback_bar() {
+        let base = blueprint(vec![(0, point(0.0))]);
+        let mut ours = blueprint(vec![(0, point(0.0)), (1, point(1.0))]);
+        ours.timeline.undo();
+        let theirs = blueprint(vec![(0, point(0.0)), (2, point(2.0))]);
+        assert!(diff(&base, &ours).is_empty());
+
+        let result = merge(&base, &ours, &theirs);
+        let timeline = &result.blueprint.timeline;
+        assert_eq!(timeline.feature_order(), &[FeatureId::from_raw(0), FeatureId::from_raw(2)]);
+        assert_eq!(timeline.rollback_bar(), 2);
+    }
+
+    #[test]
+    fn merge_takes_environment_edits_from_both_sides() {
+        let base = blueprint(vec![]);
+        let mut ours = blueprint(vec![]);
+        ours.metadata.environment.gravity = 9.81;
+        let mut theirs = blueprint(vec![]);
+        theirs.metadata.environment.fog_enabled = true;
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.blueprint.metadata.environment.gravity, 9.81);
+        assert!(result.blueprint.metadata.environment.fog_enabled);
+    }
+
+    #[test]
+    fn merge_combines_metadata_changelog_and_custom_values() {
+        let base = blueprint(vec![]);
+        let mut ours = blueprint(vec![]);
+        ours.metadata.title = "Quarry".to_owned();
+        ours.metadata.changelog.push(ChangelogEntry { version: "1.1".to_owned(), notes: "Wider mid".to_owned() });
+        ours.metadata.custom.push(("respawn_time".to_owned(), "5".to_owned()));
+        let mut theirs = blueprint(vec![]);
+        theirs.metadata.title = "Old Quarry".to_owned();
+        theirs.metadata.changelog.push(ChangelogEntry { version: "1.1".to_owned(), notes: "New skybox".to_owned() });
+        theirs.metadata.custom.push(("time_limit".to_owned(), "600".to_owned()));
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let merged = &result.blueprint.metadata;
+        assert_eq!(merged.title, "Quarry");
+        assert_eq!(merged.changelog.len(), 2);
+        assert_eq!(merged.custom_value("respawn_time"), Some("5"));
+        assert_eq!(merged.custom_value("time_limit"), Some("600"));
+    }
+
+    #[test]
+    fn diff_reports_added_custom_value() {
+        let old = blueprint(vec![]);
+        let mut new = blueprint(vec![]);
+        new.metadata.custom.push(("time_limit".to_owned(), "600".to_owned()));
+        let changes = diff(&old, &new);
+        assert_eq!(changes.metadata.len(), 1);
+        assert!(changes.metadata[0].before.is_none());
+    }
+}
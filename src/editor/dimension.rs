@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{Feature, FeatureId, FeatureTrait, PointRef};
+use crate::get;
+use crate::tool::tool_helpers::draw_taxicab_path;
+
+/// Size of the ticks across each end of a dimension line.
+const TICK: f32 = 0.2;
+
+/// The distance between two points, and how far apart they are along each axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Measurement {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl Measurement {
+    pub fn new(start: Vec3, end: Vec3) -> Self {
+        Self { start, end }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    /// Unsigned distance along each axis.
+    pub fn components(&self) -> Vec3 {
+        (self.end - self.start).abs()
+    }
+
+    /// Distance across the floor plan, ignoring height; the part of a jump covered in the air.
+    pub fn horizontal(&self) -> f32 {
+        self.start.xz().distance(self.end.xz())
+    }
+
+    pub fn midpoint(&self) -> Vec3 {
+        (self.start + self.end) / 2.0
+    }
+
+    /// The total on one line and the per-axis breakdown on the next.
+    pub fn label(&self) -> String {
+        let d = self.components();
+        format!(
+            "{}\n{}",
+            get!("editor.features.dimension.total", "total", format!("{:.2}", self.total())),
+            get!("editor.features.dimension.components",
+                "x", format!("{:.2}", d.x), "y", format!("{:.2}", d.y), "z", format!("{:.2}", d.z)),
+        )
+    }
+
+    /// `label`, headed by `note` when there is one.
+    pub fn annotated_label(&self, note: &str) -> String {
+        if note.is_empty() { self.label() } else { format!("{}\n{}", note, self.label()) }
+    }
+
+    /// The measured line with ticks at both ends, and its per-axis legs.
+    pub fn draw(&self, gizmos: &mut Gizmos, color: Color) {
+        gizmos.line(self.start, self.end, color);
+        let along = (self.end - self.start).normalize_or_zero();
+        let across = along.any_orthonormal_vector() * TICK;
+        for end in [self.start, self.end] {
+            gizmos.line(end - across, end + across, color);
+        }
+        draw_taxicab_path(gizmos, self.start, self.end);
+    }
+}
+
+/// A pinned measurement between two points, kept in the blueprint to document distances such as
+/// jumps and sightlines. It follows the points it references and has no presence in the game.
+#[derive(Serialize, Deserialize)]
+pub struct Dimension {
+    start: PointRef,
+    end: PointRef,
+    pub note: String,
+    #[serde(skip)]
+    resolved_start: Vec3,
+    #[serde(skip)]
+    resolved_end: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl Dimension {
+    pub fn from_point_refs(start: PointRef, end: PointRef) -> Self {
+        Self {
+            start,
+            end,
+            note: String::new(),
+            resolved_start: Vec3::ZERO,
+            resolved_end: Vec3::ZERO,
+            entity: None,
+        }
+    }
+
+    pub fn measurement(&self) -> Measurement {
+        Measurement::new(self.resolved_start, self.resolved_end)
+    }
+}
+
+#[typetag::serde(name = "dimension")]
+impl FeatureTrait for Dimension {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        match key {
+            "start" => Ok(self.resolved_start),
+            "end" => Ok(self.resolved_end),
+            _ => Ok(self.measurement().midpoint()),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.start.editor_ui(ui, "Start", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.end.editor_ui(ui, "End", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.dimension.note"));
+            changed |= ui.text_edit_singleline(&mut self.note).changed();
+        });
+        ui.label(self.measurement().label());
+        ui.label(get!("editor.features.dimension.horizontal", "distance", format!("{:.2}", self.measurement().horizontal())));
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.dimension.title")
+    }
+
+    fn type_key(&self) -> &'static str { "dimension" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Dimension {
+            start: self.start.clone(),
+            end: self.end.clone(),
+            note: self.note.clone(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Dimension { start, end, note } = data else { return; };
+        self.start = start.clone();
+        self.end = end.clone();
+        self.note = note.clone();
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        self.measurement().draw(gizmos, Color::srgb_u8(255, 220, 120));
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert(Transform::from_translation(self.measurement().midpoint()));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.start.resolve(features) {
+            self.resolved_start = v;
+        }
+        if let Ok(v) = self.end.resolve(features) {
+            self.resolved_end = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.start.referenced_features();
+        for id in self.end.referenced_features() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Midpoint".into()),
+            ("start".into(), "Start".into()),
+            ("end".into(), "End".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![("".into(), self.measurement().midpoint())]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["start", "end"] }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![("note", self.note.clone())]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        if key == "note" {
+            self.note = value.to_owned();
+        }
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Start" | "start" => Some(&self.start),
+            "End" | "end" => Some(&self.end),
+            _ => None,
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Start" | "start" => Some(&mut self.start),
+            "End" | "end" => Some(&mut self.end),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurement_breaks_down_by_axis() {
+        let m = Measurement::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 0.0, -1.0));
+        assert_eq!(m.components(), Vec3::new(3.0, 2.0, 4.0));
+        assert!((m.total() - 29f32.sqrt()).abs() < 1e-5);
+        assert!((m.horizontal() - 5.0).abs() < 1e-5);
+        assert_eq!(m.midpoint(), Vec3::new(2.5, 1.0, 1.0));
+    }
+}
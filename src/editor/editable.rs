@@ -3,19 +3,39 @@ use std::path::PathBuf;
 use std::rc::Rc;
 use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
-use bevy_egui::{egui, EguiContexts};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
 use lazy_static::lazy_static;
 use serde::{Serialize, Deserialize};
 use crate::common::PointResolutionError;
 use crate::constants::MAP_BLUEPRINT_EXTENSION;
-use crate::editor::action::{Action, FeatureDelta, FeatureSnapshot};
+use crate::editor::action::{Action, FeatureDelta, FeatureSnapshot, SymmetryChange};
 use crate::editor::action::FeatureData;
+use crate::editor::asset_browser::AssetBrowserState;
+use crate::editor::balance::{BalanceState, draw_balance_gizmos};
+use crate::editor::diff::same_data;
 use crate::editor::editor_room::EditorRoom;
 use crate::editor::global_point::GlobalPoint;
+use crate::editor::grackle_area_light::GrackleAreaLight;
 use crate::editor::grackle_point_light::GracklePointLight;
+use crate::editor::grackle_spot_light::GrackleSpotLight;
+use crate::editor::grackle_sun::GrackleSun;
+use crate::editor::jump_pad::{JumpPad, draw_jump_pad_arcs};
+use crate::editor::liquid_volume::{LiquidContainment, LiquidVolume, VolumeKind, draw_liquid_warnings, update_liquid_containment, update_liquid_meshes};
+use crate::editor::logic::{LogicConnection, draw_logic_connections};
+use crate::editor::logic_entity::{LogicEntity, LogicKind};
+use crate::editor::map_marker::{MapMarker, MarkerKind, Team};
 use crate::editor::map_metadata::MapMetadata;
+use crate::editor::pickup::{Pickup, PickupKind};
+use crate::editor::prop::{Prop, load_prop_scenes, measure_prop_scenes};
 use crate::editor::save;
+use crate::editor::stairs::Stairs;
+use crate::editor::dimension::Dimension;
+use crate::editor::note::{Note, draw_note_labels};
+use crate::editor::symmetry::{Symmetry, SymmetrySettings, draw_symmetry_gizmos};
+use crate::editor::teleporter::Teleporter;
+use crate::editor::trigger_volume::TriggerVolume;
 use crate::get;
+use crate::tool::playtest::PlaytestState;
 
 lazy_static! {
     static ref MAP_EXT: String = "gmp".to_owned(); // Grackle MaP
@@ -29,14 +49,25 @@ impl Plugin for EditorStepsPlugin {
         app
             .init_resource::<FeatureTimeline>()
             .init_resource::<MapMetadata>()
+            .init_resource::<BalanceState>()
+            .init_resource::<LiquidContainment>()
+            .init_resource::<AssetBrowserState>()
             .add_message::<EditEvent>()
             .add_systems(Startup, load_startup_blueprint)
             .add_systems(Update, (
-                FeatureTimeline::undo_redo_shortcuts,
+                FeatureTimeline::undo_redo_shortcuts.run_if(in_state(PlaytestState::Off)),
                 FeatureTimeline::sync_entities,
                 FeatureTimeline::handle_edits,
                 FeatureTimeline::draw_affected_gizmos,
             ).chain())
+            .add_systems(Update, draw_symmetry_gizmos)
+            .add_systems(Update, draw_balance_gizmos)
+            .add_systems(Update, draw_logic_connections)
+            .add_systems(Update, draw_jump_pad_arcs)
+            .add_systems(Update, (load_prop_scenes, measure_prop_scenes).chain().after(FeatureTimeline::sync_entities))
+            .add_systems(Update, update_liquid_meshes.after(FeatureTimeline::sync_entities))
+            .add_systems(Update, (update_liquid_containment, draw_liquid_warnings).chain())
+            .add_systems(EguiPrimaryContextPass, draw_note_labels.run_if(in_state(PlaytestState::Off)))
         ;
     }
 }
@@ -98,6 +129,16 @@ pub trait FeatureTrait: Send + Sync {
     /// object with drag handles. Used to position handles.
     fn drag_handle_bounds(&self) -> Option<(Vec3, Vec3)> { None }
 
+    /// Box used for picking and hover outlines. Defaults to the drag handle
+    /// bounds; objects without drag handles can still be picked by their bounds.
+    fn selection_bounds(&self) -> Option<(Vec3, Vec3)> { self.drag_handle_bounds() }
+
+    /// Model-space bounds of a loaded mesh, for objects whose size comes from an asset.
+    fn set_model_bounds(&mut self, _min: Vec3, _max: Vec3) {}
+
+    /// The bounds last given to `set_model_bounds`, if the object keeps them.
+    fn model_bounds(&self) -> Option<(Vec3, Vec3)> { None }
+
     /// Return all named PointRef slots on this object (for save/load).
     fn point_ref_slots(&self) -> Vec<&str> { vec![] }
 
@@ -107,6 +148,24 @@ pub trait FeatureTrait: Send + Sync {
     /// Set a scalar field by name (for loading).
     fn set_scalar_field(&mut self, _key: &str, _value: f32) {}
 
+    /// Return extra text fields for save/load (e.g. a marker's team).
+    fn text_fields(&self) -> Vec<(&str, String)> { vec![] }
+
+    /// Set a text field by name (for loading).
+    fn set_text_field(&mut self, _key: &str, _value: &str) {}
+
+    /// Names of the logic outputs this object can fire (e.g. a trigger's `OnStartTouch`).
+    fn logic_outputs(&self) -> Vec<&'static str> { vec![] }
+
+    /// Names of the logic inputs other features can wire to.
+    fn logic_inputs(&self) -> Vec<&'static str> { vec![] }
+
+    /// Outgoing logic connections, fired from this object's outputs.
+    fn connections(&self) -> &[LogicConnection] { &[] }
+
+    /// Replace the outgoing logic connections (for loading).
+    fn set_connections(&mut self, _connections: Vec<LogicConnection>) {}
+
     /// Get a reference to a named PointRef on this object.
     fn get_point_ref(&self, _key: &str) -> Option<&PointRef> { None }
 
@@ -122,10 +181,45 @@ pub fn create_object_from_type_key(type_key: &str) -> Option<Box<dyn FeatureTrai
     match type_key {
         "global_point" => Some(Box::new(GlobalPoint::new(0.0, 0.0, 0.0))),
         "grackle_point_light" => Some(Box::new(GracklePointLight::new(0.0, 0.0, 0.0))),
+        "grackle_spot_light" => Some(Box::new(GrackleSpotLight::new(0.0, 0.0, 0.0))),
+        "grackle_sun" => Some(Box::new(GrackleSun::new(0.0, 0.0, 0.0))),
+        "grackle_area_light" => Some(Box::new(GrackleAreaLight::new(0.0, 0.0, 0.0))),
         "editor_room" => Some(Box::new(EditorRoom::from_point_refs(
             PointRef::absolute(0.0, 0.0, 0.0),
             PointRef::absolute(0.0, 0.0, 0.0),
         ))),
+        "map_marker" => Some(Box::new(MapMarker::new(0.0, 0.0, 0.0, MarkerKind::Spawn, Team::Neutral))),
+        "trigger_volume" => Some(Box::new(TriggerVolume::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+        ))),
+        "logic_entity" => Some(Box::new(LogicEntity::new(0.0, 0.0, 0.0, LogicKind::Relay))),
+        "teleporter" => Some(Box::new(Teleporter::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+        ))),
+        "jump_pad" => Some(Box::new(JumpPad::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+        ))),
+        "pickup" => Some(Box::new(Pickup::new(0.0, 0.0, 0.0, PickupKind::HealthSmall))),
+        "prop" => Some(Box::new(Prop::new(0.0, 0.0, 0.0, ""))),
+        "liquid_volume" => Some(Box::new(LiquidVolume::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+            VolumeKind::Water,
+        ))),
+        "stairs" => Some(Box::new(Stairs::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+        ))),
+        "dimension" => Some(Box::new(Dimension::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(0.0, 0.0, 0.0),
+        ))),
+        "note" => Some(Box::new(Note::from_point_ref(PointRef::absolute(0.0, 0.0, 0.0), ""))),
         _ => None,
     }
 }
@@ -152,11 +246,15 @@ pub struct FeatureTimeline {
     /// Topologically sorted list of the selected feature and all its DAG descendants.
     /// Parents always appear before their dependants.
     selection_affected: Option<Vec<FeatureId>>,
+    /// Features Ctrl-clicked into the selection alongside `selected_feature`, which stays the
+    /// active one that multi-feature operations work relative to.
+    also_selected: Vec<FeatureId>,
     rollback_bar: u64,
     pending_despawns: Vec<Entity>,
     actions: Vec<Action>,
     action_cursor: usize,
     pending_snapshot: Option<(FeatureId, FeatureSnapshot)>,
+    symmetry: Symmetry,
 }
 
 impl Default for FeatureTimeline {
@@ -167,11 +265,13 @@ impl Default for FeatureTimeline {
             id_counter: 0,
             selected_feature: None,
             selection_affected: None,
+            also_selected: vec![],
             rollback_bar: 0,
             pending_despawns: vec![],
             actions: vec![],
             action_cursor: 0,
             pending_snapshot: None,
+            symmetry: Symmetry::default(),
         }
     }
 }
@@ -191,11 +291,13 @@ impl FeatureTimeline {
             id_counter,
             selected_feature: None,
             selection_affected: None,
+            also_selected: vec![],
             rollback_bar,
             pending_despawns: vec![],
             actions,
             action_cursor,
             pending_snapshot: None,
+            symmetry: Symmetry::default(),
         }
     }
 
@@ -215,10 +317,19 @@ impl FeatureTimeline {
                 before: Some(before),
                 after: None,
             }],
+            symmetry: None,
         });
         self.remove_feature_internal(feature_id);
     }
 
+    pub fn symmetry(&self) -> &Symmetry {
+        &self.symmetry
+    }
+
+    pub fn symmetry_mut(&mut self) -> &mut Symmetry {
+        &mut self.symmetry
+    }
+
     pub fn id_counter(&self) -> u64 {
         self.id_counter
     }
@@ -234,27 +345,79 @@ impl FeatureTimeline {
     }
     
     pub fn select(&mut self, selection: Option<FeatureId>) {
+        if selection != self.selected_feature {
+            self.also_selected.clear();
+        }
         self.selected_feature = selection;
-        self.selection_affected = selection.map(|root| {
-            let mut result = vec![root];
-            let mut visited: HashSet<FeatureId> = HashSet::from([root]);
-            let mut queue = vec![root];
-
-            while !queue.is_empty() {
-                let parent_set: HashSet<FeatureId> = queue.drain(..).collect();
-
-                let children: Vec<FeatureId> = self.features.iter()
-                    .filter(|(_, feature)| feature.parents.iter().any(|p| parent_set.contains(p)))
-                    .map(|(id, _)| *id)
-                    .filter(|id| visited.insert(*id))
-                    .collect();
-
-                result.extend(&children);
-                queue = children;
+        self.selection_affected = selection.map(|root| self.affected_by(root));
+    }
+
+    /// `root` followed by every feature that depends on it, directly or through other features.
+    pub fn affected_by(&self, root: FeatureId) -> Vec<FeatureId> {
+        let mut result = vec![root];
+        let mut visited: HashSet<FeatureId> = HashSet::from([root]);
+        let mut queue = vec![root];
+
+        while !queue.is_empty() {
+            let parent_set: HashSet<FeatureId> = queue.drain(..).collect();
+
+            let children: Vec<FeatureId> = self.features.iter()
+                .filter(|(_, feature)| feature.parents.iter().any(|p| parent_set.contains(p)))
+                .map(|(id, _)| *id)
+                .filter(|id| visited.insert(*id))
+                .collect();
+
+            result.extend(&children);
+            queue = children;
+        }
+
+        result
+    }
+
+    /// Every selected feature followed by everything that depends on them, each once.
+    pub fn selected_and_affected(&self) -> Vec<FeatureId> {
+        let mut result: Vec<FeatureId> = vec![];
+        for id in self.selected_features() {
+            for affected in self.affected_by(id) {
+                if !result.contains(&affected) {
+                    result.push(affected);
+                }
+            }
+        }
+        result
+    }
+
+    /// Add `id` to the selection, or take it out if it is already there. With nothing selected it
+    /// becomes the active feature.
+    pub fn toggle_selected(&mut self, id: FeatureId) {
+        match self.selected_feature {
+            None => self.select(Some(id)),
+            Some(active) if active == id => {
+                let next = (!self.also_selected.is_empty()).then(|| self.also_selected.remove(0));
+                let rest = std::mem::take(&mut self.also_selected);
+                self.select(next);
+                self.also_selected = rest;
             }
+            Some(_) => {
+                if let Some(i) = self.also_selected.iter().position(|f| *f == id) {
+                    self.also_selected.remove(i);
+                } else {
+                    self.also_selected.push(id);
+                }
+            }
+        }
+    }
 
-            result
-        });
+    /// The active feature followed by every other selected feature that still exists.
+    pub fn selected_features(&self) -> Vec<FeatureId> {
+        self.selected_feature.into_iter()
+            .chain(self.also_selected.iter().copied())
+            .filter(|id| self.features.contains_key(id))
+            .collect()
+    }
+
+    pub fn is_selected(&self, id: FeatureId) -> bool {
+        self.selected_feature == Some(id) || self.also_selected.contains(&id)
     }
 
     pub fn selection_affected(&self) -> Option<&[FeatureId]> {
@@ -330,11 +493,26 @@ impl FeatureTimeline {
                     order_index,
                 }),
             }],
+            symmetry: None,
         });
 
         new_id
     }
     
+    /// Resolve every feature's point references in timeline order (after loading or merging).
+    pub fn resolve_all_references(&mut self) {
+        for id in self.feature_order.clone() {
+            self.resolve_feature(id);
+        }
+    }
+
+    fn resolve_feature(&mut self, id: FeatureId) {
+        if let Some(mut feature) = self.features.remove(&id) {
+            feature.object.resolve_references(&self.features);
+            self.features.insert(id, feature);
+        }
+    }
+
     pub fn get_feature(&self, id: &FeatureId) -> Option<&Feature> {
         self.features.get(id)
     }
@@ -372,7 +550,12 @@ impl FeatureTimeline {
         self.action_cursor < self.actions.len()
     }
 
-    pub fn record_action(&mut self, action: Action) {
+    pub fn record_action(&mut self, mut action: Action) {
+        self.mirror_action(&mut action);
+        self.push_action(action);
+    }
+
+    fn push_action(&mut self, action: Action) {
         // Drop any redo branch: actions past the cursor are destroyed and never reapplied.
         if self.action_cursor < self.actions.len() {
             self.actions.drain(self.action_cursor..);
@@ -387,6 +570,118 @@ impl FeatureTimeline {
         self.action_cursor = self.actions.len();
     }
 
+    /// With symmetry editing on, applies the mirrored version of every delta to the feature's
+    /// counterpart (creating and linking one if needed) and appends those deltas to `action`,
+    /// so a single undo reverts both halves.
+    fn mirror_action(&mut self, action: &mut Action) {
+        if !self.symmetry.enabled {
+            return;
+        }
+        let touched: HashSet<FeatureId> = action.deltas.iter().map(|d| d.feature_id).collect();
+        let mut mirrored = Vec::new();
+
+        for delta in action.deltas.iter() {
+            let id = delta.feature_id;
+            if self.symmetry.origin == Some(id) {
+                continue;
+            }
+            let data = delta.after.as_ref().or(delta.before.as_ref()).map(|s| &s.data);
+            if data.is_some_and(|data| !Symmetry::mirrors(data)) {
+                continue;
+            }
+            let counterpart = self.symmetry.counterpart(id);
+            if counterpart.is_some_and(|c| touched.contains(&c)) {
+                continue;
+            }
+            let existing = counterpart.filter(|c| self.features.contains_key(c));
+
+            match (&delta.after, existing) {
+                (None, Some(c)) => {
+                    let before = self.snapshot_feature(c);
+                    self.apply_delta(c, None);
+                    mirrored.push(FeatureDelta { feature_id: c, before, after: None });
+                }
+                (Some(after), Some(c)) => {
+                    let Some(before) = self.snapshot_feature(c) else { continue };
+                    let data = self.symmetry.mirror_data(&after.data, &self.features);
+                    if same_data(&before.data, &data) {
+                        continue;
+                    }
+                    self.apply_delta(c, Some(&FeatureSnapshot::from_data(data, before.order_index)));
+                    self.resolve_feature(c);
+                    let after_c = self.snapshot_feature(c);
+                    mirrored.push(FeatureDelta { feature_id: c, before: Some(before), after: after_c });
+                }
+                (Some(after), None) => {
+                    // The counterpart was deleted on purpose; unlink first to mirror this feature again.
+                    if counterpart.is_some() {
+                        continue;
+                    }
+                    if self.symmetry.is_self_symmetric_data(&after.data, &self.features) {
+                        continue;
+                    }
+                    let data = self.symmetry.mirror_data(&after.data, &self.features);
+                    let new_id = self.next_id();
+                    let snapshot = FeatureSnapshot::from_data(data, self.feature_order.len());
+                    let rollback_at_end = self.rollback_bar == self.feature_order.len() as u64;
+                    self.apply_delta(new_id, Some(&snapshot));
+                    if rollback_at_end {
+                        self.rollback_bar = self.feature_order.len() as u64;
+                    }
+                    self.resolve_feature(new_id);
+                    self.symmetry.link(id, new_id);
+                    mirrored.push(FeatureDelta { feature_id: new_id, before: None, after: self.snapshot_feature(new_id) });
+                }
+                (None, None) => {}
+            }
+        }
+
+        action.deltas.extend(mirrored);
+    }
+
+    /// Change the symmetry settings as one undoable action. When symmetry editing is on and the
+    /// mode or origin changes, the later feature of every linked pair is mirrored afresh from the
+    /// earlier one in the same action, so counterparts follow the new plane.
+    pub fn set_symmetry(&mut self, settings: SymmetrySettings) {
+        let before = self.symmetry.settings();
+        if before == settings {
+            return;
+        }
+        self.symmetry.restore(&settings);
+        let moved = settings.mode != before.mode || settings.origin != before.origin;
+        let deltas = if settings.enabled && moved { self.remirror_counterparts() } else { vec![] };
+        self.push_action(Action { deltas, symmetry: Some(SymmetryChange { before, after: settings }) });
+    }
+
+    /// Re-derives the later feature of each linked pair from the earlier one, in timeline order so
+    /// counterparts of referenced features are placed before their dependants.
+    fn remirror_counterparts(&mut self) -> Vec<FeatureDelta> {
+        let position = |id: FeatureId| self.feature_order.iter().position(|f| *f == id);
+        let mut pairs: Vec<(usize, FeatureId, FeatureId)> = self.symmetry.links().into_iter()
+            .filter(|(a, b)| self.symmetry.origin != Some(*a) && self.symmetry.origin != Some(*b))
+            .filter_map(|(a, b)| match (position(a)?, position(b)?) {
+                (pa, pb) if pa < pb => Some((pa, a, b)),
+                (_, pb) => Some((pb, b, a)),
+            })
+            .collect();
+        pairs.sort_by_key(|(index, _, _)| *index);
+
+        let mut deltas = Vec::new();
+        for (_, source, target) in pairs {
+            let (Some(source), Some(before)) = (self.snapshot_feature(source), self.snapshot_feature(target)) else { continue };
+            let data = self.symmetry.mirror_data(&source.data, &self.features);
+            if same_data(&before.data, &data) {
+                continue;
+            }
+            self.apply_delta(target, Some(&FeatureSnapshot::from_data(data, before.order_index)));
+            self.resolve_feature(target);
+            let after = self.snapshot_feature(target);
+            deltas.push(FeatureDelta { feature_id: target, before: Some(before), after });
+        }
+        self.resolve_all_references();
+        deltas
+    }
+
     pub fn undo_action(&mut self) {
         if !self.can_undo_action() { return; }
         let idx = self.action_cursor - 1;
@@ -394,6 +689,9 @@ impl FeatureTimeline {
         for delta in action.deltas.iter().rev() {
             self.apply_delta(delta.feature_id, delta.before.as_ref());
         }
+        if let Some(change) = &action.symmetry {
+            self.symmetry.restore(&change.before);
+        }
         self.action_cursor -= 1;
         self.clamp_rollback_bar();
     }
@@ -406,6 +704,9 @@ impl FeatureTimeline {
         for delta in action.deltas.iter() {
             self.apply_delta(delta.feature_id, delta.after.as_ref());
         }
+        if let Some(change) = &action.symmetry {
+            self.symmetry.restore(&change.after);
+        }
         self.action_cursor += 1;
         self.clamp_rollback_bar();
         if was_at_end {
@@ -453,9 +754,34 @@ impl FeatureTimeline {
                 before: Some(before),
                 after: Some(after),
             }],
+            symmetry: None,
         });
     }
 
+    /// Apply several feature changes as one undoable action. `None` removes a feature, and a
+    /// snapshot for an id that is not in the timeline inserts it at the snapshot's order index.
+    pub fn apply_changes(&mut self, changes: Vec<(FeatureId, Option<FeatureSnapshot>)>) {
+        let rollback_at_end = self.rollback_bar == self.feature_order.len() as u64;
+        let befores: Vec<Option<FeatureSnapshot>> = changes.iter()
+            .map(|(id, _)| self.snapshot_feature(*id))
+            .collect();
+        for (id, target) in &changes {
+            self.apply_delta(*id, target.as_ref());
+        }
+        if rollback_at_end {
+            self.rollback_bar = self.feature_order.len() as u64;
+        }
+        self.resolve_all_references();
+
+        let deltas: Vec<FeatureDelta> = changes.into_iter().zip(befores)
+            .map(|((feature_id, _), before)| FeatureDelta { feature_id, before, after: self.snapshot_feature(feature_id) })
+            .filter(|delta| delta.before != delta.after)
+            .collect();
+        if !deltas.is_empty() {
+            self.record_action(Action { deltas, symmetry: None });
+        }
+    }
+
     fn snapshot_feature(&self, feature_id: FeatureId) -> Option<FeatureSnapshot> {
         let order_index = self.feature_order.iter().position(|id| *id == feature_id)?;
         let feature = self.features.get(&feature_id)?;
@@ -610,6 +936,7 @@ impl FeatureTimeline {
                                             before: Some(before),
                                             after: Some(after),
                                         }],
+                                        symmetry: None,
                                     });
                                 }
                             }
@@ -634,12 +961,13 @@ impl FeatureTimeline {
         // Section 2: Feature order (construction timeline; fills remaining space)
         let mut selection_changed = false;
         let mut next_selected = features.selected_feature;
+        let mut toggled = None;
         let pending_delete = Rc::new(RefCell::new(None::<FeatureId>));
 
         egui::ScrollArea::vertical().show(ui, |ui| {
             for (i, id) in features.feature_order.iter().enumerate() {
                 let feature = features.get_feature(id).unwrap();
-                let is_selected = features.selected_feature == Some(*id);
+                let is_selected = features.is_selected(*id);
                 let is_active = (i as u64) < features.rollback_bar;
 
                 let label_text = feature.type_name_with_id();
@@ -652,8 +980,13 @@ impl FeatureTimeline {
 
                 let response = ui.add_sized([ui.available_width(), 0.0], label);
                 if response.clicked() && is_active {
-                    next_selected = Some(if is_selected { None } else { Some(*id) }).flatten();
-                    selection_changed = true;
+                    if ui.input(|i| i.modifiers.command) {
+                        toggled = Some(*id);
+                    } else {
+                        let is_active_selection = features.selected_feature == Some(*id);
+                        next_selected = Some(if is_active_selection { None } else { Some(*id) }).flatten();
+                        selection_changed = true;
+                    }
                 }
 
                 let fid = *id;
@@ -678,6 +1011,8 @@ impl FeatureTimeline {
 
         if selection_changed {
             features.select(next_selected);
+        } else if let Some(id) = toggled {
+            features.toggle_selected(id);
         }
 
         if let Some(id) = pending_delete.borrow_mut().take() {
@@ -792,11 +1127,7 @@ impl FeatureTimeline {
         if edit_events.read().next().is_none() { return; }
         edit_events.clear();
 
-        let affected = match &features.selection_affected {
-            Some(ids) => ids.clone(),
-            None => return,
-        };
-
+        let affected = features.selected_and_affected();
         for id in &affected {
             if let Some(mut feature) = features.features.remove(id) {
                 feature.object.resolve_references(&features.features);
@@ -971,6 +1302,15 @@ impl PointRef {
         ))
     }
 
+    /// The same reference moved by `d`, whether its axes are absolute or relative.
+    pub fn offset_by(&self, d: Vec3) -> Self {
+        let mut moved = self.clone();
+        *moved.x.value_mut() += d.x;
+        *moved.y.value_mut() += d.y;
+        *moved.z.value_mut() += d.z;
+        moved
+    }
+
     pub fn referenced_features(&self) -> Vec<FeatureId> {
         self.reference.into_iter().collect()
     }
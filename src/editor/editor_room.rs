@@ -44,20 +44,23 @@ impl EditorRoom {
     }
 }
 
+/// The named point `key` of a room spanning `min`..`max`.
+pub fn room_point(min: Vec3, max: Vec3, key: &str) -> Result<Vec3, PointResolutionError> {
+    match key {
+        "min" => Ok(min),
+        "max" => Ok(max),
+        "" => Ok((min + max) / 2.0),
+        other => {
+            let cp = CuboidPoint::try_from(other)?;
+            Ok(cp.resolve_in_bounds(min, max))
+        }
+    }
+}
+
 #[typetag::serde(name = "editor_room")]
 impl FeatureTrait for EditorRoom {
     fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
-        let min = self.resolved_min.min(self.resolved_max);
-        let max = self.resolved_min.max(self.resolved_max);
-        match key {
-            "min" => Ok(min),
-            "max" => Ok(max),
-            "" => Ok((min + max) / 2.0),
-            other => {
-                let cp = CuboidPoint::try_from(other)?;
-                Ok(cp.resolve_in_bounds(min, max))
-            }
-        }
+        room_point(self.resolved_min.min(self.resolved_max), self.resolved_min.max(self.resolved_max), key)
     }
 
     fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
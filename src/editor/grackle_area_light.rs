@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::grackle_point_light::{color_edit, srgb, DEFAULT_COLOR};
+use crate::editor::grackle_spot_light::{aimed_transform, drag_location};
+use crate::get;
+
+const DEFAULT_INTENSITY: f32 = 100_000.0;
+const DEFAULT_RANGE: f32 = 20.0;
+const DEFAULT_SIZE: f32 = 1.0;
+/// Bevy's spot lights stop just short of a hemisphere.
+const EMISSION_HALF_ANGLE: f32 = std::f32::consts::FRAC_PI_2 * 0.95;
+
+/// A rectangular emitter centered on `location` and facing `target`.
+///
+/// Bevy has no rectangle lights, so it renders as a hemispherical spot light whose source radius
+/// matches the rectangle, which gives the same soft highlights and shadows from up close.
+#[derive(Serialize, Deserialize)]
+pub struct GrackleAreaLight {
+    location: PointRef,
+    target: PointRef,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    resolved_target: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[typetag::serde(name = "grackle_area_light")]
+impl FeatureTrait for GrackleAreaLight {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        match key {
+            "target" => Ok(self.resolved_target),
+            _ => Ok(self.resolved_location),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.target.editor_ui(ui, "Target", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+
+        ui.separator();
+        ui.label(get!("editor.features.grackle_point_light.params"));
+        changed |= color_edit(ui, &mut self.color);
+        changed |= ui.add(egui::Slider::new(&mut self.intensity, 0.0..=1_000_000.0).text(get!("editor.features.grackle_point_light.intensity"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.range, 0.0..=100.0).text(get!("editor.features.grackle_point_light.range"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.width, 0.0..=20.0).text(get!("editor.features.grackle_area_light.width"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.height, 0.0..=20.0).text(get!("editor.features.grackle_area_light.height"))).changed();
+
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.grackle_area_light.title")
+    }
+
+    fn type_key(&self) -> &'static str { "grackle_area_light" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::AreaLight {
+            location: self.location.clone(),
+            target: self.target.clone(),
+            color: self.color,
+            intensity: self.intensity,
+            range: self.range,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::AreaLight { location, target, color, intensity, range, width, height } = data else { return; };
+        self.location = location.clone();
+        self.target = target.clone();
+        self.color = *color;
+        self.intensity = *intensity;
+        self.range = *range;
+        self.width = *width;
+        self.height = *height;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let color = Color::srgb_u8(255, 255, 0);
+        let transform = aimed_transform(self.resolved_location, self.resolved_target);
+        gizmos.rect(
+            Isometry3d::new(self.resolved_location, transform.rotation),
+            Vec2::new(self.width, self.height),
+            color,
+        );
+        gizmos.arrow(self.resolved_location, self.resolved_location + transform.forward() * 1.0, color);
+        gizmos.line(self.resolved_location, self.resolved_target, color.with_alpha(0.3));
+        self.location.debug_gizmos(self.resolved_location, gizmos);
+        self.target.debug_gizmos(self.resolved_target, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert((
+            aimed_transform(self.resolved_location, self.resolved_target),
+            SpotLight {
+                color: srgb(self.color),
+                intensity: self.intensity,
+                range: self.range,
+                radius: Vec2::new(self.width, self.height).length() * 0.5,
+                inner_angle: 0.0,
+                outer_angle: EMISSION_HALF_ANGLE,
+                ..default()
+            },
+        ));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+        if let Ok(v) = self.target.resolve(features) {
+            self.resolved_target = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.location.referenced_features();
+        for id in self.target.referenced_features() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Light".into()),
+            ("target".into(), "Target".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![
+            ("".into(), self.resolved_location),
+            ("target".into(), self.resolved_target),
+        ]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location", "target"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("intensity", self.intensity),
+            ("range_val", self.range),
+            ("width", self.width),
+            ("height", self.height),
+            ("color_r", self.color.x),
+            ("color_g", self.color.y),
+            ("color_b", self.color.z),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "intensity" => self.intensity = value,
+            "range_val" => self.range = value,
+            "width" => self.width = value,
+            "height" => self.height = value,
+            "color_r" => self.color.x = value,
+            "color_g" => self.color.y = value,
+            "color_b" => self.color.z = value,
+            _ => {}
+        }
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Target" | "target" => Some(&self.target),
+            _ => Some(&self.location),
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Target" | "target" => Some(&mut self.target),
+            _ => Some(&mut self.location),
+        }
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        drag_location(&mut self.location, &mut self.resolved_location, axis, new_world_value)
+    }
+}
+
+impl GrackleAreaLight {
+    /// A light at `(x, y, z)` facing straight down.
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self::from_point_refs(PointRef::absolute(x, y, z), PointRef::absolute(x, y - 1.0, z))
+    }
+
+    pub fn from_point_refs(location: PointRef, target: PointRef) -> Self {
+        Self {
+            location,
+            target,
+            color: DEFAULT_COLOR,
+            intensity: DEFAULT_INTENSITY,
+            range: DEFAULT_RANGE,
+            width: DEFAULT_SIZE,
+            height: DEFAULT_SIZE,
+            resolved_location: Vec3::ZERO,
+            resolved_target: Vec3::ZERO,
+            entity: None,
+        }
+    }
+}
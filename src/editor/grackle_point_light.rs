@@ -10,10 +10,13 @@ use crate::get;
 const DEFAULT_INTENSITY: f32 = 10_000.0;
 const DEFAULT_RADIUS: f32 = 0.1;
 const DEFAULT_RANGE: f32 = 20.0;
+pub(crate) const DEFAULT_COLOR: Vec3 = Vec3::ONE;
 
 #[derive(Serialize, Deserialize)]
 pub struct GracklePointLight {
     location: PointRef,
+    /// sRGB, each channel in 0..=1.
+    pub color: Vec3,
     pub intensity: f32,
     pub radius: f32,
     pub range: f32,
@@ -40,6 +43,7 @@ impl FeatureTrait for GracklePointLight {
 
         ui.separator();
         ui.label(get!("editor.features.grackle_point_light.params"));
+        changed |= color_edit(ui, &mut self.color);
         changed |= ui.add(egui::Slider::new(&mut self.intensity, 0.0..=10000.0).text(get!("editor.features.grackle_point_light.intensity"))).changed();
         changed |= ui.add(egui::Slider::new(&mut self.radius, 0.0..=10.0).text(get!("editor.features.grackle_point_light.radius"))).changed();
         changed |= ui.add(egui::Slider::new(&mut self.range, 0.0..=100.0).text(get!("editor.features.grackle_point_light.range"))).changed();
@@ -56,6 +60,7 @@ impl FeatureTrait for GracklePointLight {
     fn snapshot(&self) -> FeatureData {
         FeatureData::PointLight {
             location: self.location.clone(),
+            color: self.color,
             intensity: self.intensity,
             radius: self.radius,
             range: self.range,
@@ -63,8 +68,9 @@ impl FeatureTrait for GracklePointLight {
     }
 
     fn apply_snapshot(&mut self, data: &FeatureData) {
-        let FeatureData::PointLight { location, intensity, radius, range } = data else { return; };
+        let FeatureData::PointLight { location, color, intensity, radius, range } = data else { return; };
         self.location = location.clone();
+        self.color = *color;
         self.intensity = *intensity;
         self.radius = *radius;
         self.range = *range;
@@ -87,6 +93,7 @@ impl FeatureTrait for GracklePointLight {
         commands.entity(entity).insert((
             Transform::from_translation(self.resolved_location),
             PointLight {
+                color: srgb(self.color),
                 intensity: self.intensity,
                 radius: self.radius,
                 range: self.range,
@@ -120,6 +127,9 @@ impl FeatureTrait for GracklePointLight {
             ("intensity", self.intensity),
             ("radius", self.radius),
             ("range_val", self.range),
+            ("color_r", self.color.x),
+            ("color_g", self.color.y),
+            ("color_b", self.color.z),
         ]
     }
 
@@ -128,6 +138,9 @@ impl FeatureTrait for GracklePointLight {
             "intensity" => self.intensity = value,
             "radius" => self.radius = value,
             "range_val" => self.range = value,
+            "color_r" => self.color.x = value,
+            "color_g" => self.color.y = value,
+            "color_b" => self.color.z = value,
             _ => {}
         }
     }
@@ -163,6 +176,7 @@ impl GracklePointLight {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Self {
             location: PointRef::absolute(x, y, z),
+            color: DEFAULT_COLOR,
             intensity: DEFAULT_INTENSITY,
             radius: DEFAULT_RADIUS,
             range: DEFAULT_RANGE,
@@ -174,6 +188,7 @@ impl GracklePointLight {
     pub fn from_point_ref(location: PointRef) -> Self {
         Self {
             location,
+            color: DEFAULT_COLOR,
             intensity: DEFAULT_INTENSITY,
             radius: DEFAULT_RADIUS,
             range: DEFAULT_RANGE,
@@ -182,3 +197,18 @@ impl GracklePointLight {
         }
     }
 }
+
+/// Color picker row shared by the light features. Returns true when the color changed.
+pub(crate) fn color_edit(ui: &mut egui::Ui, color: &mut Vec3) -> bool {
+    ui.horizontal(|ui| {
+        let mut rgb = color.to_array();
+        let changed = ui.color_edit_button_rgb(&mut rgb).changed();
+        ui.label(get!("editor.features.light.color"));
+        *color = Vec3::from_array(rgb);
+        changed
+    }).inner
+}
+
+pub(crate) fn srgb(color: Vec3) -> Color {
+    Color::srgb(color.x, color.y, color.z)
+}
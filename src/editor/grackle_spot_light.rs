@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::grackle_point_light::{color_edit, srgb, DEFAULT_COLOR};
+use crate::get;
+
+const DEFAULT_INTENSITY: f32 = 100_000.0;
+const DEFAULT_RADIUS: f32 = 0.1;
+const DEFAULT_RANGE: f32 = 20.0;
+const DEFAULT_INNER_ANGLE: f32 = 0.3;
+const DEFAULT_OUTER_ANGLE: f32 = 0.6;
+
+#[derive(Serialize, Deserialize)]
+pub struct GrackleSpotLight {
+    location: PointRef,
+    /// The light shines from `location` toward this point.
+    target: PointRef,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub radius: f32,
+    pub range: f32,
+    /// Half angles of the full-brightness and falloff cones, in radians.
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    resolved_target: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[typetag::serde(name = "grackle_spot_light")]
+impl FeatureTrait for GrackleSpotLight {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        match key {
+            "target" => Ok(self.resolved_target),
+            _ => Ok(self.resolved_location),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.target.editor_ui(ui, "Target", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+
+        ui.separator();
+        ui.label(get!("editor.features.grackle_point_light.params"));
+        changed |= color_edit(ui, &mut self.color);
+        changed |= ui.add(egui::Slider::new(&mut self.intensity, 0.0..=1_000_000.0).text(get!("editor.features.grackle_point_light.intensity"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.radius, 0.0..=10.0).text(get!("editor.features.grackle_point_light.radius"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.range, 0.0..=100.0).text(get!("editor.features.grackle_point_light.range"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.inner_angle, 0.0..=self.outer_angle).text(get!("editor.features.grackle_spot_light.inner_angle"))).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.outer_angle, 0.0..=std::f32::consts::FRAC_PI_2).text(get!("editor.features.grackle_spot_light.outer_angle"))).changed();
+
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.grackle_spot_light.title")
+    }
+
+    fn type_key(&self) -> &'static str { "grackle_spot_light" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::SpotLight {
+            location: self.location.clone(),
+            target: self.target.clone(),
+            color: self.color,
+            intensity: self.intensity,
+            radius: self.radius,
+            range: self.range,
+            inner_angle: self.inner_angle,
+            outer_angle: self.outer_angle,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::SpotLight { location, target, color, intensity, radius, range, inner_angle, outer_angle } = data else { return; };
+        self.location = location.clone();
+        self.target = target.clone();
+        self.color = *color;
+        self.intensity = *intensity;
+        self.radius = *radius;
+        self.range = *range;
+        self.inner_angle = *inner_angle;
+        self.outer_angle = *outer_angle;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let color = Color::srgb_u8(255, 255, 0);
+        gizmos.sphere(Isometry3d::from_translation(self.resolved_location), 0.2, color);
+        draw_cone(gizmos, self.resolved_location, self.resolved_target, self.outer_angle, color);
+        self.location.debug_gizmos(self.resolved_location, gizmos);
+        self.target.debug_gizmos(self.resolved_target, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert((
+            aimed_transform(self.resolved_location, self.resolved_target),
+            SpotLight {
+                color: srgb(self.color),
+                intensity: self.intensity,
+                radius: self.radius,
+                range: self.range,
+                inner_angle: self.inner_angle.min(self.outer_angle),
+                outer_angle: self.outer_angle,
+                ..default()
+            },
+        ));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+        if let Ok(v) = self.target.resolve(features) {
+            self.resolved_target = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.location.referenced_features();
+        for id in self.target.referenced_features() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Light".into()),
+            ("target".into(), "Target".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![
+            ("".into(), self.resolved_location),
+            ("target".into(), self.resolved_target),
+        ]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location", "target"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("intensity", self.intensity),
+            ("radius", self.radius),
+            ("range_val", self.range),
+            ("inner_angle", self.inner_angle),
+            ("outer_angle", self.outer_angle),
+            ("color_r", self.color.x),
+            ("color_g", self.color.y),
+            ("color_b", self.color.z),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "intensity" => self.intensity = value,
+            "radius" => self.radius = value,
+            "range_val" => self.range = value,
+            "inner_angle" => self.inner_angle = value,
+            "outer_angle" => self.outer_angle = value,
+            "color_r" => self.color.x = value,
+            "color_g" => self.color.y = value,
+            "color_b" => self.color.z = value,
+            _ => {}
+        }
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Target" | "target" => Some(&self.target),
+            _ => Some(&self.location),
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Target" | "target" => Some(&mut self.target),
+            _ => Some(&mut self.location),
+        }
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        drag_location(&mut self.location, &mut self.resolved_location, axis, new_world_value)
+    }
+}
+
+impl GrackleSpotLight {
+    /// A light at `(x, y, z)` pointing straight down.
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self::from_point_refs(PointRef::absolute(x, y, z), PointRef::absolute(x, y - 1.0, z))
+    }
+
+    pub fn from_point_refs(location: PointRef, target: PointRef) -> Self {
+        Self {
+            location,
+            target,
+            color: DEFAULT_COLOR,
+            intensity: DEFAULT_INTENSITY,
+            radius: DEFAULT_RADIUS,
+            range: DEFAULT_RANGE,
+            inner_angle: DEFAULT_INNER_ANGLE,
+            outer_angle: DEFAULT_OUTER_ANGLE,
+            resolved_location: Vec3::ZERO,
+            resolved_target: Vec3::ZERO,
+            entity: None,
+        }
+    }
+}
+
+/// Transform at `from` whose forward (-Z) axis faces `to`, the way Bevy's spot lights shine.
+pub(crate) fn aimed_transform(from: Vec3, to: Vec3) -> Transform {
+    let dir = (to - from).normalize_or(Vec3::NEG_Y);
+    let up = if dir.cross(Vec3::Y).length_squared() < 1e-6 { Vec3::Z } else { Vec3::Y };
+    Transform::from_translation(from).looking_to(dir, up)
+}
+
+/// Draws the outline of a cone from `apex` toward `target` with the given half angle.
+pub(crate) fn draw_cone(gizmos: &mut Gizmos, apex: Vec3, target: Vec3, half_angle: f32, color: Color) {
+    let length = apex.distance(target).max(0.5);
+    let transform = aimed_transform(apex, target);
+    let base = apex + transform.forward() * length;
+    let radius = length * half_angle.min(1.5).tan();
+    gizmos.line(apex, base, color);
+    gizmos.circle(Isometry3d::new(base, transform.rotation), radius, color);
+    for side in [transform.right(), transform.up()] {
+        gizmos.line(apex, base + side * radius, color);
+        gizmos.line(apex, base - side * radius, color);
+    }
+}
+
+/// Moves one axis of a light's location for the point drag handle.
+pub(crate) fn drag_location(location: &mut PointRef, resolved: &mut Vec3, axis: u8, new_world_value: f32) -> bool {
+    let axis_ref = match axis {
+        0 => &mut location.x,
+        1 => &mut location.y,
+        2 => &mut location.z,
+        _ => return false,
+    };
+    let base = location.resolved_reference.map(|b| match axis {
+        0 => b.x, 1 => b.y, _ => b.z,
+    });
+    match axis_ref {
+        AxisRef::Absolute(v) => *v = new_world_value,
+        AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+    }
+    match axis { 0 => resolved.x = new_world_value, 1 => resolved.y = new_world_value, _ => resolved.z = new_world_value }
+    true
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::grackle_point_light::{color_edit, srgb, DEFAULT_COLOR};
+use crate::editor::grackle_spot_light::drag_location;
+use crate::get;
+
+const DEFAULT_ILLUMINANCE: f32 = 10_000.0;
+const DEFAULT_AZIMUTH: f32 = 0.8;
+const DEFAULT_ELEVATION: f32 = 0.9;
+
+/// Map-wide directional light. Only its direction matters; `location` just places the gizmo.
+#[derive(Serialize, Deserialize)]
+pub struct GrackleSun {
+    location: PointRef,
+    pub color: Vec3,
+    pub illuminance: f32,
+    /// Compass angle of the sun around +Y, in radians, measured from +X toward +Z.
+    pub azimuth: f32,
+    /// Angle of the sun above the horizon, in radians.
+    pub elevation: f32,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[typetag::serde(name = "grackle_sun")]
+impl FeatureTrait for GrackleSun {
+    fn get_point(&self, _key: &str) -> Result<Vec3, PointResolutionError> {
+        Ok(self.resolved_location)
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        if changed {
+            if let Ok(v) = self.location.resolve(features) {
+                self.resolved_location = v;
+            }
+        }
+
+        ui.separator();
+        ui.label(get!("editor.features.grackle_point_light.params"));
+        changed |= color_edit(ui, &mut self.color);
+        changed |= ui.add(egui::Slider::new(&mut self.illuminance, 0.0..=100_000.0).text(get!("editor.features.grackle_sun.illuminance"))).changed();
+        ui.horizontal(|ui| {
+            changed |= ui.drag_angle(&mut self.azimuth).changed();
+            ui.label(get!("editor.features.grackle_sun.azimuth"));
+        });
+        ui.horizontal(|ui| {
+            changed |= ui.drag_angle(&mut self.elevation).changed();
+            ui.label(get!("editor.features.grackle_sun.elevation"));
+        });
+        self.elevation = self.elevation.clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.grackle_sun.title")
+    }
+
+    fn type_key(&self) -> &'static str { "grackle_sun" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Sun {
+            location: self.location.clone(),
+            color: self.color,
+            illuminance: self.illuminance,
+            azimuth: self.azimuth,
+            elevation: self.elevation,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Sun { location, color, illuminance, azimuth, elevation } = data else { return; };
+        self.location = location.clone();
+        self.color = *color;
+        self.illuminance = *illuminance;
+        self.azimuth = *azimuth;
+        self.elevation = *elevation;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let p = self.resolved_location;
+        let color = Color::srgb_u8(255, 200, 0);
+        gizmos.sphere(Isometry3d::from_translation(p), 0.4, color);
+        let dir = sun_direction(self.azimuth, self.elevation);
+        let side = dir.any_orthonormal_vector();
+        for offset in [Vec3::ZERO, side * 0.6, -side * 0.6] {
+            gizmos.arrow(p + offset, p + offset + dir * 2.0, color);
+        }
+        self.location.debug_gizmos(p, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        let dir = sun_direction(self.azimuth, self.elevation);
+        let up = if dir.cross(Vec3::Y).length_squared() < 1e-6 { Vec3::Z } else { Vec3::Y };
+        commands.entity(entity).insert((
+            Transform::from_translation(self.resolved_location).looking_to(dir, up),
+            DirectionalLight {
+                color: srgb(self.color),
+                illuminance: self.illuminance,
+                shadows_enabled: true,
+                ..default()
+            },
+        ));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        self.location.referenced_features()
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![("".into(), "Sun".into())]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![("".into(), self.resolved_location)]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("illuminance", self.illuminance),
+            ("azimuth", self.azimuth),
+            ("elevation", self.elevation),
+            ("color_r", self.color.x),
+            ("color_g", self.color.y),
+            ("color_b", self.color.z),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "illuminance" => self.illuminance = value,
+            "azimuth" => self.azimuth = value,
+            "elevation" => self.elevation = value,
+            "color_r" => self.color.x = value,
+            "color_g" => self.color.y = value,
+            "color_b" => self.color.z = value,
+            _ => {}
+        }
+    }
+
+    fn get_point_ref(&self, _key: &str) -> Option<&PointRef> {
+        Some(&self.location)
+    }
+
+    fn get_point_ref_mut(&mut self, _key: &str) -> Option<&mut PointRef> {
+        Some(&mut self.location)
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        drag_location(&mut self.location, &mut self.resolved_location, axis, new_world_value)
+    }
+}
+
+impl GrackleSun {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self {
+            resolved_location: Vec3::new(x, y, z),
+            ..Self::from_point_ref(PointRef::absolute(x, y, z))
+        }
+    }
+
+    pub fn from_point_ref(location: PointRef) -> Self {
+        Self {
+            location,
+            color: DEFAULT_COLOR,
+            illuminance: DEFAULT_ILLUMINANCE,
+            azimuth: DEFAULT_AZIMUTH,
+            elevation: DEFAULT_ELEVATION,
+            resolved_location: Vec3::ZERO,
+            entity: None,
+        }
+    }
+}
+
+/// Direction the sunlight travels (from the sky toward the ground).
+pub fn sun_direction(azimuth: f32, elevation: f32) -> Vec3 {
+    let toward_sun = Vec3::new(
+        elevation.cos() * azimuth.cos(),
+        elevation.sin(),
+        elevation.cos() * azimuth.sin(),
+    );
+    -toward_sun
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sun_direction_points_down_from_the_sky() {
+        let overhead = sun_direction(0.0, std::f32::consts::FRAC_PI_2);
+        assert!(overhead.distance(Vec3::NEG_Y) < 1e-5);
+
+        let low_east = sun_direction(0.0, 0.0);
+        assert!(low_east.distance(Vec3::NEG_X) < 1e-5);
+    }
+}
@@ -7,6 +7,7 @@ use bevy::input::mouse::MouseMotion;
 use bevy::picking::pointer::{PointerId, PointerLocation};
 use bevy::tasks::futures_lite::StreamExt;
 use crate::editor::multicam::Multicam;
+use crate::tool::playtest::PlaytestState;
 
 pub struct EditorInputPlugin;
 
@@ -18,7 +19,7 @@ impl Plugin for EditorInputPlugin {
             .add_systems(PreUpdate, (
                 Self::mouse_input,
                 Self::keyboard_input,
-            ))
+            ).run_if(in_state(PlaytestState::Off)))
         ;
     }
 }
@@ -57,6 +58,8 @@ impl Default for CurrentMouseInput {
 #[derive(Resource, Default)]
 pub struct CurrentKeyboardInput {
     pub modify: bool,
+    /// Alt: right-dragging the perspective view orbits the selection instead of turning in place.
+    pub orbit: bool,
     pub confirm: bool,
     pub cancel: bool,
     forward: bool,
@@ -187,6 +190,10 @@ impl EditorInputPlugin {
         let mut locations = Vec::new();
         for (_, pointer) in pointers {
             for (camera_entity, camera, camera_transform, cam_multicam) in &cameras {
+                // Cameras left out of the layout keep their old viewport; ignore them.
+                if !camera.is_active {
+                    continue;
+                }
                 if let Some(pointer_loc) = pointer.location() {
                     if pointer_loc.is_in_viewport(camera, &primary_window_entity) {
                         if pressed.is_some() {
@@ -239,6 +246,7 @@ impl EditorInputPlugin {
         keys: Res<ButtonInput<KeyCode>>,
     ) {
         current_input.modify = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
+        current_input.orbit = keys.pressed(KeyCode::AltLeft) || keys.pressed(KeyCode::AltRight);
         current_input.update(&keys);
         current_input.confirm = keys.just_released(KeyCode::Enter) || keys.just_released(KeyCode::NumpadEnter);
         current_input.cancel = keys.just_released(KeyCode::Escape);
@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::common::ray::ray_intersects_aabb;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTimeline, FeatureTrait, PointRef};
+use crate::editor::map_metadata::MapMetadata;
+use crate::editor::stairs::StairShape;
+use crate::get;
+use crate::tool::playtest::{collision_boxes, player_fits};
+use crate::tool::room::Room;
+use crate::tool::show::GizmoVisibility;
+use crate::tool::tool_helpers::bounds_gizmo;
+
+/// Lowest climb to the apex used for launches, so pads aimed level or downward still throw the player.
+const MIN_APEX_HEIGHT: f32 = 0.1;
+/// Flights longer than this are cut short in the preview.
+const MAX_FLIGHT_TIME: f32 = 10.0;
+const ARC_STEP: f32 = 1.0 / 30.0;
+
+/// Velocity that carries a body from `from` to the top of its arc at `apex`.
+pub fn launch_velocity(from: Vec3, apex: Vec3, gravity: f32) -> Vec3 {
+    let gravity = gravity.max(f32::EPSILON);
+    let height = (apex.y - from.y).max(MIN_APEX_HEIGHT);
+    let vertical = (2.0 * gravity * height).sqrt();
+    let time_to_apex = vertical / gravity;
+    ((apex - from).with_y(0.0) / time_to_apex).with_y(vertical)
+}
+
+/// Points along a player's flight from `from`, ending where they land: where the player stops
+/// fitting inside the rooms, or back at launch height when there are no rooms.
+pub fn trajectory(from: Vec3, velocity: Vec3, gravity: f32, rooms: &[(Vec3, Vec3)]) -> Vec<Vec3> {
+    let mut points = vec![from];
+    let mut t = 0.0;
+    while t < MAX_FLIGHT_TIME {
+        t += ARC_STEP;
+        let p = from + velocity * t - Vec3::Y * (0.5 * gravity * t * t);
+        points.push(p);
+        let falling = velocity.y - gravity * t < 0.0;
+        let landed = if rooms.is_empty() { falling && p.y <= from.y } else { !player_fits(p, rooms) };
+        if landed { break; }
+    }
+    points
+}
+
+/// Launch volume that throws players through `apex`, the top of their arc.
+#[derive(Serialize, Deserialize)]
+pub struct JumpPad {
+    min: PointRef,
+    max: PointRef,
+    apex: PointRef,
+    #[serde(skip)]
+    resolved_min: Vec3,
+    #[serde(skip)]
+    resolved_max: Vec3,
+    #[serde(skip)]
+    resolved_apex: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl JumpPad {
+    pub fn from_point_refs(min: PointRef, max: PointRef, apex: PointRef) -> Self {
+        Self {
+            min,
+            max,
+            apex,
+            resolved_min: Vec3::ZERO,
+            resolved_max: Vec3::ZERO,
+            resolved_apex: Vec3::ZERO,
+            entity: None,
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.resolved_min.min(self.resolved_max), self.resolved_min.max(self.resolved_max))
+    }
+}
+
+#[typetag::serde(name = "jump_pad")]
+impl FeatureTrait for JumpPad {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        let (min, max) = self.bounds();
+        match key {
+            "min" => Ok(min),
+            "max" => Ok(max),
+            "apex" => Ok(self.resolved_apex),
+            _ => Ok((min + max) / 2.0),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.min.editor_ui(ui, "Min", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.max.editor_ui(ui, "Max", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.apex.editor_ui(ui, "Apex", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.jump_pad.title")
+    }
+
+    fn type_key(&self) -> &'static str { "jump_pad" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::JumpPad {
+            min: self.min.clone(),
+            max: self.max.clone(),
+            apex: self.apex.clone(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::JumpPad { min, max, apex } = data else { return; };
+        self.min = min.clone();
+        self.max = max.clone();
+        self.apex = apex.clone();
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let (min, max) = self.bounds();
+        let color = Color::srgb_u8(120, 230, 60);
+        bounds_gizmo(gizmos, min, max, color);
+        gizmos.sphere(Isometry3d::from_translation(self.resolved_apex), 0.2, color);
+        self.min.debug_gizmos(self.resolved_min, gizmos);
+        self.max.debug_gizmos(self.resolved_max, gizmos);
+        self.apex.debug_gizmos(self.resolved_apex, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        let (min, max) = self.bounds();
+        commands.entity(entity).insert(Transform::from_translation((min + max) / 2.0));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.min.resolve(features) {
+            self.resolved_min = v;
+        }
+        if let Ok(v) = self.max.resolve(features) {
+            self.resolved_max = v;
+        }
+        if let Ok(v) = self.apex.resolve(features) {
+            self.resolved_apex = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.min.referenced_features();
+        for id in self.max.referenced_features().into_iter().chain(self.apex.referenced_features()) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Center".into()),
+            ("min".into(), "Min".into()),
+            ("max".into(), "Max".into()),
+            ("apex".into(), "Apex".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, ray: &Ray3d) -> Vec<(String, Vec3)> {
+        let (min, max) = self.bounds();
+        let padding = Vec3::splat(0.25);
+        if !ray_intersects_aabb(ray, min - padding, max + padding) {
+            return vec![("apex".into(), self.resolved_apex)];
+        }
+        self.available_point_keys().into_iter().filter_map(|(key, _)| {
+            self.get_point(&key).ok().map(|v| (key, v))
+        }).collect()
+    }
+
+    fn drag_handle(&mut self, is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let point_ref = if is_max { &mut self.max } else { &mut self.min };
+        let axis_ref = match axis {
+            0 => &mut point_ref.x,
+            1 => &mut point_ref.y,
+            2 => &mut point_ref.z,
+            _ => return false,
+        };
+        let base = point_ref.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        let resolved = if is_max { &mut self.resolved_max } else { &mut self.resolved_min };
+        match axis { 0 => resolved.x = new_world_value, 1 => resolved.y = new_world_value, _ => resolved.z = new_world_value }
+        true
+    }
+
+    fn drag_handle_bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some(self.bounds())
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["min", "max", "apex"] }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Min" | "min" => Some(&self.min),
+            "Max" | "max" => Some(&self.max),
+            "Apex" | "apex" => Some(&self.apex),
+            _ => None,
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Min" | "min" => Some(&mut self.min),
+            "Max" | "max" => Some(&mut self.max),
+            "Apex" | "apex" => Some(&mut self.apex),
+            _ => None,
+        }
+    }
+}
+
+/// The flight from each jump pad under the map's gravity, ending at the landing spot.
+/// Always shown for the selected pad.
+pub fn draw_jump_pad_arcs(
+    visibility: Res<GizmoVisibility>,
+    features: Res<FeatureTimeline>,
+    metadata: Res<MapMetadata>,
+    (rooms, stairs): (Query<&Room>, Query<&StairShape>),
+    mut gizmos: Gizmos,
+) {
+    let selected = features.selected_feature();
+    let gravity = metadata.environment.gravity;
+    let mut rooms_cache = None;
+    for (id, feature) in features.active_features() {
+        if feature.object().type_key() != "jump_pad" { continue; }
+        if !visibility.movers && selected != Some(id) { continue; }
+        let Some((min, max)) = feature.object().drag_handle_bounds() else { continue; };
+        let Ok(apex) = feature.object().get_point("apex") else { continue; };
+        // A player standing in the middle of the pad.
+        let from = ((min + max) / 2.0).with_y(min.y);
+
+        let rooms = rooms_cache.get_or_insert_with(|| collision_boxes(rooms.iter(), stairs.iter()));
+        let points = trajectory(from, launch_velocity(from, apex, gravity), gravity, rooms);
+        let color = Color::srgb_u8(120, 230, 60);
+        gizmos.linestrip(points.iter().copied(), color);
+        if let Some(landing) = points.last() {
+            gizmos.circle(Isometry3d::new(*landing, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)), 0.5, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_passes_through_apex() {
+        let from = Vec3::new(0.0, 1.0, 0.0);
+        let apex = Vec3::new(6.0, 5.0, -3.0);
+        let gravity = 20.0;
+        let velocity = launch_velocity(from, apex, gravity);
+        let t = velocity.y / gravity;
+        let top = from + velocity * t - Vec3::Y * (0.5 * gravity * t * t);
+        assert!(top.distance(apex) < 1e-3);
+    }
+
+    #[test]
+    fn test_trajectory_lands_on_the_floor() {
+        let rooms = [(Vec3::ZERO, Vec3::new(40.0, 10.0, 4.0))];
+        let from = Vec3::new(2.0, 0.0, 2.0);
+        let velocity = launch_velocity(from, Vec3::new(10.0, 4.0, 2.0), 20.0);
+        let points = trajectory(from, velocity, 20.0, &rooms);
+        let landing = *points.last().unwrap();
+        assert!(landing.y <= 0.0 && landing.y > -1.0);
+        assert!((landing.x - 18.0).abs() < 0.5);
+
+        let flat = trajectory(from, velocity, 20.0, &[]);
+        assert!((flat.last().unwrap().x - 18.0).abs() < 0.5);
+    }
+}
@@ -0,0 +1,566 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::common::PointResolutionError;
+use crate::common::ray::ray_intersects_aabb;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTimeline, FeatureTrait, PointRef};
+use crate::editor::grackle_point_light::color_edit;
+use crate::get;
+use crate::tool::show::GizmoVisibility;
+use crate::tool::tool_helpers::bounds_gizmo;
+
+/// Gap allowed between a volume and the rooms around it before it counts as sticking out.
+const ROOM_TOLERANCE: f32 = 0.01;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum VolumeKind {
+    Water,
+    Lava,
+    Slime,
+    /// Damages players inside it without slowing them down.
+    Hurt,
+    /// Kills players on contact.
+    Kill,
+}
+
+impl VolumeKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            VolumeKind::Water => "water",
+            VolumeKind::Lava => "lava",
+            VolumeKind::Slime => "slime",
+            VolumeKind::Hurt => "hurt",
+            VolumeKind::Kill => "kill",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|k| k.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            VolumeKind::Water => get!("editor.features.liquid_volume.kind.water"),
+            VolumeKind::Lava => get!("editor.features.liquid_volume.kind.lava"),
+            VolumeKind::Slime => get!("editor.features.liquid_volume.kind.slime"),
+            VolumeKind::Hurt => get!("editor.features.liquid_volume.kind.hurt"),
+            VolumeKind::Kill => get!("editor.features.liquid_volume.kind.kill"),
+        }
+    }
+
+    /// Whether players swim in it, so the fluid properties apply.
+    pub fn is_fluid(&self) -> bool {
+        matches!(self, VolumeKind::Water | VolumeKind::Lava | VolumeKind::Slime)
+    }
+
+    /// Damage per second, speed scale, buoyancy, tint and opacity for a new volume of this kind.
+    fn defaults(&self) -> (f32, f32, f32, Vec3, f32) {
+        match self {
+            VolumeKind::Water => (0.0, 0.7, 12.0, Vec3::new(0.2, 0.4, 0.8), 0.4),
+            VolumeKind::Lava => (50.0, 0.4, 4.0, Vec3::new(1.0, 0.35, 0.05), 0.85),
+            VolumeKind::Slime => (10.0, 0.5, 8.0, Vec3::new(0.35, 0.8, 0.2), 0.6),
+            VolumeKind::Hurt => (20.0, 1.0, 0.0, Vec3::new(1.0, 0.6, 0.0), 0.2),
+            VolumeKind::Kill => (0.0, 1.0, 0.0, Vec3::new(0.8, 0.0, 0.0), 0.25),
+        }
+    }
+}
+
+/// Box of liquid or a hazard zone, bounded like [`EditorRoom`](crate::editor::editor_room::EditorRoom).
+/// It should sit inside the rooms; see [`is_inside_rooms`].
+#[derive(Serialize, Deserialize)]
+pub struct LiquidVolume {
+    min: PointRef,
+    max: PointRef,
+    pub kind: VolumeKind,
+    /// Health lost per second inside it. Ignored by kill volumes.
+    pub damage: f32,
+    /// Multiplier on movement speed while submerged.
+    pub speed_scale: f32,
+    /// Upward acceleration while submerged, in m/s², working against gravity.
+    pub buoyancy: f32,
+    /// sRGB, each channel in 0..=1.
+    pub tint: Vec3,
+    pub opacity: f32,
+    #[serde(skip)]
+    resolved_min: Vec3,
+    #[serde(skip)]
+    resolved_max: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl LiquidVolume {
+    pub fn from_point_refs(min: PointRef, max: PointRef, kind: VolumeKind) -> Self {
+        let mut volume = Self {
+            min,
+            max,
+            kind,
+            damage: 0.0,
+            speed_scale: 1.0,
+            buoyancy: 0.0,
+            tint: Vec3::ONE,
+            opacity: 1.0,
+            resolved_min: Vec3::ZERO,
+            resolved_max: Vec3::ZERO,
+            entity: None,
+        };
+        volume.reset_to_kind_defaults();
+        volume
+    }
+
+    fn reset_to_kind_defaults(&mut self) {
+        (self.damage, self.speed_scale, self.buoyancy, self.tint, self.opacity) = self.kind.defaults();
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.resolved_min.min(self.resolved_max), self.resolved_min.max(self.resolved_max))
+    }
+}
+
+#[typetag::serde(name = "liquid_volume")]
+impl FeatureTrait for LiquidVolume {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        let (min, max) = self.bounds();
+        match key {
+            "min" => Ok(min),
+            "max" => Ok(max),
+            // The middle of the surface, handy for placing things on or under it.
+            "surface" => Ok(Vec3::new((min.x + max.x) / 2.0, max.y, (min.z + max.z) / 2.0)),
+            _ => Ok((min + max) / 2.0),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.min.editor_ui(ui, "Min", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.max.editor_ui(ui, "Max", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+        let (min, max) = self.bounds();
+        if !is_inside_rooms(min, max, &room_bounds(features.values())) {
+            ui.colored_label(egui::Color32::YELLOW, get!("editor.features.liquid_volume.outside_rooms"));
+        }
+
+        ui.separator();
+        let before = self.kind;
+        egui::ComboBox::from_label(get!("editor.features.liquid_volume.kind.label"))
+            .selected_text(self.kind.name())
+            .show_ui(ui, |ui| {
+                for kind in VolumeKind::iter() {
+                    ui.selectable_value(&mut self.kind, kind, kind.name());
+                }
+            });
+        if self.kind != before {
+            self.reset_to_kind_defaults();
+            changed = true;
+        }
+        if self.kind != VolumeKind::Kill {
+            ui.horizontal(|ui| {
+                ui.label(get!("editor.features.liquid_volume.damage"));
+                changed |= ui.add(egui::DragValue::new(&mut self.damage).range(0.0..=1000.0).speed(1.0).suffix(" /s")).changed();
+            });
+        }
+        if self.kind.is_fluid() {
+            ui.horizontal(|ui| {
+                ui.label(get!("editor.features.liquid_volume.speed_scale"));
+                changed |= ui.add(egui::Slider::new(&mut self.speed_scale, 0.0..=1.0)).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label(get!("editor.features.liquid_volume.buoyancy"));
+                changed |= ui.add(egui::DragValue::new(&mut self.buoyancy).range(0.0..=100.0).speed(0.1).suffix(" m/s²")).changed();
+            });
+        }
+        changed |= color_edit(ui, &mut self.tint);
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.liquid_volume.opacity"));
+            changed |= ui.add(egui::Slider::new(&mut self.opacity, 0.0..=1.0)).changed();
+        });
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        self.kind.name()
+    }
+
+    fn type_key(&self) -> &'static str { "liquid_volume" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Liquid {
+            min: self.min.clone(),
+            max: self.max.clone(),
+            kind: self.kind,
+            damage: self.damage,
+            speed_scale: self.speed_scale,
+            buoyancy: self.buoyancy,
+            tint: self.tint,
+            opacity: self.opacity,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Liquid { min, max, kind, damage, speed_scale, buoyancy, tint, opacity } = data else { return; };
+        self.min = min.clone();
+        self.max = max.clone();
+        self.kind = *kind;
+        self.damage = *damage;
+        self.speed_scale = *speed_scale;
+        self.buoyancy = *buoyancy;
+        self.tint = *tint;
+        self.opacity = *opacity;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let (min, max) = self.bounds();
+        bounds_gizmo(gizmos, min, max, Color::srgb(self.tint.x, self.tint.y, self.tint.z));
+        self.min.debug_gizmos(self.resolved_min, gizmos);
+        self.max.debug_gizmos(self.resolved_max, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        let (min, max) = self.bounds();
+        commands.entity(entity).insert((
+            Transform::from_translation((min + max) / 2.0).with_scale(max - min),
+            LiquidTint { color: self.tint, opacity: self.opacity },
+        ));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.min.resolve(features) {
+            self.resolved_min = v;
+        }
+        if let Ok(v) = self.max.resolve(features) {
+            self.resolved_max = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.min.referenced_features();
+        for id in self.max.referenced_features() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Center".into()),
+            ("min".into(), "Min".into()),
+            ("max".into(), "Max".into()),
+            ("surface".into(), "Surface".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, ray: &Ray3d) -> Vec<(String, Vec3)> {
+        let (min, max) = self.bounds();
+        let padding = Vec3::splat(0.25);
+        if !ray_intersects_aabb(ray, min - padding, max + padding) {
+            return vec![];
+        }
+        self.available_point_keys().into_iter().filter_map(|(key, _)| {
+            self.get_point(&key).ok().map(|v| (key, v))
+        }).collect()
+    }
+
+    fn drag_handle(&mut self, is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let point_ref = if is_max { &mut self.max } else { &mut self.min };
+        let axis_ref = match axis {
+            0 => &mut point_ref.x,
+            1 => &mut point_ref.y,
+            2 => &mut point_ref.z,
+            _ => return false,
+        };
+        let base = point_ref.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        let resolved = if is_max { &mut self.resolved_max } else { &mut self.resolved_min };
+        match axis { 0 => resolved.x = new_world_value, 1 => resolved.y = new_world_value, _ => resolved.z = new_world_value }
+        true
+    }
+
+    fn drag_handle_bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some(self.bounds())
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["min", "max"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("damage", self.damage),
+            ("speed_scale", self.speed_scale),
+            ("buoyancy", self.buoyancy),
+            ("color_r", self.tint.x),
+            ("color_g", self.tint.y),
+            ("color_b", self.tint.z),
+            ("opacity", self.opacity),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "damage" => self.damage = value,
+            "speed_scale" => self.speed_scale = value,
+            "buoyancy" => self.buoyancy = value,
+            "color_r" => self.tint.x = value,
+            "color_g" => self.tint.y = value,
+            "color_b" => self.tint.z = value,
+            "opacity" => self.opacity = value,
+            _ => {}
+        }
+    }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![("kind", self.kind.key().to_owned())]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        if key == "kind" {
+            self.kind = VolumeKind::from_key(value).unwrap_or(self.kind);
+        }
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Min" | "min" => Some(&self.min),
+            "Max" | "max" => Some(&self.max),
+            _ => None,
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Min" | "min" => Some(&mut self.min),
+            "Max" | "max" => Some(&mut self.max),
+            _ => None,
+        }
+    }
+}
+
+/// Interiors of the editor rooms among `features`. Stairs are solid, so they never hold liquid in.
+fn room_bounds<'a>(features: impl IntoIterator<Item = &'a Feature>) -> Vec<(Vec3, Vec3)> {
+    features.into_iter()
+        .filter(|f| f.object().type_key() == "editor_room")
+        .filter_map(|f| Some((f.get_point("min").ok()?, f.get_point("max").ok()?)))
+        .collect()
+}
+
+/// True when every part of the box lies inside at least one of `rooms`.
+pub fn is_inside_rooms(min: Vec3, max: Vec3, rooms: &[(Vec3, Vec3)]) -> bool {
+    let margin = Vec3::splat(ROOM_TOLERANCE);
+    let touching: Vec<(Vec3, Vec3)> = rooms.iter()
+        .filter(|(room_min, room_max)| min.cmplt(*room_max + margin).all() && max.cmpgt(*room_min - margin).all())
+        .copied()
+        .collect();
+    is_covered(min, max, &touching)
+}
+
+fn is_covered(min: Vec3, max: Vec3, rooms: &[(Vec3, Vec3)]) -> bool {
+    if (max - min).min_element() <= ROOM_TOLERANCE {
+        return true;
+    }
+    let Some(((room_min, room_max), rest)) = rooms.split_first() else { return false; };
+    let (room_min, room_max) = (*room_min - Vec3::splat(ROOM_TOLERANCE), *room_max + Vec3::splat(ROOM_TOLERANCE));
+    if min.cmpge(room_max).any() || max.cmple(room_min).any() {
+        return is_covered(min, max, rest);
+    }
+
+    // Slice off whatever sticks out of this room, one axis at a time, and check it against the others.
+    let (mut lo, mut hi) = (min, max);
+    for axis in 0..3 {
+        if lo[axis] < room_min[axis] {
+            let mut piece_hi = hi;
+            piece_hi[axis] = room_min[axis];
+            if !is_covered(lo, piece_hi, rest) { return false; }
+            lo[axis] = room_min[axis];
+        }
+        if hi[axis] > room_max[axis] {
+            let mut piece_lo = lo;
+            piece_lo[axis] = room_max[axis];
+            if !is_covered(piece_lo, hi, rest) { return false; }
+            hi[axis] = room_max[axis];
+        }
+    }
+    true
+}
+
+/// A volume with its bounds resolved, as exported for the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedVolume {
+    pub kind: VolumeKind,
+    pub min: Vec3,
+    pub max: Vec3,
+    pub damage: f32,
+    pub speed_scale: f32,
+    pub buoyancy: f32,
+    pub tint: Vec3,
+    pub opacity: f32,
+}
+
+pub fn volumes_from_timeline(timeline: &FeatureTimeline) -> Vec<BakedVolume> {
+    timeline.active_features()
+        .filter_map(|(_, f)| {
+            let FeatureData::Liquid { kind, damage, speed_scale, buoyancy, tint, opacity, .. } = f.object().snapshot() else { return None; };
+            Some(BakedVolume {
+                kind,
+                min: f.get_point("min").ok()?,
+                max: f.get_point("max").ok()?,
+                damage,
+                speed_scale,
+                buoyancy,
+                tint,
+                opacity,
+            })
+        })
+        .collect()
+}
+
+/// Tint of a volume's translucent box; the entity's transform scales a unit cube to the volume.
+#[derive(Component, PartialEq)]
+pub struct LiquidTint {
+    pub color: Vec3,
+    pub opacity: f32,
+}
+
+/// Gives new or changed volumes a translucent box mesh.
+pub fn update_liquid_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    volumes: Query<(Entity, &LiquidTint), Changed<LiquidTint>>,
+    mut unit_cube: Local<Option<Handle<Mesh>>>,
+) {
+    for (entity, tint) in &volumes {
+        let mesh = unit_cube.get_or_insert_with(|| meshes.add(Cuboid::from_length(1.0))).clone();
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgba(tint.color.x, tint.color.y, tint.color.z, tint.opacity),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        });
+        commands.entity(entity).insert((Mesh3d(mesh), MeshMaterial3d(material), Visibility::default()));
+    }
+}
+
+/// Whether each volume sits inside the rooms. A volume is only checked again when its bounds or
+/// the rooms change, since splitting it against every room is not cheap.
+#[derive(Resource, Default)]
+pub struct LiquidContainment {
+    rooms: Vec<(Vec3, Vec3)>,
+    volumes: HashMap<FeatureId, (Vec3, Vec3, bool)>,
+}
+
+impl LiquidContainment {
+    pub fn refresh(&mut self, timeline: &FeatureTimeline) {
+        let rooms = room_bounds(timeline.active_features().map(|(_, f)| f));
+        if rooms != self.rooms {
+            self.rooms = rooms;
+            self.volumes.clear();
+        }
+        let mut volumes = HashMap::new();
+        for (id, f) in timeline.active_features().filter(|(_, f)| f.object().type_key() == "liquid_volume") {
+            let (Ok(min), Ok(max)) = (f.get_point("min"), f.get_point("max")) else { continue; };
+            let inside = match self.volumes.get(&id) {
+                Some(&(was_min, was_max, inside)) if was_min == min && was_max == max => inside,
+                _ => is_inside_rooms(min, max, &self.rooms),
+            };
+            volumes.insert(id, (min, max, inside));
+        }
+        self.volumes = volumes;
+    }
+
+    /// Bounds of the volumes that stick out of the rooms.
+    pub fn outside(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.volumes.values().filter(|(_, _, inside)| !inside).map(|(min, max, _)| (*min, *max))
+    }
+}
+
+pub fn update_liquid_containment(features: Res<FeatureTimeline>, mut containment: ResMut<LiquidContainment>) {
+    if features.is_changed() {
+        containment.refresh(&features);
+    }
+}
+
+/// Outlines volumes that stick out of the rooms, since players could reach them from outside the map.
+pub fn draw_liquid_warnings(
+    containment: Res<LiquidContainment>,
+    visibility: Res<GizmoVisibility>,
+    mut gizmos: Gizmos,
+) {
+    if !visibility.volumes { return; }
+    let padding = Vec3::splat(0.05);
+    for (min, max) in containment.outside() {
+        bounds_gizmo(&mut gizmos, min - padding, max + padding, Color::srgb_u8(255, 40, 40));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editor_room::EditorRoom;
+    use crate::editor::stairs::Stairs;
+
+    #[test]
+    fn test_inside_rooms_across_two_rooms() {
+        let rooms = [
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(10.0, 4.0, 10.0)),
+            (Vec3::new(10.0, 0.0, 0.0), Vec3::new(20.0, 4.0, 10.0)),
+        ];
+        assert!(is_inside_rooms(Vec3::new(2.0, 0.0, 2.0), Vec3::new(8.0, 1.0, 8.0), &rooms));
+        assert!(is_inside_rooms(Vec3::new(5.0, 0.0, 2.0), Vec3::new(15.0, 1.0, 8.0), &rooms));
+        assert!(!is_inside_rooms(Vec3::new(5.0, 0.0, 2.0), Vec3::new(25.0, 1.0, 8.0), &rooms));
+        assert!(!is_inside_rooms(Vec3::new(2.0, 3.0, 2.0), Vec3::new(8.0, 5.0, 8.0), &rooms));
+        assert!(!is_inside_rooms(Vec3::new(2.0, 0.0, 2.0), Vec3::new(8.0, 1.0, 8.0), &[]));
+    }
+
+    #[test]
+    fn test_stairs_do_not_contain_liquid() {
+        let mut timeline = FeatureTimeline::default();
+        timeline.apply_feature(Box::new(EditorRoom::from_point_refs(
+            PointRef::absolute(0.0, 0.0, 0.0),
+            PointRef::absolute(10.0, 4.0, 10.0),
+        )));
+        timeline.apply_feature(Box::new(Stairs::from_point_refs(
+            PointRef::absolute(10.0, 0.0, 4.0),
+            PointRef::absolute(14.0, 2.0, 4.0),
+        )));
+        let inside = timeline.apply_feature(Box::new(LiquidVolume::from_point_refs(
+            PointRef::absolute(1.0, 0.0, 1.0),
+            PointRef::absolute(5.0, 1.0, 5.0),
+            VolumeKind::Water,
+        )));
+        let in_stairs = timeline.apply_feature(Box::new(LiquidVolume::from_point_refs(
+            PointRef::absolute(11.0, 0.0, 3.5),
+            PointRef::absolute(12.0, 0.2, 4.5),
+            VolumeKind::Water,
+        )));
+        timeline.resolve_all_references();
+
+        let mut containment = LiquidContainment::default();
+        containment.refresh(&timeline);
+        let outside: Vec<(Vec3, Vec3)> = containment.outside().collect();
+        assert_eq!(outside, vec![(Vec3::new(11.0, 0.0, 3.5), Vec3::new(12.0, 0.2, 4.5))]);
+        assert!(containment.volumes[&inside].2);
+        assert!(!containment.volumes[&in_stairs].2);
+    }
+}
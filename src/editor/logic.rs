@@ -0,0 +1,226 @@
+//! Map logic wiring: named outputs on one feature fire named inputs on others, Quake/Source style.
+//!
+//! Connections are stored on the feature that fires them. They are not parent links, so a trigger
+//! may target a relay created after it, and deleting a target leaves a broken connection behind
+//! instead of deleting the source.
+
+use std::fmt::{Display, Formatter};
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::editor::editable::{Feature, FeatureId, FeatureTimeline};
+use crate::get;
+use crate::tool::show::GizmoVisibility;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LogicConnection {
+    pub output: String,
+    pub target: FeatureId,
+    pub input: String,
+    /// Seconds between the output firing and the input being called.
+    pub delay: f32,
+}
+
+/// Canonical one-line form used by blueprint diffs, e.g. `OnStartTouch>#7.Trigger@0.5`.
+pub fn format_connections(connections: &[LogicConnection]) -> String {
+    connections.iter()
+        .map(|c| format!("{}>#{}.{}@{}", c.output, c.target, c.input, c.delay))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Inverse of [`format_connections`]; malformed entries are skipped.
+pub fn parse_connections(s: &str) -> Vec<LogicConnection> {
+    s.split(';')
+        .filter_map(|entry| {
+            let (output, rest) = entry.split_once(">#")?;
+            let (target, rest) = rest.split_once('.')?;
+            let (input, delay) = rest.rsplit_once('@')?;
+            Some(LogicConnection {
+                output: output.to_owned(),
+                target: FeatureId::from_raw(target.parse().ok()?),
+                input: input.to_owned(),
+                delay: delay.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Points connections at new ids, e.g. after a merge renumbers features.
+pub fn remap_connections(connections: &mut [LogicConnection], remap: impl Fn(FeatureId) -> Option<FeatureId>) {
+    for connection in connections {
+        if let Some(new_id) = remap(connection.target) {
+            connection.target = new_id;
+        }
+    }
+}
+
+/// Editor for a feature's outgoing connections. Returns true when anything changed.
+pub fn connections_ui(
+    ui: &mut egui::Ui,
+    connections: &mut Vec<LogicConnection>,
+    outputs: &[&str],
+    features: &HashMap<FeatureId, Feature>,
+) -> bool {
+    let mut changed = false;
+    let mut targets: Vec<(FeatureId, String, Vec<&'static str>)> = features.iter()
+        .map(|(id, f)| (*id, f.type_name_with_id(), f.object().logic_inputs()))
+        .filter(|(_, _, inputs)| !inputs.is_empty())
+        .collect();
+    targets.sort_by_key(|(id, _, _)| id._id());
+
+    ui.label(get!("editor.logic.outputs"));
+    let mut remove = None;
+    for (i, connection) in connections.iter_mut().enumerate() {
+        ui.push_id(i, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("output")
+                    .selected_text(connection.output.as_str())
+                    .show_ui(ui, |ui| {
+                        for output in outputs {
+                            changed |= ui.selectable_value(&mut connection.output, output.to_string(), *output).changed();
+                        }
+                    });
+                ui.label("→");
+                let target = targets.iter().find(|(id, _, _)| *id == connection.target);
+                egui::ComboBox::from_id_salt("target")
+                    .selected_text(target.map_or_else(|| get!("editor.logic.missing_target", "id", connection.target), |(_, name, _)| name.clone()))
+                    .show_ui(ui, |ui| {
+                        for (id, name, _) in &targets {
+                            changed |= ui.selectable_value(&mut connection.target, *id, name).changed();
+                        }
+                    });
+                let inputs = targets.iter()
+                    .find(|(id, _, _)| *id == connection.target)
+                    .map_or(&[][..], |(_, _, inputs)| inputs.as_slice());
+                egui::ComboBox::from_id_salt("input")
+                    .selected_text(connection.input.as_str())
+                    .show_ui(ui, |ui| {
+                        for input in inputs {
+                            changed |= ui.selectable_value(&mut connection.input, input.to_string(), *input).changed();
+                        }
+                    });
+                changed |= ui.add(egui::DragValue::new(&mut connection.delay).range(0.0..=600.0).speed(0.1).suffix(" s")).changed();
+                if ui.small_button("x").clicked() {
+                    remove = Some(i);
+                }
+            });
+        });
+    }
+    if let Some(i) = remove {
+        connections.remove(i);
+        changed = true;
+    }
+
+    let addable = outputs.first().zip(targets.first());
+    if ui.add_enabled(addable.is_some(), egui::Button::new(get!("editor.logic.add_connection"))).clicked()
+        && let Some((output, (target, _, inputs))) = addable
+    {
+        connections.push(LogicConnection {
+            output: output.to_string(),
+            target: *target,
+            input: inputs[0].to_string(),
+            delay: 0.0,
+        });
+        changed = true;
+    }
+    changed
+}
+
+/// A connection that cannot fire at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogicIssue {
+    MissingTarget { source: FeatureId, connection: LogicConnection },
+    UnknownOutput { source: FeatureId, connection: LogicConnection },
+    UnknownInput { source: FeatureId, connection: LogicConnection },
+}
+
+/// Plain English for the command-line tools, which run without the language files.
+impl Display for LogicIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTarget { source, connection } => {
+                write!(f, "#{} {}: target #{} does not exist", source, connection.output, connection.target)
+            }
+            Self::UnknownOutput { source, connection } => {
+                write!(f, "#{} has no output {}", source, connection.output)
+            }
+            Self::UnknownInput { source, connection } => {
+                write!(f, "#{} {}: #{} has no input {}", source, connection.output, connection.target, connection.input)
+            }
+        }
+    }
+}
+
+/// Every active connection in the map, in feature order, with the ones that cannot fire.
+pub fn check_connections(timeline: &FeatureTimeline) -> (Vec<(FeatureId, LogicConnection)>, Vec<LogicIssue>) {
+    let mut wiring = Vec::new();
+    let mut issues = Vec::new();
+    for (source, feature) in timeline.active_features() {
+        let outputs = feature.object().logic_outputs();
+        for connection in feature.object().connections() {
+            let issue = if !outputs.contains(&connection.output.as_str()) {
+                Some(LogicIssue::UnknownOutput { source, connection: connection.clone() })
+            } else {
+                match timeline.active_features().find(|(id, _)| *id == connection.target) {
+                    None => Some(LogicIssue::MissingTarget { source, connection: connection.clone() }),
+                    Some((_, target)) if !target.object().logic_inputs().contains(&connection.input.as_str()) => {
+                        Some(LogicIssue::UnknownInput { source, connection: connection.clone() })
+                    }
+                    Some(_) => None,
+                }
+            };
+            match issue {
+                Some(issue) => issues.push(issue),
+                None => wiring.push((source, connection.clone())),
+            }
+        }
+    }
+    (wiring, issues)
+}
+
+/// Connection lines from each source to its targets. Always shown for the selected feature.
+pub fn draw_logic_connections(
+    visibility: Res<GizmoVisibility>,
+    features: Res<FeatureTimeline>,
+    mut gizmos: Gizmos,
+) {
+    let selected = features.selected_feature();
+    for (source, feature) in features.active_features() {
+        let connections = feature.object().connections();
+        if connections.is_empty() { continue; }
+        let is_selected = selected == Some(source);
+        if !visibility.logic && !is_selected { continue; }
+        let Ok(from) = feature.object().get_point("") else { continue; };
+
+        for connection in connections {
+            let target = features.get_feature(&connection.target)
+                .and_then(|t| t.object().get_point("").ok());
+            match target {
+                Some(to) => {
+                    let color = if is_selected { Color::srgb_u8(255, 160, 0) } else { Color::srgb_u8(200, 120, 255) };
+                    gizmos.arrow(from, to, color);
+                }
+                None => {
+                    gizmos.sphere(Isometry3d::from_translation(from + Vec3::Y * 0.5), 0.15, Color::srgb_u8(255, 0, 0));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connections_round_trip_through_text() {
+        let connections = vec![
+            LogicConnection { output: "OnStartTouch".into(), target: FeatureId::from_raw(7), input: "Trigger".into(), delay: 0.5 },
+            LogicConnection { output: "OnTimer".into(), target: FeatureId::from_raw(12), input: "Add".into(), delay: 0.0 },
+        ];
+        assert_eq!(parse_connections(&format_connections(&connections)), connections);
+        assert!(parse_connections("").is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::logic::{LogicConnection, connections_ui};
+use crate::get;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum LogicKind {
+    /// Passes `Trigger` on as `OnTrigger` while enabled, to fan one output out or gate it.
+    Relay,
+    /// Fires `OnTimer` every `value` seconds while running.
+    Timer,
+    /// Counts up to `value`, then fires `OnHitMax`.
+    Counter,
+}
+
+impl LogicKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            LogicKind::Relay => "relay",
+            LogicKind::Timer => "timer",
+            LogicKind::Counter => "counter",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|k| k.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            LogicKind::Relay => get!("editor.features.logic_entity.kind.relay"),
+            LogicKind::Timer => get!("editor.features.logic_entity.kind.timer"),
+            LogicKind::Counter => get!("editor.features.logic_entity.kind.counter"),
+        }
+    }
+
+    pub fn inputs(&self) -> &'static [&'static str] {
+        match self {
+            LogicKind::Relay => &["Trigger", "Enable", "Disable"],
+            LogicKind::Timer => &["Start", "Stop", "Toggle", "Reset"],
+            LogicKind::Counter => &["Add", "Subtract", "Reset"],
+        }
+    }
+
+    pub fn outputs(&self) -> &'static [&'static str] {
+        match self {
+            LogicKind::Relay => &["OnTrigger"],
+            LogicKind::Timer => &["OnTimer"],
+            LogicKind::Counter => &["OnHitMax", "OnChanged"],
+        }
+    }
+
+    /// Label for [`LogicEntity::value`], which relays ignore.
+    fn value_label(&self) -> Option<String> {
+        match self {
+            LogicKind::Relay => None,
+            LogicKind::Timer => Some(get!("editor.features.logic_entity.interval")),
+            LogicKind::Counter => Some(get!("editor.features.logic_entity.max")),
+        }
+    }
+}
+
+/// A point entity with no body that only routes logic: a relay, timer or counter.
+#[derive(Serialize, Deserialize)]
+pub struct LogicEntity {
+    location: PointRef,
+    pub kind: LogicKind,
+    /// Timer interval in seconds, or the counter's maximum.
+    pub value: f32,
+    pub start_enabled: bool,
+    pub connections: Vec<LogicConnection>,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[typetag::serde(name = "logic_entity")]
+impl FeatureTrait for LogicEntity {
+    fn get_point(&self, _key: &str) -> Result<Vec3, PointResolutionError> {
+        Ok(self.resolved_location)
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        if changed && let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+
+        ui.separator();
+        egui::ComboBox::from_label(get!("editor.features.logic_entity.kind.label"))
+            .selected_text(self.kind.name())
+            .show_ui(ui, |ui| {
+                for kind in LogicKind::iter() {
+                    changed |= ui.selectable_value(&mut self.kind, kind, kind.name()).changed();
+                }
+            });
+        if let Some(label) = self.kind.value_label() {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                changed |= ui.add(egui::DragValue::new(&mut self.value).range(0.0..=3600.0).speed(0.1)).changed();
+            });
+        }
+        changed |= ui.checkbox(&mut self.start_enabled, get!("editor.logic.start_enabled")).changed();
+        ui.separator();
+        changed |= connections_ui(ui, &mut self.connections, self.kind.outputs(), features);
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.logic_entity.title", "kind", self.kind.name())
+    }
+
+    fn type_key(&self) -> &'static str { "logic_entity" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Logic {
+            location: self.location.clone(),
+            kind: self.kind,
+            value: self.value,
+            start_enabled: self.start_enabled,
+            connections: self.connections.clone(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Logic { location, kind, value, start_enabled, connections } = data else { return; };
+        self.location = location.clone();
+        self.kind = *kind;
+        self.value = *value;
+        self.start_enabled = *start_enabled;
+        self.connections = connections.clone();
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let p = self.resolved_location;
+        let color = Color::srgb_u8(200, 120, 255);
+        let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+        match self.kind {
+            LogicKind::Relay => {
+                gizmos.cuboid(Transform::from_translation(p).with_scale(Vec3::splat(0.4)), color);
+            }
+            LogicKind::Timer => {
+                gizmos.circle(Isometry3d::new(p, flat), 0.3, color);
+                gizmos.line(p, p + Vec3::Z * 0.2, color);
+                gizmos.line(p, p + Vec3::X * 0.15, color);
+            }
+            LogicKind::Counter => {
+                gizmos.sphere(Isometry3d::from_translation(p), 0.25, color);
+                gizmos.line(p - Vec3::X * 0.15, p + Vec3::X * 0.15, color);
+                gizmos.line(p - Vec3::Z * 0.15, p + Vec3::Z * 0.15, color);
+            }
+        }
+        self.location.debug_gizmos(p, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert(Transform::from_translation(self.resolved_location));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        self.location.referenced_features()
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![("".into(), "Logic".into())]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![("".into(), self.resolved_location)]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("value", self.value),
+            ("start_enabled", self.start_enabled as u8 as f32),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "value" => self.value = value,
+            "start_enabled" => self.start_enabled = value != 0.0,
+            _ => {}
+        }
+    }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![("kind", self.kind.key().to_owned())]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        if key == "kind" {
+            self.kind = LogicKind::from_key(value).unwrap_or(self.kind);
+        }
+    }
+
+    fn logic_outputs(&self) -> Vec<&'static str> { self.kind.outputs().to_vec() }
+
+    fn logic_inputs(&self) -> Vec<&'static str> { self.kind.inputs().to_vec() }
+
+    fn connections(&self) -> &[LogicConnection] { &self.connections }
+
+    fn set_connections(&mut self, connections: Vec<LogicConnection>) {
+        self.connections = connections;
+    }
+
+    fn get_point_ref(&self, _key: &str) -> Option<&PointRef> {
+        Some(&self.location)
+    }
+
+    fn get_point_ref_mut(&mut self, _key: &str) -> Option<&mut PointRef> {
+        Some(&mut self.location)
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let axis_ref = match axis {
+            0 => &mut self.location.x,
+            1 => &mut self.location.y,
+            2 => &mut self.location.z,
+            _ => return false,
+        };
+        let base = self.location.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        match axis { 0 => self.resolved_location.x = new_world_value, 1 => self.resolved_location.y = new_world_value, _ => self.resolved_location.z = new_world_value }
+        true
+    }
+}
+
+impl LogicEntity {
+    pub fn new(x: f32, y: f32, z: f32, kind: LogicKind) -> Self {
+        Self {
+            location: PointRef::absolute(x, y, z),
+            kind,
+            value: 1.0,
+            start_enabled: true,
+            connections: Vec::new(),
+            resolved_location: Vec3::new(x, y, z),
+            entity: None,
+        }
+    }
+
+    pub fn from_point_ref(location: PointRef, kind: LogicKind) -> Self {
+        Self {
+            location,
+            kind,
+            value: 1.0,
+            start_enabled: true,
+            connections: Vec::new(),
+            resolved_location: Vec3::ZERO,
+            entity: None,
+        }
+    }
+}
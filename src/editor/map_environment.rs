@@ -0,0 +1,301 @@
+use std::path::Path;
+use bevy::core_pipeline::Skybox;
+use bevy::prelude::*;
+use bevy::render::render_resource::{TextureViewDescriptor, TextureViewDimension};
+use bevy_egui::egui;
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use crate::editor::grackle_point_light::color_edit;
+use crate::editor::map_metadata::MapMetadata;
+use crate::get;
+
+/// Skybox images live here, relative to the asset root.
+pub const SKYBOX_DIR: &str = "default/skyboxes";
+pub const DEFAULT_GRAVITY: f32 = 20.0;
+
+/// Per-map lighting, sky, fog and gameplay globals, stored alongside the other map metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapEnvironment {
+    /// sRGB, each channel in 0..=1.
+    pub ambient_color: Vec3,
+    pub ambient_brightness: f32,
+    /// File name inside [`SKYBOX_DIR`]: a cubemap `.ktx2` or six faces stacked vertically in one image.
+    pub skybox: Option<String>,
+    pub skybox_brightness: f32,
+    pub fog_enabled: bool,
+    pub fog_color: Vec3,
+    /// Fog starts at `fog_start` and is fully opaque at `fog_end`, in metres from the camera.
+    pub fog_start: f32,
+    pub fog_end: f32,
+    /// Downward acceleration for players and physics objects, in m/s².
+    pub gravity: f32,
+}
+
+impl Default for MapEnvironment {
+    fn default() -> Self {
+        Self {
+            ambient_color: Vec3::ONE,
+            ambient_brightness: 100.0,
+            skybox: None,
+            skybox_brightness: 1000.0,
+            fog_enabled: false,
+            fog_color: Vec3::new(0.5, 0.55, 0.6),
+            fog_start: 20.0,
+            fog_end: 200.0,
+            gravity: DEFAULT_GRAVITY,
+        }
+    }
+}
+
+fn format_color(c: Vec3) -> String {
+    format!("{},{},{}", c.x, c.y, c.z)
+}
+
+fn parse_color(s: &str) -> Option<Vec3> {
+    let mut parts = s.split(',').map(|p| p.trim().parse::<f32>());
+    let color = Vec3::new(parts.next()?.ok()?, parts.next()?.ok()?, parts.next()?.ok()?);
+    parts.next().is_none().then_some(color)
+}
+
+impl MapEnvironment {
+    /// `(metadata key, value)` rows as written to the blueprint.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("ambient_color", format_color(self.ambient_color)),
+            ("ambient_brightness", self.ambient_brightness.to_string()),
+            ("skybox", self.skybox.clone().unwrap_or_default()),
+            ("skybox_brightness", self.skybox_brightness.to_string()),
+            ("fog_enabled", self.fog_enabled.to_string()),
+            ("fog_color", format_color(self.fog_color)),
+            ("fog_start", self.fog_start.to_string()),
+            ("fog_end", self.fog_end.to_string()),
+            ("gravity", self.gravity.to_string()),
+        ]
+    }
+
+    /// Applies one metadata row; unknown keys and unparsable values keep the default.
+    pub(crate) fn set_row(&mut self, key: &str, value: &str) {
+        match key {
+            "ambient_color" => self.ambient_color = parse_color(value).unwrap_or(self.ambient_color),
+            "ambient_brightness" => self.ambient_brightness = value.parse().unwrap_or(self.ambient_brightness),
+            "skybox" => self.skybox = Some(value.to_owned()).filter(|s| !s.is_empty()),
+            "skybox_brightness" => self.skybox_brightness = value.parse().unwrap_or(self.skybox_brightness),
+            "fog_enabled" => self.fog_enabled = value.parse().unwrap_or(self.fog_enabled),
+            "fog_color" => self.fog_color = parse_color(value).unwrap_or(self.fog_color),
+            "fog_start" => self.fog_start = value.parse().unwrap_or(self.fog_start),
+            "fog_end" => self.fog_end = value.parse().unwrap_or(self.fog_end),
+            "gravity" => self.gravity = value.parse().unwrap_or(self.gravity),
+            _ => {}
+        }
+    }
+
+    /// Maps saved before environment settings existed get the defaults.
+    pub(crate) fn load_from_connection(conn: &Connection) -> rusqlite::Result<Self> {
+        let mut environment = Self::default();
+        for (key, _) in Self::default().rows() {
+            let value: Option<String> = conn
+                .query_row("SELECT value FROM metadata WHERE key = ?1", params![key], |row| row.get(0))
+                .optional()?;
+            if let Some(value) = value {
+                environment.set_row(key, &value);
+            }
+        }
+        Ok(environment)
+    }
+
+    pub(crate) fn insert_rows(&self, tx: &Transaction<'_>) -> rusqlite::Result<()> {
+        for (key, value) in self.rows() {
+            tx.execute("INSERT INTO metadata (key, value) VALUES (?1, ?2)", params![key, value])?;
+        }
+        Ok(())
+    }
+}
+
+/// Skybox file names available in the asset pack.
+pub fn available_skyboxes() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(Path::new("assets").join(SKYBOX_DIR)) else { return vec![]; };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn environment_panel_ui(ui: &mut egui::Ui, environment: &mut MapEnvironment) {
+    egui::Grid::new("editor_map_environment_grid")
+        .num_columns(2)
+        .spacing([10.0, 6.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label(get!("editor.environment.ambient"));
+            ui.horizontal(|ui| {
+                color_edit(ui, &mut environment.ambient_color);
+                ui.add(egui::DragValue::new(&mut environment.ambient_brightness).range(0.0..=10_000.0));
+            });
+            ui.end_row();
+
+            ui.label(get!("editor.environment.skybox"));
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("editor_map_skybox")
+                    .selected_text(environment.skybox.clone().unwrap_or_else(|| get!("editor.environment.no_skybox")))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut environment.skybox, None, get!("editor.environment.no_skybox"));
+                        for name in available_skyboxes() {
+                            ui.selectable_value(&mut environment.skybox, Some(name.clone()), name);
+                        }
+                    });
+                ui.add(egui::DragValue::new(&mut environment.skybox_brightness).range(0.0..=100_000.0));
+            });
+            ui.end_row();
+
+            ui.label(get!("editor.environment.fog"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut environment.fog_enabled, "");
+                ui.add_enabled_ui(environment.fog_enabled, |ui| {
+                    color_edit(ui, &mut environment.fog_color);
+                    ui.add(egui::DragValue::new(&mut environment.fog_start).range(0.0..=environment.fog_end).prefix(get!("editor.environment.fog_start")));
+                    ui.add(egui::DragValue::new(&mut environment.fog_end).range(environment.fog_start..=10_000.0).prefix(get!("editor.environment.fog_end")));
+                });
+            });
+            ui.end_row();
+
+            ui.label(get!("editor.environment.gravity"));
+            ui.add(egui::DragValue::new(&mut environment.gravity).range(0.0..=100.0).suffix(" m/s²"));
+            ui.end_row();
+        });
+}
+
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LoadedSkybox>()
+            .add_systems(Update, (
+                Self::load_skybox,
+                Self::prepare_skybox,
+                Self::apply,
+            ).chain())
+        ;
+    }
+}
+
+/// The skybox image currently in use and whether it has been turned into a cubemap yet.
+#[derive(Resource, Default)]
+struct LoadedSkybox {
+    name: Option<String>,
+    image: Option<Handle<Image>>,
+    ready: bool,
+}
+
+impl EnvironmentPlugin {
+    fn load_skybox(
+        metadata: Res<MapMetadata>,
+        asset_server: Res<AssetServer>,
+        mut skybox: ResMut<LoadedSkybox>,
+    ) {
+        if skybox.name == metadata.environment.skybox { return; }
+        skybox.name = metadata.environment.skybox.clone();
+        skybox.image = skybox.name.as_ref().map(|name| asset_server.load(format!("{}/{}", SKYBOX_DIR, name)));
+        skybox.ready = false;
+    }
+
+    /// Stacked images have to be reinterpreted as six layers before a cube view can be made of them.
+    fn prepare_skybox(
+        asset_server: Res<AssetServer>,
+        mut images: ResMut<Assets<Image>>,
+        mut skybox: ResMut<LoadedSkybox>,
+    ) {
+        if skybox.ready { return; }
+        let Some(handle) = skybox.image.clone() else { return; };
+        if !asset_server.load_state(&handle).is_loaded() { return; }
+        let Some(image) = images.get_mut(&handle) else { return; };
+
+        if image.texture_descriptor.array_layer_count() == 1 {
+            if image.height() != image.width() * 6 {
+                warn!("Skybox {:?} is not six square faces stacked vertically", skybox.name);
+                skybox.image = None;
+                return;
+            }
+            image.reinterpret_stacked_2d_as_array(6);
+        }
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+        skybox.ready = true;
+    }
+
+    fn apply(
+        mut commands: Commands,
+        metadata: Res<MapMetadata>,
+        skybox: Res<LoadedSkybox>,
+        mut ambient: ResMut<AmbientLight>,
+        cameras: Query<Entity, With<Camera3d>>,
+        new_cameras: Query<(), Added<Camera3d>>,
+        mut applied: Local<Option<MapEnvironment>>,
+    ) {
+        // The metadata panel borrows the resource mutably every frame, so compare against what was
+        // last applied rather than relying on change detection.
+        let environment = &metadata.environment;
+        if applied.as_ref() == Some(environment) && !skybox.is_changed() && new_cameras.is_empty() { return; }
+        *applied = Some(environment.clone());
+
+        let srgb = |c: Vec3| Color::srgb(c.x, c.y, c.z);
+
+        ambient.color = srgb(environment.ambient_color);
+        ambient.brightness = environment.ambient_brightness;
+
+        for camera in &cameras {
+            let mut camera = commands.entity(camera);
+            match (&skybox.image, skybox.ready) {
+                (Some(image), true) => camera.insert(Skybox {
+                    image: image.clone(),
+                    brightness: environment.skybox_brightness,
+                    ..default()
+                }),
+                _ => camera.remove::<Skybox>(),
+            };
+            if environment.fog_enabled {
+                camera.insert(DistanceFog {
+                    color: srgb(environment.fog_color),
+                    falloff: FogFalloff::Linear { start: environment.fog_start, end: environment.fog_end },
+                    ..default()
+                });
+            } else {
+                camera.remove::<DistanceFog>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_round_trip() {
+        let environment = MapEnvironment {
+            ambient_color: Vec3::new(0.2, 0.4, 0.6),
+            skybox: Some("dusk.ktx2".to_owned()),
+            fog_enabled: true,
+            gravity: 9.81,
+            ..default()
+        };
+        let mut loaded = MapEnvironment::default();
+        for (key, value) in environment.rows() {
+            loaded.set_row(key, &value);
+        }
+        assert_eq!(loaded, environment);
+    }
+
+    #[test]
+    fn test_bad_values_keep_defaults() {
+        let mut environment = MapEnvironment::default();
+        environment.set_row("ambient_color", "1,2");
+        environment.set_row("gravity", "heavy");
+        environment.set_row("skybox", "");
+        assert_eq!(environment, MapEnvironment::default());
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::logic::{LogicConnection, connections_ui};
+use crate::get;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum Team {
+    Neutral,
+    Red,
+    Blue,
+}
+
+impl Team {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Team::Neutral => "neutral",
+            Team::Red => "red",
+            Team::Blue => "blue",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|t| t.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Team::Neutral => get!("editor.features.map_marker.team.neutral"),
+            Team::Red => get!("editor.features.map_marker.team.red"),
+            Team::Blue => get!("editor.features.map_marker.team.blue"),
+        }
+    }
+
+    /// The other side; neutral stays neutral.
+    pub fn opponent(&self) -> Self {
+        match self {
+            Team::Neutral => Team::Neutral,
+            Team::Red => Team::Blue,
+            Team::Blue => Team::Red,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Team::Neutral => Color::srgb_u8(220, 220, 220),
+            Team::Red => Color::srgb_u8(230, 70, 60),
+            Team::Blue => Color::srgb_u8(70, 120, 230),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum MarkerKind {
+    Spawn,
+    Flag,
+    ControlPoint,
+}
+
+impl MarkerKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            MarkerKind::Spawn => "spawn",
+            MarkerKind::Flag => "flag",
+            MarkerKind::ControlPoint => "control_point",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|k| k.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            MarkerKind::Spawn => get!("editor.features.map_marker.kind.spawn"),
+            MarkerKind::Flag => get!("editor.features.map_marker.kind.flag"),
+            MarkerKind::ControlPoint => get!("editor.features.map_marker.kind.control_point"),
+        }
+    }
+
+    pub fn inputs(&self) -> &'static [&'static str] {
+        match self {
+            MarkerKind::Spawn => &["Enable", "Disable"],
+            MarkerKind::Flag => &["Return"],
+            MarkerKind::ControlPoint => &["Lock", "Unlock"],
+        }
+    }
+
+    pub fn outputs(&self) -> &'static [&'static str] {
+        match self {
+            MarkerKind::Spawn => &[],
+            MarkerKind::Flag => &["OnPickedUp", "OnCaptured", "OnReturned"],
+            MarkerKind::ControlPoint => &["OnCaptured"],
+        }
+    }
+}
+
+/// A gameplay location: a team's spawn, a flag or a control point.
+#[derive(Serialize, Deserialize)]
+pub struct MapMarker {
+    location: PointRef,
+    pub kind: MarkerKind,
+    pub team: Team,
+    #[serde(default)]
+    pub connections: Vec<LogicConnection>,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[typetag::serde(name = "map_marker")]
+impl FeatureTrait for MapMarker {
+    fn get_point(&self, _key: &str) -> Result<Vec3, PointResolutionError> {
+        Ok(self.resolved_location)
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        if changed {
+            if let Ok(v) = self.location.resolve(features) {
+                self.resolved_location = v;
+            }
+        }
+
+        ui.separator();
+        egui::ComboBox::from_label(get!("editor.features.map_marker.kind.label"))
+            .selected_text(self.kind.name())
+            .show_ui(ui, |ui| {
+                for kind in MarkerKind::iter() {
+                    changed |= ui.selectable_value(&mut self.kind, kind, kind.name()).changed();
+                }
+            });
+        egui::ComboBox::from_label(get!("editor.features.map_marker.team.label"))
+            .selected_text(self.team.name())
+            .show_ui(ui, |ui| {
+                for team in Team::iter() {
+                    changed |= ui.selectable_value(&mut self.team, team, team.name()).changed();
+                }
+            });
+        if !self.kind.outputs().is_empty() {
+            ui.separator();
+            changed |= connections_ui(ui, &mut self.connections, self.kind.outputs(), features);
+        }
+
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.map_marker.title", "kind", self.kind.name())
+    }
+
+    fn type_key(&self) -> &'static str { "map_marker" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Marker {
+            location: self.location.clone(),
+            kind: self.kind,
+            team: self.team,
+            connections: self.connections.clone(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Marker { location, kind, team, connections } = data else { return; };
+        self.location = location.clone();
+        self.kind = *kind;
+        self.team = *team;
+        self.connections = connections.clone();
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let p = self.resolved_location;
+        let color = self.team.color();
+        let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+        match self.kind {
+            MarkerKind::Spawn => {
+                gizmos.circle(Isometry3d::new(p, flat), 0.4, color);
+                gizmos.arrow(p, p + Vec3::Y * 1.2, color);
+            }
+            MarkerKind::Flag => {
+                let top = p + Vec3::Y * 1.5;
+                gizmos.line(p, top, color);
+                gizmos.line(top, top + Vec3::new(0.5, -0.2, 0.0), color);
+                gizmos.line(top + Vec3::new(0.5, -0.2, 0.0), top - Vec3::Y * 0.4, color);
+            }
+            MarkerKind::ControlPoint => {
+                gizmos.circle(Isometry3d::new(p, flat), 1.0, color);
+                gizmos.circle(Isometry3d::new(p, flat), 0.6, color);
+            }
+        }
+        self.location.debug_gizmos(p, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert(Transform::from_translation(self.resolved_location));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        self.location.referenced_features()
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![("".into(), "Marker".into())]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![("".into(), self.resolved_location)]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location"] }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("kind", self.kind.key().to_owned()),
+            ("team", self.team.key().to_owned()),
+        ]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        match key {
+            "kind" => self.kind = MarkerKind::from_key(value).unwrap_or(self.kind),
+            "team" => self.team = Team::from_key(value).unwrap_or(self.team),
+            _ => {}
+        }
+    }
+
+    fn logic_outputs(&self) -> Vec<&'static str> { self.kind.outputs().to_vec() }
+
+    fn logic_inputs(&self) -> Vec<&'static str> { self.kind.inputs().to_vec() }
+
+    fn connections(&self) -> &[LogicConnection] { &self.connections }
+
+    fn set_connections(&mut self, connections: Vec<LogicConnection>) {
+        self.connections = connections;
+    }
+
+    fn get_point_ref(&self, _key: &str) -> Option<&PointRef> {
+        Some(&self.location)
+    }
+
+    fn get_point_ref_mut(&mut self, _key: &str) -> Option<&mut PointRef> {
+        Some(&mut self.location)
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let axis_ref = match axis {
+            0 => &mut self.location.x,
+            1 => &mut self.location.y,
+            2 => &mut self.location.z,
+            _ => return false,
+        };
+        let base = self.location.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        match axis { 0 => self.resolved_location.x = new_world_value, 1 => self.resolved_location.y = new_world_value, _ => self.resolved_location.z = new_world_value }
+        true
+    }
+}
+
+impl MapMarker {
+    pub fn new(x: f32, y: f32, z: f32, kind: MarkerKind, team: Team) -> Self {
+        Self {
+            location: PointRef::absolute(x, y, z),
+            kind,
+            team,
+            connections: Vec::new(),
+            resolved_location: Vec3::new(x, y, z),
+            entity: None,
+        }
+    }
+
+    pub fn from_point_ref(location: PointRef, kind: MarkerKind, team: Team) -> Self {
+        Self {
+            location,
+            kind,
+            team,
+            connections: Vec::new(),
+            resolved_location: Vec3::ZERO,
+            entity: None,
+        }
+    }
+}
@@ -1,14 +1,42 @@
+use std::collections::HashSet;
 use bevy::prelude::Resource;
 use rusqlite::{Connection, OptionalExtension, Transaction, params};
 
 use crate::common::mode::GameMode;
 use crate::constants::SCHEMA_VERSION;
+use crate::editor::camera_bookmark::CameraBookmark;
+use crate::editor::map_environment::MapEnvironment;
+
+pub const DEFAULT_MAP_VERSION: &str = "1.0";
+pub const DEFAULT_MIN_PLAYERS: u32 = 2;
+pub const DEFAULT_MAX_PLAYERS: u32 = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangelogEntry {
+    pub version: String,
+    pub notes: String,
+}
 
 /// Strongly typed map metadata (backed by the `metadata` key/value table in the blueprint DB).
 #[derive(Resource, Debug, Clone)]
 pub struct MapMetadata {
     pub authors: Vec<String>,
     pub game_mode: GameMode,
+    /// Display name for server browsers and the map vote; the file name is used when empty.
+    pub title: String,
+    pub description: String,
+    /// Release version of the map itself, e.g. `1.2`.
+    pub version: String,
+    /// Recommended player counts.
+    pub min_players: u32,
+    pub max_players: u32,
+    /// Oldest entry first.
+    pub changelog: Vec<ChangelogEntry>,
+    /// Game-specific key/value settings, in the order they were added.
+    pub custom: Vec<(String, String)>,
+    pub environment: MapEnvironment,
+    /// Saved free camera views, in the order they were added.
+    pub camera_bookmarks: Vec<CameraBookmark>,
     /// Comma-separated authors line in the metadata panel; kept in sync when `authors` is replaced
     /// from disk. Not a separate persisted column.
     authors_ui_text: String,
@@ -22,6 +50,15 @@ impl Default for MapMetadata {
         Self {
             authors,
             game_mode: GameMode::Arena,
+            title: String::new(),
+            description: String::new(),
+            version: DEFAULT_MAP_VERSION.to_string(),
+            min_players: DEFAULT_MIN_PLAYERS,
+            max_players: DEFAULT_MAX_PLAYERS,
+            changelog: Vec::new(),
+            custom: Vec::new(),
+            environment: MapEnvironment::default(),
+            camera_bookmarks: Vec::new(),
             authors_ui_text,
             authors_ui_synced_from: vec!["anonymous".to_string()],
         }
@@ -63,7 +100,8 @@ impl MapMetadata {
         self.authors_ui_synced_from.clone_from(&self.authors);
     }
 
-    pub(crate) fn load_from_connection(conn: &Connection) -> rusqlite::Result<Self> {
+    /// `fallback_game_mode` is used when the blueprint has no `game_mode` row.
+    pub(crate) fn load_from_connection(conn: &Connection, fallback_game_mode: GameMode) -> rusqlite::Result<Self> {
         let authors_str: Option<String> = conn
             .query_row(
                 "SELECT value FROM metadata WHERE key = 'authors'",
@@ -87,7 +125,24 @@ impl MapMetadata {
         let game_mode = game_mode_str
             .as_deref()
             .and_then(|s| GameMode::try_from(s).ok())
-            .unwrap_or(GameMode::Arena);
+            .unwrap_or(fallback_game_mode);
+
+        let min_players = metadata_value(conn, "min_players")?
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MIN_PLAYERS);
+        let max_players = metadata_value(conn, "max_players")?
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_PLAYERS)
+            .max(min_players);
+
+        let changelog = conn
+            .prepare("SELECT version, notes FROM metadata_changelog ORDER BY position")?
+            .query_map([], |row| Ok(ChangelogEntry { version: row.get(0)?, notes: row.get(1)? }))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let custom = conn
+            .prepare("SELECT key, value FROM metadata_custom ORDER BY position")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
 
         let authors_ui_text = format_authors_for_ui(&authors);
         let authors_ui_synced_from = authors.clone();
@@ -95,6 +150,15 @@ impl MapMetadata {
         Ok(Self {
             authors,
             game_mode,
+            title: metadata_value(conn, "title")?.unwrap_or_default(),
+            description: metadata_value(conn, "description")?.unwrap_or_default(),
+            version: metadata_value(conn, "version")?.unwrap_or_else(|| DEFAULT_MAP_VERSION.to_string()),
+            min_players,
+            max_players,
+            changelog,
+            custom,
+            environment: MapEnvironment::load_from_connection(conn)?,
+            camera_bookmarks: CameraBookmark::load_from_connection(conn)?,
             authors_ui_text,
             authors_ui_synced_from,
         })
@@ -109,8 +173,48 @@ impl MapMetadata {
             "INSERT INTO metadata (key, value) VALUES ('game_mode', ?1)",
             params![self.game_mode.prefix()],
         )?;
+        for (key, value) in [
+            ("title", self.title.clone()),
+            ("description", self.description.clone()),
+            ("version", self.version.clone()),
+            ("min_players", self.min_players.to_string()),
+            ("max_players", self.max_players.to_string()),
+        ] {
+            tx.execute("INSERT INTO metadata (key, value) VALUES (?1, ?2)", params![key, value])?;
+        }
+        for (position, entry) in self.changelog.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO metadata_changelog (position, version, notes) VALUES (?1, ?2, ?3)",
+                params![position as i64, entry.version, entry.notes],
+            )?;
+        }
+        for (position, (key, value)) in self.custom_values().enumerate() {
+            tx.execute(
+                "INSERT INTO metadata_custom (key, value, position) VALUES (?1, ?2, ?3)",
+                params![key, value, position as i64],
+            )?;
+        }
+        self.environment.insert_rows(tx)?;
+        CameraBookmark::insert_rows(&self.camera_bookmarks, tx)?;
         Ok(())
     }
+
+    /// Custom values as persisted: blank keys are dropped and the first of any duplicate key wins.
+    pub fn custom_values(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mut seen = HashSet::new();
+        self.custom.iter()
+            .map(|(key, value)| (key.trim(), value.as_str()))
+            .filter(move |(key, _)| !key.is_empty() && seen.insert(*key))
+    }
+
+    pub fn custom_value(&self, key: &str) -> Option<&str> {
+        self.custom_values().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+}
+
+fn metadata_value(conn: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT value FROM metadata WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
 }
 
 /// Serialize authors as comma-separated quoted fields; internal `"` doubled (RFC 4180).
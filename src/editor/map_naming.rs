@@ -0,0 +1,113 @@
+//! Blueprint file naming: `<prefix>_<name>.gmb`, where the prefix is [`GameMode::prefix`], as in `ctf_2fort.gmb`.
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use crate::common::mode::GameMode;
+use crate::constants::MAP_BLUEPRINT_EXTENSION;
+use crate::get;
+
+/// Game mode named by the file's prefix, if it has a recognized one.
+pub fn game_mode_from_file_name(path: &Path) -> Option<GameMode> {
+    let stem = path.file_stem()?.to_str()?;
+    let (prefix, _) = stem.split_once('_')?;
+    GameMode::try_from(prefix).ok()
+}
+
+/// The file stem without its game mode prefix.
+pub fn map_name(path: &Path) -> Option<&str> {
+    let stem = path.file_stem()?.to_str()?;
+    match (game_mode_from_file_name(path), stem.split_once('_')) {
+        (Some(_), Some((_, name))) => Some(name),
+        _ => Some(stem),
+    }
+}
+
+/// Lowercase ASCII letters and digits separated by single underscores.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_end_matches('_');
+    if slug.is_empty() { "untitled".to_owned() } else { slug.to_owned() }
+}
+
+pub fn suggested_file_name(game_mode: GameMode, name: &str) -> String {
+    format!("{}_{}.{}", game_mode.prefix(), slugify(name), MAP_BLUEPRINT_EXTENSION)
+}
+
+/// Ways a blueprint's file name can disagree with its metadata.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NamingIssue {
+    /// The name doesn't start with any game mode prefix.
+    MissingPrefix { expected: GameMode },
+    /// The prefix names a different game mode than the metadata does.
+    WrongPrefix { found: GameMode, expected: GameMode },
+}
+
+impl NamingIssue {
+    pub fn message(&self) -> String {
+        match self {
+            Self::MissingPrefix { expected } => get!("editor.naming.missing_prefix", "prefix", expected.prefix()),
+            Self::WrongPrefix { found, expected } => get!("editor.naming.wrong_prefix", "found", found.prefix(), "prefix", expected.prefix()),
+        }
+    }
+}
+
+/// Plain English for the command-line tools, which run without the language files.
+impl Display for NamingIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingPrefix { expected } => write!(f, "missing the \"{}_\" prefix", expected.prefix()),
+            Self::WrongPrefix { found, expected } => {
+                write!(f, "prefix \"{}\" contradicts game mode \"{}\"", found.prefix(), expected.prefix())
+            }
+        }
+    }
+}
+
+pub fn check_file_name(path: &Path, game_mode: GameMode) -> Option<NamingIssue> {
+    match game_mode_from_file_name(path) {
+        None => Some(NamingIssue::MissingPrefix { expected: game_mode }),
+        Some(found) if found != game_mode => Some(NamingIssue::WrongPrefix { found, expected: game_mode }),
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_is_read_from_file_name() {
+        assert_eq!(game_mode_from_file_name(Path::new("maps/ctf_2fort.gmb")), Some(GameMode::CTF));
+        assert_eq!(game_mode_from_file_name(Path::new("KOTH_Viaduct.gmb")), Some(GameMode::KOTH));
+        assert_eq!(game_mode_from_file_name(Path::new("quarry.gmb")), None);
+        assert_eq!(game_mode_from_file_name(Path::new("big_quarry.gmb")), None);
+        assert_eq!(map_name(Path::new("ctf_2fort.gmb")), Some("2fort"));
+        assert_eq!(map_name(Path::new("big_quarry.gmb")), Some("big_quarry"));
+    }
+
+    #[test]
+    fn test_suggested_file_name() {
+        assert_eq!(suggested_file_name(GameMode::PL, "Upward (Night)"), "pl_upward_night.gmb");
+        assert_eq!(suggested_file_name(GameMode::Arena, "  "), "arena_untitled.gmb");
+    }
+
+    #[test]
+    fn test_check_file_name() {
+        assert_eq!(check_file_name(Path::new("ctf_2fort.gmb"), GameMode::CTF), None);
+        assert_eq!(
+            check_file_name(Path::new("koth_2fort.gmb"), GameMode::CTF),
+            Some(NamingIssue::WrongPrefix { found: GameMode::KOTH, expected: GameMode::CTF }),
+        );
+        assert_eq!(
+            check_file_name(Path::new("2fort.gmb"), GameMode::CTF),
+            Some(NamingIssue::MissingPrefix { expected: GameMode::CTF }),
+        );
+    }
+}
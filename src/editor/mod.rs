@@ -1,10 +1,37 @@
 pub mod multicam;
 pub mod input;
+pub mod preferences;
+pub mod render_mode;
 pub mod editable;
 pub mod map_metadata;
+pub mod camera_bookmark;
+pub mod map_environment;
+pub mod map_naming;
 pub mod action;
 pub mod global_point;
 pub mod grackle_point_light;
+pub mod grackle_spot_light;
+pub mod grackle_sun;
+pub mod grackle_area_light;
 pub mod editor_room;
+pub mod map_marker;
+pub mod logic;
+pub mod trigger_volume;
+pub mod logic_entity;
+pub mod teleporter;
+pub mod jump_pad;
+pub mod pickup;
+pub mod prop;
+pub mod liquid_volume;
+pub mod stairs;
+pub mod dimension;
+pub mod note;
 pub mod panels;
 pub mod save;
+pub mod diff;
+pub mod symmetry;
+pub mod balance;
+pub mod asset_browser;
+pub mod transform;
+pub mod room_ops;
+pub mod align;
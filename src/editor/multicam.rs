@@ -9,10 +9,20 @@ use bevy::render::view::Hdr;
 use bevy::window::{PrimaryWindow, WindowResized};
 use bevy_egui::{egui, EguiPrimaryContextPass, EguiContexts, EguiGlobalSettings, PrimaryEguiContext};
 use bevy_vector_shapes::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 use crate::common::painter;
+use crate::editor::input::CurrentMouseInput;
+use crate::editor::preferences::EditorPreferences;
+use crate::editor::render_mode::RenderMode;
 use crate::tool::selection::EditorSelectable;
 use crate::get;
 
+/// How far orthographic views sit back from the point they look at.
+const ORTHO_DISTANCE: f32 = 5.0;
+/// Where the perspective view sits relative to the point it looks at.
+const PERSPECTIVE_OFFSET: Vec3 = Vec3::new(-2.5, 4.5, 9.0);
+
 pub struct MulticamPlugin {
     pub test_scene: bool,
 }
@@ -28,17 +38,41 @@ pub struct MulticamState {
     pub debug_mouse_circle: bool,
     pub draw_ortho_cameras: bool,
     pub draw_perspective_cameras: bool,
+    pub layout: ViewportLayout,
+    /// What each camera looks along, by camera id.
+    pub views: [CameraAxis; 4],
+    /// How each camera draws the level, by camera id.
+    pub render_modes: [RenderMode; 4],
+    /// The camera filling the whole viewport area, whatever the layout.
+    pub maximized: Option<u32>,
+}
+
+impl MulticamState {
+    /// The cameras on screen, with the fraction of the viewport area each covers.
+    pub fn visible_cells(&self) -> Vec<(u32, Rect)> {
+        if let Some(id) = self.maximized {
+            return vec![(id, Rect::new(0.0, 0.0, 1.0, 1.0))];
+        }
+        self.layout.cells().into_iter()
+            .enumerate()
+            .map(|(id, cell)| (id as u32, cell))
+            .collect()
+    }
 }
 
 #[derive(Component)]
 pub struct Multicam {
     pub name: String,
-    pub screen_pos: UVec2,
     pub id: u32,
     pub axis: CameraAxis,
 }
 
-#[derive(PartialEq, Clone, Copy, Eq)]
+/// The name shown in the corner of a camera's viewport.
+#[derive(Component)]
+struct MulticamLabel(u32);
+
+/// `None` is the free perspective view; the others are orthographic views looking along that axis.
+#[derive(PartialEq, Clone, Copy, Eq, Debug, Serialize, Deserialize, EnumIter)]
 pub enum CameraAxis {
     None,
     X,
@@ -46,6 +80,98 @@ pub enum CameraAxis {
     Z,
 }
 
+impl CameraAxis {
+    pub fn name(&self) -> String {
+        match self {
+            CameraAxis::None => get!("viewport.free"),
+            CameraAxis::X => get!("viewport.front"),
+            CameraAxis::Y => get!("viewport.top"),
+            CameraAxis::Z => get!("viewport.right"),
+        }
+    }
+
+    fn projection(&self) -> Projection {
+        match self {
+            CameraAxis::None => Projection::Perspective(PerspectiveProjection {
+                fov: 120.0,
+                ..Default::default()
+            }),
+            _ => Projection::Orthographic(OrthographicProjection {
+                near: 0.05,
+                far: 1000.0,
+                scaling_mode: Default::default(),
+                scale: 0.01,
+                ..OrthographicProjection::default_2d()
+            }),
+        }
+    }
+
+    /// A camera with this view, looking at `pivot`.
+    fn transform(&self, pivot: Vec3) -> Transform {
+        match self {
+            CameraAxis::None => Transform::from_translation(pivot + PERSPECTIVE_OFFSET).looking_at(pivot, Vec3::Y),
+            CameraAxis::X => Transform::from_translation(pivot + Vec3::X * ORTHO_DISTANCE).looking_at(pivot, Vec3::Y),
+            CameraAxis::Y => Transform::from_translation(pivot + Vec3::Y * ORTHO_DISTANCE).looking_at(pivot, -Vec3::X),
+            CameraAxis::Z => Transform::from_translation(pivot + Vec3::Z * ORTHO_DISTANCE).looking_at(pivot, Vec3::Y),
+        }
+    }
+
+    /// The point a camera with this view at `transform` is looking at.
+    fn pivot(&self, transform: &Transform) -> Vec3 {
+        let distance = match self {
+            CameraAxis::None => PERSPECTIVE_OFFSET.length(),
+            _ => ORTHO_DISTANCE,
+        };
+        transform.translation + transform.forward() * distance
+    }
+}
+
+/// How the cameras share the space between the panels.
+#[derive(PartialEq, Clone, Copy, Eq, Debug, Default, Serialize, Deserialize, EnumIter)]
+pub enum ViewportLayout {
+    Single,
+    /// One large view with the other three stacked beside it.
+    OneAndThree,
+    #[default]
+    Grid,
+    SideBySide,
+}
+
+impl ViewportLayout {
+    pub fn name(&self) -> String {
+        match self {
+            ViewportLayout::Single => get!("viewport.layout.single"),
+            ViewportLayout::OneAndThree => get!("viewport.layout.one_and_three"),
+            ViewportLayout::Grid => get!("viewport.layout.grid"),
+            ViewportLayout::SideBySide => get!("viewport.layout.side_by_side"),
+        }
+    }
+
+    /// The fraction of the viewport area each camera covers, by camera id. Cameras past the end are hidden.
+    pub fn cells(&self) -> Vec<Rect> {
+        let third = 1.0 / 3.0;
+        match self {
+            ViewportLayout::Single => vec![Rect::new(0.0, 0.0, 1.0, 1.0)],
+            ViewportLayout::OneAndThree => vec![
+                Rect::new(0.0, 0.0, 2.0 * third, 1.0),
+                Rect::new(2.0 * third, 0.0, 1.0, third),
+                Rect::new(2.0 * third, third, 1.0, 2.0 * third),
+                Rect::new(2.0 * third, 2.0 * third, 1.0, 1.0),
+            ],
+            ViewportLayout::Grid => vec![
+                Rect::new(0.0, 0.0, 0.5, 0.5),
+                Rect::new(0.5, 0.0, 1.0, 0.5),
+                Rect::new(0.0, 0.5, 0.5, 1.0),
+                Rect::new(0.5, 0.5, 1.0, 1.0),
+            ],
+            ViewportLayout::SideBySide => vec![
+                Rect::new(0.0, 0.0, 0.5, 1.0),
+                Rect::new(0.5, 0.0, 1.0, 1.0),
+            ],
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct MulticamTestScene;
 
@@ -61,6 +187,10 @@ impl Default for MulticamState {
             debug_window: false,
             draw_ortho_cameras: false,
             draw_perspective_cameras: true,
+            layout: ViewportLayout::default(),
+            views: [CameraAxis::None, CameraAxis::X, CameraAxis::Y, CameraAxis::Z],
+            render_modes: [RenderMode::Lit; 4],
+            maximized: None,
         }
     }
 }
@@ -77,9 +207,11 @@ impl Plugin for MulticamPlugin {
                 Self::setup.after(Self::setup_first_camera),
             ))
             .add_systems(Update, (
+                Self::maximize_shortcut,
+                Self::apply_views,
                 Self::set_camera_viewports,
                 Self::debug_boxes,
-            ))
+            ).chain())
             // Global transforms are propagated from transforms during PostUpdate, so we need to draw the camera after that.
             .add_systems(PostUpdate, Self::draw_camera_gizmos.after(TransformSystems::Propagate))
             .add_systems(EguiPrimaryContextPass, Self::debug_window)
@@ -94,35 +226,20 @@ impl MulticamPlugin {
 
     fn setup(
         mut commands: Commands,
-        state: Res<MulticamState>,
+        mut state: ResMut<MulticamState>,
+        preferences: Res<EditorPreferences>,
         mut meshes: ResMut<Assets<Mesh>>,
         mut materials: ResMut<Assets<StandardMaterial>>,
         mut egui_global_settings: ResMut<EguiGlobalSettings>,
     ) {
         egui_global_settings.auto_create_primary_context = false;
 
-        let perspective = Projection::Perspective(PerspectiveProjection {
-            fov: 120.0,
-            ..Default::default()
-        });
-        let orthographic = Projection::Orthographic(OrthographicProjection {
-            near: 0.05,
-            far: 1000.0,
-            scaling_mode: Default::default(),
-            scale: 0.01,
-            ..OrthographicProjection::default_2d()
-        });
+        state.layout = preferences.viewports.layout;
+        state.views = preferences.viewports.views;
+        state.render_modes = preferences.viewports.render_modes;
 
-        let dist = 5.0;
-        let cameras = [
-            (get!("viewport.free"), Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y), &perspective, CameraAxis::None),
-            (get!("viewport.front"), Transform::from_xyz(dist, 0.0, 0.0).looking_at(Vec3::ZERO, Vec3::Y), &orthographic, CameraAxis::X),
-            (get!("viewport.top"), Transform::from_xyz(0.0, dist, 0.0).looking_at(Vec3::ZERO, -Vec3::X), &orthographic, CameraAxis::Y),
-            (get!("viewport.right"), Transform::from_xyz(0.0, 0.0, dist).looking_at(Vec3::ZERO, Vec3::Y), &orthographic, CameraAxis::Z),
-        ];
-        let cameras_len = cameras.len();
-
-        for (idx, (camera_name, camera_pos, projection, axis)) in cameras.into_iter().enumerate() {
+        let cameras_len = state.views.len();
+        for (idx, axis) in state.views.into_iter().enumerate() {
             let camera = commands
                 .spawn((
                     Camera3d::default(),
@@ -131,16 +248,15 @@ impl MulticamPlugin {
                         ..Default::default()
                     },
                     Hdr,
-                    camera_pos,
+                    axis.transform(Vec3::ZERO),
                     Bloom::NATURAL,
                     Tonemapping::TonyMcMapface,
                     Multicam {
-                        name: camera_name.to_string(),
-                        screen_pos: UVec2::new((idx % 2) as u32, (idx / 2) as u32),
+                        name: axis.name(),
                         id: idx as u32,
                         axis,
                     },
-                    projection.clone(),
+                    axis.projection(),
                 ))
                 .id();
 
@@ -161,7 +277,8 @@ impl MulticamPlugin {
                                 left: Val::Px(12.),
                                 ..Default::default()
                             },
-                            Text::new(camera_name),
+                            Text::new(axis.name()),
+                            MulticamLabel(idx as u32),
                         ));
                     });
         }
@@ -313,27 +430,74 @@ impl MulticamPlugin {
             (state.start.y * window_size.y as f32) as u32,
         );
         
-        // Calculate the size of each camera's viewport (2x2 grid)
-        let camera_size = UVec2::new(
-            viewport_size.x / 2,
-            viewport_size.y / 2,
-        );
-
+        let cells = state.visible_cells();
         for (mut camera, multicam) in cameras {
-            // Calculate this camera's position within the viewport
-            let camera_pos = viewport_start + UVec2::new(
-                multicam.screen_pos.x * camera_size.x,
-                multicam.screen_pos.y * camera_size.y,
-            );
+            let Some((_, cell)) = cells.iter().find(|(id, _)| *id == multicam.id) else {
+                camera.is_active = false;
+                continue;
+            };
+            let min = (cell.min * viewport_size.as_vec2()).as_uvec2();
+            let max = (cell.max * viewport_size.as_vec2()).as_uvec2();
 
+            camera.is_active = true;
             camera.viewport = Some(Viewport {
-                physical_position: camera_pos,
-                physical_size: camera_size,
+                physical_position: viewport_start + min,
+                physical_size: (max - min).max(UVec2::ONE),
                 ..Default::default()
             });
         }
     }
 
+    /// Ctrl+Space fills the viewport area with the hovered view, and puts the layout back.
+    fn maximize_shortcut(
+        keys: Res<ButtonInput<KeyCode>>,
+        mouse_input: Res<CurrentMouseInput>,
+        mut state: ResMut<MulticamState>,
+        cameras: Query<&Multicam>,
+        mut egui_contexts: EguiContexts,
+    ) {
+        if let Ok(ctx) = egui_contexts.ctx_mut()
+            && ctx.wants_keyboard_input()
+        {
+            return;
+        }
+        let ctrl = keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight);
+        if !ctrl || !keys.just_pressed(KeyCode::Space) {
+            return;
+        }
+        if state.maximized.is_some() {
+            state.maximized = None;
+        } else if let Some(multicam) = mouse_input.in_camera.and_then(|entity| cameras.get(entity).ok()) {
+            state.maximized = Some(multicam.id);
+        }
+    }
+
+    /// Switch cameras whose view was changed in the panel, keeping them on the point they looked at.
+    fn apply_views(
+        state: Res<MulticamState>,
+        mut cameras: Query<(&mut Multicam, &mut Transform, &mut Projection)>,
+        mut labels: Query<(&mut Text, &MulticamLabel)>,
+    ) {
+        for (mut multicam, mut transform, mut projection) in &mut cameras {
+            let Some(axis) = state.views.get(multicam.id as usize).copied() else { continue; };
+            if axis == multicam.axis {
+                continue;
+            }
+            let pivot = multicam.axis.pivot(&transform);
+            *transform = axis.transform(pivot);
+            if (axis == CameraAxis::None) != (multicam.axis == CameraAxis::None) {
+                *projection = axis.projection();
+            }
+            multicam.axis = axis;
+            multicam.name = axis.name();
+            for (mut text, label) in &mut labels {
+                if label.0 == multicam.id {
+                    text.0 = multicam.name.clone();
+                }
+            }
+        }
+    }
+
     fn debug_window(
         mut state: ResMut<MulticamState>,
         mut contexts: EguiContexts,
@@ -523,3 +687,32 @@ fn draw_rect(
     painter.line(Vec3::new(min.x, min.y, 0.0), Vec3::new(min.x, max.y, 0.0)); // Left
     painter.line(Vec3::new(max.x, min.y, 0.0), Vec3::new(max.x, max.y, 0.0)); // Right
 }
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+    use super::*;
+
+    #[test]
+    fn test_layouts_tile_the_viewport_area() {
+        for layout in ViewportLayout::iter() {
+            let cells = layout.cells();
+            let area: f32 = cells.iter().map(|c| c.width() * c.height()).sum();
+            assert!((area - 1.0).abs() < 1e-5, "{:?} covers {}", layout, area);
+            for (i, a) in cells.iter().enumerate() {
+                for b in &cells[i + 1..] {
+                    let overlap = a.intersect(*b);
+                    assert!(overlap.is_empty() || overlap.width() * overlap.height() < 1e-5, "{:?} overlaps", layout);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_maximized_view_fills_the_area() {
+        let mut state = MulticamState::default();
+        assert_eq!(state.visible_cells().len(), 4);
+        state.maximized = Some(2);
+        assert_eq!(state.visible_cells(), vec![(2, Rect::new(0.0, 0.0, 1.0, 1.0))]);
+    }
+}
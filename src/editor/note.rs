@@ -0,0 +1,357 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::common::PointResolutionError;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTimeline, FeatureTrait, PointRef};
+use crate::editor::grackle_point_light::color_edit;
+use crate::editor::multicam::Multicam;
+use crate::get;
+use crate::tool::show::GizmoVisibility;
+use crate::tool::tool_helpers::paint_viewport_labels;
+
+pub const DEFAULT_COLOR: Vec3 = Vec3::new(1.0, 0.85, 0.2);
+
+/// Height of the pin a note hangs from, above its location.
+const PIN_HEIGHT: f32 = 1.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum NoteStatus {
+    Open,
+    Resolved,
+}
+
+impl NoteStatus {
+    pub fn key(&self) -> &'static str {
+        match self {
+            NoteStatus::Open => "open",
+            NoteStatus::Resolved => "resolved",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|s| s.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            NoteStatus::Open => get!("editor.features.note.status.open"),
+            NoteStatus::Resolved => get!("editor.features.note.status.resolved"),
+        }
+    }
+}
+
+/// A review comment left in the level. Notes are saved with the blueprint's other features, but no
+/// bake reads them (the navmesh and room geometry come from rooms and stairs, the baked volumes from
+/// liquids) and nothing can reference one, so nothing of a note reaches the runtime.
+#[derive(Serialize, Deserialize)]
+pub struct Note {
+    location: PointRef,
+    pub text: String,
+    pub author: String,
+    pub status: NoteStatus,
+    pub color: Vec3,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl Note {
+    pub fn from_point_ref(location: PointRef, author: &str) -> Self {
+        Self {
+            location,
+            text: String::new(),
+            author: author.to_owned(),
+            status: NoteStatus::Open,
+            color: DEFAULT_COLOR,
+            resolved_location: Vec3::ZERO,
+            entity: None,
+        }
+    }
+
+    /// Where the text sits, at the top of the pin.
+    pub fn label_position(&self) -> Vec3 {
+        self.resolved_location + Vec3::Y * PIN_HEIGHT
+    }
+
+    /// Resolved notes fade to grey so open ones stand out.
+    pub fn display_color(&self) -> Color {
+        match self.status {
+            NoteStatus::Open => Color::srgb(self.color.x, self.color.y, self.color.z),
+            NoteStatus::Resolved => Color::srgb_u8(120, 120, 120),
+        }
+    }
+}
+
+/// The notes in the timeline, in timeline order, with the point their label hangs from.
+fn notes_in_timeline(timeline: &FeatureTimeline) -> Vec<(FeatureId, NoteListing)> {
+    timeline.active_features()
+        .filter_map(|(id, feature)| {
+            let FeatureData::Note { text, author, status, color, .. } = feature.object().snapshot() else { return None; };
+            let position = feature.get_point("").ok()? + Vec3::Y * PIN_HEIGHT;
+            Some((id, NoteListing { text, author, status, color, position }))
+        })
+        .collect()
+}
+
+struct NoteListing {
+    text: String,
+    author: String,
+    status: NoteStatus,
+    color: Vec3,
+    position: Vec3,
+}
+
+/// The first line of a note, for lists and labels.
+fn summary(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+/// Every note with its author and status. Clicking one selects it and sets `focus` to where it is,
+/// for the cameras to jump to.
+pub fn notes_panel_ui(ui: &mut egui::Ui, timeline: &mut FeatureTimeline, focus: &mut Option<Vec3>) {
+    let notes = notes_in_timeline(timeline);
+    if notes.is_empty() {
+        ui.label(get!("editor.notes.empty"));
+        return;
+    }
+    let open = notes.iter().filter(|(_, n)| n.status == NoteStatus::Open).count();
+    ui.label(get!("editor.notes.open", "open", open, "total", notes.len()));
+
+    egui::Grid::new("editor_notes_grid")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (id, note) in &notes {
+                let title = match summary(&note.text) {
+                    "" => get!("editor.notes.untitled"),
+                    line => line.to_owned(),
+                };
+                let selected = timeline.selected_feature() == Some(*id);
+                if ui.selectable_label(selected, title).on_hover_text(&note.text).clicked() {
+                    timeline.select(Some(*id));
+                    *focus = Some(note.position);
+                }
+                ui.label(&note.author);
+                ui.label(note.status.name());
+                ui.end_row();
+            }
+        });
+}
+
+/// The first line of each note, over the viewports while notes are shown.
+pub fn draw_note_labels(
+    mut contexts: EguiContexts,
+    features: Res<FeatureTimeline>,
+    visibility: Res<GizmoVisibility>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Multicam>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+) {
+    if !visibility.notes {
+        return;
+    }
+    let labels: Vec<(Vec3, String, egui::Color32)> = notes_in_timeline(&features).into_iter()
+        .map(|(_, note)| {
+            let color = match note.status {
+                NoteStatus::Open => egui::Color32::from_rgb((note.color.x * 255.0) as u8, (note.color.y * 255.0) as u8, (note.color.z * 255.0) as u8),
+                NoteStatus::Resolved => egui::Color32::GRAY,
+            };
+            (note.position, summary(&note.text).to_owned(), color)
+        })
+        .filter(|(_, text, _)| !text.is_empty())
+        .collect();
+    if labels.is_empty() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+    paint_viewport_labels(ctx, "note_labels", cameras.iter(), window.scale_factor(), &labels);
+}
+
+#[typetag::serde(name = "note")]
+impl FeatureTrait for Note {
+    fn get_point(&self, _key: &str) -> Result<Vec3, PointResolutionError> {
+        Ok(self.resolved_location)
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        if changed {
+            if let Ok(v) = self.location.resolve(features) {
+                self.resolved_location = v;
+            }
+        }
+
+        ui.separator();
+        changed |= ui.add(egui::TextEdit::multiline(&mut self.text).hint_text(get!("editor.features.note.text_hint"))).changed();
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.note.author"));
+            changed |= ui.text_edit_singleline(&mut self.author).changed();
+        });
+        egui::ComboBox::from_label(get!("editor.features.note.status.label"))
+            .selected_text(self.status.name())
+            .show_ui(ui, |ui| {
+                for status in NoteStatus::iter() {
+                    changed |= ui.selectable_value(&mut self.status, status, status.name()).changed();
+                }
+            });
+        changed |= color_edit(ui, &mut self.color);
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.note.title")
+    }
+
+    fn type_key(&self) -> &'static str { "note" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Note {
+            location: self.location.clone(),
+            text: self.text.clone(),
+            author: self.author.clone(),
+            status: self.status,
+            color: self.color,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Note { location, text, author, status, color } = data else { return; };
+        self.location = location.clone();
+        self.text = text.clone();
+        self.author = author.clone();
+        self.status = *status;
+        self.color = *color;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let color = self.display_color();
+        let top = self.label_position();
+        gizmos.line(self.resolved_location, top, color);
+        gizmos.rect(Isometry3d::from_translation(top + Vec3::new(0.25, -0.15, 0.0)), Vec2::new(0.5, 0.3), color);
+        gizmos.sphere(Isometry3d::from_translation(self.resolved_location), 0.08, color);
+        self.location.debug_gizmos(self.resolved_location, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert(Transform::from_translation(self.resolved_location));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        self.location.referenced_features()
+    }
+
+    // Notes are not part of the level, so nothing in the level may be placed relative to one.
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("color_r", self.color.x),
+            ("color_g", self.color.y),
+            ("color_b", self.color.z),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "color_r" => self.color.x = value,
+            "color_g" => self.color.y = value,
+            "color_b" => self.color.z = value,
+            _ => {}
+        }
+    }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("text", self.text.clone()),
+            ("author", self.author.clone()),
+            ("status", self.status.key().to_owned()),
+        ]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        match key {
+            "text" => self.text = value.to_owned(),
+            "author" => self.author = value.to_owned(),
+            "status" => self.status = NoteStatus::from_key(value).unwrap_or(self.status),
+            _ => {}
+        }
+    }
+
+    fn get_point_ref(&self, _key: &str) -> Option<&PointRef> {
+        Some(&self.location)
+    }
+
+    fn get_point_ref_mut(&mut self, _key: &str) -> Option<&mut PointRef> {
+        Some(&mut self.location)
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let axis_ref = match axis {
+            0 => &mut self.location.x,
+            1 => &mut self.location.y,
+            2 => &mut self.location.z,
+            _ => return false,
+        };
+        let base = self.location.resolved_reference.map(|b| b[axis as usize]);
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        self.resolved_location[axis as usize] = new_world_value;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_keys_round_trip() {
+        for status in NoteStatus::iter() {
+            assert_eq!(NoteStatus::from_key(status.key()), Some(status));
+        }
+        assert_eq!(NoteStatus::from_key("wontfix"), None);
+    }
+
+    #[test]
+    fn test_summary_is_first_line() {
+        assert_eq!(summary(""), "");
+        assert_eq!(summary("Too dark here\nAdd a light by the door"), "Too dark here");
+    }
+
+    #[test]
+    fn test_notes_offer_nothing_to_reference() {
+        let note = Note::from_point_ref(PointRef::absolute(1.0, 2.0, 3.0), "");
+        assert!(note.available_point_keys().is_empty());
+        assert!(note.reference_points_for_ray(&Ray3d::new(Vec3::ZERO, Dir3::X)).is_empty());
+    }
+}
@@ -1,23 +1,37 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy_egui::{egui, EguiPrimaryContextPass, EguiContexts};
 use bevy_egui::egui::{Ui, UiKind, WidgetText};
 use egui_dock::{DockArea, DockState, TabViewer};
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::Display;
 use crate::common::mode::GameMode;
 use crate::constants::MAP_BLUEPRINT_EXTENSION;
+use crate::editor::asset_browser::{asset_browser_panel_ui, AssetBrowserState};
+use crate::editor::balance::{balance_panel_ui, BalanceState};
+use crate::editor::camera_bookmark::{camera_bookmarks_panel_ui, BookmarkRequests};
 use crate::editor::editable::{EditEvent, FeatureId, FeatureTimeline};
-use crate::editor::map_metadata::MapMetadata;
+use crate::editor::map_environment::environment_panel_ui;
+use crate::editor::map_metadata::{ChangelogEntry, MapMetadata};
+use crate::editor::map_naming::{check_file_name, map_name, suggested_file_name};
 use crate::editor::multicam::MulticamState;
+use crate::editor::note::notes_panel_ui;
+use crate::editor::preferences::PreferencesWindow;
 use crate::editor::save::{self, LoadedBlueprint};
+use crate::editor::symmetry::symmetry_panel_ui;
 use crate::get;
 use crate::tool::Tools;
 use crate::tool::bakes::{BakePlugin, BakeCommands, LogECS};
+use crate::tool::navmesh::{CalculateNavmesh, ClearNavmesh, Navmesh};
+use crate::tool::playtest::{playtest_panel_ui, PlaytestSettings, PlaytestState};
 use crate::tool::retarget::RetargetState;
 use crate::tool::show::{ShowPlugin, GizmoVisibility};
+use crate::tool::measure::{measure_panel_ui, MeasureToolState};
+use crate::tool::movement::{BookmarkCamera, FocusCamera, JumpCamera};
+use crate::tool::transform_gizmo::{transform_panel_ui, TransformGizmoState};
 use crate::tool::room::{CalculateRoomGeometry, ClearRoomGeometry};
 
 enum DialogResult {
@@ -42,6 +56,7 @@ impl Default for CurrentFilePath {
     }
 }
 
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 enum TabKinds {
     Empty(String),
     Tools,
@@ -50,6 +65,12 @@ enum TabKinds {
     Metadata,
     Timeline,
     History,
+    Symmetry,
+    Playtest,
+    Balance,
+    Assets,
+    Notes,
+    Cameras,
 }
 
 #[derive(Default)]
@@ -62,11 +83,20 @@ struct TabViewerAndResources<'a> {
     next_tool: &'a mut NextState<Tools>,
     editor_features: &'a mut FeatureTimeline,
     map_metadata: &'a mut MapMetadata,
+    current_file_path: Option<&'a Path>,
     multicam_state: &'a mut MulticamState,
     bake_commands: &'a mut BakeCommands,
     gizmo_visibility: &'a mut GizmoVisibility,
     pending_edits: &'a mut PendingEditEvents,
     retarget_request: &'a mut Option<(FeatureId, String)>,
+    playtest_settings: &'a mut PlaytestSettings,
+    balance_state: &'a mut BalanceState,
+    asset_browser_state: &'a mut AssetBrowserState,
+    transform_gizmo_state: &'a mut TransformGizmoState,
+    measure_tool_state: &'a mut MeasureToolState,
+    focus_request: &'a mut Option<Vec3>,
+    new_bookmark_name: &'a mut String,
+    bookmark_requests: &'a mut BookmarkRequests,
     gizmos: Gizmos<'a, 'a>,
 }
 
@@ -82,6 +112,12 @@ impl<'a> TabViewer for TabViewerAndResources<'a> {
             TabKinds::Metadata => { get!("editor.metadata.title").into() }
             TabKinds::Timeline => { get!("editor.timeline.title").into() }
             TabKinds::History => { get!("editor.history.title").into() }
+            TabKinds::Symmetry => { get!("editor.symmetry.title").into() }
+            TabKinds::Playtest => { get!("playtest.title").into() }
+            TabKinds::Balance => { get!("editor.balance.title").into() }
+            TabKinds::Assets => { get!("editor.assets.title").into() }
+            TabKinds::Notes => { get!("editor.notes.title").into() }
+            TabKinds::Cameras => { get!("editor.bookmarks.title").into() }
         }
     }
 
@@ -92,6 +128,13 @@ impl<'a> TabViewer for TabViewerAndResources<'a> {
             }
             TabKinds::Tools => {
                 Tools::ui(ui, self.current_tool, self.next_tool);
+                if *self.current_tool.get() == Tools::Transform {
+                    ui.separator();
+                    transform_panel_ui(ui, self.transform_gizmo_state, self.editor_features, &mut self.pending_edits.events);
+                } else if *self.current_tool.get() == Tools::Measure {
+                    ui.separator();
+                    measure_panel_ui(ui, self.measure_tool_state, self.editor_features);
+                }
             }
             TabKinds::Bakes => {
                 *self.bake_commands = BakePlugin::ui(ui);
@@ -100,7 +143,7 @@ impl<'a> TabViewer for TabViewerAndResources<'a> {
                 ShowPlugin::ui(ui, self.multicam_state, self.gizmo_visibility);
             }
             TabKinds::Metadata => {
-                map_metadata_panel_ui(ui, self.map_metadata);
+                map_metadata_panel_ui(ui, self.map_metadata, self.current_file_path);
             }
             TabKinds::Timeline => {
                 FeatureTimeline::ui(ui, self.editor_features, &mut self.pending_edits.events, self.retarget_request)
@@ -108,6 +151,25 @@ impl<'a> TabViewer for TabViewerAndResources<'a> {
             TabKinds::History => {
                 self.editor_features.history_ui(ui);
             }
+            TabKinds::Symmetry => {
+                symmetry_panel_ui(ui, self.editor_features);
+            }
+            TabKinds::Playtest => {
+                playtest_panel_ui(ui, self.playtest_settings, self.editor_features);
+            }
+            TabKinds::Balance => {
+                let run_speed = self.playtest_settings.physics.max_speed;
+                balance_panel_ui(ui, self.balance_state, self.editor_features, self.map_metadata, run_speed);
+            }
+            TabKinds::Assets => {
+                asset_browser_panel_ui(ui, self.asset_browser_state, self.editor_features, self.next_tool);
+            }
+            TabKinds::Notes => {
+                notes_panel_ui(ui, self.editor_features, self.focus_request);
+            }
+            TabKinds::Cameras => {
+                camera_bookmarks_panel_ui(ui, &mut self.map_metadata.camera_bookmarks, self.new_bookmark_name, self.bookmark_requests);
+            }
         }
     }
 }
@@ -119,7 +181,7 @@ impl Plugin for EditorPanelPlugin {
             .init_resource::<EditorPanels>()
             .init_resource::<CurrentFilePath>()
             .add_systems(Startup, EditorPanels::set_multicam_size)
-            .add_systems(EguiPrimaryContextPass, EditorPanels::ui)
+            .add_systems(EguiPrimaryContextPass, EditorPanels::ui.run_if(in_state(PlaytestState::Off)))
         ;
     }
 }
@@ -144,6 +206,21 @@ pub struct EditorPanels {
     left_width: f32,
     right_tabs: DockState<TabKinds>,
     right_width: f32,
+    /// Set once a saved layout is applied, so its sizes replace egui's defaults.
+    sizes_restored: bool,
+}
+
+/// The tabs in each dock and the sizes of the panels around the viewports, as kept in the preferences.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PanelLayout {
+    top_tabs: DockState<TabKinds>,
+    left_tabs: DockState<TabKinds>,
+    right_tabs: DockState<TabKinds>,
+    bottom_tabs: DockState<TabKinds>,
+    top_height: f32,
+    bottom_height: f32,
+    left_width: f32,
+    right_width: f32,
 }
 
 pub enum PanelError {
@@ -161,9 +238,9 @@ impl Default for EditorPanels {
 impl EditorPanels {
     pub fn new() -> Self {
         let default_top_tabs = vec![TabKinds::Tools,];
-        let default_left_tabs = vec![TabKinds::Timeline, TabKinds::Bakes,];
-        let default_right_tabs = vec![TabKinds::Show, TabKinds::Metadata, TabKinds::History,];
-        let default_bottom_tabs = vec![TabKinds::Empty("Delta".to_owned()), TabKinds::Empty("Epsilon".to_owned())];
+        let default_left_tabs = vec![TabKinds::Timeline, TabKinds::Bakes, TabKinds::Symmetry, TabKinds::Playtest, TabKinds::Balance, TabKinds::Notes,];
+        let default_right_tabs = vec![TabKinds::Show, TabKinds::Metadata, TabKinds::History, TabKinds::Cameras,];
+        let default_bottom_tabs = vec![TabKinds::Assets, TabKinds::Empty("Epsilon".to_owned())];
         
         Self {
             top_tabs: DockState::new(default_top_tabs),
@@ -176,6 +253,60 @@ impl EditorPanels {
             left_width: 40.0,
             right_tabs: DockState::new(default_right_tabs),
             right_width: 40.0,
+            sizes_restored: false,
+        }
+    }
+
+    pub fn layout(&self) -> PanelLayout {
+        PanelLayout {
+            top_tabs: self.top_tabs.clone(),
+            left_tabs: self.left_tabs.clone(),
+            right_tabs: self.right_tabs.clone(),
+            bottom_tabs: self.bottom_tabs.clone(),
+            top_height: self.top_height,
+            bottom_height: self.bottom_height,
+            left_width: self.left_width,
+            right_width: self.right_width,
+        }
+    }
+
+    /// Use a saved layout. Tabs added to the editor since it was saved go where they are by default.
+    pub fn apply_layout(&mut self, layout: PanelLayout) {
+        self.top_tabs = layout.top_tabs;
+        self.left_tabs = layout.left_tabs;
+        self.right_tabs = layout.right_tabs;
+        self.bottom_tabs = layout.bottom_tabs;
+        self.top_height = layout.top_height;
+        self.bottom_height = layout.bottom_height;
+        self.left_width = layout.left_width;
+        self.right_width = layout.right_width;
+        self.sizes_restored = true;
+
+        let defaults = Self::new();
+        let missing: Vec<(EditorPanelLocation, TabKinds)> = defaults.docks()
+            .flat_map(|(location, dock)| dock.iter_all_tabs().map(move |(_, tab)| (location, tab.clone())))
+            .filter(|(_, tab)| !self.docks().any(|(_, dock)| dock.find_tab_from(|t| t == tab).is_some()))
+            .collect();
+        for (location, tab) in missing {
+            self.dock_mut(location).push_to_focused_leaf(tab);
+        }
+    }
+
+    fn docks(&self) -> impl Iterator<Item = (EditorPanelLocation, &DockState<TabKinds>)> {
+        [
+            (EditorPanelLocation::Top, &self.top_tabs),
+            (EditorPanelLocation::Left, &self.left_tabs),
+            (EditorPanelLocation::Right, &self.right_tabs),
+            (EditorPanelLocation::Bottom, &self.bottom_tabs),
+        ].into_iter()
+    }
+
+    fn dock_mut(&mut self, location: EditorPanelLocation) -> &mut DockState<TabKinds> {
+        match location {
+            EditorPanelLocation::Top => &mut self.top_tabs,
+            EditorPanelLocation::Left => &mut self.left_tabs,
+            EditorPanelLocation::Right => &mut self.right_tabs,
+            EditorPanelLocation::Bottom => &mut self.bottom_tabs,
         }
     }
 
@@ -190,13 +321,20 @@ impl EditorPanels {
         mut next_tool: ResMut<NextState<Tools>>,
         mut editor_features: ResMut<FeatureTimeline>,
         mut gizmo_visibility: ResMut<GizmoVisibility>,
-        mut room_events: MessageWriter<CalculateRoomGeometry>,
-        mut clear_room_events: MessageWriter<ClearRoomGeometry>,
-        mut log_ecs_events: MessageWriter<LogECS>,
+        (mut room_events, mut clear_room_events, mut navmesh_events, mut clear_navmesh_events, mut log_ecs_events, mut focus_events): (
+            MessageWriter<CalculateRoomGeometry>,
+            MessageWriter<ClearRoomGeometry>,
+            MessageWriter<CalculateNavmesh>,
+            MessageWriter<ClearNavmesh>,
+            MessageWriter<LogECS>,
+            MessageWriter<FocusCamera>,
+        ),
+        (mut bookmark_events, mut jump_events, mut new_bookmark_name, mut navmesh): (MessageWriter<BookmarkCamera>, MessageWriter<JumpCamera>, Local<String>, ResMut<Navmesh>),
         mut edit_events: MessageWriter<EditEvent>,
         mut retarget_state: ResMut<RetargetState>,
         mut current_file: ResMut<CurrentFilePath>,
         mut map_metadata: ResMut<MapMetadata>,
+        (mut playtest_settings, mut balance_state, mut asset_browser_state, mut transform_gizmo_state, mut measure_tool_state, mut preferences_window): (ResMut<PlaytestSettings>, ResMut<BalanceState>, ResMut<AssetBrowserState>, ResMut<TransformGizmoState>, ResMut<MeasureToolState>, ResMut<PreferencesWindow>),
     ) {
         let ctx = contexts.ctx_mut();
         if ctx.is_err() {
@@ -210,22 +348,34 @@ impl EditorPanels {
         let mut bake_commands = BakeCommands::default();
         let mut pending_edits = PendingEditEvents::default();
         let mut retarget_request: Option<(FeatureId, String)> = None;
+        let mut focus_request: Option<Vec3> = None;
+        let mut bookmark_requests = BookmarkRequests::default();
         let mut loaded_blueprint: Option<LoadedBlueprint> = None;
 
         enum FileOp { New, Save, SaveAs, Load }
         let mut pending_file_op: Option<FileOp> = None;
         
+        let current_file_path = current_file.path.clone();
         let mut viewer = TabViewerAndResources  {
             current_tool: & *current_tool,
             gizmos,
             next_tool: &mut *next_tool,
             editor_features: &mut *editor_features,
             map_metadata: &mut *map_metadata,
+            current_file_path: current_file_path.as_deref(),
             multicam_state: &mut *multicam_state,
             bake_commands: &mut bake_commands,
             gizmo_visibility: &mut *gizmo_visibility,
             pending_edits: &mut pending_edits,
             retarget_request: &mut retarget_request,
+            playtest_settings: &mut *playtest_settings,
+            balance_state: &mut *balance_state,
+            asset_browser_state: &mut *asset_browser_state,
+            transform_gizmo_state: &mut *transform_gizmo_state,
+            measure_tool_state: &mut *measure_tool_state,
+            focus_request: &mut focus_request,
+            new_bookmark_name: &mut new_bookmark_name,
+            bookmark_requests: &mut bookmark_requests,
         };
 
         panels.menu_bar_height = egui::TopBottomPanel::top("menu_bar")
@@ -250,6 +400,11 @@ impl EditorPanels {
                             pending_file_op = Some(FileOp::Load);
                         }
                         ui.separator();
+                        if ui.button(get!("editor.preferences.open")).clicked() {
+                            ui.close_kind(UiKind::Menu);
+                            preferences_window.open = true;
+                        }
+                        ui.separator();
                         if ui.button("Quit").clicked() {
                             ui.close_kind(UiKind::Menu);
                         }
@@ -260,8 +415,11 @@ impl EditorPanels {
             .rect
             .height();
 
-        panels.top_height = egui::TopBottomPanel::top("top_panel")
-            .resizable(true)
+        let mut top_panel = egui::TopBottomPanel::top("top_panel").resizable(true);
+        if panels.sizes_restored {
+            top_panel = top_panel.default_height(panels.top_height);
+        }
+        panels.top_height = top_panel
             .show(ctx, |ui| {
                 DockArea::new(&mut panels.top_tabs)
                     .id(egui::Id::new("egui_dock::DockArea::top"))
@@ -275,8 +433,11 @@ impl EditorPanels {
             .response
             .rect
             .height();
-        panels.left_width = egui::SidePanel::left("left_panel")
-            .resizable(true)
+        let mut left_panel = egui::SidePanel::left("left_panel").resizable(true);
+        if panels.sizes_restored {
+            left_panel = left_panel.default_width(panels.left_width);
+        }
+        panels.left_width = left_panel
             .show(ctx, |ui| {
                 DockArea::new(&mut panels.left_tabs)
                     .id(egui::Id::new("egui_dock::DockArea::left"))
@@ -290,8 +451,11 @@ impl EditorPanels {
             .response
             .rect
             .width();
-        panels.right_width = egui::SidePanel::right("right_panel")
-            .resizable(true)
+        let mut right_panel = egui::SidePanel::right("right_panel").resizable(true);
+        if panels.sizes_restored {
+            right_panel = right_panel.default_width(panels.right_width);
+        }
+        panels.right_width = right_panel
             .show(ctx, |ui| {
                 DockArea::new(&mut panels.right_tabs)
                     .id(egui::Id::new("egui_dock::DockArea::right"))
@@ -305,8 +469,11 @@ impl EditorPanels {
             .response
             .rect
             .width();
-        panels.bottom_height = egui::TopBottomPanel::bottom("bottom_panel")
-            .resizable(true)
+        let mut bottom_panel = egui::TopBottomPanel::bottom("bottom_panel").resizable(true);
+        if panels.sizes_restored {
+            bottom_panel = bottom_panel.default_height(panels.bottom_height);
+        }
+        panels.bottom_height = bottom_panel
             .show(ctx, |ui| {
                 DockArea::new(&mut panels.bottom_tabs)
                     .id(egui::Id::new("egui_dock::DockArea::bottom"))
@@ -328,9 +495,10 @@ impl EditorPanels {
         if let Some(result) = dialog_result {
             match result {
                 DialogResult::SavePath(path) => {
-                    match save::save(&path, &editor_features, &map_metadata) {
+                    match save::save(&path, &editor_features, &map_metadata, &navmesh) {
                         Ok(()) => {
                             info!("Saved to {:?}", path);
+                            warn_about_file_name(&path, &map_metadata);
                             current_file.path = Some(path);
                         }
                         Err(e) => error!("Save failed: {}", e),
@@ -340,6 +508,8 @@ impl EditorPanels {
                     match save::load(&path) {
                         Ok(loaded) => {
                             info!("Loaded from {:?}", path);
+                            warn_about_file_name(&path, &loaded.metadata);
+                            *navmesh = save::load_navmesh(&path).unwrap_or_default();
                             loaded_blueprint = Some(loaded);
                             current_file.path = Some(path);
                         }
@@ -363,6 +533,7 @@ impl EditorPanels {
                                 timeline: loaded.timeline,
                                 metadata: MapMetadata::default(),
                             });
+                            *navmesh = Navmesh::default();
                             current_file.path = None;
                         }
                         Err(e) => error!("New failed: {}", e),
@@ -370,16 +541,17 @@ impl EditorPanels {
                 }
                 FileOp::Save => {
                     if let Some(ref path) = current_file.path {
-                        match save::save(path, &editor_features, &map_metadata) {
+                        match save::save(path, &editor_features, &map_metadata, &navmesh) {
                             Ok(()) => info!("Saved to {:?}", path),
                             Err(e) => error!("Save failed: {}", e),
                         }
                     } else {
                         let slot = current_file.dialog_result.clone();
+                        let file_name = suggested_save_name(None, &map_metadata);
                         std::thread::spawn(move || {
                             let handle = pollster::block_on(
                                 rfd::AsyncFileDialog::new()
-                                    .set_file_name(format!("Untitled.{}", MAP_BLUEPRINT_EXTENSION))
+                                    .set_file_name(file_name)
                                     .add_filter("Grackle Map Blueprint", &[MAP_BLUEPRINT_EXTENSION])
                                     .save_file()
                             );
@@ -392,18 +564,13 @@ impl EditorPanels {
                 FileOp::SaveAs => {
                     let slot = current_file.dialog_result.clone();
                     let existing = current_file.path.clone();
+                    let file_name = suggested_save_name(existing.as_deref(), &map_metadata);
                     std::thread::spawn(move || {
                         let mut dialog = rfd::AsyncFileDialog::new()
-                            .add_filter("Grackle Map Blueprint", &[MAP_BLUEPRINT_EXTENSION]);
-                        if let Some(ref existing) = existing {
-                            if let Some(dir) = existing.parent() {
-                                dialog = dialog.set_directory(dir);
-                            }
-                            if let Some(name) = existing.file_name() {
-                                dialog = dialog.set_file_name(name.to_string_lossy().to_string());
-                            }
-                        } else {
-                            dialog = dialog.set_file_name(format!("Untitled.{}", MAP_BLUEPRINT_EXTENSION));
+                            .add_filter("Grackle Map Blueprint", &[MAP_BLUEPRINT_EXTENSION])
+                            .set_file_name(file_name);
+                        if let Some(dir) = existing.as_ref().and_then(|existing| existing.parent()) {
+                            dialog = dialog.set_directory(dir);
                         }
                         let handle = pollster::block_on(dialog.save_file());
                         if let Some(h) = handle {
@@ -456,10 +623,26 @@ impl EditorPanels {
         if bake_commands.clear_room_geometry {
             clear_room_events.write(ClearRoomGeometry);
         }
+        if bake_commands.calculate_navmesh {
+            navmesh_events.write(CalculateNavmesh);
+        }
+        if bake_commands.clear_navmesh {
+            clear_navmesh_events.write(ClearNavmesh);
+        }
         if bake_commands.log_ecs {
             log_ecs_events.write(LogECS);
         }
 
+        if let Some(target) = focus_request {
+            focus_events.write(FocusCamera { target });
+        }
+        if let Some(name) = bookmark_requests.add {
+            bookmark_events.write(BookmarkCamera { name });
+        }
+        if let Some(transform) = bookmark_requests.jump {
+            jump_events.write(JumpCamera { transform });
+        }
+
         // Handle retarget request
         if let Some((feature_id, label)) = retarget_request {
             retarget_state.target_feature = Some(feature_id);
@@ -500,8 +683,24 @@ impl EditorPanels {
     }
 }
 
-fn map_metadata_panel_ui(ui: &mut egui::Ui, meta: &mut MapMetadata) {
+/// File name offered by the save dialogs, following the `<prefix>_<name>` convention.
+fn suggested_save_name(existing: Option<&Path>, meta: &MapMetadata) -> String {
+    let name = existing.and_then(map_name).unwrap_or(&meta.title);
+    suggested_file_name(meta.game_mode, name)
+}
+
+fn warn_about_file_name(path: &Path, meta: &MapMetadata) {
+    if let Some(issue) = check_file_name(path, meta.game_mode) {
+        warn!("{:?}: {}", path, issue);
+    }
+}
+
+fn map_metadata_panel_ui(ui: &mut egui::Ui, meta: &mut MapMetadata, file_path: Option<&Path>) {
     egui::ScrollArea::vertical().show(ui, |ui| {
+        if let Some(issue) = file_path.and_then(|path| check_file_name(path, meta.game_mode)) {
+            ui.colored_label(ui.visuals().warn_fg_color, issue.message());
+            ui.separator();
+        }
         egui::Grid::new("editor_map_metadata_grid")
             .num_columns(2)
             .spacing([10.0, 6.0])
@@ -542,7 +741,81 @@ fn map_metadata_panel_ui(ui: &mut egui::Ui, meta: &mut MapMetadata) {
                     }
                 });
                 ui.end_row();
+
+                ui.label(get!("editor.metadata.map_title"));
+                ui.add(egui::TextEdit::singleline(&mut meta.title).desired_width(ui.available_width()));
+                ui.end_row();
+
+                ui.label(get!("editor.metadata.description"));
+                ui.add(
+                    egui::TextEdit::multiline(&mut meta.description)
+                        .desired_width(ui.available_width())
+                        .desired_rows(3),
+                );
+                ui.end_row();
+
+                ui.label(get!("editor.metadata.version"));
+                ui.add(egui::TextEdit::singleline(&mut meta.version).desired_width(ui.available_width()));
+                ui.end_row();
+
+                ui.label(get!("editor.metadata.players"));
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut meta.min_players).range(1..=meta.max_players));
+                    ui.label("–");
+                    ui.add(egui::DragValue::new(&mut meta.max_players).range(meta.min_players..=256));
+                });
+                ui.end_row();
             });
+
+        ui.collapsing(get!("editor.metadata.changelog"), |ui| {
+            let mut remove = None;
+            for (i, entry) in meta.changelog.iter_mut().enumerate().rev() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut entry.version).desired_width(60.0));
+                    if ui.small_button("x").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                ui.add(
+                    egui::TextEdit::multiline(&mut entry.notes)
+                        .desired_width(ui.available_width())
+                        .desired_rows(2),
+                );
+            }
+            if let Some(i) = remove {
+                meta.changelog.remove(i);
+            }
+            if ui.button(get!("editor.metadata.changelog_add")).clicked() {
+                let version = meta.version.clone();
+                meta.changelog.push(ChangelogEntry { version, notes: String::new() });
+            }
+        });
+
+        ui.collapsing(get!("editor.metadata.custom"), |ui| {
+            let mut remove = None;
+            egui::Grid::new("editor_map_custom_grid")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (i, (key, value)) in meta.custom.iter_mut().enumerate() {
+                        ui.add(egui::TextEdit::singleline(key).desired_width(100.0).hint_text(get!("editor.metadata.custom_key")));
+                        ui.add(egui::TextEdit::singleline(value).desired_width(140.0));
+                        if ui.small_button("x").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                meta.custom.remove(i);
+            }
+            if ui.button(get!("editor.metadata.custom_add")).clicked() {
+                meta.custom.push((String::new(), String::new()));
+            }
+        });
+
+        ui.separator();
+        ui.heading(get!("editor.environment.title"));
+        environment_panel_ui(ui, &mut meta.environment);
     });
 }
 
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::common::PointResolutionError;
+use crate::common::item::PROTOTYPES;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::map_marker::Team;
+use crate::get;
+
+/// Seconds before a taken pickup reappears, for newly placed pickups.
+pub const DEFAULT_RESPAWN_TIME: f32 = 10.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum PickupKind {
+    HealthSmall,
+    HealthMedium,
+    HealthLarge,
+    Ammo,
+    /// Gives the item named by [`Pickup::item`].
+    Weapon,
+}
+
+impl PickupKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            PickupKind::HealthSmall => "health_small",
+            PickupKind::HealthMedium => "health_medium",
+            PickupKind::HealthLarge => "health_large",
+            PickupKind::Ammo => "ammo",
+            PickupKind::Weapon => "weapon",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|k| k.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            PickupKind::HealthSmall => get!("editor.features.pickup.kind.health_small"),
+            PickupKind::HealthMedium => get!("editor.features.pickup.kind.health_medium"),
+            PickupKind::HealthLarge => get!("editor.features.pickup.kind.health_large"),
+            PickupKind::Ammo => get!("editor.features.pickup.kind.ammo"),
+            PickupKind::Weapon => get!("editor.features.pickup.kind.weapon"),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PickupKind::HealthSmall | PickupKind::HealthMedium | PickupKind::HealthLarge => Color::srgb_u8(90, 220, 90),
+            PickupKind::Ammo => Color::srgb_u8(230, 200, 60),
+            PickupKind::Weapon => Color::srgb_u8(200, 200, 210),
+        }
+    }
+
+    /// Gizmo size, so bigger health packs read as bigger.
+    fn size(&self) -> f32 {
+        match self {
+            PickupKind::HealthSmall => 0.2,
+            PickupKind::HealthMedium => 0.3,
+            PickupKind::HealthLarge => 0.4,
+            PickupKind::Ammo => 0.3,
+            PickupKind::Weapon => 0.35,
+        }
+    }
+}
+
+/// Item prototype keys a weapon pickup can give, sorted.
+pub fn item_keys() -> Vec<&'static str> {
+    let mut keys: Vec<&'static str> = PROTOTYPES.keys().map(|k| k.as_str()).collect();
+    keys.sort();
+    keys
+}
+
+/// Health, ammo or a weapon lying on the floor, reappearing `respawn` seconds after it is taken.
+#[derive(Serialize, Deserialize)]
+pub struct Pickup {
+    location: PointRef,
+    pub kind: PickupKind,
+    /// Prototype key from [`PROTOTYPES`]; only used by weapon pickups.
+    pub item: String,
+    /// Seconds before it comes back.
+    pub respawn: f32,
+    /// Team allowed to take it; neutral pickups are for everyone.
+    pub team: Team,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[typetag::serde(name = "pickup")]
+impl FeatureTrait for Pickup {
+    fn get_point(&self, _key: &str) -> Result<Vec3, PointResolutionError> {
+        Ok(self.resolved_location)
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        if changed && let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+
+        ui.separator();
+        egui::ComboBox::from_label(get!("editor.features.pickup.kind.label"))
+            .selected_text(self.kind.name())
+            .show_ui(ui, |ui| {
+                for kind in PickupKind::iter() {
+                    changed |= ui.selectable_value(&mut self.kind, kind, kind.name()).changed();
+                }
+            });
+        if self.kind == PickupKind::Weapon {
+            let selected = if PROTOTYPES.contains_key(&self.item) {
+                get!(format!("item.name.{}", self.item))
+            } else {
+                get!("editor.features.pickup.unknown_item", "item", self.item)
+            };
+            egui::ComboBox::from_label(get!("editor.features.pickup.item"))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for key in item_keys() {
+                        changed |= ui.selectable_value(&mut self.item, key.to_owned(), get!(format!("item.name.{}", key))).changed();
+                    }
+                });
+        }
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.pickup.respawn"));
+            changed |= ui.add(egui::DragValue::new(&mut self.respawn).range(0.0..=600.0).speed(0.5).suffix(" s")).changed();
+        });
+        egui::ComboBox::from_label(get!("editor.features.pickup.team"))
+            .selected_text(self.team.name())
+            .show_ui(ui, |ui| {
+                for team in Team::iter() {
+                    changed |= ui.selectable_value(&mut self.team, team, team.name()).changed();
+                }
+            });
+
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        match self.kind {
+            PickupKind::Weapon if PROTOTYPES.contains_key(&self.item) => {
+                get!("editor.features.pickup.title", "kind", get!(format!("item.name.{}", self.item)))
+            }
+            _ => get!("editor.features.pickup.title", "kind", self.kind.name()),
+        }
+    }
+
+    fn type_key(&self) -> &'static str { "pickup" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Pickup {
+            location: self.location.clone(),
+            kind: self.kind,
+            item: self.item.clone(),
+            respawn: self.respawn,
+            team: self.team,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Pickup { location, kind, item, respawn, team } = data else { return; };
+        self.location = location.clone();
+        self.kind = *kind;
+        self.item = item.clone();
+        self.respawn = *respawn;
+        self.team = *team;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let p = self.resolved_location;
+        let size = self.kind.size();
+        let center = p + Vec3::Y * size;
+        gizmos.cuboid(Transform::from_translation(center).with_scale(Vec3::splat(size * 2.0)), self.kind.color());
+        if self.team != Team::Neutral {
+            let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+            gizmos.circle(Isometry3d::new(p, flat), size * 2.0, self.team.color());
+        }
+        self.location.debug_gizmos(p, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert(Transform::from_translation(self.resolved_location));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        self.location.referenced_features()
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![("".into(), "Pickup".into())]
+    }
+
+    fn reference_points_for_ray(&self, _ray: &Ray3d) -> Vec<(String, Vec3)> {
+        vec![("".into(), self.resolved_location)]
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![("respawn", self.respawn)]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        if key == "respawn" {
+            self.respawn = value;
+        }
+    }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("kind", self.kind.key().to_owned()),
+            ("item", self.item.clone()),
+            ("team", self.team.key().to_owned()),
+        ]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        match key {
+            "kind" => self.kind = PickupKind::from_key(value).unwrap_or(self.kind),
+            "item" => self.item = value.to_owned(),
+            "team" => self.team = Team::from_key(value).unwrap_or(self.team),
+            _ => {}
+        }
+    }
+
+    fn get_point_ref(&self, _key: &str) -> Option<&PointRef> {
+        Some(&self.location)
+    }
+
+    fn get_point_ref_mut(&mut self, _key: &str) -> Option<&mut PointRef> {
+        Some(&mut self.location)
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let axis_ref = match axis {
+            0 => &mut self.location.x,
+            1 => &mut self.location.y,
+            2 => &mut self.location.z,
+            _ => return false,
+        };
+        let base = self.location.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        match axis { 0 => self.resolved_location.x = new_world_value, 1 => self.resolved_location.y = new_world_value, _ => self.resolved_location.z = new_world_value }
+        true
+    }
+}
+
+impl Pickup {
+    pub fn new(x: f32, y: f32, z: f32, kind: PickupKind) -> Self {
+        Self {
+            resolved_location: Vec3::new(x, y, z),
+            ..Self::from_point_ref(PointRef::absolute(x, y, z), kind)
+        }
+    }
+
+    pub fn from_point_ref(location: PointRef, kind: PickupKind) -> Self {
+        Self {
+            location,
+            kind,
+            item: item_keys().first().map(|k| k.to_string()).unwrap_or_default(),
+            respawn: DEFAULT_RESPAWN_TIME,
+            team: Team::Neutral,
+            resolved_location: Vec3::ZERO,
+            entity: None,
+        }
+    }
+}
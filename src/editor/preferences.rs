@@ -0,0 +1,396 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::editor::multicam::{CameraAxis, MulticamState, ViewportLayout};
+use crate::editor::panels::{EditorPanels, PanelLayout};
+use crate::editor::render_mode::RenderMode;
+use crate::get;
+use crate::tool::movement::{movement_settings_ui, MovementSettings};
+use crate::tool::show::GizmoVisibility;
+use crate::tool::tool_helpers::{SnapSettings, SNAP_GRANULARITY_RANGE};
+use crate::tool::transform_gizmo::TransformGizmoState;
+
+const PREFERENCES_FILE: &str = "preferences.toml";
+
+pub struct PreferencesPlugin;
+
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(EditorPreferences::load())
+            .init_resource::<PreferencesWindow>()
+            .add_systems(Startup, apply_preferences)
+            .add_systems(Update, sync_preferences)
+            .add_systems(EguiPrimaryContextPass, preferences_window)
+            .add_systems(Last, save_layout_on_exit)
+        ;
+    }
+}
+
+/// Per-user editor settings, kept between launches in the user's config directory.
+/// Unlike map metadata these belong to whoever runs the editor, not to a blueprint.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct EditorPreferences {
+    pub viewports: ViewportPreferences,
+    pub movement: MovementSettings,
+    pub visibility: GizmoVisibility,
+    pub debug: DebugPreferences,
+    pub snapping: SnapPreferences,
+    /// The dock layout as the editor was last closed, if it has been.
+    #[serde(deserialize_with = "lenient_layout", skip_serializing_if = "Option::is_none")]
+    pub layout: Option<PanelLayout>,
+}
+
+/// Whether the editor's debug windows and overlays are showing.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct DebugPreferences {
+    pub viewport_window: bool,
+    pub viewport_box: bool,
+    pub mouseover_boxes: bool,
+    pub mouse_circle: bool,
+    pub ortho_cameras: bool,
+    pub perspective_cameras: bool,
+}
+
+impl Default for DebugPreferences {
+    fn default() -> Self {
+        Self::from_state(&MulticamState::default())
+    }
+}
+
+impl DebugPreferences {
+    fn from_state(state: &MulticamState) -> Self {
+        Self {
+            viewport_window: state.debug_window,
+            viewport_box: state.debug_viewport_box,
+            mouseover_boxes: state.debug_mouseover_boxes,
+            mouse_circle: state.debug_mouse_circle,
+            ortho_cameras: state.draw_ortho_cameras,
+            perspective_cameras: state.draw_perspective_cameras,
+        }
+    }
+
+    fn apply(&self, state: &mut MulticamState) {
+        state.debug_window = self.viewport_window;
+        state.debug_viewport_box = self.viewport_box;
+        state.debug_mouseover_boxes = self.mouseover_boxes;
+        state.debug_mouse_circle = self.mouse_circle;
+        state.draw_ortho_cameras = self.ortho_cameras;
+        state.draw_perspective_cameras = self.perspective_cameras;
+    }
+}
+
+/// Grid snapping for the placement tools and for moving features with the transform gizmo.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SnapPreferences {
+    pub placement: SnapSettings,
+    pub transform: SnapSettings,
+}
+
+impl Default for SnapPreferences {
+    fn default() -> Self {
+        Self {
+            placement: SnapSettings::default(),
+            transform: transform_snap(&TransformGizmoState::default()),
+        }
+    }
+}
+
+fn transform_snap(state: &TransformGizmoState) -> SnapSettings {
+    SnapSettings {
+        enabled: state.snap,
+        granularity: state.snap_granularity,
+    }
+}
+
+/// A saved layout that no longer parses, say because a tab it names was removed, is dropped
+/// rather than costing the rest of the preferences.
+fn lenient_layout<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<PanelLayout>, D::Error> {
+    Ok(Option::<PanelLayout>::deserialize(deserializer).unwrap_or_else(|e| {
+        warn!("Ignoring saved panel layout: {}", e);
+        None
+    }))
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ViewportPreferences {
+    pub layout: ViewportLayout,
+    pub views: [CameraAxis; 4],
+    pub render_modes: [RenderMode; 4],
+}
+
+impl ViewportPreferences {
+    fn from_state(state: &MulticamState) -> Self {
+        Self {
+            layout: state.layout,
+            views: state.views,
+            render_modes: state.render_modes,
+        }
+    }
+}
+
+impl Default for ViewportPreferences {
+    fn default() -> Self {
+        Self {
+            layout: ViewportLayout::default(),
+            views: [CameraAxis::None, CameraAxis::X, CameraAxis::Y, CameraAxis::Z],
+            render_modes: [RenderMode::Lit; 4],
+        }
+    }
+}
+
+/// `grackle/preferences.toml` under the platform's per-user config directory.
+pub fn preferences_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("grackle").join(PREFERENCES_FILE))
+}
+
+impl EditorPreferences {
+    /// The saved preferences, or the defaults when there are none or they can't be read.
+    pub fn load() -> Self {
+        let Some(path) = preferences_path() else { return Self::default(); };
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load_from(&path).unwrap_or_else(|message| {
+            warn!("Ignoring preferences: {}", message);
+            Self::default()
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("could not read {:?}: {}", path, e))?;
+        let mut preferences: Self = toml::from_str(&text).map_err(|e| format!("could not parse {:?}: {}", path, e))?;
+        preferences.sanitize();
+        Ok(preferences)
+    }
+
+    /// Bring hand-edited values back within what the editor's own controls allow.
+    fn sanitize(&mut self) {
+        self.snapping.placement.sanitize();
+        self.snapping.transform.sanitize();
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = preferences_path().ok_or("no config directory for preferences")?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("could not create {:?}: {}", dir, e))?;
+        }
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("could not write {:?}: {}", path, e))
+    }
+}
+
+/// Whether the Preferences window is showing.
+#[derive(Resource, Default)]
+pub struct PreferencesWindow {
+    pub open: bool,
+}
+
+/// Hand the saved preferences to the resources they configure. The viewport layout is applied
+/// by the multicam setup, which needs it before spawning the cameras.
+fn apply_preferences(
+    preferences: Res<EditorPreferences>,
+    mut state: ResMut<MulticamState>,
+    mut movement: ResMut<MovementSettings>,
+    mut visibility: ResMut<GizmoVisibility>,
+    mut snap: ResMut<SnapSettings>,
+    mut transform_gizmo: ResMut<TransformGizmoState>,
+    mut panels: ResMut<EditorPanels>,
+) {
+    preferences.debug.apply(&mut state);
+    *movement = preferences.movement.clone();
+    *visibility = preferences.visibility.clone();
+    *snap = preferences.snapping.placement;
+    transform_gizmo.snap = preferences.snapping.transform.enabled;
+    transform_gizmo.snap_granularity = preferences.snapping.transform.granularity;
+    if let Some(layout) = preferences.layout.clone() {
+        panels.apply_layout(layout);
+    }
+}
+
+/// Save whenever a setting changes, once any drag that is changing it has finished.
+fn sync_preferences(
+    mut contexts: EguiContexts,
+    state: Res<MulticamState>,
+    movement: Res<MovementSettings>,
+    visibility: Res<GizmoVisibility>,
+    snap: Res<SnapSettings>,
+    transform_gizmo: Res<TransformGizmoState>,
+    mut preferences: ResMut<EditorPreferences>,
+) {
+    if let Ok(ctx) = contexts.ctx_mut()
+        && ctx.is_using_pointer()
+    {
+        return;
+    }
+    let viewports = ViewportPreferences::from_state(&state);
+    let debug = DebugPreferences::from_state(&state);
+    let snapping = SnapPreferences {
+        placement: *snap,
+        transform: transform_snap(&transform_gizmo),
+    };
+    if preferences.viewports == viewports
+        && preferences.movement == *movement
+        && preferences.visibility == *visibility
+        && preferences.debug == debug
+        && preferences.snapping == snapping
+    {
+        return;
+    }
+    preferences.viewports = viewports;
+    preferences.movement = movement.clone();
+    preferences.visibility = visibility.clone();
+    preferences.debug = debug;
+    preferences.snapping = snapping;
+    if let Err(message) = preferences.save() {
+        warn!("Could not save preferences: {}", message);
+    }
+}
+
+/// The dock layout changes with every frame the panels are drawn, so it is only saved on the way out.
+fn save_layout_on_exit(
+    mut exits: MessageReader<AppExit>,
+    panels: Res<EditorPanels>,
+    mut preferences: ResMut<EditorPreferences>,
+) {
+    if exits.read().last().is_none() {
+        return;
+    }
+    preferences.layout = Some(panels.layout());
+    if let Err(message) = preferences.save() {
+        warn!("Could not save preferences: {}", message);
+    }
+}
+
+fn snap_ui(ui: &mut egui::Ui, label: String, snap: &mut SnapSettings) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut snap.enabled, label);
+        ui.add_enabled(snap.enabled, egui::DragValue::new(&mut snap.granularity).range(SNAP_GRANULARITY_RANGE).speed(0.05));
+        ui.label(get!("editor.preferences.granularity"));
+    });
+}
+
+fn preferences_window(
+    mut contexts: EguiContexts,
+    mut window: ResMut<PreferencesWindow>,
+    mut movement: ResMut<MovementSettings>,
+    mut snap: ResMut<SnapSettings>,
+    mut transform_gizmo: ResMut<TransformGizmoState>,
+    mut state: ResMut<MulticamState>,
+    mut panels: ResMut<EditorPanels>,
+) {
+    if !window.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+
+    let mut open = window.open;
+    egui::Window::new(get!("editor.preferences.title"))
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::CollapsingHeader::new(get!("editor.preferences.movement"))
+                .default_open(true)
+                .show(ui, |ui| movement_settings_ui(ui, &mut movement));
+
+            egui::CollapsingHeader::new(get!("editor.preferences.snapping"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    snap_ui(ui, get!("editor.preferences.snap_placement"), &mut snap);
+                    let mut transform = transform_snap(&transform_gizmo);
+                    snap_ui(ui, get!("editor.preferences.snap_transform"), &mut transform);
+                    if transform != transform_snap(&transform_gizmo) {
+                        transform_gizmo.snap = transform.enabled;
+                        transform_gizmo.snap_granularity = transform.granularity;
+                    }
+                });
+
+            egui::CollapsingHeader::new(get!("editor.preferences.debug"))
+                .show(ui, |ui| {
+                    ui.checkbox(&mut state.debug_window, get!("editor.preferences.debug_viewport"));
+                    ui.checkbox(&mut movement.debug_window, get!("editor.preferences.debug_movement"));
+                });
+
+            egui::CollapsingHeader::new(get!("editor.preferences.layout"))
+                .show(ui, |ui| {
+                    ui.label(get!("editor.preferences.layout_hint"));
+                    if ui.button(get!("editor.preferences.reset_layout")).clicked() {
+                        *panels = EditorPanels::new();
+                    }
+                });
+
+            ui.separator();
+            ui.label(get!("editor.preferences.show_hint"));
+            match preferences_path() {
+                Some(path) => ui.weak(get!("editor.preferences.path", "path", path.display())),
+                None => ui.weak(get!("editor.preferences.no_path")),
+            };
+        });
+    window.open = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_round_trip() {
+        let path = std::env::temp_dir().join(format!("grackle_preferences_test_{}.toml", std::process::id()));
+        let mut preferences = EditorPreferences::default();
+        preferences.viewports.layout = ViewportLayout::OneAndThree;
+        preferences.viewports.views[0] = CameraAxis::Y;
+        preferences.viewports.render_modes[1] = RenderMode::Wireframe;
+        preferences.movement.perspective_speed = 3.5;
+        preferences.visibility.rooms = true;
+        preferences.debug.viewport_window = true;
+        preferences.snapping.placement.granularity = 0.25;
+        preferences.save_to(&path).unwrap();
+
+        let loaded = EditorPreferences::load_from(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.viewports, preferences.viewports);
+        assert_eq!(loaded.movement, preferences.movement);
+        assert_eq!(loaded.visibility, preferences.visibility);
+        assert_eq!(loaded.debug, preferences.debug);
+        assert_eq!(loaded.snapping, preferences.snapping);
+    }
+
+    #[test]
+    fn test_missing_sections_use_defaults() {
+        let loaded: EditorPreferences = toml::from_str("[viewports]\nlayout = \"Single\"\n").unwrap();
+        assert_eq!(loaded.viewports.layout, ViewportLayout::Single);
+        assert_eq!(loaded.viewports.views, ViewportPreferences::default().views);
+        assert_eq!(loaded.snapping, SnapPreferences::default());
+    }
+
+    #[test]
+    fn test_snap_granularity_is_clamped_on_load() {
+        let path = std::env::temp_dir().join(format!("grackle_preferences_clamp_test_{}.toml", std::process::id()));
+        fs::write(&path, "[snapping.placement]\ngranularity = 0.0\n\n[snapping.transform]\ngranularity = 500.0\n").unwrap();
+        let loaded = EditorPreferences::load_from(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.snapping.placement.granularity, *SNAP_GRANULARITY_RANGE.start());
+        assert_eq!(loaded.snapping.transform.granularity, *SNAP_GRANULARITY_RANGE.end());
+    }
+
+    #[test]
+    fn test_unreadable_layout_keeps_other_sections() {
+        let text = "[movement]\nperspective_speed = 2.0\n\n[layout]\nleft_width = \"wide\"\n";
+        let loaded: EditorPreferences = toml::from_str(text).unwrap();
+        assert!(loaded.layout.is_none());
+        assert_eq!(loaded.movement.perspective_speed, 2.0);
+    }
+}
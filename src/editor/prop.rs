@@ -0,0 +1,400 @@
+use std::path::Path;
+use bevy::prelude::*;
+use bevy::camera::primitives::Aabb;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::common::ray::ray_intersects_aabb;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTag, FeatureTimeline, FeatureTrait, PointRef};
+use crate::get;
+use crate::tool::tool_helpers::bounds_gizmo;
+
+pub const PROP_DIR: &str = "default/models";
+
+/// Half-size of the box drawn and picked before the model has loaded.
+const PLACEHOLDER_HALF_SIZE: f32 = 0.5;
+
+/// glTF/GLB files in the asset pack, as paths inside [`PROP_DIR`], sorted.
+pub fn available_models() -> Vec<String> {
+    fn walk(dir: &Path, prefix: &str, out: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return; };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let Ok(name) = entry.file_name().into_string() else { continue; };
+            let path = entry.path();
+            let relative = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+            if path.is_dir() {
+                walk(&path, &relative, out);
+            } else if path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("gltf") || e.eq_ignore_ascii_case("glb")) {
+                out.push(relative);
+            }
+        }
+    }
+    let mut models = vec![];
+    walk(&Path::new("assets").join(PROP_DIR), "", &mut models);
+    models.sort();
+    models
+}
+
+/// Rotation from Euler angles in degrees: yaw about Y, then pitch about X, then roll about Z.
+pub fn prop_rotation(degrees: Vec3) -> Quat {
+    Quat::from_euler(EulerRot::YXZ, degrees.y.to_radians(), degrees.x.to_radians(), degrees.z.to_radians())
+}
+
+/// World-space box around the corners of a model-space box after scaling, rotating and moving it.
+pub fn transformed_bounds(local: (Vec3, Vec3), transform: &Transform) -> (Vec3, Vec3) {
+    let (lo, hi) = local;
+    let mut min = Vec3::splat(f32::MAX);
+    let mut max = Vec3::splat(f32::MIN);
+    for i in 0..8 {
+        let corner = Vec3::new(
+            if i & 1 == 0 { lo.x } else { hi.x },
+            if i & 2 == 0 { lo.y } else { hi.y },
+            if i & 4 == 0 { lo.z } else { hi.z },
+        );
+        let p = transform.transform_point(corner);
+        min = min.min(p);
+        max = max.max(p);
+    }
+    (min, max)
+}
+
+/// Static mesh from a glTF/GLB file in [`PROP_DIR`], placed at `location`.
+#[derive(Serialize, Deserialize)]
+pub struct Prop {
+    location: PointRef,
+    /// Path inside [`PROP_DIR`], with `/` separators.
+    pub model: String,
+    /// Euler angles in degrees, see [`prop_rotation`].
+    pub rotation: Vec3,
+    pub scale: Vec3,
+    /// Whether players collide with it.
+    pub collision: bool,
+    #[serde(skip)]
+    resolved_location: Vec3,
+    /// The loaded model's bounds in model space, once its scene has spawned.
+    #[serde(skip)]
+    model_bounds: Option<(Vec3, Vec3)>,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl Prop {
+    pub fn new(x: f32, y: f32, z: f32, model: &str) -> Self {
+        Self {
+            resolved_location: Vec3::new(x, y, z),
+            ..Self::from_point_ref(PointRef::absolute(x, y, z), model)
+        }
+    }
+
+    pub fn from_point_ref(location: PointRef, model: &str) -> Self {
+        Self {
+            location,
+            model: model.to_owned(),
+            rotation: Vec3::ZERO,
+            scale: Vec3::ONE,
+            collision: true,
+            resolved_location: Vec3::ZERO,
+            model_bounds: None,
+            entity: None,
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        Transform {
+            translation: self.resolved_location,
+            rotation: prop_rotation(self.rotation),
+            scale: self.scale,
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let local = self.model_bounds.unwrap_or((Vec3::splat(-PLACEHOLDER_HALF_SIZE), Vec3::splat(PLACEHOLDER_HALF_SIZE)));
+        transformed_bounds(local, &self.transform())
+    }
+}
+
+#[typetag::serde(name = "prop")]
+impl FeatureTrait for Prop {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        let (min, max) = self.bounds();
+        match key {
+            "min" => Ok(min),
+            "max" => Ok(max),
+            _ => Ok(self.resolved_location),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.location.editor_ui(ui, "Location", features, prior_feature_order, retarget_request);
+        if changed && let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+
+        ui.separator();
+        egui::ComboBox::from_label(get!("editor.features.prop.model"))
+            .selected_text(&self.model)
+            .show_ui(ui, |ui| {
+                for model in available_models() {
+                    changed |= ui.selectable_value(&mut self.model, model.clone(), model).changed();
+                }
+            });
+        if !available_models().contains(&self.model) {
+            ui.colored_label(egui::Color32::YELLOW, get!("editor.features.prop.missing", "model", self.model));
+        }
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.prop.rotation"));
+            for angle in [&mut self.rotation.x, &mut self.rotation.y, &mut self.rotation.z] {
+                changed |= ui.add(egui::DragValue::new(angle).range(-180.0..=180.0).speed(1.0).suffix("°")).changed();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.prop.scale"));
+            for factor in [&mut self.scale.x, &mut self.scale.y, &mut self.scale.z] {
+                changed |= ui.add(egui::DragValue::new(factor).range(0.01..=100.0).speed(0.01)).changed();
+            }
+        });
+        changed |= ui.checkbox(&mut self.collision, get!("editor.features.prop.collision")).changed();
+
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        let name = self.model.rsplit('/').next().unwrap_or(&self.model);
+        get!("editor.features.prop.title", "model", name)
+    }
+
+    fn type_key(&self) -> &'static str { "prop" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Prop {
+            location: self.location.clone(),
+            model: self.model.clone(),
+            rotation: self.rotation,
+            scale: self.scale,
+            collision: self.collision,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Prop { location, model, rotation, scale, collision } = data else { return; };
+        if *model != self.model {
+            self.model_bounds = None;
+        }
+        self.location = location.clone();
+        self.model = model.clone();
+        self.rotation = *rotation;
+        self.scale = *scale;
+        self.collision = *collision;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let (min, max) = self.bounds();
+        let color = if self.collision { Color::srgb_u8(200, 160, 110) } else { Color::srgb_u8(140, 140, 140) };
+        bounds_gizmo(gizmos, min, max, color);
+        self.location.debug_gizmos(self.resolved_location, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert((self.transform(), PropModel { path: self.model.clone() }));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.location.resolve(features) {
+            self.resolved_location = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        self.location.referenced_features()
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Origin".into()),
+            ("min".into(), "Min".into()),
+            ("max".into(), "Max".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, ray: &Ray3d) -> Vec<(String, Vec3)> {
+        let (min, max) = self.bounds();
+        let padding = Vec3::splat(0.25);
+        if !ray_intersects_aabb(ray, min - padding, max + padding) {
+            return vec![("".into(), self.resolved_location)];
+        }
+        self.available_point_keys().into_iter().filter_map(|(key, _)| {
+            self.get_point(&key).ok().map(|v| (key, v))
+        }).collect()
+    }
+
+    fn selection_bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some(self.bounds())
+    }
+
+    fn set_model_bounds(&mut self, min: Vec3, max: Vec3) {
+        self.model_bounds = Some((min, max));
+    }
+
+    fn model_bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.model_bounds
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["location"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("rotation_x", self.rotation.x),
+            ("rotation_y", self.rotation.y),
+            ("rotation_z", self.rotation.z),
+            ("scale_x", self.scale.x),
+            ("scale_y", self.scale.y),
+            ("scale_z", self.scale.z),
+            ("collision", self.collision as u8 as f32),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "rotation_x" => self.rotation.x = value,
+            "rotation_y" => self.rotation.y = value,
+            "rotation_z" => self.rotation.z = value,
+            "scale_x" => self.scale.x = value,
+            "scale_y" => self.scale.y = value,
+            "scale_z" => self.scale.z = value,
+            "collision" => self.collision = value != 0.0,
+            _ => {}
+        }
+    }
+
+    fn text_fields(&self) -> Vec<(&str, String)> {
+        vec![("model", self.model.clone())]
+    }
+
+    fn set_text_field(&mut self, key: &str, value: &str) {
+        if key == "model" {
+            self.model = value.to_owned();
+        }
+    }
+
+    fn get_point_ref(&self, _key: &str) -> Option<&PointRef> {
+        Some(&self.location)
+    }
+
+    fn get_point_ref_mut(&mut self, _key: &str) -> Option<&mut PointRef> {
+        Some(&mut self.location)
+    }
+
+    fn drag_handle(&mut self, _is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let axis_ref = match axis {
+            0 => &mut self.location.x,
+            1 => &mut self.location.y,
+            2 => &mut self.location.z,
+            _ => return false,
+        };
+        let base = self.location.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        match axis { 0 => self.resolved_location.x = new_world_value, 1 => self.resolved_location.y = new_world_value, _ => self.resolved_location.z = new_world_value }
+        true
+    }
+}
+
+/// The model a prop's entity should show. Its scene is (re)loaded whenever the path changes.
+#[derive(Component, PartialEq)]
+pub struct PropModel {
+    pub path: String,
+}
+
+/// Model path whose scene is currently attached, and whether its bounds have been measured.
+#[derive(Component)]
+pub struct PropScene {
+    path: String,
+    measured: bool,
+}
+
+/// Spawns a prop's glTF scene under its feature entity, replacing the old one when the model changes.
+pub fn load_prop_scenes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    props: Query<(Entity, &PropModel, Option<&PropScene>), Changed<PropModel>>,
+) {
+    for (entity, model, scene) in &props {
+        if scene.is_some_and(|s| s.path == model.path) { continue; }
+        let mut entity = commands.entity(entity);
+        entity.despawn_related::<Children>();
+        if model.path.is_empty() {
+            entity.remove::<(SceneRoot, PropScene)>();
+            continue;
+        }
+        let handle = asset_server.load(GltfAssetLabel::Scene(0).from_asset(format!("{}/{}", PROP_DIR, model.path)));
+        entity.insert((
+            SceneRoot(handle),
+            Visibility::default(),
+            PropScene { path: model.path.clone(), measured: false },
+        ));
+    }
+}
+
+/// Once a prop's scene has spawned, tells the feature how big the model is so it can be picked.
+/// Scenes respawn whenever their prop is refreshed; the timeline is only touched when a measurement
+/// differs from what the feature already has, so re-measuring doesn't count as an edit.
+pub fn measure_prop_scenes(
+    mut features: ResMut<FeatureTimeline>,
+    mut props: Query<(Entity, &FeatureTag, &GlobalTransform, &mut PropScene)>,
+    children: Query<&Children>,
+    meshes: Query<(&Aabb, &GlobalTransform)>,
+) {
+    for (entity, tag, root, mut scene) in &mut props {
+        if scene.measured { continue; }
+        let to_local = root.affine().inverse();
+        let mut bounds: Option<(Vec3, Vec3)> = None;
+        for child in children.iter_descendants(entity) {
+            let Ok((aabb, global)) = meshes.get(child) else { continue; };
+            let transform = Transform::from_matrix((to_local * global.affine()).into());
+            let (min, max) = transformed_bounds((aabb.min().into(), aabb.max().into()), &transform);
+            bounds = Some(bounds.map_or((min, max), |(lo, hi)| (lo.min(min), hi.max(max))));
+        }
+        let Some((min, max)) = bounds else { continue; };
+        scene.measured = true;
+        let id = FeatureId::from_raw(tag.feature_id);
+        let known = features.get_feature(&id).is_none_or(|f| f.object().model_bounds() == Some((min, max)));
+        if known {
+            continue;
+        }
+        if let Some(feature) = features.features_mut().get_mut(&id) {
+            feature.object_mut().set_model_bounds(min, max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transformed_bounds_follow_yaw_and_scale() {
+        let transform = Transform {
+            translation: Vec3::new(10.0, 0.0, 0.0),
+            rotation: prop_rotation(Vec3::new(0.0, 90.0, 0.0)),
+            scale: Vec3::new(2.0, 1.0, 1.0),
+        };
+        let (min, max) = transformed_bounds((Vec3::new(-1.0, 0.0, -0.5), Vec3::new(1.0, 1.0, 0.5)), &transform);
+        assert!(min.distance(Vec3::new(9.5, 0.0, -2.0)) < 1e-4);
+        assert!(max.distance(Vec3::new(10.5, 1.0, 2.0)) < 1e-4);
+    }
+}
@@ -0,0 +1,300 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::gizmos::config::{DefaultGizmoConfigGroup, GizmoConfigStore};
+use bevy::pbr::wireframe::{Wireframe, WireframeColor, WireframePlugin};
+use bevy::picking::Pickable;
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::post_process::bloom::Bloom;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use crate::editor::editable::{FeatureId, FeatureTag, FeatureTimeline};
+use crate::editor::multicam::{Multicam, MulticamState};
+use crate::get;
+use crate::tool::bakes::BakedRoomGeometry;
+
+/// Pulls gizmo lines slightly towards the camera so room outlines win over the faces they lie on.
+const OVERLAY_DEPTH_BIAS: f32 = -0.02;
+
+pub struct RenderModePlugin;
+
+impl Plugin for RenderModePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(WireframePlugin::default())
+            .init_resource::<ProxyMaterials>()
+            .add_systems(Startup, show_gizmos_in_every_mode)
+            .add_systems(PostUpdate, (
+                apply_render_modes,
+                sync_render_proxies.after(TransformSystems::Propagate),
+            ))
+        ;
+    }
+}
+
+/// How a viewport draws the level. Lit views see the scene itself; the others see copies of its
+/// meshes on their own render layer, so each viewport can pick its mode independently.
+#[derive(PartialEq, Clone, Copy, Eq, Hash, Debug, Default, Serialize, Deserialize, EnumIter)]
+pub enum RenderMode {
+    #[default]
+    Lit,
+    /// Flat colours from the meshes' materials, ignoring the level's lights.
+    Unlit,
+    Wireframe,
+    /// Flat colours by what kind of feature the mesh belongs to.
+    FeatureColor,
+}
+
+impl RenderMode {
+    pub fn name(&self) -> String {
+        match self {
+            RenderMode::Lit => get!("viewport.render_mode.lit"),
+            RenderMode::Unlit => get!("viewport.render_mode.unlit"),
+            RenderMode::Wireframe => get!("viewport.render_mode.wireframe"),
+            RenderMode::FeatureColor => get!("viewport.render_mode.feature_color"),
+        }
+    }
+
+    /// The render layer views in this mode draw.
+    pub fn layer(&self) -> usize {
+        match self {
+            RenderMode::Lit => 0,
+            RenderMode::Unlit => 1,
+            RenderMode::Wireframe => 2,
+            RenderMode::FeatureColor => 3,
+        }
+    }
+}
+
+/// Every layer a viewport can draw, for things that show whatever mode a viewport is in.
+pub fn all_view_layers() -> RenderLayers {
+    RenderLayers::from_layers(&[
+        RenderMode::Lit.layer(),
+        RenderMode::Unlit.layer(),
+        RenderMode::Wireframe.layer(),
+        RenderMode::FeatureColor.layer(),
+    ])
+}
+
+/// The colour a feature's meshes get in `RenderMode::FeatureColor`, grouped the way the Show panel groups gizmos.
+pub fn feature_type_color(type_key: &str) -> Color {
+    match type_key {
+        "editor_room" | "stairs" => Color::srgb_u8(150, 160, 180),
+        "grackle_point_light" | "grackle_spot_light" | "grackle_sun" | "grackle_area_light" => Color::srgb_u8(255, 220, 90),
+        "map_marker" | "pickup" => Color::srgb_u8(110, 210, 110),
+        "trigger_volume" | "logic_entity" => Color::srgb_u8(220, 110, 220),
+        "teleporter" | "jump_pad" => Color::srgb_u8(90, 220, 230),
+        "prop" => Color::srgb_u8(240, 150, 70),
+        "liquid_volume" => Color::srgb_u8(70, 120, 240),
+        _ => Color::srgb_u8(200, 200, 200),
+    }
+}
+
+/// A copy of a scene mesh drawn by the views in `mode`.
+#[derive(Component)]
+struct RenderProxy {
+    source: Entity,
+    mode: RenderMode,
+    /// The source's material when this proxy's material was derived from it.
+    material: AssetId<StandardMaterial>,
+}
+
+/// Materials shared between proxies: unlit copies of the scene's materials, one per feature colour,
+/// and an invisible one for wireframes to draw over. Unlit copies follow their source as it loads
+/// or changes and are dropped once it is gone.
+#[derive(Resource, Default)]
+struct ProxyMaterials {
+    unlit: HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>,
+    by_color: HashMap<[u8; 4], Handle<StandardMaterial>>,
+    hidden: Option<Handle<StandardMaterial>>,
+}
+
+impl ProxyMaterials {
+    fn unlit_copy(&mut self, source: &Handle<StandardMaterial>, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        if let Some(handle) = self.unlit.get(&source.id()) {
+            return handle.clone();
+        }
+        let mut material = materials.get(source).cloned().unwrap_or_default();
+        material.unlit = true;
+        let handle = materials.add(material);
+        self.unlit.insert(source.id(), handle.clone());
+        handle
+    }
+
+    /// Re-copy sources that loaded or changed since their copy was made, and forget copies whose
+    /// source no longer exists.
+    fn refresh(&mut self, events: &mut MessageReader<AssetEvent<StandardMaterial>>, materials: &mut Assets<StandardMaterial>) {
+        for event in events.read() {
+            let (AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id }) = event else {
+                continue;
+            };
+            let Some(copy) = self.unlit.get(id) else { continue };
+            let Some(mut material) = materials.get(*id).cloned() else { continue };
+            material.unlit = true;
+            if let Some(target) = materials.get_mut(copy) {
+                *target = material;
+            }
+        }
+        self.unlit.retain(|id, _| materials.contains(*id));
+    }
+
+    fn flat(&mut self, color: Color, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        self.by_color.entry(color.to_srgba().to_u8_array())
+            .or_insert_with(|| materials.add(StandardMaterial {
+                base_color: color,
+                unlit: true,
+                ..Default::default()
+            }))
+            .clone()
+    }
+
+    fn hidden(&mut self, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        self.hidden.get_or_insert_with(|| materials.add(StandardMaterial {
+            base_color: Color::NONE,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..Default::default()
+        })).clone()
+    }
+}
+
+fn show_gizmos_in_every_mode(mut gizmo_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = gizmo_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.render_layers = all_view_layers();
+    config.depth_bias = OVERLAY_DEPTH_BIAS;
+}
+
+/// Point each camera at its mode's layer. Only lit views get bloom and tonemapping, so the flat
+/// modes show their colours as they are.
+fn apply_render_modes(
+    mut commands: Commands,
+    state: Res<MulticamState>,
+    cameras: Query<(Entity, &Multicam, Option<&RenderLayers>)>,
+) {
+    for (entity, multicam, layers) in &cameras {
+        let mode = state.render_modes.get(multicam.id as usize).copied().unwrap_or_default();
+        let wanted = RenderLayers::layer(mode.layer());
+        if layers == Some(&wanted) {
+            continue;
+        }
+        let mut camera = commands.entity(entity);
+        camera.insert(wanted);
+        if mode == RenderMode::Lit {
+            camera.insert((Bloom::NATURAL, Tonemapping::TonyMcMapface));
+        } else {
+            camera.remove::<Bloom>().insert(Tonemapping::None);
+        }
+    }
+}
+
+/// The feature a mesh was spawned for, found by walking up to the entity tagged with it.
+fn owning_feature_type(
+    entity: Entity,
+    parents: &Query<&ChildOf>,
+    tags: &Query<&FeatureTag>,
+    features: &FeatureTimeline,
+) -> Option<&'static str> {
+    let owner = std::iter::once(entity).chain(parents.iter_ancestors(entity))
+        .find_map(|e| tags.get(e).ok())?;
+    features.get_feature(&FeatureId::from_raw(owner.feature_id)).map(|f| f.object().type_key())
+}
+
+type ProxyState<'a> = (Entity, &'a mut RenderProxy, &'a mut Mesh3d, &'a mut MeshMaterial3d<StandardMaterial>, &'a mut Transform, &'a mut Visibility);
+type ProxySource<'a> = (Entity, &'a Mesh3d, &'a MeshMaterial3d<StandardMaterial>, &'a GlobalTransform, &'a InheritedVisibility);
+
+/// Keep a proxy of every scene mesh for each mode some visible viewport is using, following the
+/// original's mesh, position and visibility. Entities that already choose their layers, like
+/// drag handles, are left alone.
+fn sync_render_proxies(
+    mut commands: Commands,
+    state: Res<MulticamState>,
+    features: Res<FeatureTimeline>,
+    sources: Query<ProxySource, (Without<RenderLayers>, Without<RenderProxy>)>,
+    mut proxies: Query<ProxyState>,
+    (parents, tags, baked): (Query<&ChildOf>, Query<&FeatureTag>, Query<(), With<BakedRoomGeometry>>),
+    (mut proxy_materials, mut materials, mut material_events): (ResMut<ProxyMaterials>, ResMut<Assets<StandardMaterial>>, MessageReader<AssetEvent<StandardMaterial>>),
+) {
+    proxy_materials.refresh(&mut material_events, &mut materials);
+
+    let modes: HashSet<RenderMode> = state.visible_cells().iter()
+        .filter_map(|(id, _)| state.render_modes.get(*id as usize).copied())
+        .filter(|mode| *mode != RenderMode::Lit)
+        .collect();
+
+    let mut existing: HashSet<(Entity, RenderMode)> = HashSet::new();
+    for (entity, mut proxy, mut mesh, mut material, mut transform, mut visibility) in &mut proxies {
+        let Ok((_, source_mesh, source_material, global, inherited)) = sources.get(proxy.source) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        if !modes.contains(&proxy.mode) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        existing.insert((proxy.source, proxy.mode));
+        if mesh.0 != source_mesh.0 {
+            mesh.0 = source_mesh.0.clone();
+        }
+        if proxy.material != source_material.id() {
+            proxy.material = source_material.id();
+            if proxy.mode == RenderMode::Unlit {
+                material.0 = proxy_materials.unlit_copy(&source_material.0, &mut materials);
+            }
+        }
+        let wanted = global.compute_transform();
+        if *transform != wanted {
+            *transform = wanted;
+        }
+        let wanted = if inherited.get() { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+
+    for (source, mesh, material, global, inherited) in &sources {
+        for mode in &modes {
+            if existing.contains(&(source, *mode)) {
+                continue;
+            }
+            let proxy_material = match mode {
+                RenderMode::Unlit => proxy_materials.unlit_copy(&material.0, &mut materials),
+                RenderMode::Wireframe => proxy_materials.hidden(&mut materials),
+                _ => {
+                    let type_key = if baked.contains(source) {
+                        Some("editor_room")
+                    } else {
+                        owning_feature_type(source, &parents, &tags, &features)
+                    };
+                    proxy_materials.flat(feature_type_color(type_key.unwrap_or_default()), &mut materials)
+                }
+            };
+            let mut proxy = commands.spawn((
+                RenderProxy { source, mode: *mode, material: material.id() },
+                Mesh3d(mesh.0.clone()),
+                MeshMaterial3d(proxy_material),
+                global.compute_transform(),
+                if inherited.get() { Visibility::Inherited } else { Visibility::Hidden },
+                RenderLayers::layer(mode.layer()),
+                Pickable::IGNORE,
+            ));
+            if *mode == RenderMode::Wireframe {
+                proxy.insert((Wireframe, WireframeColor { color: Color::srgb_u8(220, 220, 220) }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+    use super::*;
+
+    #[test]
+    fn test_each_mode_has_its_own_layer() {
+        let layers: HashSet<usize> = RenderMode::iter().map(|m| m.layer()).collect();
+        assert_eq!(layers.len(), RenderMode::iter().count());
+        for mode in RenderMode::iter() {
+            assert!(all_view_layers().intersects(&RenderLayers::layer(mode.layer())));
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use crate::editor::action::FeatureSnapshot;
+use crate::editor::editable::{FeatureId, FeatureTimeline};
+use crate::editor::editor_room::room_point;
+use crate::editor::transform::retarget_point_ref;
+use crate::get;
+
+/// Faces closer than this count as touching, and extents this close as equal.
+const EPSILON: f32 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoomOpError {
+    NotARoom,
+    PlaneOutsideRoom,
+    NotAdjacent,
+}
+
+impl RoomOpError {
+    pub fn message(&self) -> String {
+        match self {
+            RoomOpError::NotARoom => get!("editor.room_ops.not_a_room"),
+            RoomOpError::PlaneOutsideRoom => get!("editor.room_ops.plane_outside"),
+            RoomOpError::NotAdjacent => get!("editor.room_ops.not_adjacent"),
+        }
+    }
+}
+
+/// Split plane chosen in the panel: an axis index and a world coordinate along it.
+#[derive(Default)]
+pub struct RoomOpsState {
+    pub split_axis: usize,
+    pub split_at: Option<f32>,
+    pub error: Option<RoomOpError>,
+}
+
+fn room_bounds(timeline: &FeatureTimeline, id: FeatureId) -> Option<(Vec3, Vec3)> {
+    timeline.active_features()
+        .find(|(active, _)| *active == id)
+        .filter(|(_, feature)| feature.object().type_key() == "editor_room")
+        .and_then(|(_, feature)| feature.object().drag_handle_bounds())
+}
+
+/// The axis along which two rooms share a whole face, if they do.
+fn shared_face_axis(a: (Vec3, Vec3), b: (Vec3, Vec3)) -> Option<usize> {
+    let close = |x: f32, y: f32| (x - y).abs() < EPSILON;
+    (0..3).find(|&axis| {
+        let touching = close(a.1[axis], b.0[axis]) || close(b.1[axis], a.0[axis]);
+        let same_face = (0..3).filter(|&other| other != axis)
+            .all(|other| close(a.0[other], b.0[other]) && close(a.1[other], b.1[other]));
+        touching && same_face
+    })
+}
+
+/// Active rooms that share a whole face with `room`, so they can be merged into it.
+pub fn adjacent_rooms(timeline: &FeatureTimeline, room: FeatureId) -> Vec<FeatureId> {
+    let Some(bounds) = room_bounds(timeline, room) else { return vec![]; };
+    timeline.active_features()
+        .filter(|(id, _)| *id != room)
+        .filter(|(id, _)| room_bounds(timeline, *id).is_some_and(|other| shared_face_axis(bounds, other).is_some()))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// `snapshot` of a room with each corner moved to `reshape(corner)`. The room's own references
+/// are kept and their offsets adjusted.
+fn reshaped_room(timeline: &FeatureTimeline, snapshot: &FeatureSnapshot, reshape: impl Fn(Vec3) -> Vec3) -> FeatureSnapshot {
+    let map = timeline.features_map();
+    let mut object = snapshot.blank_object();
+    for slot in ["min", "max"] {
+        let Some(pr) = object.get_point_ref(slot).cloned() else { continue; };
+        let Ok(world) = pr.clone().resolve(map) else { continue; };
+        let base = pr.reference
+            .and_then(|r| map.get(&r))
+            .and_then(|f| f.get_point(&pr.point_key).ok());
+        if let Some(slot_ref) = object.get_point_ref_mut(slot) {
+            *slot_ref = retarget_point_ref(&pr, reshape(world), base);
+        }
+    }
+    FeatureSnapshot::from_data(object.snapshot(), snapshot.order_index)
+}
+
+/// New snapshots for every active feature outside `rooms` that references one of them. `target`
+/// maps a referenced room, point key and the point's old position to the room it should reference
+/// now and where that room's point will be; every dependant stays exactly where it was.
+fn retarget_dependants(
+    timeline: &FeatureTimeline,
+    rooms: &[FeatureId],
+    target: impl Fn(FeatureId, &str, Vec3) -> Option<(FeatureId, Vec3)>,
+) -> Vec<(FeatureId, Option<FeatureSnapshot>)> {
+    let map = timeline.features_map();
+    let mut changes = vec![];
+    for (id, feature) in timeline.active_features() {
+        if rooms.contains(&id) {
+            continue;
+        }
+        let Some(snapshot) = timeline.feature_snapshot(id) else { continue; };
+        let mut object = snapshot.blank_object();
+        let mut changed = false;
+        for slot in feature.object().point_ref_slots() {
+            let Some(pr) = feature.object().get_point_ref(slot) else { continue; };
+            let Some(room) = pr.reference.filter(|r| rooms.contains(r)) else { continue; };
+            let Ok(world) = pr.clone().resolve(map) else { continue; };
+            let Some(old_base) = map.get(&room).and_then(|f| f.get_point(&pr.point_key).ok()) else { continue; };
+            let Some((new_room, new_base)) = target(room, &pr.point_key, old_base) else { continue; };
+            let mut moved = retarget_point_ref(pr, world, Some(new_base));
+            moved.reference = Some(new_room);
+            if let Some(slot_ref) = object.get_point_ref_mut(slot) {
+                *slot_ref = moved;
+                changed = true;
+            }
+        }
+        if changed {
+            changes.push((id, Some(FeatureSnapshot::from_data(object.snapshot(), snapshot.order_index))));
+        }
+    }
+    changes
+}
+
+/// Split `room` at `at` along `axis`. The room keeps the low side and a new room, returned, takes
+/// the high side. Dependants reference whichever half now holds the point they referenced.
+pub fn split_room(timeline: &mut FeatureTimeline, room: FeatureId, axis: usize, at: f32) -> Result<FeatureId, RoomOpError> {
+    let (min, max) = room_bounds(timeline, room).ok_or(RoomOpError::NotARoom)?;
+    if at <= min[axis] + EPSILON || at >= max[axis] - EPSILON {
+        return Err(RoomOpError::PlaneOutsideRoom);
+    }
+    let snapshot = timeline.feature_snapshot(room).ok_or(RoomOpError::NotARoom)?;
+    let low = reshaped_room(timeline, &snapshot, |mut p| { p[axis] = p[axis].min(at); p });
+    let mut high = reshaped_room(timeline, &snapshot, |mut p| { p[axis] = p[axis].max(at); p });
+    // Straight after the original, so dependants that move onto it still come after what they reference.
+    high.order_index = snapshot.order_index + 1;
+    let high_id = timeline.next_id();
+
+    let mut low_bounds = (min, max);
+    low_bounds.1[axis] = at;
+    let mut high_bounds = (min, max);
+    high_bounds.0[axis] = at;
+    let dependants = retarget_dependants(timeline, &[room], |_, key, old_base| {
+        let keeps = |(lo, hi): (Vec3, Vec3)| room_point(lo, hi, key).is_ok_and(|p| p.distance(old_base) < EPSILON);
+        let (id, (lo, hi)) = if keeps(low_bounds) {
+            (room, low_bounds)
+        } else if keeps(high_bounds) || old_base[axis] > at {
+            (high_id, high_bounds)
+        } else {
+            (room, low_bounds)
+        };
+        room_point(lo, hi, key).ok().map(|p| (id, p))
+    });
+
+    let mut changes = vec![(room, Some(low)), (high_id, Some(high))];
+    changes.extend(dependants);
+    timeline.apply_changes(changes);
+    Ok(high_id)
+}
+
+/// Merge two rooms that share a whole face. The earlier room in the timeline grows to cover both
+/// and is returned; the other is removed and its dependants reference the merged room instead.
+pub fn merge_rooms(timeline: &mut FeatureTimeline, a: FeatureId, b: FeatureId) -> Result<FeatureId, RoomOpError> {
+    let bounds_a = room_bounds(timeline, a).ok_or(RoomOpError::NotARoom)?;
+    let bounds_b = room_bounds(timeline, b).ok_or(RoomOpError::NotARoom)?;
+    let axis = shared_face_axis(bounds_a, bounds_b).filter(|_| a != b).ok_or(RoomOpError::NotAdjacent)?;
+
+    let index = |id: FeatureId| timeline.feature_order().iter().position(|f| *f == id);
+    let (keep, gone, (keep_min, keep_max)) = if index(a) < index(b) { (a, b, bounds_a) } else { (b, a, bounds_b) };
+    let (union_min, union_max) = (bounds_a.0.min(bounds_b.0), bounds_a.1.max(bounds_b.1));
+
+    let snapshot = timeline.feature_snapshot(keep).ok_or(RoomOpError::NotARoom)?;
+    let merged = reshaped_room(timeline, &snapshot, |mut p| {
+        let on_min_side = (p[axis] - keep_min[axis]).abs() <= (p[axis] - keep_max[axis]).abs();
+        p[axis] = if on_min_side { union_min[axis] } else { union_max[axis] };
+        p
+    });
+    let dependants = retarget_dependants(timeline, &[a, b], |_, key, _| {
+        room_point(union_min, union_max, key).ok().map(|p| (keep, p))
+    });
+
+    let mut changes = vec![(keep, Some(merged))];
+    changes.extend(dependants);
+    changes.push((gone, None));
+    timeline.apply_changes(changes);
+    Ok(keep)
+}
+
+/// Split and merge controls for the selected room.
+pub fn room_ops_panel_ui(ui: &mut egui::Ui, state: &mut RoomOpsState, timeline: &mut FeatureTimeline) {
+    let Some(room) = timeline.selected_feature() else { return; };
+    let Some((min, max)) = room_bounds(timeline, room) else { return; };
+
+    ui.label(get!("editor.room_ops.title"));
+    let axis = state.split_axis.min(2);
+    let at = state.split_at.unwrap_or((min[axis] + max[axis]) / 2.0);
+    let mut split_at = at.clamp(min[axis], max[axis]);
+    ui.horizontal(|ui| {
+        for (i, name) in ["X", "Y", "Z"].into_iter().enumerate() {
+            if ui.selectable_label(axis == i, name).clicked() && axis != i {
+                state.split_axis = i;
+                state.split_at = None;
+            }
+        }
+        ui.add(egui::DragValue::new(&mut split_at).range(min[axis]..=max[axis]).speed(0.1));
+        if split_at != at {
+            state.split_at = Some(split_at);
+        }
+        if ui.button(get!("editor.room_ops.split")).clicked() {
+            state.error = split_room(timeline, room, axis, split_at).err();
+            state.split_at = None;
+        }
+    });
+
+    for other in adjacent_rooms(timeline, room) {
+        if ui.button(get!("editor.room_ops.merge_with", "room", other)).clicked() {
+            match merge_rooms(timeline, room, other) {
+                Ok(merged) => {
+                    timeline.select(Some(merged));
+                    state.error = None;
+                }
+                Err(error) => state.error = Some(error),
+            }
+        }
+    }
+
+    if let Some(error) = state.error {
+        ui.colored_label(egui::Color32::YELLOW, error.message());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editable::PointRef;
+    use crate::editor::editor_room::EditorRoom;
+    use crate::editor::global_point::GlobalPoint;
+
+    fn add_room(timeline: &mut FeatureTimeline, min: Vec3, max: Vec3) -> FeatureId {
+        timeline.apply_feature(Box::new(EditorRoom::from_point_refs(
+            PointRef::absolute(min.x, min.y, min.z),
+            PointRef::absolute(max.x, max.y, max.z),
+        )))
+    }
+
+    fn add_point_on(timeline: &mut FeatureTimeline, room: FeatureId, key: &str, offset: Vec3) -> FeatureId {
+        let mut location = PointRef::reference_with_offset(room, offset.x, offset.y, offset.z);
+        location.point_key = key.to_owned();
+        let id = timeline.apply_feature(Box::new(GlobalPoint::from_point_ref(location)));
+        timeline.resolve_all_references();
+        id
+    }
+
+    fn position(timeline: &FeatureTimeline, id: FeatureId) -> Vec3 {
+        timeline.get_feature(&id).unwrap().get_point("").ok().unwrap()
+    }
+
+    #[test]
+    fn test_split_keeps_dependants_in_place_and_undoes_in_one_step() {
+        let mut timeline = FeatureTimeline::default();
+        let room = add_room(&mut timeline, Vec3::ZERO, Vec3::new(8.0, 3.0, 4.0));
+        let near_max = add_point_on(&mut timeline, room, "max", Vec3::new(-1.0, -1.0, -1.0));
+        let near_min = add_point_on(&mut timeline, room, "min", Vec3::ONE);
+        let actions = timeline.applied_actions().len();
+
+        let high = split_room(&mut timeline, room, 0, 5.0).unwrap();
+        assert_eq!(timeline.applied_actions().len(), actions + 1);
+        assert_eq!(room_bounds(&timeline, room), Some((Vec3::ZERO, Vec3::new(5.0, 3.0, 4.0))));
+        assert_eq!(room_bounds(&timeline, high), Some((Vec3::new(5.0, 0.0, 0.0), Vec3::new(8.0, 3.0, 4.0))));
+
+        let max_ref = timeline.get_feature(&near_max).unwrap().object().get_point_ref("location").unwrap();
+        assert_eq!(max_ref.reference, Some(high));
+        assert_eq!(max_ref.x.value(), -1.0);
+        assert_eq!(position(&timeline, near_max), Vec3::new(7.0, 2.0, 3.0));
+        assert_eq!(position(&timeline, near_min), Vec3::ONE);
+        assert!(timeline.get_feature(&near_min).unwrap().parents().contains(&room));
+
+        timeline.undo_action();
+        timeline.resolve_all_references();
+        assert!(timeline.get_feature(&high).is_none());
+        assert_eq!(room_bounds(&timeline, room), Some((Vec3::ZERO, Vec3::new(8.0, 3.0, 4.0))));
+        assert_eq!(position(&timeline, near_max), Vec3::new(7.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_merge_needs_a_whole_shared_face() {
+        let mut timeline = FeatureTimeline::default();
+        let a = add_room(&mut timeline, Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0));
+        let b = add_room(&mut timeline, Vec3::new(0.0, 0.0, 4.0), Vec3::new(4.0, 3.0, 10.0));
+        let narrow = add_room(&mut timeline, Vec3::new(4.0, 0.0, 0.0), Vec3::new(6.0, 3.0, 2.0));
+        timeline.resolve_all_references();
+        let on_b = add_point_on(&mut timeline, b, "", Vec3::new(0.0, 0.0, 1.0));
+
+        assert_eq!(adjacent_rooms(&timeline, a), vec![b]);
+        assert_eq!(merge_rooms(&mut timeline, a, narrow), Err(RoomOpError::NotAdjacent));
+
+        assert_eq!(merge_rooms(&mut timeline, b, a), Ok(a));
+        assert!(timeline.get_feature(&b).is_none());
+        assert_eq!(room_bounds(&timeline, a), Some((Vec3::ZERO, Vec3::new(4.0, 3.0, 10.0))));
+        assert_eq!(timeline.get_feature(&on_b).unwrap().parents(), &[a]);
+        assert_eq!(position(&timeline, on_b), Vec3::new(2.0, 1.5, 8.0));
+
+        timeline.undo_action();
+        timeline.resolve_all_references();
+        assert!(timeline.get_feature(&b).is_some());
+        assert_eq!(room_bounds(&timeline, a), Some((Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0))));
+        assert_eq!(position(&timeline, on_b), Vec3::new(2.0, 1.5, 8.0));
+    }
+}
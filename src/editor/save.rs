@@ -1,14 +1,26 @@
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use bevy::platform::collections::HashMap;
-use bevy::prelude::{Vec3, info};
-use rusqlite::{Connection, Transaction, params};
+use bevy::prelude::{Vec2, Vec3, info};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Transaction, params};
+use crate::common::mode::GameMode;
 use crate::constants::{SCHEMA_VERSION, MAP_BLUEPRINT_EXTENSION, MAP_BACKUP_EXTENSION};
-use crate::editor::action::{Action, FeatureData, FeatureDelta, FeatureSnapshot};
+use crate::editor::action::{Action, FeatureData, FeatureDelta, FeatureSnapshot, SymmetryChange};
 use crate::editor::editable::{
     AxisRef, Feature, FeatureId, FeatureTimeline, PointRef,
     create_object_from_type_key,
 };
+use crate::editor::liquid_volume::{BakedVolume, VolumeKind, volumes_from_timeline};
+use crate::editor::logic::LogicConnection;
+use crate::editor::logic_entity::LogicKind;
+use crate::editor::map_marker::{MarkerKind, Team};
+use crate::editor::note::NoteStatus;
 use crate::editor::map_metadata::MapMetadata;
+use crate::editor::map_naming::game_mode_from_file_name;
+use crate::editor::pickup::PickupKind;
+use crate::editor::symmetry::{Symmetry, SymmetryMode, SymmetrySettings};
+use crate::tool::navmesh::{build_navmesh, rooms_from_timeline, stairs_from_timeline, NavHull, NavLink, NavPolygon, Navmesh};
 
 /// Result of loading a blueprint file (features + map metadata).
 pub struct LoadedBlueprint {
@@ -110,10 +122,138 @@ fn migrations() -> Vec<(u64, Vec<&'static str>)> {
                 UNIQUE(action_id, delta_index)
             );",
         ]),
+        (4, vec![
+            "CREATE TABLE IF NOT EXISTS symmetry (
+                enabled           INTEGER NOT NULL,
+                mode              TEXT    NOT NULL,
+                origin_feature_id INTEGER REFERENCES features(id)
+            );",
+            "CREATE TABLE IF NOT EXISTS feature_counterparts (
+                feature_id     INTEGER PRIMARY KEY REFERENCES features(id),
+                counterpart_id INTEGER NOT NULL REFERENCES features(id)
+            );",
+        ]),
+        (5, vec![
+            "CREATE TABLE IF NOT EXISTS navmesh_polygons (
+                id     INTEGER PRIMARY KEY,
+                height REAL    NOT NULL,
+                min_x  REAL    NOT NULL,
+                min_z  REAL    NOT NULL,
+                max_x  REAL    NOT NULL,
+                max_z  REAL    NOT NULL
+            );",
+            "CREATE TABLE IF NOT EXISTS navmesh_links (
+                from_polygon INTEGER NOT NULL REFERENCES navmesh_polygons(id),
+                to_polygon   INTEGER NOT NULL REFERENCES navmesh_polygons(id),
+                portal_ax    REAL    NOT NULL,
+                portal_ay    REAL    NOT NULL,
+                portal_az    REAL    NOT NULL,
+                portal_bx    REAL    NOT NULL,
+                portal_by    REAL    NOT NULL,
+                portal_bz    REAL    NOT NULL
+            );",
+        ]),
+        (6, vec![
+            "CREATE TABLE IF NOT EXISTS text_fields (
+                owner_feature_id INTEGER NOT NULL REFERENCES features(id),
+                field_key        TEXT    NOT NULL,
+                field_value      TEXT    NOT NULL,
+                PRIMARY KEY (owner_feature_id, field_key)
+            );",
+            "CREATE TABLE IF NOT EXISTS snapshot_text_fields (
+                snapshot_id INTEGER NOT NULL REFERENCES feature_snapshots(id) ON DELETE CASCADE,
+                field_key   TEXT NOT NULL,
+                field_value TEXT NOT NULL,
+                PRIMARY KEY (snapshot_id, field_key)
+            );",
+        ]),
+        (7, vec![
+            "CREATE TABLE IF NOT EXISTS metadata_changelog (
+                position INTEGER PRIMARY KEY,
+                version  TEXT    NOT NULL,
+                notes    TEXT    NOT NULL
+            );",
+            "CREATE TABLE IF NOT EXISTS metadata_custom (
+                key      TEXT    PRIMARY KEY,
+                value    TEXT    NOT NULL,
+                position INTEGER NOT NULL
+            );",
+        ]),
+        (8, vec![
+            // Targets may be deleted while the connection stays behind, so they are not foreign keys.
+            "CREATE TABLE IF NOT EXISTS logic_connections (
+                owner_feature_id  INTEGER NOT NULL REFERENCES features(id),
+                position          INTEGER NOT NULL,
+                output            TEXT    NOT NULL,
+                target_feature_id INTEGER NOT NULL,
+                input             TEXT    NOT NULL,
+                delay             REAL    NOT NULL,
+                PRIMARY KEY (owner_feature_id, position)
+            );",
+            "CREATE TABLE IF NOT EXISTS snapshot_logic_connections (
+                snapshot_id       INTEGER NOT NULL REFERENCES feature_snapshots(id) ON DELETE CASCADE,
+                position          INTEGER NOT NULL,
+                output            TEXT    NOT NULL,
+                target_feature_id INTEGER NOT NULL,
+                input             TEXT    NOT NULL,
+                delay             REAL    NOT NULL,
+                PRIMARY KEY (snapshot_id, position)
+            );",
+        ]),
+        (9, vec![
+            // Resolved liquid and hazard volumes for the game, rewritten on every save like the navmesh.
+            "CREATE TABLE IF NOT EXISTS baked_volumes (
+                id          INTEGER PRIMARY KEY,
+                kind        TEXT    NOT NULL,
+                min_x       REAL    NOT NULL,
+                min_y       REAL    NOT NULL,
+                min_z       REAL    NOT NULL,
+                max_x       REAL    NOT NULL,
+                max_y       REAL    NOT NULL,
+                max_z       REAL    NOT NULL,
+                damage      REAL    NOT NULL,
+                speed_scale REAL    NOT NULL,
+                buoyancy    REAL    NOT NULL,
+                tint_r      REAL    NOT NULL,
+                tint_g      REAL    NOT NULL,
+                tint_b      REAL    NOT NULL,
+                opacity     REAL    NOT NULL
+            );",
+        ]),
+        (10, vec![
+            "CREATE TABLE IF NOT EXISTS camera_bookmarks (
+                position INTEGER PRIMARY KEY,
+                name     TEXT    NOT NULL,
+                x        REAL    NOT NULL,
+                y        REAL    NOT NULL,
+                z        REAL    NOT NULL,
+                qx       REAL    NOT NULL,
+                qy       REAL    NOT NULL,
+                qz       REAL    NOT NULL,
+                qw       REAL    NOT NULL
+            );",
+        ]),
+        (11, vec![
+            // Symmetry panel changes in the undo history; `side` is 'before' or 'after'.
+            "CREATE TABLE IF NOT EXISTS history_action_symmetry (
+                action_id         INTEGER NOT NULL REFERENCES history_actions(id) ON DELETE CASCADE,
+                side              TEXT    NOT NULL,
+                enabled           INTEGER NOT NULL,
+                mode              TEXT    NOT NULL,
+                origin_feature_id INTEGER,
+                PRIMARY KEY (action_id, side)
+            );",
+            "CREATE TABLE IF NOT EXISTS history_action_counterparts (
+                action_id      INTEGER NOT NULL REFERENCES history_actions(id) ON DELETE CASCADE,
+                side           TEXT    NOT NULL,
+                feature_id     INTEGER NOT NULL,
+                counterpart_id INTEGER NOT NULL
+            );",
+        ]),
     ]
 }
 
-fn run_migrations(conn: &Connection, from_version: u64, update_metadata: bool) -> rusqlite::Result<()> {
+pub(crate) fn run_migrations(conn: &Connection, from_version: u64, update_metadata: bool) -> rusqlite::Result<()> {
     for (version, statements) in migrations() {
         if version <= from_version { continue; }
         if version > SCHEMA_VERSION { break; }
@@ -258,12 +398,26 @@ fn load_snapshot_parents(conn: &Connection, snapshot_id: i64) -> rusqlite::Resul
     Ok(out)
 }
 
-fn snapshot_data_kind(data: &FeatureData) -> &'static str {
+pub(crate) fn snapshot_data_kind(data: &FeatureData) -> &'static str {
     match data {
         FeatureData::GlobalPoint { .. } => "global_point",
         FeatureData::PointLight { .. } => "point_light",
+        FeatureData::SpotLight { .. } => "spot_light",
+        FeatureData::Sun { .. } => "sun",
+        FeatureData::AreaLight { .. } => "area_light",
         FeatureData::Room { .. } => "room",
         FeatureData::Cuboid { .. } => "cuboid",
+        FeatureData::Marker { .. } => "marker",
+        FeatureData::Trigger { .. } => "trigger",
+        FeatureData::Logic { .. } => "logic",
+        FeatureData::Teleporter { .. } => "teleporter",
+        FeatureData::JumpPad { .. } => "jump_pad",
+        FeatureData::Pickup { .. } => "pickup",
+        FeatureData::Prop { .. } => "prop",
+        FeatureData::Liquid { .. } => "liquid",
+        FeatureData::Stairs { .. } => "stairs",
+        FeatureData::Dimension { .. } => "dimension",
+        FeatureData::Note { .. } => "note",
     }
 }
 
@@ -290,21 +444,73 @@ fn save_feature_snapshot(
         }
         FeatureData::PointLight {
             location,
+            color,
             intensity,
             radius,
             range,
         } => {
             save_snapshot_point_ref(tx, sid, "location", location)?;
-            for (k, v) in [
+            save_snapshot_scalars(tx, sid, &[
                 ("intensity", *intensity),
                 ("radius", *radius),
                 ("range_val", *range),
-            ] {
-                tx.execute(
-                    "INSERT INTO snapshot_scalar_fields (snapshot_id, field_key, field_value) VALUES (?1, ?2, ?3)",
-                    params![sid, k, v as f64],
-                )?;
-            }
+            ])?;
+            save_snapshot_color(tx, sid, *color)?;
+        }
+        FeatureData::SpotLight {
+            location,
+            target,
+            color,
+            intensity,
+            radius,
+            range,
+            inner_angle,
+            outer_angle,
+        } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            save_snapshot_point_ref(tx, sid, "target", target)?;
+            save_snapshot_scalars(tx, sid, &[
+                ("intensity", *intensity),
+                ("radius", *radius),
+                ("range_val", *range),
+                ("inner_angle", *inner_angle),
+                ("outer_angle", *outer_angle),
+            ])?;
+            save_snapshot_color(tx, sid, *color)?;
+        }
+        FeatureData::Sun {
+            location,
+            color,
+            illuminance,
+            azimuth,
+            elevation,
+        } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            save_snapshot_scalars(tx, sid, &[
+                ("illuminance", *illuminance),
+                ("azimuth", *azimuth),
+                ("elevation", *elevation),
+            ])?;
+            save_snapshot_color(tx, sid, *color)?;
+        }
+        FeatureData::AreaLight {
+            location,
+            target,
+            color,
+            intensity,
+            range,
+            width,
+            height,
+        } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            save_snapshot_point_ref(tx, sid, "target", target)?;
+            save_snapshot_scalars(tx, sid, &[
+                ("intensity", *intensity),
+                ("range_val", *range),
+                ("width", *width),
+                ("height", *height),
+            ])?;
+            save_snapshot_color(tx, sid, *color)?;
         }
         FeatureData::Room { min, max } => {
             save_snapshot_point_ref(tx, sid, "min", min)?;
@@ -326,10 +532,186 @@ fn save_feature_snapshot(
                 )?;
             }
         }
+        FeatureData::Marker { location, kind, team, connections } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            for (k, v) in [("kind", kind.key()), ("team", team.key())] {
+                tx.execute(
+                    "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, ?2, ?3)",
+                    params![sid, k, v],
+                )?;
+            }
+            save_snapshot_connections(tx, sid, connections)?;
+        }
+        FeatureData::Trigger { min, max, start_enabled, once, connections } => {
+            save_snapshot_point_ref(tx, sid, "min", min)?;
+            save_snapshot_point_ref(tx, sid, "max", max)?;
+            save_snapshot_scalars(tx, sid, &[
+                ("start_enabled", *start_enabled as u8 as f32),
+                ("once", *once as u8 as f32),
+            ])?;
+            save_snapshot_connections(tx, sid, connections)?;
+        }
+        FeatureData::Logic { location, kind, value, start_enabled, connections } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            tx.execute(
+                "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, 'kind', ?2)",
+                params![sid, kind.key()],
+            )?;
+            save_snapshot_scalars(tx, sid, &[
+                ("value", *value),
+                ("start_enabled", *start_enabled as u8 as f32),
+            ])?;
+            save_snapshot_connections(tx, sid, connections)?;
+        }
+        FeatureData::Teleporter { min, max, destination, yaw } => {
+            save_snapshot_point_ref(tx, sid, "min", min)?;
+            save_snapshot_point_ref(tx, sid, "max", max)?;
+            save_snapshot_point_ref(tx, sid, "destination", destination)?;
+            save_snapshot_scalars(tx, sid, &[("yaw", *yaw)])?;
+        }
+        FeatureData::JumpPad { min, max, apex } => {
+            save_snapshot_point_ref(tx, sid, "min", min)?;
+            save_snapshot_point_ref(tx, sid, "max", max)?;
+            save_snapshot_point_ref(tx, sid, "apex", apex)?;
+        }
+        FeatureData::Pickup { location, kind, item, respawn, team } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            for (k, v) in [("kind", kind.key()), ("item", item.as_str()), ("team", team.key())] {
+                tx.execute(
+                    "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, ?2, ?3)",
+                    params![sid, k, v],
+                )?;
+            }
+            save_snapshot_scalars(tx, sid, &[("respawn", *respawn)])?;
+        }
+        FeatureData::Prop { location, model, rotation, scale, collision } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            tx.execute(
+                "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, 'model', ?2)",
+                params![sid, model],
+            )?;
+            save_snapshot_scalars(tx, sid, &[
+                ("rotation_x", rotation.x),
+                ("rotation_y", rotation.y),
+                ("rotation_z", rotation.z),
+                ("scale_x", scale.x),
+                ("scale_y", scale.y),
+                ("scale_z", scale.z),
+                ("collision", *collision as u8 as f32),
+            ])?;
+        }
+        FeatureData::Liquid { min, max, kind, damage, speed_scale, buoyancy, tint, opacity } => {
+            save_snapshot_point_ref(tx, sid, "min", min)?;
+            save_snapshot_point_ref(tx, sid, "max", max)?;
+            tx.execute(
+                "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, 'kind', ?2)",
+                params![sid, kind.key()],
+            )?;
+            save_snapshot_color(tx, sid, *tint)?;
+            save_snapshot_scalars(tx, sid, &[
+                ("damage", *damage),
+                ("speed_scale", *speed_scale),
+                ("buoyancy", *buoyancy),
+                ("opacity", *opacity),
+            ])?;
+        }
+        FeatureData::Stairs { start, end, width, step_height, ramp } => {
+            save_snapshot_point_ref(tx, sid, "start", start)?;
+            save_snapshot_point_ref(tx, sid, "end", end)?;
+            save_snapshot_scalars(tx, sid, &[
+                ("width", *width),
+                ("step_height", *step_height),
+                ("ramp", *ramp as u8 as f32),
+            ])?;
+        }
+        FeatureData::Dimension { start, end, note } => {
+            save_snapshot_point_ref(tx, sid, "start", start)?;
+            save_snapshot_point_ref(tx, sid, "end", end)?;
+            tx.execute(
+                "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, 'note', ?2)",
+                params![sid, note],
+            )?;
+        }
+        FeatureData::Note { location, text, author, status, color } => {
+            save_snapshot_point_ref(tx, sid, "location", location)?;
+            for (k, v) in [("text", text.as_str()), ("author", author.as_str()), ("status", status.key())] {
+                tx.execute(
+                    "INSERT INTO snapshot_text_fields (snapshot_id, field_key, field_value) VALUES (?1, ?2, ?3)",
+                    params![sid, k, v],
+                )?;
+            }
+            save_snapshot_color(tx, sid, *color)?;
+        }
     }
     Ok(sid)
 }
 
+fn save_connections(tx: &Transaction, owner_id: u64, connections: &[LogicConnection]) -> rusqlite::Result<()> {
+    for (position, c) in connections.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO logic_connections (owner_feature_id, position, output, target_feature_id, input, delay)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![owner_id as i64, position as i64, c.output, c.target._id() as i64, c.input, c.delay as f64],
+        )?;
+    }
+    Ok(())
+}
+
+fn save_snapshot_connections(tx: &Transaction, snapshot_id: i64, connections: &[LogicConnection]) -> rusqlite::Result<()> {
+    for (position, c) in connections.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO snapshot_logic_connections (snapshot_id, position, output, target_feature_id, input, delay)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![snapshot_id, position as i64, c.output, c.target._id() as i64, c.input, c.delay as f64],
+        )?;
+    }
+    Ok(())
+}
+
+fn connection_from_row(row: &rusqlite::Row) -> rusqlite::Result<LogicConnection> {
+    Ok(LogicConnection {
+        output: row.get(0)?,
+        target: FeatureId::from_raw(row.get::<_, i64>(1)? as u64),
+        input: row.get(2)?,
+        delay: row.get::<_, f64>(3)? as f32,
+    })
+}
+
+fn load_snapshot_connections(conn: &Connection, snapshot_id: i64) -> rusqlite::Result<Vec<LogicConnection>> {
+    let mut stmt = conn.prepare(
+        "SELECT output, target_feature_id, input, delay FROM snapshot_logic_connections
+         WHERE snapshot_id = ?1 ORDER BY position",
+    )?;
+    let rows = stmt.query_map(params![snapshot_id], connection_from_row)?;
+    rows.collect()
+}
+
+fn save_snapshot_scalars(tx: &Transaction, snapshot_id: i64, fields: &[(&str, f32)]) -> rusqlite::Result<()> {
+    for (k, v) in fields {
+        tx.execute(
+            "INSERT INTO snapshot_scalar_fields (snapshot_id, field_key, field_value) VALUES (?1, ?2, ?3)",
+            params![snapshot_id, k, *v as f64],
+        )?;
+    }
+    Ok(())
+}
+
+fn save_snapshot_color(tx: &Transaction, snapshot_id: i64, color: Vec3) -> rusqlite::Result<()> {
+    save_snapshot_scalars(tx, snapshot_id, &[("color_r", color.x), ("color_g", color.y), ("color_b", color.z)])
+}
+
+/// Snapshots saved before lights had a color fall back to white.
+fn load_snapshot_color(conn: &Connection, snapshot_id: i64) -> rusqlite::Result<Vec3> {
+    let channel = |key: &str| -> rusqlite::Result<f32> {
+        Ok(conn.query_row(
+            "SELECT field_value FROM snapshot_scalar_fields WHERE snapshot_id = ?1 AND field_key = ?2",
+            params![snapshot_id, key],
+            |row| row.get::<_, f64>(0),
+        ).optional()?.map_or(1.0, |v| v as f32))
+    };
+    Ok(Vec3::new(channel("color_r")?, channel("color_g")?, channel("color_b")?))
+}
+
 fn load_snapshot_scalar(conn: &Connection, snapshot_id: i64, key: &str) -> rusqlite::Result<f32> {
     let v: f64 = conn.query_row(
         "SELECT field_value FROM snapshot_scalar_fields WHERE snapshot_id = ?1 AND field_key = ?2",
@@ -339,6 +721,14 @@ fn load_snapshot_scalar(conn: &Connection, snapshot_id: i64, key: &str) -> rusql
     Ok(v as f32)
 }
 
+fn load_snapshot_text(conn: &Connection, snapshot_id: i64, key: &str) -> rusqlite::Result<String> {
+    conn.query_row(
+        "SELECT field_value FROM snapshot_text_fields WHERE snapshot_id = ?1 AND field_key = ?2",
+        params![snapshot_id, key],
+        |row| row.get(0),
+    )
+}
+
 fn load_feature_snapshot(conn: &Connection, snapshot_id: i64) -> rusqlite::Result<FeatureSnapshot> {
     let (order_index, data_kind): (i64, String) = conn.query_row(
         "SELECT order_index, data_kind FROM feature_snapshots WHERE id = ?1",
@@ -358,11 +748,38 @@ fn load_feature_snapshot(conn: &Connection, snapshot_id: i64) -> rusqlite::Resul
             let range = load_snapshot_scalar(conn, snapshot_id, "range_val")?;
             FeatureData::PointLight {
                 location,
+                color: load_snapshot_color(conn, snapshot_id)?,
                 intensity,
                 radius,
                 range,
             }
         }
+        "spot_light" => FeatureData::SpotLight {
+            location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+            target: load_snapshot_point_ref(conn, snapshot_id, "target")?,
+            color: load_snapshot_color(conn, snapshot_id)?,
+            intensity: load_snapshot_scalar(conn, snapshot_id, "intensity")?,
+            radius: load_snapshot_scalar(conn, snapshot_id, "radius")?,
+            range: load_snapshot_scalar(conn, snapshot_id, "range_val")?,
+            inner_angle: load_snapshot_scalar(conn, snapshot_id, "inner_angle")?,
+            outer_angle: load_snapshot_scalar(conn, snapshot_id, "outer_angle")?,
+        },
+        "sun" => FeatureData::Sun {
+            location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+            color: load_snapshot_color(conn, snapshot_id)?,
+            illuminance: load_snapshot_scalar(conn, snapshot_id, "illuminance")?,
+            azimuth: load_snapshot_scalar(conn, snapshot_id, "azimuth")?,
+            elevation: load_snapshot_scalar(conn, snapshot_id, "elevation")?,
+        },
+        "area_light" => FeatureData::AreaLight {
+            location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+            target: load_snapshot_point_ref(conn, snapshot_id, "target")?,
+            color: load_snapshot_color(conn, snapshot_id)?,
+            intensity: load_snapshot_scalar(conn, snapshot_id, "intensity")?,
+            range: load_snapshot_scalar(conn, snapshot_id, "range_val")?,
+            width: load_snapshot_scalar(conn, snapshot_id, "width")?,
+            height: load_snapshot_scalar(conn, snapshot_id, "height")?,
+        },
         "room" => {
             let min = load_snapshot_point_ref(conn, snapshot_id, "min")?;
             let max = load_snapshot_point_ref(conn, snapshot_id, "max")?;
@@ -381,6 +798,103 @@ fn load_feature_snapshot(conn: &Connection, snapshot_id: i64) -> rusqlite::Resul
             );
             FeatureData::Cuboid { min, max }
         }
+        "marker" => {
+            let location = load_snapshot_point_ref(conn, snapshot_id, "location")?;
+            let kind = load_snapshot_text(conn, snapshot_id, "kind")?;
+            let team = load_snapshot_text(conn, snapshot_id, "team")?;
+            FeatureData::Marker {
+                location,
+                kind: MarkerKind::from_key(&kind).unwrap_or(MarkerKind::Spawn),
+                team: Team::from_key(&team).unwrap_or(Team::Neutral),
+                connections: load_snapshot_connections(conn, snapshot_id)?,
+            }
+        }
+        "trigger" => FeatureData::Trigger {
+            min: load_snapshot_point_ref(conn, snapshot_id, "min")?,
+            max: load_snapshot_point_ref(conn, snapshot_id, "max")?,
+            start_enabled: load_snapshot_scalar(conn, snapshot_id, "start_enabled")? != 0.0,
+            once: load_snapshot_scalar(conn, snapshot_id, "once")? != 0.0,
+            connections: load_snapshot_connections(conn, snapshot_id)?,
+        },
+        "logic" => {
+            let kind = load_snapshot_text(conn, snapshot_id, "kind")?;
+            FeatureData::Logic {
+                location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+                kind: LogicKind::from_key(&kind).unwrap_or(LogicKind::Relay),
+                value: load_snapshot_scalar(conn, snapshot_id, "value")?,
+                start_enabled: load_snapshot_scalar(conn, snapshot_id, "start_enabled")? != 0.0,
+                connections: load_snapshot_connections(conn, snapshot_id)?,
+            }
+        }
+        "teleporter" => FeatureData::Teleporter {
+            min: load_snapshot_point_ref(conn, snapshot_id, "min")?,
+            max: load_snapshot_point_ref(conn, snapshot_id, "max")?,
+            destination: load_snapshot_point_ref(conn, snapshot_id, "destination")?,
+            yaw: load_snapshot_scalar(conn, snapshot_id, "yaw")?,
+        },
+        "jump_pad" => FeatureData::JumpPad {
+            min: load_snapshot_point_ref(conn, snapshot_id, "min")?,
+            max: load_snapshot_point_ref(conn, snapshot_id, "max")?,
+            apex: load_snapshot_point_ref(conn, snapshot_id, "apex")?,
+        },
+        "pickup" => {
+            let kind = load_snapshot_text(conn, snapshot_id, "kind")?;
+            let team = load_snapshot_text(conn, snapshot_id, "team")?;
+            FeatureData::Pickup {
+                location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+                kind: PickupKind::from_key(&kind).unwrap_or(PickupKind::HealthSmall),
+                item: load_snapshot_text(conn, snapshot_id, "item")?,
+                respawn: load_snapshot_scalar(conn, snapshot_id, "respawn")?,
+                team: Team::from_key(&team).unwrap_or(Team::Neutral),
+            }
+        }
+        "prop" => FeatureData::Prop {
+            location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+            model: load_snapshot_text(conn, snapshot_id, "model")?,
+            rotation: Vec3::new(
+                load_snapshot_scalar(conn, snapshot_id, "rotation_x")?,
+                load_snapshot_scalar(conn, snapshot_id, "rotation_y")?,
+                load_snapshot_scalar(conn, snapshot_id, "rotation_z")?,
+            ),
+            scale: Vec3::new(
+                load_snapshot_scalar(conn, snapshot_id, "scale_x")?,
+                load_snapshot_scalar(conn, snapshot_id, "scale_y")?,
+                load_snapshot_scalar(conn, snapshot_id, "scale_z")?,
+            ),
+            collision: load_snapshot_scalar(conn, snapshot_id, "collision")? != 0.0,
+        },
+        "liquid" => {
+            let kind = load_snapshot_text(conn, snapshot_id, "kind")?;
+            FeatureData::Liquid {
+                min: load_snapshot_point_ref(conn, snapshot_id, "min")?,
+                max: load_snapshot_point_ref(conn, snapshot_id, "max")?,
+                kind: VolumeKind::from_key(&kind).unwrap_or(VolumeKind::Water),
+                damage: load_snapshot_scalar(conn, snapshot_id, "damage")?,
+                speed_scale: load_snapshot_scalar(conn, snapshot_id, "speed_scale")?,
+                buoyancy: load_snapshot_scalar(conn, snapshot_id, "buoyancy")?,
+                tint: load_snapshot_color(conn, snapshot_id)?,
+                opacity: load_snapshot_scalar(conn, snapshot_id, "opacity")?,
+            }
+        }
+        "stairs" => FeatureData::Stairs {
+            start: load_snapshot_point_ref(conn, snapshot_id, "start")?,
+            end: load_snapshot_point_ref(conn, snapshot_id, "end")?,
+            width: load_snapshot_scalar(conn, snapshot_id, "width")?,
+            step_height: load_snapshot_scalar(conn, snapshot_id, "step_height")?,
+            ramp: load_snapshot_scalar(conn, snapshot_id, "ramp")? != 0.0,
+        },
+        "dimension" => FeatureData::Dimension {
+            start: load_snapshot_point_ref(conn, snapshot_id, "start")?,
+            end: load_snapshot_point_ref(conn, snapshot_id, "end")?,
+            note: load_snapshot_text(conn, snapshot_id, "note")?,
+        },
+        "note" => FeatureData::Note {
+            location: load_snapshot_point_ref(conn, snapshot_id, "location")?,
+            text: load_snapshot_text(conn, snapshot_id, "text")?,
+            author: load_snapshot_text(conn, snapshot_id, "author")?,
+            status: NoteStatus::from_key(&load_snapshot_text(conn, snapshot_id, "status")?).unwrap_or(NoteStatus::Open),
+            color: load_snapshot_color(conn, snapshot_id)?,
+        },
         other => {
             return Err(rusqlite::Error::InvalidParameterName(format!(
                 "Unknown snapshot data_kind: {}",
@@ -435,12 +949,214 @@ fn load_applied_actions(conn: &Connection) -> rusqlite::Result<Vec<Action>> {
                 after,
             });
         }
-        actions.push(Action { deltas });
+        let symmetry = match (load_symmetry_settings(conn, aid, "before")?, load_symmetry_settings(conn, aid, "after")?) {
+            (Some(before), Some(after)) => Some(SymmetryChange { before, after }),
+            _ => None,
+        };
+        actions.push(Action { deltas, symmetry });
     }
     Ok(actions)
 }
 
-pub fn save(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) -> rusqlite::Result<()> {
+fn save_symmetry_settings(tx: &Transaction, action_id: i64, side: &str, settings: &SymmetrySettings) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO history_action_symmetry (action_id, side, enabled, mode, origin_feature_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![action_id, side, settings.enabled, settings.mode.key(), settings.origin.map(|id| id._id() as i64)],
+    )?;
+    for (a, b) in &settings.links {
+        tx.execute(
+            "INSERT INTO history_action_counterparts (action_id, side, feature_id, counterpart_id) VALUES (?1, ?2, ?3, ?4)",
+            params![action_id, side, a._id() as i64, b._id() as i64],
+        )?;
+    }
+    Ok(())
+}
+
+fn load_symmetry_settings(conn: &Connection, action_id: i64, side: &str) -> rusqlite::Result<Option<SymmetrySettings>> {
+    let row: Option<(bool, String, Option<i64>)> = conn
+        .query_row(
+            "SELECT enabled, mode, origin_feature_id FROM history_action_symmetry WHERE action_id = ?1 AND side = ?2",
+            params![action_id, side],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let Some((enabled, mode, origin)) = row else { return Ok(None); };
+
+    let mut stmt = conn.prepare(
+        "SELECT feature_id, counterpart_id FROM history_action_counterparts WHERE action_id = ?1 AND side = ?2",
+    )?;
+    let links = stmt.query_map(params![action_id, side], |row| {
+        Ok((FeatureId::from_raw(row.get::<_, i64>(0)? as u64), FeatureId::from_raw(row.get::<_, i64>(1)? as u64)))
+    })?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(Some(SymmetrySettings {
+        enabled,
+        mode: SymmetryMode::from_key(&mode).unwrap_or(SymmetryMode::Rotate180),
+        origin: origin.map(|id| FeatureId::from_raw(id as u64)),
+        links,
+    }))
+}
+
+fn save_symmetry(tx: &Transaction, symmetry: &Symmetry) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO symmetry (enabled, mode, origin_feature_id) VALUES (?1, ?2, ?3)",
+        params![symmetry.enabled, symmetry.mode.key(), symmetry.origin.map(|id| id._id() as i64)],
+    )?;
+    for (a, b) in symmetry.links() {
+        for (feature, counterpart) in [(a, b), (b, a)] {
+            tx.execute(
+                "INSERT INTO feature_counterparts (feature_id, counterpart_id) VALUES (?1, ?2)",
+                params![feature._id() as i64, counterpart._id() as i64],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn load_symmetry(conn: &Connection) -> rusqlite::Result<Symmetry> {
+    let mut symmetry = Symmetry::default();
+    let settings: Option<(bool, String, Option<i64>)> = conn
+        .query_row(
+            "SELECT enabled, mode, origin_feature_id FROM symmetry LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    if let Some((enabled, mode, origin)) = settings {
+        symmetry.enabled = enabled;
+        symmetry.mode = SymmetryMode::from_key(&mode).unwrap_or(symmetry.mode);
+        symmetry.origin = origin.map(|id| FeatureId::from_raw(id as u64));
+    }
+
+    let mut stmt = conn.prepare("SELECT feature_id, counterpart_id FROM feature_counterparts")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64))
+    })?;
+    for row in rows {
+        let (a, b) = row?;
+        symmetry.link(FeatureId::from_raw(a), FeatureId::from_raw(b));
+    }
+    Ok(symmetry)
+}
+
+fn save_navmesh(tx: &Transaction, navmesh: &Navmesh) -> rusqlite::Result<()> {
+    for (id, polygon) in navmesh.polygons.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO navmesh_polygons (id, height, min_x, min_z, max_x, max_z) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id as i64, polygon.height, polygon.min.x, polygon.min.y, polygon.max.x, polygon.max.y],
+        )?;
+    }
+    for link in &navmesh.links {
+        let [a, b] = link.portal;
+        tx.execute(
+            "INSERT INTO navmesh_links (from_polygon, to_polygon, portal_ax, portal_ay, portal_az, portal_bx, portal_by, portal_bz)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![link.from as i64, link.to as i64, a.x, a.y, a.z, b.x, b.y, b.z],
+        )?;
+    }
+    Ok(())
+}
+
+/// Bake a navmesh for the timeline's rooms and stairs as they resolve now.
+pub fn bake_navmesh(features: &FeatureTimeline) -> Navmesh {
+    build_navmesh(&rooms_from_timeline(features), &stairs_from_timeline(features), &NavHull::default())
+}
+
+/// Reads the navmesh last baked before a blueprint was saved. Files saved before navmeshes existed,
+/// or without baking one, have none.
+pub fn load_navmesh(path: &Path) -> rusqlite::Result<Navmesh> {
+    let conn = Connection::open(path)?;
+    let has_navmesh: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'navmesh_polygons')",
+        [],
+        |row| row.get(0),
+    )?;
+    let mut navmesh = Navmesh::default();
+    if !has_navmesh {
+        return Ok(navmesh);
+    }
+
+    let mut stmt = conn.prepare("SELECT height, min_x, min_z, max_x, max_z FROM navmesh_polygons ORDER BY id")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(NavPolygon {
+            height: row.get(0)?,
+            min: Vec2::new(row.get(1)?, row.get(2)?),
+            max: Vec2::new(row.get(3)?, row.get(4)?),
+        })
+    })?;
+    for row in rows {
+        navmesh.polygons.push(row?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT from_polygon, to_polygon, portal_ax, portal_ay, portal_az, portal_bx, portal_by, portal_bz FROM navmesh_links",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(NavLink {
+            from: row.get::<_, i64>(0)? as usize,
+            to: row.get::<_, i64>(1)? as usize,
+            portal: [
+                Vec3::new(row.get(2)?, row.get(3)?, row.get(4)?),
+                Vec3::new(row.get(5)?, row.get(6)?, row.get(7)?),
+            ],
+        })
+    })?;
+    for row in rows {
+        navmesh.links.push(row?);
+    }
+    Ok(navmesh)
+}
+
+fn save_baked_volumes(tx: &Transaction, volumes: &[BakedVolume]) -> rusqlite::Result<()> {
+    for (id, v) in volumes.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO baked_volumes (id, kind, min_x, min_y, min_z, max_x, max_y, max_z, damage, speed_scale, buoyancy, tint_r, tint_g, tint_b, opacity)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                id as i64, v.kind.key(),
+                v.min.x, v.min.y, v.min.z, v.max.x, v.max.y, v.max.z,
+                v.damage, v.speed_scale, v.buoyancy,
+                v.tint.x, v.tint.y, v.tint.z, v.opacity,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Reads the liquid and hazard volumes baked into a blueprint at save time. Older files have none.
+pub fn load_baked_volumes(path: &Path) -> rusqlite::Result<Vec<BakedVolume>> {
+    let conn = Connection::open(path)?;
+    let has_volumes: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'baked_volumes')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_volumes {
+        return Ok(vec![]);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT kind, min_x, min_y, min_z, max_x, max_y, max_z, damage, speed_scale, buoyancy, tint_r, tint_g, tint_b, opacity
+         FROM baked_volumes ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let kind: String = row.get(0)?;
+        Ok(BakedVolume {
+            kind: VolumeKind::from_key(&kind).unwrap_or(VolumeKind::Water),
+            min: Vec3::new(row.get(1)?, row.get(2)?, row.get(3)?),
+            max: Vec3::new(row.get(4)?, row.get(5)?, row.get(6)?),
+            damage: row.get(7)?,
+            speed_scale: row.get(8)?,
+            buoyancy: row.get(9)?,
+            tint: Vec3::new(row.get(10)?, row.get(11)?, row.get(12)?),
+            opacity: row.get(13)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Write the blueprint to `path`, along with `navmesh` as last baked. The navmesh is not rebuilt
+/// here: baking is slow on large maps, so it only happens when asked for.
+pub fn save(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata, navmesh: &Navmesh) -> rusqlite::Result<()> {
     let backup_path = path.with_extension(format!("{}.{}", MAP_BLUEPRINT_EXTENSION, MAP_BACKUP_EXTENSION));
     let had_existing = path.exists();
 
@@ -450,7 +1166,7 @@ pub fn save(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) ->
         })?;
     }
 
-    let result = save_inner(path, features, metadata);
+    let result = save_inner(path, features, metadata, navmesh);
 
     if result.is_err() && had_existing {
         info!("Save failed, restoring from backup");
@@ -464,14 +1180,28 @@ pub fn save(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) ->
     result
 }
 
-fn save_inner(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) -> rusqlite::Result<()> {
+fn save_inner(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata, navmesh: &Navmesh) -> rusqlite::Result<()> {
     let conn = Connection::open(path)?;
-    conn.execute_batch("DROP TABLE IF EXISTS history_action_deltas;
+    conn.execute_batch("DROP TABLE IF EXISTS camera_bookmarks;
+                        DROP TABLE IF EXISTS baked_volumes;
+                        DROP TABLE IF EXISTS snapshot_logic_connections;
+                        DROP TABLE IF EXISTS logic_connections;
+                        DROP TABLE IF EXISTS metadata_custom;
+                        DROP TABLE IF EXISTS metadata_changelog;
+                        DROP TABLE IF EXISTS navmesh_links;
+                        DROP TABLE IF EXISTS navmesh_polygons;
+                        DROP TABLE IF EXISTS feature_counterparts;
+                        DROP TABLE IF EXISTS symmetry;
+                        DROP TABLE IF EXISTS history_action_counterparts;
+                        DROP TABLE IF EXISTS history_action_symmetry;
+                        DROP TABLE IF EXISTS history_action_deltas;
                         DROP TABLE IF EXISTS history_actions;
+                        DROP TABLE IF EXISTS snapshot_text_fields;
                         DROP TABLE IF EXISTS snapshot_scalar_fields;
                         DROP TABLE IF EXISTS snapshot_point_refs;
                         DROP TABLE IF EXISTS snapshot_parents;
                         DROP TABLE IF EXISTS feature_snapshots;
+                        DROP TABLE IF EXISTS text_fields;
                         DROP TABLE IF EXISTS scalar_fields;
                         DROP TABLE IF EXISTS point_refs;
                         DROP TABLE IF EXISTS feature_parents;
@@ -523,8 +1253,21 @@ fn save_inner(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) -
                 params![raw_id, key, value as f64],
             )?;
         }
+
+        for (key, value) in obj.text_fields() {
+            tx.execute(
+                "INSERT INTO text_fields (owner_feature_id, field_key, field_value) VALUES (?1, ?2, ?3)",
+                params![raw_id, key, value],
+            )?;
+        }
+
+        save_connections(&tx, id._id(), obj.connections())?;
     }
 
+    save_symmetry(&tx, features.symmetry())?;
+    save_navmesh(&tx, navmesh)?;
+    save_baked_volumes(&tx, &volumes_from_timeline(features))?;
+
     for (seq, action) in features.applied_actions().iter().enumerate() {
         tx.execute(
             "INSERT INTO history_actions (seq) VALUES (?1)",
@@ -552,6 +1295,10 @@ fn save_inner(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) -
                 ],
             )?;
         }
+        if let Some(change) = &action.symmetry {
+            save_symmetry_settings(&tx, action_id, "before", &change.before)?;
+            save_symmetry_settings(&tx, action_id, "after", &change.after)?;
+        }
     }
 
     tx.commit()?;
@@ -560,6 +1307,21 @@ fn save_inner(path: &Path, features: &FeatureTimeline, metadata: &MapMetadata) -
 
 pub fn load(path: &Path) -> rusqlite::Result<LoadedBlueprint> {
     let conn = Connection::open(path)?;
+    load_from_connection(&conn, path)
+}
+
+/// Like [`load`], but never writes to `path`. Older files are migrated in an in-memory copy, so
+/// tools that only inspect blueprints leave them exactly as they found them.
+pub fn load_read_only(path: &Path) -> rusqlite::Result<LoadedBlueprint> {
+    let file = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut conn = Connection::open_in_memory()?;
+    Backup::new(&file, &mut conn)?.run_to_completion(256, Duration::ZERO, None)?;
+    load_from_connection(&conn, path)
+}
+
+/// Read a blueprint from `conn`, migrating it in place first when its schema is older. `path` is
+/// only used to guess the game mode of files saved before it was stored.
+fn load_from_connection(conn: &Connection, path: &Path) -> rusqlite::Result<LoadedBlueprint> {
 
     let file_version: u64 = conn.query_row(
         "SELECT value FROM metadata WHERE key = 'schema_version'",
@@ -581,7 +1343,7 @@ pub fn load(path: &Path) -> rusqlite::Result<LoadedBlueprint> {
     }
 
     if file_version < SCHEMA_VERSION {
-        run_migrations(&conn, file_version, true)?;
+        run_migrations(conn, file_version, true)?;
     }
 
     let (id_counter, rollback_bar) = conn.query_row(
@@ -637,6 +1399,37 @@ pub fn load(path: &Path) -> rusqlite::Result<LoadedBlueprint> {
         }
     }
 
+    let mut text_map: HashMap<u64, Vec<(String, String)>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare("SELECT owner_feature_id, field_key, field_value FROM text_fields")?;
+        let rows = stmt.query_map([], |row| {
+            let owner: i64 = row.get(0)?;
+            let key: String = row.get(1)?;
+            let val: String = row.get(2)?;
+            Ok((owner as u64, key, val))
+        })?;
+        for row in rows {
+            let (owner, key, val) = row?;
+            text_map.entry(owner).or_default().push((key, val));
+        }
+    }
+
+    let mut connection_map: HashMap<u64, Vec<LogicConnection>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT output, target_feature_id, input, delay, owner_feature_id FROM logic_connections
+             ORDER BY owner_feature_id, position",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let owner: i64 = row.get(4)?;
+            Ok((owner as u64, connection_from_row(row)?))
+        })?;
+        for row in rows {
+            let (owner, connection) = row?;
+            connection_map.entry(owner).or_default().push(connection);
+        }
+    }
+
     let mut features_map: HashMap<FeatureId, Feature> = HashMap::new();
     let mut feature_order: Vec<FeatureId> = Vec::new();
 
@@ -650,7 +1443,7 @@ pub fn load(path: &Path) -> rusqlite::Result<LoadedBlueprint> {
 
         let slots: Vec<String> = obj.point_ref_slots().iter().map(|s| s.to_string()).collect();
         for slot in &slots {
-            let pr = load_point_ref(&conn, *raw_id, slot)?;
+            let pr = load_point_ref(conn, *raw_id, slot)?;
             if let Some(target) = obj.get_point_ref_mut(slot) {
                 *target = pr;
             }
@@ -662,26 +1455,84 @@ pub fn load(path: &Path) -> rusqlite::Result<LoadedBlueprint> {
             }
         }
 
+        if let Some(texts) = text_map.get(raw_id) {
+            for (key, val) in texts {
+                obj.set_text_field(key, val);
+            }
+        }
+
+        if let Some(connections) = connection_map.remove(raw_id) {
+            obj.set_connections(connections);
+        }
+
         let parents = parent_map.remove(raw_id).unwrap_or_default();
         let feature = Feature::new(id, obj, parents);
         features_map.insert(id, feature);
     }
 
-    let actions = load_applied_actions(&conn)?;
+    let actions = load_applied_actions(conn)?;
     let mut editor_features =
         FeatureTimeline::from_parts(features_map, feature_order, id_counter, rollback_bar, actions);
 
-    for id in editor_features.feature_order().to_vec() {
-        if let Some(mut feature) = editor_features.features_mut().remove(&id) {
-            feature.object_mut().resolve_references(editor_features.features_map());
-            editor_features.features_mut().insert(id, feature);
-        }
-    }
+    *editor_features.symmetry_mut() = load_symmetry(conn)?;
+    editor_features.resolve_all_references();
 
-    let map_metadata = MapMetadata::load_from_connection(&conn)?;
+    let fallback_game_mode = game_mode_from_file_name(path).unwrap_or(GameMode::Arena);
+    let map_metadata = MapMetadata::load_from_connection(conn, fallback_game_mode)?;
 
     Ok(LoadedBlueprint {
         timeline: editor_features,
         metadata: map_metadata,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::global_point::GlobalPoint;
+
+    #[test]
+    fn test_read_only_load_migrates_without_touching_the_file() {
+        let path = std::env::temp_dir().join(format!("grackle_read_only_test_{}.{}", std::process::id(), MAP_BLUEPRINT_EXTENSION));
+        let mut timeline = FeatureTimeline::default();
+        let id = timeline.apply_feature(Box::new(GlobalPoint::new(1.0, 2.0, 3.0)));
+        save(&path, &timeline, &MapMetadata::default(), &Navmesh::default()).unwrap();
+        {
+            // Roll the file back to before camera bookmarks existed.
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch("DROP TABLE camera_bookmarks;
+                                UPDATE metadata SET value = '9' WHERE key = 'schema_version';").unwrap();
+        }
+        let before = std::fs::read(&path).unwrap();
+
+        let loaded = load_read_only(&path);
+        let after = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(loaded.unwrap().timeline.get_feature(&id).is_some());
+        assert!(before == after);
+    }
+
+    #[test]
+    fn test_symmetry_changes_stay_undoable_after_a_reload() {
+        let path = std::env::temp_dir().join(format!("grackle_symmetry_history_test_{}.{}", std::process::id(), MAP_BLUEPRINT_EXTENSION));
+        let mut timeline = FeatureTimeline::default();
+        timeline.symmetry_mut().enabled = true;
+        timeline.symmetry_mut().mode = SymmetryMode::MirrorX;
+        let id = timeline.apply_feature(Box::new(GlobalPoint::new(3.0, 0.0, 2.0)));
+        let counterpart = timeline.symmetry().counterpart(id).unwrap();
+        let mut settings = timeline.symmetry().settings();
+        settings.mode = SymmetryMode::MirrorZ;
+        timeline.set_symmetry(settings);
+        save(&path, &timeline, &MapMetadata::default(), &Navmesh::default()).unwrap();
+
+        let loaded = load(&path);
+        let _ = std::fs::remove_file(&path);
+        let mut timeline = loaded.unwrap().timeline;
+        assert_eq!(timeline.symmetry().mode, SymmetryMode::MirrorZ);
+        timeline.undo_action();
+        assert_eq!(timeline.symmetry().mode, SymmetryMode::MirrorX);
+        assert_eq!(timeline.symmetry().counterpart(id), Some(counterpart));
+        timeline.resolve_all_references();
+        assert_eq!(timeline.get_feature(&counterpart).unwrap().get_point("").ok(), Some(Vec3::new(-3.0, 0.0, 2.0)));
+    }
+}
@@ -0,0 +1,422 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::common::ray::ray_intersects_aabb;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{Feature, FeatureId, FeatureTrait, PointRef};
+use crate::get;
+
+pub const DEFAULT_WIDTH: f32 = 2.0;
+pub const DEFAULT_STEP_HEIGHT: f32 = 0.25;
+
+/// Rises smaller than this make no steps at all.
+const MIN_RISE: f32 = 0.01;
+
+/// Resolved stairs, enough to lay out and bake the steps.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct StairShape {
+    /// Middle of the bottom edge of the first step, on the lower floor.
+    pub start: Vec3,
+    /// Middle of the top edge of the last step, on the upper floor.
+    pub end: Vec3,
+    pub width: f32,
+    /// Largest allowed step height. Steps are evened out so the last one lands exactly on `end`.
+    pub step_height: f32,
+    /// A single slope instead of steps.
+    pub ramp: bool,
+}
+
+impl StairShape {
+    /// The shape of `feature` as it currently resolves, if it is stairs.
+    pub fn of(feature: &Feature) -> Option<Self> {
+        let FeatureData::Stairs { width, step_height, ramp, .. } = feature.object().snapshot() else { return None; };
+        Some(Self {
+            start: feature.get_point("start").ok()?,
+            end: feature.get_point("end").ok()?,
+            width,
+            step_height,
+            ramp,
+        })
+    }
+
+    /// Start and end ordered bottom to top.
+    fn ends(&self) -> (Vec3, Vec3) {
+        if self.end.y >= self.start.y { (self.start, self.end) } else { (self.end, self.start) }
+    }
+
+    /// Horizontal direction up the stairs and the direction to its right.
+    fn axes(&self) -> (Vec3, Vec3) {
+        let (bottom, top) = self.ends();
+        let forward = Vec3::new(top.x - bottom.x, 0.0, top.z - bottom.z).try_normalize().unwrap_or(Vec3::NEG_Z);
+        (forward, forward.cross(Vec3::Y))
+    }
+
+    fn rise(&self) -> f32 {
+        (self.end.y - self.start.y).abs()
+    }
+
+    fn run(&self) -> f32 {
+        let (bottom, top) = self.ends();
+        Vec2::new(top.x - bottom.x, top.z - bottom.z).length()
+    }
+
+    pub fn step_count(&self) -> usize {
+        if self.ramp || self.rise() < MIN_RISE {
+            return 0;
+        }
+        (self.rise() / self.step_height.max(MIN_RISE)).ceil() as usize
+    }
+
+    /// World position of a point given as distance along the run, height and offset to the right.
+    fn local(&self, along: f32, up: f32, side: f32) -> Vec3 {
+        let (bottom, _) = self.ends();
+        let (forward, right) = self.axes();
+        bottom + forward * along + Vec3::Y * up + right * side
+    }
+
+    /// Outward-facing polygons, counter-clockwise seen from outside, of each step or of the ramp.
+    pub fn faces(&self) -> Vec<Vec<Vec3>> {
+        let half = self.width / 2.0;
+        let mut faces = vec![];
+        if self.ramp {
+            if self.rise() < MIN_RISE { return faces; }
+            let (run, rise) = (self.run(), self.rise());
+            let p = |a: f32, u: f32, s: f32| self.local(a * run, u * rise, (s * 2.0 - 1.0) * half);
+            faces.push(vec![p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0), p(1.0, 0.0, 1.0), p(0.0, 0.0, 1.0)]);
+            faces.push(vec![p(1.0, 0.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 1.0, 1.0), p(1.0, 0.0, 1.0)]);
+            faces.push(vec![p(0.0, 0.0, 0.0), p(0.0, 0.0, 1.0), p(1.0, 1.0, 1.0), p(1.0, 1.0, 0.0)]);
+            faces.push(vec![p(0.0, 0.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 0.0, 0.0)]);
+            faces.push(vec![p(0.0, 0.0, 1.0), p(1.0, 0.0, 1.0), p(1.0, 1.0, 1.0)]);
+            return faces;
+        }
+
+        let steps = self.step_count();
+        if steps == 0 { return faces; }
+        let (depth, height) = (self.run() / steps as f32, self.rise() / steps as f32);
+        for i in 0..steps {
+            let (a0, a1) = (i as f32 * depth, (i + 1) as f32 * depth);
+            let top = (i + 1) as f32 * height;
+            let p = |a: f32, u: f32, s: f32| self.local(a0 + a * (a1 - a0), u * top, (s * 2.0 - 1.0) * half);
+            faces.push(vec![p(0.0, 0.0, 0.0), p(0.0, 0.0, 1.0), p(0.0, 1.0, 1.0), p(0.0, 1.0, 0.0)]);
+            faces.push(vec![p(1.0, 0.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 1.0, 1.0), p(1.0, 0.0, 1.0)]);
+            faces.push(vec![p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0), p(1.0, 0.0, 1.0), p(0.0, 0.0, 1.0)]);
+            faces.push(vec![p(0.0, 1.0, 0.0), p(0.0, 1.0, 1.0), p(1.0, 1.0, 1.0), p(1.0, 1.0, 0.0)]);
+            faces.push(vec![p(0.0, 0.0, 0.0), p(0.0, 1.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 0.0, 0.0)]);
+            faces.push(vec![p(0.0, 0.0, 1.0), p(1.0, 0.0, 1.0), p(1.0, 1.0, 1.0), p(0.0, 1.0, 1.0)]);
+        }
+        faces
+    }
+
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let half = self.width / 2.0;
+        let (run, rise) = (self.run(), self.rise());
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for (a, u, s) in [(0.0, 0.0, -half), (0.0, 0.0, half), (run, rise, -half), (run, rise, half)] {
+            let p = self.local(a, u, s);
+            min = min.min(p);
+            max = max.max(p);
+        }
+        (min, max)
+    }
+
+    /// Axis-aligned boxes standing in for the steps in collision and navigation: one per step, or
+    /// for a ramp, steps no taller than `step_height` under its slope. Stairs that don't run along
+    /// an axis get each step's bounding box.
+    pub fn solid_boxes(&self) -> Vec<(Vec3, Vec3)> {
+        if self.rise() < MIN_RISE {
+            return vec![];
+        }
+        let steps = (self.rise() / self.step_height.max(MIN_RISE)).ceil() as usize;
+        let (depth, height) = (self.run() / steps as f32, self.rise() / steps as f32);
+        let half = self.width / 2.0;
+        (0..steps).map(|i| {
+            let (a0, a1) = (i as f32 * depth, (i + 1) as f32 * depth);
+            let corners = [self.local(a0, 0.0, -half), self.local(a0, 0.0, half), self.local(a1, 0.0, -half), self.local(a1, 0.0, half)];
+            let min = corners.iter().fold(Vec3::MAX, |m, p| m.min(*p));
+            let max = corners.iter().fold(Vec3::MIN, |m, p| m.max(*p));
+            (min, max.with_y(min.y + (i + 1) as f32 * height))
+        }).collect()
+    }
+
+    /// Solid geometry for the steps, with outward normals.
+    pub fn bake_mesh(&self) -> Mesh {
+        let mut vertices: Vec<[f32; 3]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for face in self.faces() {
+            let base = vertices.len() as u32;
+            let normal = (face[1] - face[0]).cross(face[face.len() - 1] - face[0]).normalize_or_zero();
+            for p in &face {
+                vertices.push((*p).into());
+                normals.push(normal.into());
+            }
+            for i in 1..face.len() as u32 - 1 {
+                indices.extend_from_slice(&[base, base + i, base + i + 1]);
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            bevy::render::render_resource::PrimitiveTopology::TriangleList,
+            bevy::asset::RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(bevy::mesh::Indices::U32(indices));
+        mesh
+    }
+}
+
+/// Stairs or a ramp between two points, regenerated whenever the points they hang off move.
+#[derive(Serialize, Deserialize)]
+pub struct Stairs {
+    start: PointRef,
+    end: PointRef,
+    pub width: f32,
+    pub step_height: f32,
+    pub ramp: bool,
+    #[serde(skip)]
+    resolved_start: Vec3,
+    #[serde(skip)]
+    resolved_end: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl Stairs {
+    pub fn from_point_refs(start: PointRef, end: PointRef) -> Self {
+        Self {
+            start,
+            end,
+            width: DEFAULT_WIDTH,
+            step_height: DEFAULT_STEP_HEIGHT,
+            ramp: false,
+            resolved_start: Vec3::ZERO,
+            resolved_end: Vec3::ZERO,
+            entity: None,
+        }
+    }
+
+    pub fn shape(&self) -> StairShape {
+        StairShape {
+            start: self.resolved_start,
+            end: self.resolved_end,
+            width: self.width,
+            step_height: self.step_height,
+            ramp: self.ramp,
+        }
+    }
+}
+
+#[typetag::serde(name = "stairs")]
+impl FeatureTrait for Stairs {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        let (min, max) = self.shape().bounds();
+        match key {
+            "start" => Ok(self.resolved_start),
+            "end" => Ok(self.resolved_end),
+            "min" => Ok(min),
+            "max" => Ok(max),
+            _ => Ok((self.resolved_start + self.resolved_end) / 2.0),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.start.editor_ui(ui, "Start", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.end.editor_ui(ui, "End", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.stairs.width"));
+            changed |= ui.add(egui::DragValue::new(&mut self.width).range(0.1..=50.0).speed(0.05)).changed();
+        });
+        changed |= ui.checkbox(&mut self.ramp, get!("editor.features.stairs.ramp")).changed();
+        if !self.ramp {
+            ui.horizontal(|ui| {
+                ui.label(get!("editor.features.stairs.step_height"));
+                changed |= ui.add(egui::DragValue::new(&mut self.step_height).range(0.05..=2.0).speed(0.01)).changed();
+            });
+            ui.label(get!("editor.features.stairs.steps", "count", self.shape().step_count()));
+        }
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        if self.ramp { get!("editor.features.stairs.ramp_title") } else { get!("editor.features.stairs.title") }
+    }
+
+    fn type_key(&self) -> &'static str { "stairs" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Stairs {
+            start: self.start.clone(),
+            end: self.end.clone(),
+            width: self.width,
+            step_height: self.step_height,
+            ramp: self.ramp,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Stairs { start, end, width, step_height, ramp } = data else { return; };
+        self.start = start.clone();
+        self.end = end.clone();
+        self.width = *width;
+        self.step_height = *step_height;
+        self.ramp = *ramp;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let color = Color::srgb_u8(200, 200, 200);
+        for face in self.shape().faces() {
+            gizmos.linestrip(face.iter().chain(face.first()).copied(), color);
+        }
+        self.start.debug_gizmos(self.resolved_start, gizmos);
+        self.end.debug_gizmos(self.resolved_end, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).insert((
+            Transform::from_translation((self.resolved_start + self.resolved_end) / 2.0),
+            self.shape(),
+        ));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.start.resolve(features) {
+            self.resolved_start = v;
+        }
+        if let Ok(v) = self.end.resolve(features) {
+            self.resolved_end = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.start.referenced_features();
+        for id in self.end.referenced_features() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Center".into()),
+            ("start".into(), "Start".into()),
+            ("end".into(), "End".into()),
+            ("min".into(), "Min".into()),
+            ("max".into(), "Max".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, ray: &Ray3d) -> Vec<(String, Vec3)> {
+        let (min, max) = self.shape().bounds();
+        let padding = Vec3::splat(0.25);
+        if !ray_intersects_aabb(ray, min - padding, max + padding) {
+            return vec![];
+        }
+        self.available_point_keys().into_iter().filter_map(|(key, _)| {
+            self.get_point(&key).ok().map(|v| (key, v))
+        }).collect()
+    }
+
+    fn selection_bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some(self.shape().bounds())
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["start", "end"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("width", self.width),
+            ("step_height", self.step_height),
+            ("ramp", self.ramp as u8 as f32),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "width" => self.width = value,
+            "step_height" => self.step_height = value,
+            "ramp" => self.ramp = value != 0.0,
+            _ => {}
+        }
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Start" | "start" => Some(&self.start),
+            "End" | "end" => Some(&self.end),
+            _ => None,
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Start" | "start" => Some(&mut self.start),
+            "End" | "end" => Some(&mut self.end),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(ramp: bool) -> StairShape {
+        StairShape { start: Vec3::new(0.0, 0.0, 0.0), end: Vec3::new(0.0, 1.0, -4.0), width: 2.0, step_height: 0.3, ramp }
+    }
+
+    #[test]
+    fn test_steps_even_out_to_reach_the_end() {
+        let stairs = shape(false);
+        assert_eq!(stairs.step_count(), 4);
+        let faces = stairs.faces();
+        assert_eq!(faces.len(), 4 * 6);
+        let top = faces.iter().flatten().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!((top - 1.0).abs() < 1e-5);
+        let (min, max) = stairs.bounds();
+        assert!(min.distance(Vec3::new(-1.0, 0.0, -4.0)) < 1e-5);
+        assert!(max.distance(Vec3::new(1.0, 1.0, 0.0)) < 1e-5);
+    }
+
+    #[test]
+    fn test_faces_point_outward() {
+        // The first step and the ramp are both convex, so every face points away from their middle.
+        for (ramp, faces, middle) in [
+            (false, 6, Vec3::new(0.0, 0.125, -0.5)),
+            (true, 5, Vec3::new(0.0, 1.0 / 3.0, -8.0 / 3.0)),
+        ] {
+            for face in shape(ramp).faces().iter().take(faces) {
+                let center = face.iter().sum::<Vec3>() / face.len() as f32;
+                let normal = (face[1] - face[0]).cross(face[face.len() - 1] - face[0]);
+                assert!(normal.dot(center - middle) > 0.0, "{:?} faces inward", face);
+            }
+        }
+    }
+
+    #[test]
+    fn test_downward_stairs_match_upward() {
+        let up = shape(false);
+        let down = StairShape { start: up.end, end: up.start, ..up.clone() };
+        assert_eq!(up.faces(), down.faces());
+    }
+}
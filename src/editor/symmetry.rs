@@ -0,0 +1,635 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTimeline, PointRef};
+use crate::editor::logic::{LogicConnection, remap_connections};
+use crate::editor::prop::prop_rotation;
+use crate::editor::teleporter::facing_direction;
+use crate::get;
+
+/// Positions closer than this to their own mirror image count as lying on the plane / center.
+const SELF_SYMMETRIC_EPSILON: f32 = 1e-3;
+
+/// How one half of a map maps onto the other.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+pub enum SymmetryMode {
+    /// Mirror across the YZ plane through the origin.
+    MirrorX,
+    /// Mirror across the XY plane through the origin.
+    MirrorZ,
+    /// Rotate half a turn around the vertical axis through the origin.
+    Rotate180,
+}
+
+impl SymmetryMode {
+    pub fn key(&self) -> &'static str {
+        match self {
+            SymmetryMode::MirrorX => "mirror_x",
+            SymmetryMode::MirrorZ => "mirror_z",
+            SymmetryMode::Rotate180 => "rotate_180",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::iter().find(|m| m.key() == key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            SymmetryMode::MirrorX => get!("editor.symmetry.mirror_x"),
+            SymmetryMode::MirrorZ => get!("editor.symmetry.mirror_z"),
+            SymmetryMode::Rotate180 => get!("editor.symmetry.rotate_180"),
+        }
+    }
+
+    /// Which world axes change sign (relative to the origin) under this symmetry.
+    fn flips(&self) -> BVec3 {
+        match self {
+            SymmetryMode::MirrorX => BVec3::new(true, false, false),
+            SymmetryMode::MirrorZ => BVec3::new(false, false, true),
+            SymmetryMode::Rotate180 => BVec3::new(true, false, true),
+        }
+    }
+}
+
+/// Everything the symmetry panel edits, as recorded in the undo history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SymmetrySettings {
+    pub enabled: bool,
+    pub mode: SymmetryMode,
+    pub origin: Option<FeatureId>,
+    /// Each linked pair once, lower id first.
+    pub links: Vec<(FeatureId, FeatureId)>,
+}
+
+/// Symmetry settings for a blueprint plus the links between features and their mirrored counterparts.
+#[derive(Clone)]
+pub struct Symmetry {
+    pub enabled: bool,
+    pub mode: SymmetryMode,
+    /// Feature whose point is the mirror plane / rotation center. `None` uses the world origin.
+    pub origin: Option<FeatureId>,
+    counterparts: HashMap<FeatureId, FeatureId>,
+}
+
+impl Default for Symmetry {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: SymmetryMode::Rotate180,
+            origin: None,
+            counterparts: HashMap::new(),
+        }
+    }
+}
+
+impl Symmetry {
+    pub fn settings(&self) -> SymmetrySettings {
+        SymmetrySettings {
+            enabled: self.enabled,
+            mode: self.mode,
+            origin: self.origin,
+            links: self.links(),
+        }
+    }
+
+    pub fn restore(&mut self, settings: &SymmetrySettings) {
+        self.enabled = settings.enabled;
+        self.mode = settings.mode;
+        self.origin = settings.origin;
+        self.counterparts.clear();
+        for (a, b) in &settings.links {
+            self.link(*a, *b);
+        }
+    }
+
+    pub fn counterpart(&self, id: FeatureId) -> Option<FeatureId> {
+        self.counterparts.get(&id).copied()
+    }
+
+    pub fn link(&mut self, a: FeatureId, b: FeatureId) {
+        self.unlink(a);
+        self.unlink(b);
+        self.counterparts.insert(a, b);
+        self.counterparts.insert(b, a);
+    }
+
+    pub fn unlink(&mut self, id: FeatureId) {
+        if let Some(other) = self.counterparts.remove(&id) {
+            self.counterparts.remove(&other);
+        }
+    }
+
+    /// Each linked pair once, lower id first.
+    pub fn links(&self) -> Vec<(FeatureId, FeatureId)> {
+        let mut pairs: Vec<(FeatureId, FeatureId)> = self.counterparts.iter()
+            .filter(|(a, b)| a._id() < b._id())
+            .map(|(a, b)| (*a, *b))
+            .collect();
+        pairs.sort_by_key(|(a, _)| a._id());
+        pairs
+    }
+
+    pub fn origin_position(&self, features: &HashMap<FeatureId, Feature>) -> Vec3 {
+        self.origin
+            .and_then(|id| features.get(&id))
+            .and_then(|f| f.get_point("").ok())
+            .unwrap_or(Vec3::ZERO)
+    }
+
+    pub fn mirror_position(&self, p: Vec3, origin: Vec3) -> Vec3 {
+        let flips = self.mode.flips();
+        Vec3::new(
+            if flips.x { 2.0 * origin.x - p.x } else { p.x },
+            p.y,
+            if flips.z { 2.0 * origin.z - p.z } else { p.z },
+        )
+    }
+
+    fn mirror_axis(axis: &AxisRef, flip: bool, origin: f32) -> AxisRef {
+        match (axis, flip) {
+            (_, false) => axis.clone(),
+            (AxisRef::Absolute(v), true) => AxisRef::Absolute(2.0 * origin - v),
+            (AxisRef::Relative(d), true) => AxisRef::Relative(-d),
+        }
+    }
+
+    /// Mirror a facing in degrees, as used by teleporter arrivals.
+    fn mirror_yaw(&self, yaw: f32) -> f32 {
+        let flips = self.mode.flips();
+        let d = facing_direction(yaw);
+        let d = Vec3::new(if flips.x { -d.x } else { d.x }, 0.0, if flips.z { -d.z } else { d.z });
+        (-d.x).atan2(-d.z).to_degrees()
+    }
+
+    /// Mirror a prop's Euler angles in degrees. Props are turned rather than flipped, so a mirror
+    /// keeps the model's handedness and only reverses its yaw and roll.
+    fn mirror_rotation(&self, rotation: Vec3) -> Vec3 {
+        let flips = self.mode.flips();
+        let roll = if flips.x != flips.z { -rotation.z } else { rotation.z };
+        Vec3::new(rotation.x, self.mirror_yaw(rotation.y), roll)
+    }
+
+    fn is_self_symmetric(&self, p: Vec3, origin: Vec3) -> bool {
+        self.mirror_position(p, origin).distance(p) < SELF_SYMMETRIC_EPSILON
+    }
+
+    /// Mirror a point reference. References to a feature's point follow that feature's counterpart
+    /// (or the feature itself when it sits on the plane), with the key mapped to whichever point lands
+    /// on the mirror image, so a room's `max` becomes the matching corner of the mirrored room.
+    /// Anything else is re-expressed relative to the symmetry origin feature, or absolutely when there
+    /// is none.
+    fn mirror_point_ref(&self, pr: &PointRef, features: &HashMap<FeatureId, Feature>) -> PointRef {
+        let origin = self.origin_position(features);
+        let flips = self.mode.flips();
+        let flipped = |target: Option<FeatureId>, point_key: String| PointRef {
+            reference: target,
+            point_key,
+            x: Self::mirror_axis(&pr.x, flips.x, origin.x),
+            y: pr.y.clone(),
+            z: Self::mirror_axis(&pr.z, flips.z, origin.z),
+            resolved_reference: None,
+        };
+
+        let Some(reference) = pr.reference else {
+            return flipped(None, pr.point_key.clone());
+        };
+        let anchor = features.get(&reference).and_then(|f| f.get_point(&pr.point_key).ok());
+        if let Some(anchor) = anchor {
+            let mirrored_anchor = self.mirror_position(anchor, origin);
+            let targets = [self.counterpart(reference), Some(reference)];
+            for target in targets.into_iter().flatten() {
+                let Some(feature) = features.get(&target) else { continue };
+                if let Some(key) = Self::point_key_at(feature, &pr.point_key, mirrored_anchor) {
+                    return flipped(Some(target), key);
+                }
+            }
+        }
+
+        let world = pr.clone().resolve(features).ok().unwrap_or(Vec3::new(pr.x.value(), pr.y.value(), pr.z.value()));
+        let mirrored = self.mirror_position(world, origin);
+        match self.origin.filter(|id| features.contains_key(id)) {
+            Some(origin_id) => {
+                let d = mirrored - origin;
+                PointRef::reference_with_offset(origin_id, d.x, d.y, d.z)
+            }
+            None => PointRef::absolute(mirrored.x, mirrored.y, mirrored.z),
+        }
+    }
+
+    /// The key of `feature`'s point at `position`, trying `preferred` first.
+    fn point_key_at(feature: &Feature, preferred: &str, position: Vec3) -> Option<String> {
+        std::iter::once(preferred.to_string())
+            .chain(feature.object().available_point_keys().into_iter().map(|(key, _)| key))
+            .find(|key| feature.get_point(key).is_ok_and(|p| p.distance(position) < SELF_SYMMETRIC_EPSILON))
+    }
+
+    /// Connections rewired to the targets' counterparts, so a mirrored trigger drives the mirrored door.
+    fn mirror_connections(&self, connections: &[LogicConnection]) -> Vec<LogicConnection> {
+        let mut mirrored = connections.to_vec();
+        remap_connections(&mut mirrored, |id| self.counterpart(id));
+        mirrored
+    }
+
+    /// Whether features like `data` get a counterpart at all. Review notes are about one spot in
+    /// the level, not part of it, so they are never mirrored.
+    pub fn mirrors(data: &FeatureData) -> bool {
+        !matches!(data, FeatureData::Note { .. })
+    }
+
+    /// The counterpart's data for a feature with `data`.
+    pub fn mirror_data(&self, data: &FeatureData, features: &HashMap<FeatureId, Feature>) -> FeatureData {
+        let origin = self.origin_position(features);
+        match data {
+            FeatureData::GlobalPoint { location } => FeatureData::GlobalPoint {
+                location: self.mirror_point_ref(location, features),
+            },
+            FeatureData::PointLight { location, color, intensity, radius, range } => FeatureData::PointLight {
+                location: self.mirror_point_ref(location, features),
+                color: *color,
+                intensity: *intensity,
+                radius: *radius,
+                range: *range,
+            },
+            FeatureData::SpotLight { location, target, color, intensity, radius, range, inner_angle, outer_angle } => FeatureData::SpotLight {
+                location: self.mirror_point_ref(location, features),
+                target: self.mirror_point_ref(target, features),
+                color: *color,
+                intensity: *intensity,
+                radius: *radius,
+                range: *range,
+                inner_angle: *inner_angle,
+                outer_angle: *outer_angle,
+            },
+            FeatureData::AreaLight { location, target, color, intensity, range, width, height } => FeatureData::AreaLight {
+                location: self.mirror_point_ref(location, features),
+                target: self.mirror_point_ref(target, features),
+                color: *color,
+                intensity: *intensity,
+                range: *range,
+                width: *width,
+                height: *height,
+            },
+            // There is one sun for the whole map; see `is_self_symmetric_data`.
+            FeatureData::Sun { .. } => data.clone(),
+            FeatureData::Room { min, max } => FeatureData::Room {
+                min: self.mirror_point_ref(min, features),
+                max: self.mirror_point_ref(max, features),
+            },
+            FeatureData::Cuboid { min, max } => FeatureData::Cuboid {
+                min: self.mirror_position(*min, origin),
+                max: self.mirror_position(*max, origin),
+            },
+            FeatureData::Marker { location, kind, team, connections } => FeatureData::Marker {
+                location: self.mirror_point_ref(location, features),
+                kind: *kind,
+                team: team.opponent(),
+                connections: self.mirror_connections(connections),
+            },
+            FeatureData::Trigger { min, max, start_enabled, once, connections } => FeatureData::Trigger {
+                min: self.mirror_point_ref(min, features),
+                max: self.mirror_point_ref(max, features),
+                start_enabled: *start_enabled,
+                once: *once,
+                connections: self.mirror_connections(connections),
+            },
+            FeatureData::Logic { location, kind, value, start_enabled, connections } => FeatureData::Logic {
+                location: self.mirror_point_ref(location, features),
+                kind: *kind,
+                value: *value,
+                start_enabled: *start_enabled,
+                connections: self.mirror_connections(connections),
+            },
+            FeatureData::Teleporter { min, max, destination, yaw } => FeatureData::Teleporter {
+                min: self.mirror_point_ref(min, features),
+                max: self.mirror_point_ref(max, features),
+                destination: self.mirror_point_ref(destination, features),
+                yaw: self.mirror_yaw(*yaw),
+            },
+            FeatureData::JumpPad { min, max, apex } => FeatureData::JumpPad {
+                min: self.mirror_point_ref(min, features),
+                max: self.mirror_point_ref(max, features),
+                apex: self.mirror_point_ref(apex, features),
+            },
+            FeatureData::Pickup { location, kind, item, respawn, team } => FeatureData::Pickup {
+                location: self.mirror_point_ref(location, features),
+                kind: *kind,
+                item: item.clone(),
+                respawn: *respawn,
+                team: team.opponent(),
+            },
+            FeatureData::Prop { location, model, rotation, scale, collision } => FeatureData::Prop {
+                location: self.mirror_point_ref(location, features),
+                model: model.clone(),
+                rotation: self.mirror_rotation(*rotation),
+                scale: *scale,
+                collision: *collision,
+            },
+            FeatureData::Liquid { min, max, kind, damage, speed_scale, buoyancy, tint, opacity } => FeatureData::Liquid {
+                min: self.mirror_point_ref(min, features),
+                max: self.mirror_point_ref(max, features),
+                kind: *kind,
+                damage: *damage,
+                speed_scale: *speed_scale,
+                buoyancy: *buoyancy,
+                tint: *tint,
+                opacity: *opacity,
+            },
+            FeatureData::Stairs { start, end, width, step_height, ramp } => FeatureData::Stairs {
+                start: self.mirror_point_ref(start, features),
+                end: self.mirror_point_ref(end, features),
+                width: *width,
+                step_height: *step_height,
+                ramp: *ramp,
+            },
+            FeatureData::Dimension { start, end, note } => FeatureData::Dimension {
+                start: self.mirror_point_ref(start, features),
+                end: self.mirror_point_ref(end, features),
+                note: note.clone(),
+            },
+            FeatureData::Note { location, text, author, status, color } => FeatureData::Note {
+                location: self.mirror_point_ref(location, features),
+                text: text.clone(),
+                author: author.clone(),
+                status: *status,
+                color: *color,
+            },
+        }
+    }
+
+    /// True when a feature is its own mirror image (e.g. a point on the plane, a room straddling it
+    /// evenly), so creating a counterpart would only duplicate it.
+    pub fn is_self_symmetric_data(&self, data: &FeatureData, features: &HashMap<FeatureId, Feature>) -> bool {
+        let origin = self.origin_position(features);
+        let resolve = |pr: &PointRef| pr.clone().resolve(features).ok();
+        match data {
+            FeatureData::GlobalPoint { location }
+            | FeatureData::PointLight { location, .. }
+            | FeatureData::Logic { location, .. }
+            | FeatureData::Note { location, .. } => {
+                resolve(location).is_some_and(|p| self.is_self_symmetric(p, origin))
+            }
+            FeatureData::SpotLight { location, target, .. }
+            | FeatureData::AreaLight { location, target, .. }
+            | FeatureData::Stairs { start: location, end: target, .. }
+            | FeatureData::Dimension { start: location, end: target, .. } => {
+                [location, target].into_iter()
+                    .all(|pr| resolve(pr).is_some_and(|p| self.is_self_symmetric(p, origin)))
+            }
+            FeatureData::Sun { .. } => true,
+            FeatureData::Prop { location, rotation, .. } => {
+                resolve(location).is_some_and(|p| self.is_self_symmetric(p, origin))
+                    && prop_rotation(self.mirror_rotation(*rotation)).angle_between(prop_rotation(*rotation)) < SELF_SYMMETRIC_EPSILON
+            }
+            FeatureData::Teleporter { min, max, destination, yaw } => {
+                self.is_self_symmetric_data(&FeatureData::Room { min: min.clone(), max: max.clone() }, features)
+                    && resolve(destination).is_some_and(|p| self.is_self_symmetric(p, origin))
+                    && facing_direction(self.mirror_yaw(*yaw)).distance(facing_direction(*yaw)) < SELF_SYMMETRIC_EPSILON
+            }
+            FeatureData::JumpPad { min, max, apex } => {
+                self.is_self_symmetric_data(&FeatureData::Room { min: min.clone(), max: max.clone() }, features)
+                    && resolve(apex).is_some_and(|p| self.is_self_symmetric(p, origin))
+            }
+            FeatureData::Room { min, max } | FeatureData::Trigger { min, max, .. } | FeatureData::Liquid { min, max, .. } => match (resolve(min), resolve(max)) {
+                (Some(a), Some(b)) => {
+                    let (lo, hi) = (a.min(b), a.max(b));
+                    let (ma, mb) = (self.mirror_position(lo, origin), self.mirror_position(hi, origin));
+                    ma.min(mb).distance(lo) < SELF_SYMMETRIC_EPSILON && ma.max(mb).distance(hi) < SELF_SYMMETRIC_EPSILON
+                }
+                _ => false,
+            },
+            FeatureData::Cuboid { min, max } => {
+                let (ma, mb) = (self.mirror_position(*min, origin), self.mirror_position(*max, origin));
+                ma.min(mb).distance(min.min(*max)) < SELF_SYMMETRIC_EPSILON
+                    && ma.max(mb).distance(min.max(*max)) < SELF_SYMMETRIC_EPSILON
+            }
+            // A team's marker or pickup always needs the other team's copy, even on the mirror plane.
+            FeatureData::Marker { location, team, .. } | FeatureData::Pickup { location, team, .. } => {
+                team.opponent() == *team && resolve(location).is_some_and(|p| self.is_self_symmetric(p, origin))
+            }
+        }
+    }
+}
+
+/// Changes go through [`FeatureTimeline::set_symmetry`], so each one is a single undoable step.
+pub fn symmetry_panel_ui(ui: &mut egui::Ui, timeline: &mut FeatureTimeline) {
+    let origin_choices: Vec<(FeatureId, String)> = timeline.active_features()
+        .filter(|(_, f)| f.object().type_key() == "global_point")
+        .map(|(id, f)| (id, f.type_name_with_id()))
+        .collect();
+    let selected = timeline.selected_feature();
+    let counterpart = selected.and_then(|id| timeline.symmetry().counterpart(id));
+
+    let mut settings = timeline.symmetry().settings();
+    ui.checkbox(&mut settings.enabled, get!("editor.symmetry.enabled"));
+
+    egui::Grid::new("editor_symmetry_grid")
+        .num_columns(2)
+        .spacing([10.0, 6.0])
+        .show(ui, |ui| {
+            ui.label(get!("editor.symmetry.mode"));
+            egui::ComboBox::from_id_salt("editor_symmetry_mode")
+                .selected_text(settings.mode.name())
+                .show_ui(ui, |ui| {
+                    for mode in SymmetryMode::iter() {
+                        ui.selectable_value(&mut settings.mode, mode, mode.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label(get!("editor.symmetry.origin"));
+            let origin_text = settings.origin
+                .and_then(|id| origin_choices.iter().find(|(c, _)| *c == id))
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| get!("editor.symmetry.world_origin"));
+            egui::ComboBox::from_id_salt("editor_symmetry_origin")
+                .selected_text(origin_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.origin, None, get!("editor.symmetry.world_origin"));
+                    for (id, name) in &origin_choices {
+                        ui.selectable_value(&mut settings.origin, Some(*id), name);
+                    }
+                });
+            ui.end_row();
+        });
+
+    if let Some(selected) = selected {
+        ui.separator();
+        ui.horizontal(|ui| {
+            match counterpart {
+                Some(other) => {
+                    ui.label(get!("editor.symmetry.counterpart", "id", other));
+                    if ui.small_button(get!("editor.symmetry.unlink")).clicked() {
+                        settings.links.retain(|(a, b)| *a != selected && *b != selected);
+                    }
+                }
+                None => {
+                    ui.label(egui::RichText::new(get!("editor.symmetry.no_counterpart")).weak());
+                }
+            }
+        });
+    }
+
+    timeline.set_symmetry(settings);
+}
+
+/// Draws the mirror plane or rotation axis while symmetry editing is on.
+pub fn draw_symmetry_gizmos(features: Res<FeatureTimeline>, mut gizmos: Gizmos) {
+    let symmetry = features.symmetry();
+    if !symmetry.enabled {
+        return;
+    }
+    let origin = symmetry.origin_position(features.features_map());
+    let color = Color::srgba(1.0, 0.4, 0.9, 0.6);
+    let extent = 50.0;
+    match symmetry.mode {
+        SymmetryMode::MirrorX | SymmetryMode::MirrorZ => {
+            let along = if symmetry.mode == SymmetryMode::MirrorX { Vec3::Z } else { Vec3::X };
+            for i in -5..=5 {
+                let offset = along * (i as f32 * extent / 5.0);
+                gizmos.line(origin + offset - Vec3::Y * extent, origin + offset + Vec3::Y * extent, color);
+                let height = Vec3::Y * (i as f32 * extent / 5.0);
+                gizmos.line(origin + height - along * extent, origin + height + along * extent, color);
+            }
+        }
+        SymmetryMode::Rotate180 => {
+            gizmos.line(origin - Vec3::Y * extent, origin + Vec3::Y * extent, color);
+            gizmos.circle(Isometry3d::new(origin, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)), 1.0, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editor_room::EditorRoom;
+    use crate::editor::global_point::GlobalPoint;
+    use crate::editor::note::Note;
+
+    fn point_position(timeline: &FeatureTimeline, id: FeatureId) -> Vec3 {
+        let FeatureData::GlobalPoint { mut location } = timeline.get_feature(&id).unwrap().object().snapshot() else {
+            panic!("expected a global point");
+        };
+        location.resolve(timeline.features_map()).ok().unwrap()
+    }
+
+    fn symmetric_timeline(mode: SymmetryMode) -> FeatureTimeline {
+        let mut timeline = FeatureTimeline::default();
+        timeline.symmetry_mut().enabled = true;
+        timeline.symmetry_mut().mode = mode;
+        timeline
+    }
+
+    #[test]
+    fn create_spawns_linked_counterpart_in_same_action() {
+        let mut timeline = symmetric_timeline(SymmetryMode::Rotate180);
+        let id = timeline.apply_feature(Box::new(GlobalPoint::new(3.0, 1.0, 2.0)));
+        let counterpart = timeline.symmetry().counterpart(id).expect("counterpart");
+        assert_eq!(point_position(&timeline, counterpart), Vec3::new(-3.0, 1.0, -2.0));
+        assert_eq!(timeline.applied_actions().len(), 1);
+        assert_eq!(timeline.applied_actions()[0].deltas.len(), 2);
+
+        timeline.undo_action();
+        assert!(timeline.get_feature(&id).is_none());
+        assert!(timeline.get_feature(&counterpart).is_none());
+    }
+
+    #[test]
+    fn point_on_mirror_plane_has_no_counterpart() {
+        let mut timeline = symmetric_timeline(SymmetryMode::MirrorX);
+        let id = timeline.apply_feature(Box::new(GlobalPoint::new(0.0, 0.0, 5.0)));
+        assert!(timeline.symmetry().counterpart(id).is_none());
+        assert_eq!(timeline.feature_order().len(), 1);
+    }
+
+    #[test]
+    fn dependent_feature_references_counterpart_of_its_parent() {
+        let mut timeline = symmetric_timeline(SymmetryMode::MirrorX);
+        let parent = timeline.apply_feature(Box::new(GlobalPoint::new(4.0, 0.0, 0.0)));
+        let child = timeline.apply_feature(Box::new(GlobalPoint::from_point_ref(
+            PointRef::reference_with_offset(parent, 1.0, 2.0, 0.0),
+        )));
+        let parent_counterpart = timeline.symmetry().counterpart(parent).unwrap();
+        let child_counterpart = timeline.symmetry().counterpart(child).unwrap();
+        assert_eq!(timeline.get_feature(&child_counterpart).unwrap().parents(), &[parent_counterpart]);
+        assert_eq!(point_position(&timeline, child_counterpart), Vec3::new(-5.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn notes_are_not_mirrored() {
+        let mut timeline = symmetric_timeline(SymmetryMode::Rotate180);
+        let id = timeline.apply_feature(Box::new(Note::from_point_ref(PointRef::absolute(3.0, 1.0, 2.0), "")));
+        assert!(timeline.symmetry().counterpart(id).is_none());
+        assert_eq!(timeline.feature_order().len(), 1);
+    }
+
+    #[test]
+    fn changing_the_mode_replaces_counterparts_in_one_undoable_step() {
+        let mut timeline = symmetric_timeline(SymmetryMode::MirrorX);
+        let id = timeline.apply_feature(Box::new(GlobalPoint::new(3.0, 1.0, 2.0)));
+        let counterpart = timeline.symmetry().counterpart(id).unwrap();
+        assert_eq!(point_position(&timeline, counterpart), Vec3::new(-3.0, 1.0, 2.0));
+
+        let mut settings = timeline.symmetry().settings();
+        settings.mode = SymmetryMode::MirrorZ;
+        timeline.set_symmetry(settings);
+        assert_eq!(point_position(&timeline, counterpart), Vec3::new(3.0, 1.0, -2.0));
+        assert_eq!(point_position(&timeline, id), Vec3::new(3.0, 1.0, 2.0));
+        assert_eq!(timeline.applied_actions().len(), 2);
+
+        timeline.undo_action();
+        assert_eq!(timeline.symmetry().mode, SymmetryMode::MirrorX);
+        assert_eq!(point_position(&timeline, counterpart), Vec3::new(-3.0, 1.0, 2.0));
+        timeline.redo_action();
+        assert_eq!(timeline.symmetry().mode, SymmetryMode::MirrorZ);
+        assert_eq!(point_position(&timeline, counterpart), Vec3::new(3.0, 1.0, -2.0));
+    }
+
+    #[test]
+    fn unlinking_can_be_undone() {
+        let mut timeline = symmetric_timeline(SymmetryMode::Rotate180);
+        let id = timeline.apply_feature(Box::new(GlobalPoint::new(3.0, 1.0, 2.0)));
+        let counterpart = timeline.symmetry().counterpart(id).unwrap();
+
+        let mut settings = timeline.symmetry().settings();
+        settings.links.clear();
+        timeline.set_symmetry(settings);
+        assert!(timeline.symmetry().counterpart(id).is_none());
+
+        timeline.undo_action();
+        assert_eq!(timeline.symmetry().counterpart(id), Some(counterpart));
+    }
+
+    #[test]
+    fn reference_to_a_named_point_follows_the_mirrored_point() {
+        let mut timeline = symmetric_timeline(SymmetryMode::MirrorX);
+        let room = timeline.apply_feature(Box::new(EditorRoom::from_point_refs(
+            PointRef::absolute(2.0, 0.0, 0.0),
+            PointRef::absolute(4.0, 3.0, 2.0),
+        )));
+        timeline.resolve_all_references();
+        let mut against_max = PointRef::reference_with_offset(room, 1.0, -3.0, -2.0);
+        against_max.point_key = "max".into();
+        let annex = timeline.apply_feature(Box::new(EditorRoom::from_point_refs(
+            against_max,
+            PointRef::absolute(7.0, 3.0, 2.0),
+        )));
+
+        let room_counterpart = timeline.symmetry().counterpart(room).unwrap();
+        let annex_counterpart = timeline.symmetry().counterpart(annex).unwrap();
+        let FeatureData::Room { min, .. } = timeline.get_feature(&annex_counterpart).unwrap().object().snapshot() else {
+            panic!("expected a room");
+        };
+        assert_eq!(min.reference, Some(room_counterpart));
+        let mirrored_room = timeline.get_feature(&room_counterpart).unwrap();
+        assert_eq!(mirrored_room.get_point(&min.point_key).ok(), Some(Vec3::new(-4.0, 3.0, 2.0)));
+
+        let mirrored_annex = timeline.get_feature(&annex_counterpart).unwrap();
+        assert_eq!(mirrored_annex.get_point("min").ok(), Some(Vec3::new(-7.0, 0.0, 0.0)));
+        assert_eq!(mirrored_annex.get_point("max").ok(), Some(Vec3::new(-5.0, 3.0, 2.0)));
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::common::ray::ray_intersects_aabb;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTrait, PointRef};
+use crate::get;
+use crate::tool::tool_helpers::{bounds_gizmo, dashed_line};
+
+/// Direction a player faces after arriving, for a yaw in degrees. Zero faces -Z, like the playtest camera.
+pub fn facing_direction(yaw_degrees: f32) -> Vec3 {
+    Quat::from_rotation_y(yaw_degrees.to_radians()) * Vec3::NEG_Z
+}
+
+/// Volume that moves players who enter it to `destination`, turned to face `yaw`.
+#[derive(Serialize, Deserialize)]
+pub struct Teleporter {
+    min: PointRef,
+    max: PointRef,
+    destination: PointRef,
+    /// Facing on arrival, in degrees about +Y.
+    pub yaw: f32,
+    #[serde(skip)]
+    resolved_min: Vec3,
+    #[serde(skip)]
+    resolved_max: Vec3,
+    #[serde(skip)]
+    resolved_destination: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl Teleporter {
+    pub fn from_point_refs(min: PointRef, max: PointRef, destination: PointRef) -> Self {
+        Self {
+            min,
+            max,
+            destination,
+            yaw: 0.0,
+            resolved_min: Vec3::ZERO,
+            resolved_max: Vec3::ZERO,
+            resolved_destination: Vec3::ZERO,
+            entity: None,
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.resolved_min.min(self.resolved_max), self.resolved_min.max(self.resolved_max))
+    }
+}
+
+#[typetag::serde(name = "teleporter")]
+impl FeatureTrait for Teleporter {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        let (min, max) = self.bounds();
+        match key {
+            "min" => Ok(min),
+            "max" => Ok(max),
+            "destination" => Ok(self.resolved_destination),
+            _ => Ok((min + max) / 2.0),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.min.editor_ui(ui, "Min", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.max.editor_ui(ui, "Max", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.destination.editor_ui(ui, "Destination", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(get!("editor.features.teleporter.yaw"));
+            changed |= ui.add(egui::DragValue::new(&mut self.yaw).range(-180.0..=180.0).speed(1.0).suffix("°")).changed();
+        });
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.teleporter.title")
+    }
+
+    fn type_key(&self) -> &'static str { "teleporter" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Teleporter {
+            min: self.min.clone(),
+            max: self.max.clone(),
+            destination: self.destination.clone(),
+            yaw: self.yaw,
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Teleporter { min, max, destination, yaw } = data else { return; };
+        self.min = min.clone();
+        self.max = max.clone();
+        self.destination = destination.clone();
+        self.yaw = *yaw;
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let (min, max) = self.bounds();
+        let color = Color::srgb_u8(0, 200, 200);
+        bounds_gizmo(gizmos, min, max, color);
+        let arrival = self.resolved_destination;
+        dashed_line(gizmos, (min + max) / 2.0, arrival, color, 0.3, 0.2);
+        gizmos.circle(Isometry3d::new(arrival, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)), 0.4, color);
+        gizmos.arrow(arrival, arrival + facing_direction(self.yaw), color);
+        self.min.debug_gizmos(self.resolved_min, gizmos);
+        self.max.debug_gizmos(self.resolved_max, gizmos);
+        self.destination.debug_gizmos(arrival, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        let (min, max) = self.bounds();
+        commands.entity(entity).insert(Transform::from_translation((min + max) / 2.0));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.min.resolve(features) {
+            self.resolved_min = v;
+        }
+        if let Ok(v) = self.max.resolve(features) {
+            self.resolved_max = v;
+        }
+        if let Ok(v) = self.destination.resolve(features) {
+            self.resolved_destination = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.min.referenced_features();
+        for id in self.max.referenced_features().into_iter().chain(self.destination.referenced_features()) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Center".into()),
+            ("min".into(), "Min".into()),
+            ("max".into(), "Max".into()),
+            ("destination".into(), "Destination".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, ray: &Ray3d) -> Vec<(String, Vec3)> {
+        let (min, max) = self.bounds();
+        let padding = Vec3::splat(0.25);
+        if !ray_intersects_aabb(ray, min - padding, max + padding) {
+            return vec![("destination".into(), self.resolved_destination)];
+        }
+        self.available_point_keys().into_iter().filter_map(|(key, _)| {
+            self.get_point(&key).ok().map(|v| (key, v))
+        }).collect()
+    }
+
+    fn drag_handle(&mut self, is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let point_ref = if is_max { &mut self.max } else { &mut self.min };
+        let axis_ref = match axis {
+            0 => &mut point_ref.x,
+            1 => &mut point_ref.y,
+            2 => &mut point_ref.z,
+            _ => return false,
+        };
+        let base = point_ref.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        let resolved = if is_max { &mut self.resolved_max } else { &mut self.resolved_min };
+        match axis { 0 => resolved.x = new_world_value, 1 => resolved.y = new_world_value, _ => resolved.z = new_world_value }
+        true
+    }
+
+    fn drag_handle_bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some(self.bounds())
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["min", "max", "destination"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![("yaw", self.yaw)]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        if key == "yaw" {
+            self.yaw = value;
+        }
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Min" | "min" => Some(&self.min),
+            "Max" | "max" => Some(&self.max),
+            "Destination" | "destination" => Some(&self.destination),
+            _ => None,
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Min" | "min" => Some(&mut self.min),
+            "Max" | "max" => Some(&mut self.max),
+            "Destination" | "destination" => Some(&mut self.destination),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use bevy::prelude::*;
+use crate::editor::action::{Action, FeatureDelta, FeatureSnapshot};
+use crate::editor::editable::{AxisRef, FeatureId, FeatureTimeline, PointRef};
+
+/// Scalar fields holding a facing in degrees about the vertical axis.
+const YAW_FIELDS: [&str; 2] = ["yaw", "rotation_y"];
+/// Scalar fields holding a roll in degrees, which a mirror reverses.
+const ROLL_FIELDS: [&str; 1] = ["rotation_z"];
+/// Scalar fields holding a size factor, which a scale multiplies.
+const SIZE_FIELDS: [&str; 3] = ["scale_x", "scale_y", "scale_z"];
+
+/// A change applied to every point of a selection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelectionTransform {
+    Translate(Vec3),
+    /// Quarter turns counter-clockwise seen from above, about the vertical axis through `pivot`.
+    RotateY { pivot: Vec3, quarter_turns: i32 },
+    /// Reflection across the vertical plane through `pivot`, facing X when `flip_x` and Z otherwise.
+    Mirror { pivot: Vec3, flip_x: bool },
+    /// Uniform scaling away from `pivot`; facings are unchanged and sizes grow by `factor`.
+    Scale { pivot: Vec3, factor: f32 },
+}
+
+impl SelectionTransform {
+    pub fn apply_point(&self, p: Vec3) -> Vec3 {
+        match *self {
+            SelectionTransform::Translate(d) => p + d,
+            SelectionTransform::RotateY { pivot, quarter_turns } => {
+                let d = p - pivot;
+                let d = match quarter_turns.rem_euclid(4) {
+                    1 => Vec3::new(d.z, d.y, -d.x),
+                    2 => Vec3::new(-d.x, d.y, -d.z),
+                    3 => Vec3::new(-d.z, d.y, d.x),
+                    _ => d,
+                };
+                pivot + d
+            }
+            SelectionTransform::Mirror { pivot, flip_x } => {
+                if flip_x {
+                    Vec3::new(2.0 * pivot.x - p.x, p.y, p.z)
+                } else {
+                    Vec3::new(p.x, p.y, 2.0 * pivot.z - p.z)
+                }
+            }
+            SelectionTransform::Scale { pivot, factor } => pivot + (p - pivot) * factor,
+        }
+    }
+
+    /// A facing in degrees, using the teleporter / prop convention of yaw about +Y from -Z.
+    pub fn apply_yaw(&self, yaw: f32) -> f32 {
+        match *self {
+            SelectionTransform::Translate(_) | SelectionTransform::Scale { .. } => yaw,
+            SelectionTransform::RotateY { quarter_turns, .. } => wrap_degrees(yaw + 90.0 * quarter_turns as f32),
+            SelectionTransform::Mirror { flip_x: true, .. } => wrap_degrees(-yaw),
+            SelectionTransform::Mirror { flip_x: false, .. } => wrap_degrees(180.0 - yaw),
+        }
+    }
+
+    fn apply_roll(&self, roll: f32) -> f32 {
+        match self {
+            SelectionTransform::Mirror { .. } => -roll,
+            _ => roll,
+        }
+    }
+
+    fn apply_size(&self, size: f32) -> f32 {
+        match self {
+            SelectionTransform::Scale { factor, .. } => size * factor,
+            _ => size,
+        }
+    }
+}
+
+fn wrap_degrees(degrees: f32) -> f32 {
+    (degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// `original` re-expressed so it resolves to `target`. Absolute axes take the target directly;
+/// relative axes keep their reference and become offsets from `base`, the referenced point as it
+/// is now. Without a base the relative axes are left as they were.
+pub fn retarget_point_ref(original: &PointRef, target: Vec3, base: Option<Vec3>) -> PointRef {
+    let retarget = |axis: &AxisRef, target: f32, base: Option<f32>| match (axis, base) {
+        (AxisRef::Absolute(_), _) => AxisRef::Absolute(target),
+        (AxisRef::Relative(_), Some(base)) => AxisRef::Relative(target - base),
+        (AxisRef::Relative(_), None) => axis.clone(),
+    };
+    PointRef {
+        reference: original.reference,
+        point_key: original.point_key.clone(),
+        x: retarget(&original.x, target.x, base.map(|b| b.x)),
+        y: retarget(&original.y, target.y, base.map(|b| b.y)),
+        z: retarget(&original.z, target.z, base.map(|b| b.z)),
+        resolved_reference: None,
+    }
+}
+
+struct CapturedFeature {
+    id: FeatureId,
+    before: FeatureSnapshot,
+    /// Slot name, the point ref as captured and where it resolved to.
+    points: Vec<(String, PointRef, Vec3)>,
+    scalars: Vec<(String, f32)>,
+}
+
+/// A selection as it was before a transform. Drags re-apply their transform from this state every
+/// frame, so snapping and cancelling never accumulate error, and finish as one undoable action.
+pub struct CapturedSelection {
+    features: Vec<CapturedFeature>,
+    bounds: Option<(Vec3, Vec3)>,
+}
+
+impl CapturedSelection {
+    /// Capture `ids` in timeline order, so referenced features are always moved before their dependants.
+    pub fn capture(timeline: &FeatureTimeline, ids: &[FeatureId]) -> Self {
+        let map = timeline.features_map();
+        let mut features = vec![];
+        let mut bounds: Option<(Vec3, Vec3)> = None;
+        let mut grow = |min: Vec3, max: Vec3| {
+            bounds = Some(match bounds {
+                Some((a, b)) => (a.min(min), b.max(max)),
+                None => (min, max),
+            });
+        };
+
+        for id in timeline.feature_order().iter().filter(|id| ids.contains(id)) {
+            let (Some(feature), Some(before)) = (map.get(id), timeline.feature_snapshot(*id)) else { continue; };
+            let object = feature.object();
+            let mut points = vec![];
+            for slot in object.point_ref_slots() {
+                let Some(pr) = object.get_point_ref(slot) else { continue; };
+                let Ok(world) = pr.clone().resolve(map) else { continue; };
+                grow(world, world);
+                points.push((slot.to_owned(), pr.clone(), world));
+            }
+            if let Some((min, max)) = object.selection_bounds() {
+                grow(min, max);
+            }
+            let scalars = object.scalar_fields().into_iter()
+                .filter(|(key, _)| YAW_FIELDS.contains(key) || ROLL_FIELDS.contains(key) || SIZE_FIELDS.contains(key))
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect();
+            features.push(CapturedFeature { id: *id, before, points, scalars });
+        }
+
+        Self { features, bounds }
+    }
+
+    /// Center of everything captured; the pivot for rotating, mirroring and scaling.
+    pub fn center(&self) -> Option<Vec3> {
+        self.bounds.map(|(min, max)| (min + max) / 2.0)
+    }
+
+    /// Set every captured feature to its captured state with `transform` applied.
+    pub fn apply(&self, timeline: &mut FeatureTimeline, transform: &SelectionTransform) {
+        self.apply_with(timeline, |_, original, world, base| {
+            retarget_point_ref(original, transform.apply_point(world), base)
+        });
+        let map = timeline.features_mut();
+        for captured in &self.features {
+            let Some(feature) = map.get_mut(&captured.id) else { continue; };
+            for (key, value) in &captured.scalars {
+                let value = if ROLL_FIELDS.contains(&key.as_str()) {
+                    transform.apply_roll(*value)
+                } else if SIZE_FIELDS.contains(&key.as_str()) {
+                    transform.apply_size(*value)
+                } else {
+                    transform.apply_yaw(*value)
+                };
+                feature.object_mut().set_scalar_field(key, value);
+            }
+        }
+    }
+
+    /// Set every captured feature to its captured state, with each point replaced by `place`. It is
+    /// given the feature, the point ref and position as captured, and where the point's reference
+    /// resolves to now.
+    pub fn apply_with(
+        &self,
+        timeline: &mut FeatureTimeline,
+        place: impl Fn(FeatureId, &PointRef, Vec3, Option<Vec3>) -> PointRef,
+    ) {
+        let map = timeline.features_mut();
+        for captured in &self.features {
+            let Some(mut feature) = map.remove(&captured.id) else { continue; };
+            let object = feature.object_mut();
+            object.apply_snapshot(&captured.before.data);
+            for (slot, original, world) in &captured.points {
+                let base = original.reference
+                    .and_then(|r| map.get(&r))
+                    .and_then(|f| f.get_point(&original.point_key).ok());
+                let moved = place(captured.id, original, *world, base);
+                if let Some(pr) = object.get_point_ref_mut(slot) {
+                    *pr = moved;
+                }
+            }
+            object.resolve_references(map);
+            let parents = feature.object().parent_ids();
+            feature.set_parents(parents);
+            map.insert(captured.id, feature);
+        }
+    }
+
+    /// Put the change since capture through `FeatureTimeline::apply_changes`, for one-off edits
+    /// rather than drags: it records a single action, refreshes the moved features' entities and
+    /// re-resolves everything depending on them.
+    pub fn commit(self, timeline: &mut FeatureTimeline) {
+        let changes: Vec<(FeatureId, Option<FeatureSnapshot>)> = self.features.iter()
+            .filter_map(|captured| {
+                let after = timeline.feature_snapshot(captured.id)?;
+                (after != captured.before).then_some((captured.id, Some(after)))
+            })
+            .collect();
+        // Back to the captured state, so the action records it as the before.
+        let map = timeline.features_mut();
+        for captured in &self.features {
+            let Some(feature) = map.get_mut(&captured.id) else { continue; };
+            feature.object_mut().apply_snapshot(&captured.before.data);
+            feature.set_parents(captured.before.parents.clone());
+        }
+        if !changes.is_empty() {
+            timeline.apply_changes(changes);
+        }
+    }
+
+    /// Record the change since capture as a single action. Nothing is recorded if nothing moved.
+    pub fn finish(self, timeline: &mut FeatureTimeline) {
+        let deltas: Vec<FeatureDelta> = self.features.into_iter()
+            .filter_map(|captured| {
+                let after = timeline.feature_snapshot(captured.id)?;
+                (after != captured.before).then_some(FeatureDelta {
+                    feature_id: captured.id,
+                    before: Some(captured.before),
+                    after: Some(after),
+                })
+            })
+            .collect();
+        if !deltas.is_empty() {
+            timeline.record_action(Action { deltas, symmetry: None });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::global_point::GlobalPoint;
+
+    #[test]
+    fn test_transforms_move_points_and_facings() {
+        let pivot = Vec3::new(1.0, 0.0, 1.0);
+        let p = Vec3::new(3.0, 2.0, 1.0);
+
+        let turn = SelectionTransform::RotateY { pivot, quarter_turns: 1 };
+        let turned = turn.apply_point(p);
+        let expected = pivot + Quat::from_rotation_y(std::f32::consts::FRAC_PI_2) * (p - pivot);
+        assert!(turned.distance(expected) < 1e-5);
+        assert_eq!(turn.apply_yaw(135.0), -135.0);
+
+        let mirror_x = SelectionTransform::Mirror { pivot, flip_x: true };
+        assert_eq!(mirror_x.apply_point(p), Vec3::new(-1.0, 2.0, 1.0));
+        assert_eq!(mirror_x.apply_yaw(30.0), -30.0);
+
+        let mirror_z = SelectionTransform::Mirror { pivot, flip_x: false };
+        assert_eq!(mirror_z.apply_point(Vec3::new(0.0, 0.0, 4.0)), Vec3::new(0.0, 0.0, -2.0));
+        assert_eq!(mirror_z.apply_yaw(30.0), 150.0);
+
+        let scale = SelectionTransform::Scale { pivot, factor: 2.0 };
+        assert_eq!(scale.apply_point(p), Vec3::new(5.0, 4.0, 1.0));
+        assert_eq!(scale.apply_yaw(30.0), 30.0);
+        assert_eq!(scale.apply_size(1.5), 3.0);
+    }
+
+    #[test]
+    fn test_scaling_a_selection_scales_relative_offsets() {
+        let mut timeline = FeatureTimeline::default();
+        let parent = timeline.apply_feature(Box::new(GlobalPoint::new(2.0, 0.0, 0.0)));
+        let child = timeline.apply_feature(Box::new(GlobalPoint::from_point_ref(
+            PointRef::reference_with_offset(parent, 1.0, 0.0, 2.0),
+        )));
+        timeline.resolve_all_references();
+
+        let captured = CapturedSelection::capture(&timeline, &[parent, child]);
+        captured.apply(&mut timeline, &SelectionTransform::Scale { pivot: Vec3::ZERO, factor: 2.0 });
+        captured.commit(&mut timeline);
+        assert_eq!(timeline.get_feature(&parent).unwrap().get_point("").ok(), Some(Vec3::new(4.0, 0.0, 0.0)));
+        assert_eq!(timeline.get_feature(&child).unwrap().get_point("").ok(), Some(Vec3::new(6.0, 0.0, 4.0)));
+        let pr = timeline.get_feature(&child).unwrap().object().get_point_ref("").unwrap().clone();
+        assert_eq!(pr.reference, Some(parent));
+        assert!(pr.x == AxisRef::Relative(2.0));
+        assert!(pr.z == AxisRef::Relative(4.0));
+    }
+
+    #[test]
+    fn test_retarget_keeps_relative_offsets_when_the_base_moves_along() {
+        let parent = FeatureId::from_raw(1);
+        let mut pr = PointRef::reference_with_offset(parent, 1.0, 0.0, 2.0);
+        pr.y = AxisRef::Absolute(3.0);
+
+        let d = Vec3::new(4.0, 1.0, -2.0);
+        let base = Vec3::new(10.0, 0.0, 10.0);
+        let world = Vec3::new(11.0, 3.0, 12.0);
+        let moved = retarget_point_ref(&pr, world + d, Some(base + d));
+        assert_eq!(moved.reference, Some(parent));
+        assert!(moved.x == AxisRef::Relative(1.0));
+        assert!(moved.y == AxisRef::Absolute(4.0));
+        assert!(moved.z == AxisRef::Relative(2.0));
+
+        // A base that stays put takes up the whole move in the offsets.
+        let stayed = retarget_point_ref(&pr, world + d, Some(base));
+        assert!(stayed.x == AxisRef::Relative(5.0));
+        assert!(stayed.z == AxisRef::Relative(0.0));
+    }
+
+    #[test]
+    fn test_moving_a_multi_selection_carries_every_selected_features_dependants() {
+        let mut timeline = FeatureTimeline::default();
+        let a = timeline.apply_feature(Box::new(GlobalPoint::new(0.0, 0.0, 0.0)));
+        let b = timeline.apply_feature(Box::new(GlobalPoint::new(5.0, 0.0, 0.0)));
+        let on_b = timeline.apply_feature(Box::new(GlobalPoint::from_point_ref(
+            PointRef::reference_with_offset(b, 0.0, 1.0, 0.0),
+        )));
+        timeline.resolve_all_references();
+        timeline.select(Some(a));
+        timeline.toggle_selected(b);
+        let ids = timeline.selected_and_affected();
+        assert_eq!(ids, vec![a, b, on_b]);
+
+        let captured = CapturedSelection::capture(&timeline, &ids);
+        captured.apply(&mut timeline, &SelectionTransform::Translate(Vec3::X));
+        captured.commit(&mut timeline);
+        assert_eq!(timeline.get_feature(&b).unwrap().get_point("").ok(), Some(Vec3::new(6.0, 0.0, 0.0)));
+        assert_eq!(timeline.get_feature(&on_b).unwrap().get_point("").ok(), Some(Vec3::new(6.0, 1.0, 0.0)));
+    }
+}
@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use crate::common::PointResolutionError;
+use crate::common::ray::ray_intersects_aabb;
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{AxisRef, Feature, FeatureId, FeatureTrait, PointRef};
+use crate::editor::logic::{LogicConnection, connections_ui};
+use crate::get;
+use crate::tool::tool_helpers::bounds_gizmo;
+
+const OUTPUTS: [&str; 3] = ["OnStartTouch", "OnEndTouch", "OnTrigger"];
+const INPUTS: [&str; 3] = ["Enable", "Disable", "Toggle"];
+
+/// Invisible box that fires outputs when a player enters or leaves it. Bounded like [`EditorRoom`](crate::editor::editor_room::EditorRoom),
+/// but never collides or gets baked into geometry.
+#[derive(Serialize, Deserialize)]
+pub struct TriggerVolume {
+    min: PointRef,
+    max: PointRef,
+    pub start_enabled: bool,
+    /// Disable itself after the first `OnTrigger`.
+    pub once: bool,
+    pub connections: Vec<LogicConnection>,
+    #[serde(skip)]
+    resolved_min: Vec3,
+    #[serde(skip)]
+    resolved_max: Vec3,
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+impl TriggerVolume {
+    pub fn from_point_refs(min: PointRef, max: PointRef) -> Self {
+        Self {
+            min,
+            max,
+            start_enabled: true,
+            once: false,
+            connections: Vec::new(),
+            resolved_min: Vec3::ZERO,
+            resolved_max: Vec3::ZERO,
+            entity: None,
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.resolved_min.min(self.resolved_max), self.resolved_min.max(self.resolved_max))
+    }
+}
+
+#[typetag::serde(name = "trigger_volume")]
+impl FeatureTrait for TriggerVolume {
+    fn get_point(&self, key: &str) -> Result<Vec3, PointResolutionError> {
+        let (min, max) = self.bounds();
+        match key {
+            "min" => Ok(min),
+            "max" => Ok(max),
+            _ => Ok((min + max) / 2.0),
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui, features: &HashMap<FeatureId, Feature>, prior_feature_order: &[FeatureId], retarget_request: &mut Option<String>) -> bool {
+        let mut changed = false;
+        changed |= self.min.editor_ui(ui, "Min", features, prior_feature_order, retarget_request);
+        ui.separator();
+        changed |= self.max.editor_ui(ui, "Max", features, prior_feature_order, retarget_request);
+        if changed {
+            self.resolve_references(features);
+        }
+
+        ui.separator();
+        changed |= ui.checkbox(&mut self.start_enabled, get!("editor.logic.start_enabled")).changed();
+        changed |= ui.checkbox(&mut self.once, get!("editor.features.trigger_volume.once")).changed();
+        ui.separator();
+        changed |= connections_ui(ui, &mut self.connections, &OUTPUTS, features);
+        changed
+    }
+
+    fn type_name(&self) -> String {
+        get!("editor.features.trigger_volume.title")
+    }
+
+    fn type_key(&self) -> &'static str { "trigger_volume" }
+
+    fn snapshot(&self) -> FeatureData {
+        FeatureData::Trigger {
+            min: self.min.clone(),
+            max: self.max.clone(),
+            start_enabled: self.start_enabled,
+            once: self.once,
+            connections: self.connections.clone(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, data: &FeatureData) {
+        let FeatureData::Trigger { min, max, start_enabled, once, connections } = data else { return; };
+        self.min = min.clone();
+        self.max = max.clone();
+        self.start_enabled = *start_enabled;
+        self.once = *once;
+        self.connections = connections.clone();
+    }
+
+    fn debug_gizmos(&self, gizmos: &mut Gizmos) {
+        let (min, max) = self.bounds();
+        bounds_gizmo(gizmos, min, max, Color::srgb_u8(255, 140, 0));
+        self.min.debug_gizmos(self.resolved_min, gizmos);
+        self.max.debug_gizmos(self.resolved_max, gizmos);
+    }
+
+    fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    fn set_entity(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+    }
+
+    fn apply_to_entity(&self, commands: &mut Commands, entity: Entity) {
+        let (min, max) = self.bounds();
+        commands.entity(entity).insert(Transform::from_translation((min + max) / 2.0));
+    }
+
+    fn resolve_references(&mut self, features: &HashMap<FeatureId, Feature>) {
+        if let Ok(v) = self.min.resolve(features) {
+            self.resolved_min = v;
+        }
+        if let Ok(v) = self.max.resolve(features) {
+            self.resolved_max = v;
+        }
+    }
+
+    fn parent_ids(&self) -> Vec<FeatureId> {
+        let mut ids = self.min.referenced_features();
+        for id in self.max.referenced_features() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn available_point_keys(&self) -> Vec<(String, String)> {
+        vec![
+            ("".into(), "Center".into()),
+            ("min".into(), "Min".into()),
+            ("max".into(), "Max".into()),
+        ]
+    }
+
+    fn reference_points_for_ray(&self, ray: &Ray3d) -> Vec<(String, Vec3)> {
+        let (min, max) = self.bounds();
+        let padding = Vec3::splat(0.25);
+        if !ray_intersects_aabb(ray, min - padding, max + padding) {
+            return vec![];
+        }
+        self.available_point_keys().into_iter().filter_map(|(key, _)| {
+            self.get_point(&key).ok().map(|v| (key, v))
+        }).collect()
+    }
+
+    fn drag_handle(&mut self, is_max: bool, axis: u8, new_world_value: f32) -> bool {
+        let point_ref = if is_max { &mut self.max } else { &mut self.min };
+        let axis_ref = match axis {
+            0 => &mut point_ref.x,
+            1 => &mut point_ref.y,
+            2 => &mut point_ref.z,
+            _ => return false,
+        };
+        let base = point_ref.resolved_reference.map(|b| match axis {
+            0 => b.x, 1 => b.y, _ => b.z,
+        });
+        match axis_ref {
+            AxisRef::Absolute(v) => *v = new_world_value,
+            AxisRef::Relative(offset) => *offset = new_world_value - base.unwrap_or(0.0),
+        }
+        let resolved = if is_max { &mut self.resolved_max } else { &mut self.resolved_min };
+        match axis { 0 => resolved.x = new_world_value, 1 => resolved.y = new_world_value, _ => resolved.z = new_world_value }
+        true
+    }
+
+    fn drag_handle_bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some(self.bounds())
+    }
+
+    fn point_ref_slots(&self) -> Vec<&str> { vec!["min", "max"] }
+
+    fn scalar_fields(&self) -> Vec<(&str, f32)> {
+        vec![
+            ("start_enabled", self.start_enabled as u8 as f32),
+            ("once", self.once as u8 as f32),
+        ]
+    }
+
+    fn set_scalar_field(&mut self, key: &str, value: f32) {
+        match key {
+            "start_enabled" => self.start_enabled = value != 0.0,
+            "once" => self.once = value != 0.0,
+            _ => {}
+        }
+    }
+
+    fn logic_outputs(&self) -> Vec<&'static str> { OUTPUTS.to_vec() }
+
+    fn logic_inputs(&self) -> Vec<&'static str> { INPUTS.to_vec() }
+
+    fn connections(&self) -> &[LogicConnection] { &self.connections }
+
+    fn set_connections(&mut self, connections: Vec<LogicConnection>) {
+        self.connections = connections;
+    }
+
+    fn get_point_ref(&self, key: &str) -> Option<&PointRef> {
+        match key {
+            "Min" | "min" => Some(&self.min),
+            "Max" | "max" => Some(&self.max),
+            _ => None,
+        }
+    }
+
+    fn get_point_ref_mut(&mut self, key: &str) -> Option<&mut PointRef> {
+        match key {
+            "Min" | "min" => Some(&mut self.min),
+            "Max" | "max" => Some(&mut self.max),
+            _ => None,
+        }
+    }
+}
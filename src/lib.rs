@@ -1,6 +1,6 @@
 pub mod startup;
 pub mod common;
 pub mod constants;
-mod editor;
+pub mod editor;
 mod tool;
 pub mod unlock;
@@ -12,8 +12,11 @@ use crate::common::lang::change_lang;
 use crate::common::perf::PerfPlugin;
 use crate::editor::editable::EditorStepsPlugin;
 use crate::editor::input::EditorInputPlugin;
+use crate::editor::map_environment::EnvironmentPlugin;
 use crate::editor::multicam::MulticamPlugin;
 use crate::editor::panels::EditorPanelPlugin;
+use crate::editor::preferences::PreferencesPlugin;
+use crate::editor::render_mode::RenderModePlugin;
 use crate::tool::ToolPlugin;
 
 
@@ -49,18 +52,16 @@ fn main() {
             EguiPlugin::default(),
             Shape2dPlugin::default(),
         ))
-        .insert_resource(AmbientLight {
-            color: Color::WHITE,
-            brightness: 100.0,
-            ..default()
-        })
         .add_plugins((
             EditorInputPlugin,
+            PreferencesPlugin,
             MulticamPlugin {
                 test_scene: false,
             },
+            RenderModePlugin,
             EditorPanelPlugin,
             EditorStepsPlugin,
+            EnvironmentPlugin,
             ToolPlugin,
             PerfPlugin,
             ))
@@ -2,6 +2,7 @@ use bevy::diagnostic::FrameCount;
 use bevy::prelude::*;
 use bevy_egui::egui;
 use crate::editor::editable::FeatureTag;
+use crate::editor::stairs::StairShape;
 use crate::get;
 use crate::tool::room::{CalculateRoomGeometry, ClearRoomGeometry, Room};
 
@@ -33,6 +34,14 @@ impl BakePlugin {
                     commands.clear_room_geometry = true;
                 }
             });
+            ui.horizontal(|ui| {
+                if ui.button(get!("bakes.navmesh")).clicked() {
+                    commands.calculate_navmesh = true;
+                }
+                if ui.small_button("x").clicked() {
+                    commands.clear_navmesh = true;
+                }
+            });
             if ui.button("Log ECS").clicked() {
                 commands.log_ecs = true;
             }
@@ -52,6 +61,7 @@ impl BakePlugin {
     fn bake_room_geometry(
         mut events: MessageReader<CalculateRoomGeometry>,
         rooms: Query<&Room>,
+        stairs: Query<&StairShape>,
         existing_bakes: Query<Entity, With<BakedRoomGeometry>>,
         mut commands: Commands,
         mut meshes: ResMut<Assets<Mesh>>,
@@ -84,7 +94,15 @@ impl BakePlugin {
             ));
         }
 
-        info!("Baked geometry for {} room(s)", all_rooms.len());
+        for shape in &stairs {
+            commands.spawn((
+                BakedRoomGeometry,
+                Mesh3d(meshes.add(shape.bake_mesh())),
+                MeshMaterial3d(material.clone()),
+            ));
+        }
+
+        info!("Baked geometry for {} room(s) and {} stair(s)", all_rooms.len(), stairs.iter().count());
     }
 
     fn clear_room_geometry(
@@ -137,5 +155,7 @@ pub struct BakedRoomGeometry;
 pub struct BakeCommands {
     pub calculate_room_geometry: bool,
     pub clear_room_geometry: bool,
+    pub calculate_navmesh: bool,
+    pub clear_navmesh: bool,
     pub log_ecs: bool,
 }
@@ -0,0 +1,257 @@
+use bevy::app::App;
+use bevy::prelude::*;
+use crate::editor::asset_browser::AssetBrowserState;
+use crate::editor::editable::{FeatureId, FeatureTimeline, FeatureTrait, PointRef};
+use crate::editor::jump_pad::JumpPad;
+use crate::editor::liquid_volume::{LiquidVolume, VolumeKind};
+use crate::editor::logic_entity::{LogicEntity, LogicKind};
+use crate::editor::map_marker::{MapMarker, MarkerKind, Team};
+use crate::editor::map_metadata::MapMetadata;
+use crate::editor::note::Note;
+use crate::editor::pickup::{Pickup, PickupKind};
+use crate::editor::prop::Prop;
+use crate::editor::stairs::Stairs;
+use crate::editor::teleporter::Teleporter;
+use crate::editor::trigger_volume::TriggerVolume;
+use crate::editor::input::CurrentMouseInput;
+use crate::editor::multicam::Multicam;
+use crate::tool::room::Room;
+use crate::tool::tool_helpers::*;
+use crate::tool::Tools;
+use crate::tool::playtest::PlaytestState;
+
+/// Where a new teleporter sends players, and where a new jump pad peaks, relative to the click.
+const TELEPORTER_DESTINATION: Vec3 = Vec3::new(0.0, 0.0, -6.0);
+const JUMP_PAD_APEX: Vec3 = Vec3::new(0.0, 4.0, -6.0);
+
+/// Top of new stairs, relative to their foot at the click.
+const STAIRS_END: Vec3 = Vec3::new(0.0, 2.0, -4.0);
+
+/// The marker tool also places the other single-click gameplay features.
+fn is_placement_tool(tool: &Tools) -> bool {
+    matches!(tool, Tools::Marker | Tools::Trigger | Tools::Logic | Tools::Teleporter | Tools::JumpPad | Tools::Pickup | Tools::Prop | Tools::Liquid | Tools::Stairs | Tools::Note)
+}
+
+/// Corners of the volume the tool places, relative to the cursor on the floor.
+fn volume_corners(tool: &Tools) -> Option<(Vec3, Vec3)> {
+    match tool {
+        Tools::Trigger | Tools::Teleporter => Some((Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 2.0, 1.0))),
+        Tools::JumpPad => Some((Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 0.25, 1.0))),
+        Tools::Liquid => Some((Vec3::new(-2.0, 0.0, -2.0), Vec3::new(2.0, 1.0, 2.0))),
+        _ => None,
+    }
+}
+
+/// The feature the current tool places at `location`. Props use the model picked in the asset browser,
+/// and notes are signed by `author`.
+fn placed_feature(tool: &Tools, location: PointRef, model: &str, author: &str) -> Box<dyn FeatureTrait> {
+    if let Some((min, max)) = volume_corners(tool) {
+        let (min, max) = (location.offset_by(min), location.offset_by(max));
+        return match tool {
+            Tools::Teleporter => Box::new(Teleporter::from_point_refs(min, max, location.offset_by(TELEPORTER_DESTINATION))),
+            Tools::JumpPad => Box::new(JumpPad::from_point_refs(min, max, location.offset_by(JUMP_PAD_APEX))),
+            Tools::Liquid => Box::new(LiquidVolume::from_point_refs(min, max, VolumeKind::Water)),
+            _ => Box::new(TriggerVolume::from_point_refs(min, max)),
+        };
+    }
+    match tool {
+        Tools::Logic => Box::new(LogicEntity::from_point_ref(location, LogicKind::Relay)),
+        Tools::Pickup => Box::new(Pickup::from_point_ref(location, PickupKind::HealthSmall)),
+        Tools::Prop => Box::new(Prop::from_point_ref(location, model)),
+        Tools::Stairs => Box::new(Stairs::from_point_refs(location.clone(), location.offset_by(STAIRS_END))),
+        Tools::Note => Box::new(Note::from_point_ref(location, author)),
+        _ => Box::new(MapMarker::from_point_ref(location, MarkerKind::Spawn, Team::Neutral)),
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum MarkerToolMode {
+    Normal,
+    Picking,
+    RelativeSelected,
+}
+
+#[derive(Resource)]
+struct MarkerTool {
+    mode: MarkerToolMode,
+    last_position: Vec3,
+    cursor: Option<Vec3>,
+    reference_feature: Option<FeatureId>,
+    reference_key: String,
+    reference_resolved: Option<Vec3>,
+    hovered_point: Option<(FeatureId, String, Vec3)>,
+}
+
+impl Default for MarkerTool {
+    fn default() -> Self {
+        Self {
+            mode: MarkerToolMode::Normal,
+            last_position: Vec3::ZERO,
+            cursor: None,
+            reference_feature: None,
+            reference_key: String::new(),
+            reference_resolved: None,
+            hovered_point: None,
+        }
+    }
+}
+
+pub struct MarkerPlugin;
+
+impl Plugin for MarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MarkerTool>()
+            .add_systems(Update, (
+                MarkerTool::interface,
+                MarkerTool::draw_gizmos,
+            ).chain().run_if(|tool: Res<State<Tools>>| is_placement_tool(tool.get())).run_if(in_state(PlaytestState::Off)))
+            .add_systems(OnExit(Tools::Marker), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Trigger), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Logic), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Teleporter), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::JumpPad), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Pickup), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Prop), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Liquid), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Stairs), MarkerTool::on_exit)
+            .add_systems(OnExit(Tools::Note), MarkerTool::on_exit)
+        ;
+    }
+}
+
+impl MarkerTool {
+    fn on_exit(mut tool: ResMut<Self>) {
+        tool.mode = MarkerToolMode::Normal;
+        tool.cursor = None;
+        tool.hovered_point = None;
+        tool.reference_feature = None;
+        tool.reference_key.clear();
+        tool.reference_resolved = None;
+    }
+
+    fn interface(
+        mut tool: ResMut<Self>,
+        cameras: Query<(Entity, &Multicam)>,
+        mouse_input: Res<CurrentMouseInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut features: ResMut<FeatureTimeline>,
+        rooms: Query<&Room>,
+        current_tool: Res<State<Tools>>,
+        mut next_tool: ResMut<NextState<Tools>>,
+        (asset_browser, map_metadata, snap): (Res<AssetBrowserState>, Res<MapMetadata>, Res<SnapSettings>),
+    ) {
+        let shift_held = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
+        let shift_just_pressed = keys.just_pressed(KeyCode::ShiftLeft) || keys.just_pressed(KeyCode::ShiftRight);
+
+        tool.cursor = compute_cursor(
+            &mouse_input, &cameras, tool.last_position,
+            snap.enabled, snap.granularity, &rooms,
+        );
+        // Pickups and props rest on the floor, whichever face or view the cursor came from.
+        if matches!(current_tool.get(), Tools::Pickup | Tools::Prop) {
+            tool.cursor = tool.cursor.map(|cursor| snap_to_floor(cursor, rooms.iter()));
+        }
+        // Nothing to place until a model is picked in the asset browser.
+        let model = asset_browser.selected.as_deref().unwrap_or_default();
+        let author = map_metadata.authors.first().map(String::as_str).unwrap_or_default();
+        if *current_tool.get() == Tools::Prop && model.is_empty() {
+            tool.cursor = None;
+        }
+
+        match tool.mode {
+            MarkerToolMode::Normal => {
+                if shift_held {
+                    tool.mode = MarkerToolMode::Picking;
+                    tool.hovered_point = None;
+                } else if let Some(cursor) = tool.cursor {
+                    if mouse_input.released == Some(MouseButton::Left) {
+                        let location = PointRef::absolute(cursor.x, cursor.y, cursor.z);
+                        let id = features.apply_feature(placed_feature(current_tool.get(), location, model, author));
+                        features.select(Some(id));
+                        tool.last_position = cursor;
+                        next_tool.set(Tools::Select);
+                    }
+                }
+            }
+            MarkerToolMode::Picking => {
+                if !shift_held {
+                    tool.mode = MarkerToolMode::Normal;
+                    tool.hovered_point = None;
+                    return;
+                }
+
+                tool.hovered_point = mouse_input.world_pos
+                    .and_then(|ray| find_hovered_point(&ray, &features, PICK_RADIUS));
+
+                if mouse_input.released == Some(MouseButton::Left) {
+                    if let Some((feature_id, key, resolved)) = tool.hovered_point.take() {
+                        tool.reference_feature = Some(feature_id);
+                        tool.reference_key = key;
+                        tool.reference_resolved = Some(resolved);
+                        tool.mode = MarkerToolMode::RelativeSelected;
+                    }
+                }
+            }
+            MarkerToolMode::RelativeSelected => {
+                if shift_just_pressed {
+                    tool.mode = MarkerToolMode::Normal;
+                    tool.reference_feature = None;
+                    tool.reference_key.clear();
+                    tool.reference_resolved = None;
+                    return;
+                }
+
+                if let Some(cursor) = tool.cursor {
+                    if mouse_input.released == Some(MouseButton::Left) {
+                        if let (Some(ref_feature), Some(ref_resolved)) = (tool.reference_feature, tool.reference_resolved) {
+                            let d = cursor - ref_resolved;
+                            let mut pr = PointRef::reference_with_offset(ref_feature, d.x, d.y, d.z);
+                            if !tool.reference_key.is_empty() {
+                                pr.point_key = tool.reference_key.clone();
+                            }
+                            let id = features.apply_feature(placed_feature(current_tool.get(), pr, model, author));
+                            features.select(Some(id));
+                            tool.last_position = cursor;
+                            next_tool.set(Tools::Select);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_gizmos(
+        tool: Res<MarkerTool>,
+        features: Res<FeatureTimeline>,
+        mouse_input: Res<CurrentMouseInput>,
+        current_tool: Res<State<Tools>>,
+        mut gizmos: Gizmos,
+    ) {
+        if let Some(cursor) = tool.cursor {
+            let color = match tool.mode {
+                MarkerToolMode::RelativeSelected => Color::srgb_u8(80, 140, 255),
+                _ => Color::srgb_u8(60, 120, 255),
+            };
+            gizmos.sphere(Isometry3d::from_translation(cursor), 0.15, color);
+            if let Some((min, max)) = volume_corners(current_tool.get()) {
+                bounds_gizmo(&mut gizmos, cursor + min, cursor + max, color);
+            }
+            if *current_tool.get() == Tools::Stairs {
+                gizmos.line(cursor, cursor + STAIRS_END, color);
+            }
+
+            if tool.mode == MarkerToolMode::RelativeSelected {
+                if let Some(base) = tool.reference_resolved {
+                    draw_taxicab_path(&mut gizmos, base, cursor);
+                }
+            }
+        }
+
+        if tool.mode == MarkerToolMode::Picking {
+            if let Some(ray) = mouse_input.world_pos {
+                draw_picking_gizmos(&mut gizmos, &ray, &features, &tool.hovered_point);
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use crate::editor::action::FeatureData;
+use crate::editor::dimension::{Dimension, Measurement};
+use crate::editor::editable::{FeatureId, FeatureTimeline, PointRef};
+use crate::editor::input::CurrentMouseInput;
+use crate::editor::multicam::Multicam;
+use crate::get;
+use crate::tool::playtest::PlaytestState;
+use crate::tool::show::GizmoVisibility;
+use crate::tool::tool_helpers::{draw_picking_gizmos, find_hovered_point, paint_viewport_labels, PICK_RADIUS};
+use crate::tool::Tools;
+
+type PickedPoint = (FeatureId, String, Vec3);
+
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MeasureToolState>()
+            .add_systems(Update, (
+                MeasureToolState::interface,
+                MeasureToolState::draw_gizmos,
+            ).chain().run_if(in_state(Tools::Measure)).run_if(in_state(PlaytestState::Off)))
+            .add_systems(EguiPrimaryContextPass, MeasureToolState::viewport_labels.run_if(in_state(PlaytestState::Off)))
+            .add_systems(OnExit(Tools::Measure), MeasureToolState::on_exit)
+        ;
+    }
+}
+
+/// Click two pickable points to measure between them. While the second is being chosen the
+/// measurement follows the hovered point; a finished one can be pinned into the blueprint.
+#[derive(Resource, Default)]
+pub struct MeasureToolState {
+    start: Option<PickedPoint>,
+    end: Option<PickedPoint>,
+    hovered: Option<PickedPoint>,
+}
+
+/// "Room 3: Max" style name of a picked point, using the feature's own names for its points.
+fn point_name(features: &FeatureTimeline, (id, key, _): &PickedPoint) -> String {
+    let Some(feature) = features.get_feature(id) else { return String::new(); };
+    let key_name = feature.object().available_point_keys().into_iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, name)| name)
+        .unwrap_or_else(|| key.clone());
+    format!("{}: {}", feature.type_name_with_id(), key_name)
+}
+
+fn picked_ref((id, key, _): &PickedPoint) -> PointRef {
+    let mut pr = PointRef::reference(*id);
+    pr.point_key = key.clone();
+    pr
+}
+
+impl MeasureToolState {
+    /// The finished measurement, or the one in progress towards the hovered point.
+    fn measurement(&self) -> Option<Measurement> {
+        let (_, _, start) = self.start.as_ref()?;
+        let (_, _, end) = self.end.as_ref().or(self.hovered.as_ref())?;
+        Some(Measurement::new(*start, *end))
+    }
+
+    fn clear(&mut self) {
+        self.start = None;
+        self.end = None;
+        self.hovered = None;
+    }
+
+    fn on_exit(mut state: ResMut<Self>) {
+        state.clear();
+    }
+
+    fn interface(
+        mut state: ResMut<Self>,
+        mouse_input: Res<CurrentMouseInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        features: Res<FeatureTimeline>,
+    ) {
+        if keys.just_pressed(KeyCode::Escape) {
+            state.clear();
+            return;
+        }
+
+        state.hovered = mouse_input.world_pos
+            .and_then(|ray| find_hovered_point(&ray, &features, PICK_RADIUS));
+
+        if mouse_input.released != Some(MouseButton::Left) {
+            return;
+        }
+        let Some(picked) = state.hovered.clone() else { return; };
+        if state.start.is_none() || state.end.is_some() {
+            state.start = Some(picked);
+            state.end = None;
+        } else if state.start.as_ref().is_some_and(|(id, key, _)| *id != picked.0 || *key != picked.1) {
+            state.end = Some(picked);
+        }
+    }
+
+    fn draw_gizmos(
+        state: Res<Self>,
+        features: Res<FeatureTimeline>,
+        mouse_input: Res<CurrentMouseInput>,
+        mut gizmos: Gizmos,
+    ) {
+        if state.end.is_none()
+            && let Some(ray) = mouse_input.world_pos
+        {
+            draw_picking_gizmos(&mut gizmos, &ray, &features, &state.hovered);
+        }
+        if let Some(measurement) = state.measurement() {
+            measurement.draw(&mut gizmos, Color::WHITE);
+        }
+        // Pinned dimensions show while measuring even when their gizmos are hidden.
+        for (_, feature) in features.active_features() {
+            if feature.object().type_key() == "dimension" {
+                feature.object().debug_gizmos(&mut gizmos);
+            }
+        }
+    }
+
+    /// Label measurements where they are in every viewport: the one in progress, and the pinned
+    /// dimensions while they are shown or the Measure tool is active.
+    fn viewport_labels(
+        mut contexts: EguiContexts,
+        state: Res<Self>,
+        features: Res<FeatureTimeline>,
+        visibility: Res<GizmoVisibility>,
+        current_tool: Res<State<Tools>>,
+        cameras: Query<(&Camera, &GlobalTransform), With<Multicam>>,
+        window: Single<&Window, With<PrimaryWindow>>,
+    ) {
+        let measuring = *current_tool.get() == Tools::Measure;
+        let color = egui::Color32::from_rgb(255, 220, 120);
+        let mut labels: Vec<(Vec3, String, egui::Color32)> = vec![];
+        if measuring && let Some(measurement) = state.measurement() {
+            labels.push((measurement.midpoint(), measurement.label(), color));
+        }
+        if measuring || visibility.dimensions {
+            for (_, feature) in features.active_features() {
+                if let FeatureData::Dimension { note, .. } = feature.object().snapshot()
+                    && let (Ok(start), Ok(end)) = (feature.get_point("start"), feature.get_point("end"))
+                {
+                    let measurement = Measurement::new(start, end);
+                    labels.push((measurement.midpoint(), measurement.annotated_label(&note), color));
+                }
+            }
+        }
+        if labels.is_empty() {
+            return;
+        }
+
+        let Ok(ctx) = contexts.ctx_mut() else { return; };
+        paint_viewport_labels(ctx, "measure_labels", cameras.iter(), window.scale_factor(), &labels);
+    }
+}
+
+/// The picked points and their measurement, with a button to pin it as a dimension.
+pub fn measure_panel_ui(ui: &mut egui::Ui, state: &mut MeasureToolState, features: &mut FeatureTimeline) {
+    let Some(start) = &state.start else {
+        ui.label(get!("tools.measure.hint"));
+        return;
+    };
+    ui.label(get!("tools.measure.from", "point", point_name(features, start)));
+    let Some(end) = &state.end else {
+        ui.label(get!("tools.measure.pick_end"));
+        return;
+    };
+    ui.label(get!("tools.measure.to", "point", point_name(features, end)));
+    if let Some(measurement) = state.measurement() {
+        ui.label(measurement.label());
+    }
+
+    if ui.button(get!("tools.measure.pin")).clicked() {
+        features.apply_feature(Box::new(Dimension::from_point_refs(picked_ref(start), picked_ref(end))));
+        state.clear();
+    }
+}
@@ -5,25 +5,36 @@ use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
 use crate::get;
 use crate::tool::bakes::BakePlugin;
+use crate::tool::marker::MarkerPlugin;
+use crate::tool::measure::MeasurePlugin;
 use crate::tool::movement::MovementPlugin;
+use crate::tool::navmesh::NavmeshPlugin;
 use crate::tool::point::PointPlugin;
 use crate::tool::point_drag::PointDragPlugin;
 use crate::tool::point_light::PointLightPlugin;
+use crate::tool::playtest::PlaytestPlugin;
 use crate::tool::retarget::RetargetPlugin;
 use crate::tool::room::RoomPlugin;
 use crate::tool::selection::SelectionPlugin;
 use crate::tool::show::ShowPlugin;
+use crate::tool::tool_helpers::SnapSettings;
+use crate::tool::transform_gizmo::TransformGizmoPlugin;
 
 pub mod selection;
 pub mod point;
 pub mod point_light;
+pub mod marker;
 pub mod point_drag;
 pub mod retarget;
 pub mod room;
 pub mod movement;
 pub mod bakes;
+pub mod navmesh;
 pub mod show;
+pub mod playtest;
 pub mod tool_helpers;
+pub mod transform_gizmo;
+pub mod measure;
 
 pub struct ToolPlugin;
 
@@ -31,16 +42,22 @@ impl Plugin for ToolPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_resource::<ToolData>()
+            .init_resource::<SnapSettings>()
             .init_state::<Tools>()
             .add_plugins(ShowPlugin)
             .add_plugins(BakePlugin)
+            .add_plugins(NavmeshPlugin)
             .add_plugins(MovementPlugin)
             .add_plugins(SelectionPlugin)
             .add_plugins(PointPlugin)
             .add_plugins(PointLightPlugin)
+            .add_plugins(MarkerPlugin)
             .add_plugins(PointDragPlugin)
+            .add_plugins(TransformGizmoPlugin)
+            .add_plugins(MeasurePlugin)
             .add_plugins(RetargetPlugin)
             .add_plugins(RoomPlugin)
+            .add_plugins(PlaytestPlugin)
             // Toolbar moved to panels.rs Tools tab
             // .add_systems(EguiPrimaryContextPass, Self::toolbar)
         ;
@@ -63,8 +80,23 @@ impl Default for ToolData {
 pub enum Tools {
     #[default]
     Select,
+    Transform,
+    Measure,
     Point,
     PointLight,
+    SpotLight,
+    Sun,
+    AreaLight,
+    Marker,
+    Trigger,
+    Logic,
+    Teleporter,
+    JumpPad,
+    Pickup,
+    Prop,
+    Liquid,
+    Stairs,
+    Note,
     Room,
     Retarget,
 }
@@ -73,8 +105,23 @@ impl Tools {
     pub fn name(&self) -> String {
         match self {
             Self::Select => get!("tools.select"),
+            Self::Transform => get!("tools.transform.title"),
+            Self::Measure => get!("tools.measure.title"),
             Self::Point => get!("tools.point"),
             Self::PointLight => get!("tools.point_light"),
+            Self::SpotLight => get!("tools.spot_light"),
+            Self::Sun => get!("tools.sun"),
+            Self::AreaLight => get!("tools.area_light"),
+            Self::Marker => get!("tools.marker"),
+            Self::Trigger => get!("tools.trigger"),
+            Self::Logic => get!("tools.logic"),
+            Self::Teleporter => get!("tools.teleporter"),
+            Self::JumpPad => get!("tools.jump_pad"),
+            Self::Pickup => get!("tools.pickup"),
+            Self::Prop => get!("tools.prop"),
+            Self::Liquid => get!("tools.liquid"),
+            Self::Stairs => get!("tools.stairs"),
+            Self::Note => get!("tools.note"),
             Self::Room => get!("tools.room"),
             Self::Retarget => "Retarget".into(),
         }
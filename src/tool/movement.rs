@@ -3,19 +3,76 @@
  use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
 use bevy_egui::{egui, EguiPrimaryContextPass, EguiContexts};
+use crate::editor::camera_bookmark::CameraBookmark;
+use crate::editor::editable::FeatureTimeline;
 use crate::editor::input::{CurrentKeyboardInput, CurrentMouseInput};
-use crate::editor::multicam::Multicam;
+use crate::editor::map_metadata::MapMetadata;
+use crate::editor::multicam::{CameraAxis, Multicam};
 use crate::get;
+use serde::{Deserialize, Serialize};
+use crate::tool::playtest::PlaytestState;
 
 pub struct MovementPlugin;
 
+/// Bring `target` into the middle of every viewport, keeping each camera's view direction.
+#[derive(Message)]
+pub struct FocusCamera {
+    pub target: Vec3,
+}
+
+/// Save the free camera's current view to the map's bookmarks.
+#[derive(Message)]
+pub struct BookmarkCamera {
+    pub name: String,
+}
+
+/// Move the free camera to a saved view.
+#[derive(Message)]
+pub struct JumpCamera {
+    pub transform: Transform,
+}
+
+/// How far from a focused point the perspective camera stops.
+const FOCUS_DISTANCE: f32 = 6.0;
+/// Room left around the selection when framing it, as a fraction of its size.
+const FRAME_MARGIN: f32 = 1.25;
+/// Zoom limits for orthographic views, shared by scrolling and framing.
+const ORTHO_SCALE_RANGE: (f32, f32) = (0.001, 0.1);
+
+/// The box around everything selected: bounds for volumes, the point itself for point-like features.
+fn selection_extent(features: &FeatureTimeline) -> Option<(Vec3, Vec3)> {
+    features.selected_features().iter()
+        .filter_map(|id| features.get_feature(id))
+        .filter_map(|feature| feature.object().selection_bounds()
+            .or_else(|| feature.get_point("").ok().map(|p| (p, p))))
+        .reduce(|(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)))
+}
+
+/// How far back a perspective camera with vertical field of view `fov` must sit to fit a sphere of `radius`.
+fn framing_distance(radius: f32, fov: f32) -> f32 {
+    let half_tan = (fov / 2.0).tan().abs().max(0.05);
+    (radius * FRAME_MARGIN / half_tan).max(1.0)
+}
+
+/// The orthographic scale, in world units per pixel, that fits `extent` into a viewport of `viewport` pixels.
+fn framing_scale(extent: Vec2, viewport: Vec2) -> f32 {
+    let fit = extent * FRAME_MARGIN / viewport.max(Vec2::ONE);
+    fit.max_element().clamp(ORTHO_SCALE_RANGE.0, ORTHO_SCALE_RANGE.1)
+}
+
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_resource::<MovementSettings>()
+            .add_message::<FocusCamera>()
+            .add_message::<BookmarkCamera>()
+            .add_message::<JumpCamera>()
             .add_systems(Update, (
                 Self::handle,
-                )
+                Self::focus,
+                Self::frame_selected,
+                Self::bookmarks,
+                ).run_if(in_state(PlaytestState::Off))
             )
             .add_systems(EguiPrimaryContextPass, Self::debug_window)
         ;
@@ -30,6 +87,7 @@ impl MovementPlugin {
         keyboard_input: Res<CurrentKeyboardInput>,
         mut cameras: Query<(Entity, &mut Transform, &GlobalTransform, &Multicam, &mut Projection, &Camera)>,
         mut evr_scroll: MessageReader<MouseWheel>,
+        features: Res<FeatureTimeline>,
     ) {
         if mouse_input.started_in_camera.is_none() {
             cursor_options.grab_mode = CursorGrabMode::None;
@@ -49,7 +107,14 @@ impl MovementPlugin {
                             let forward = transform.forward();
                             match projection.into_inner() {
                                 Projection::Perspective(projection) => {
-                                    Self::perspective_move(&mut transform, global_transform, delta, &settings, &keyboard_input);
+                                    if keyboard_input.orbit {
+                                        let pivot = selection_extent(&features)
+                                            .map(|(min, max)| (min + max) / 2.0)
+                                            .unwrap_or(transform.translation + forward * FOCUS_DISTANCE);
+                                        Self::orbit(&mut transform, delta, pivot, &settings);
+                                    } else {
+                                        Self::perspective_move(&mut transform, global_transform, delta, &settings, &keyboard_input);
+                                    }
 
                                     for ev in evr_scroll.read() {
                                         match ev.unit {
@@ -74,12 +139,7 @@ impl MovementPlugin {
                                                 projection.scale -= settings.orthographic_scroll * ev.y;
                                             }
                                         }
-                                        if projection.scale < 0.001 {
-                                            projection.scale = 0.001;
-                                        }
-                                        if projection.scale > 0.1 {
-                                            projection.scale = 0.1;
-                                        }
+                                        projection.scale = projection.scale.clamp(ORTHO_SCALE_RANGE.0, ORTHO_SCALE_RANGE.1);
                                     }
                                 }
                                 Projection::Custom(_) => {}
@@ -91,6 +151,103 @@ impl MovementPlugin {
         }
     }
 
+    fn focus(
+        mut focus_events: MessageReader<FocusCamera>,
+        mut cameras: Query<(&mut Transform, &Projection), With<Multicam>>,
+    ) {
+        let Some(FocusCamera { target }) = focus_events.read().last() else { return; };
+        for (mut transform, projection) in &mut cameras {
+            let forward = *transform.forward();
+            match projection {
+                Projection::Perspective(_) => {
+                    transform.translation = *target - forward * FOCUS_DISTANCE;
+                }
+                // Orthographic views only slide across their plane, so their depth never changes.
+                _ => {
+                    let d = *target - transform.translation;
+                    transform.translation += d - forward * forward.dot(d);
+                }
+            }
+        }
+    }
+
+    /// F moves the hovered view to fit the selection: perspective views back off along their view
+    /// direction, orthographic ones centre on it and zoom to fit.
+    fn frame_selected(
+        keys: Res<ButtonInput<KeyCode>>,
+        mouse_input: Res<CurrentMouseInput>,
+        features: Res<FeatureTimeline>,
+        mut cameras: Query<(&mut Transform, &mut Projection, &Camera), With<Multicam>>,
+        mut egui_contexts: EguiContexts,
+    ) {
+        if let Ok(ctx) = egui_contexts.ctx_mut()
+            && ctx.wants_keyboard_input()
+        {
+            return;
+        }
+        if !keys.just_pressed(KeyCode::KeyF) {
+            return;
+        }
+        let Some(entity) = mouse_input.in_camera else { return; };
+        let Some((min, max)) = selection_extent(&features) else { return; };
+        let Ok((mut transform, mut projection, camera)) = cameras.get_mut(entity) else { return; };
+
+        let center = (min + max) / 2.0;
+        let forward = *transform.forward();
+        match projection.as_mut() {
+            Projection::Perspective(perspective) => {
+                let radius = (max - min).length() / 2.0;
+                transform.translation = center - forward * framing_distance(radius, perspective.fov);
+            }
+            Projection::Orthographic(ortho) => {
+                let d = center - transform.translation;
+                transform.translation += d - forward * forward.dot(d);
+                let size = max - min;
+                let extent = Vec2::new(
+                    size.dot(transform.right().abs()),
+                    size.dot(transform.up().abs()),
+                );
+                if let Some(viewport) = camera.logical_viewport_size() {
+                    ortho.scale = framing_scale(extent, viewport);
+                }
+            }
+            Projection::Custom(_) => {}
+        }
+    }
+
+    /// The free camera is the first perspective view on screen.
+    fn bookmarks(
+        mut bookmark_events: MessageReader<BookmarkCamera>,
+        mut jump_events: MessageReader<JumpCamera>,
+        mut map_metadata: ResMut<MapMetadata>,
+        mut cameras: Query<(&mut Transform, &Multicam, &Camera)>,
+    ) {
+        let free_camera = cameras.iter_mut()
+            .filter(|(_, multicam, camera)| camera.is_active && multicam.axis == CameraAxis::None)
+            .min_by_key(|(_, multicam, _)| multicam.id)
+            .map(|(transform, _, _)| transform);
+        let Some(mut transform) = free_camera else {
+            bookmark_events.clear();
+            jump_events.clear();
+            return;
+        };
+        for BookmarkCamera { name } in bookmark_events.read() {
+            map_metadata.camera_bookmarks.push(CameraBookmark::from_transform(name, &transform));
+        }
+        if let Some(JumpCamera { transform: target }) = jump_events.read().last() {
+            *transform = *target;
+        }
+    }
+
+    /// Turn around `pivot`, left and right about the vertical and up and down short of the poles.
+    fn orbit(transform: &mut Mut<Transform>, delta: Vec2, pivot: Vec3, movement_settings: &Res<MovementSettings>) {
+        let yaw = Quat::from_rotation_y(-delta.x * movement_settings.perspective_rotate);
+        let pitch = Quat::from_axis_angle(*transform.local_x(), -delta.y * movement_settings.perspective_rotate);
+        let pitched_forward = pitch * *transform.forward();
+        let rotation = if pitched_forward.y.abs() < 0.95 { yaw * pitch } else { yaw };
+        transform.rotate_around(pivot, rotation);
+    }
+
     fn perspective_move(transform: &mut Mut<Transform>, global_transform: &GlobalTransform, delta: Vec2, movement_settings: &Res<MovementSettings>, keyboard_input: &Res<CurrentKeyboardInput>) {
         if keyboard_input.modify {
             let pan_scaled_x = delta.x * movement_settings.perspective_pan;
@@ -157,27 +314,34 @@ impl MovementPlugin {
         }
         
         egui::Window::new(get!("debug.movement.title")).show(ctx, |ui| {
-            ui.heading(get!("debug.movement.perspective.title"));
-            ui.add(egui::Slider::new(&mut settings.perspective_pan, 0.0..=1.0).text(get!("debug.movement.perspective.pan")));
-            ui.add(egui::Slider::new(&mut settings.perspective_rotate, 0.0..=0.1).text(get!("debug.movement.perspective.rotate")));
-            ui.add(egui::Slider::new(&mut settings.perspective_speed, 0.0..=10.0).text(get!("debug.movement.perspective.speed")));
-            ui.add(egui::Slider::new(&mut settings.perspective_scroll, 0.0..=1.0).text(get!("debug.movement.perspective.scroll")));
-
-            ui.heading(get!("debug.movement.orthographic.title"));
-            ui.add(egui::Slider::new(&mut settings.orthographic_scroll, 0.0..=1.0).text(get!("debug.movement.orthographic.scroll")));
+            movement_settings_ui(ui, &mut settings);
         });
     }
 }
 
-#[derive(Resource)]
+/// Sliders for the camera speeds, shared by the movement debug window and the preferences.
+pub fn movement_settings_ui(ui: &mut egui::Ui, settings: &mut MovementSettings) {
+    ui.heading(get!("debug.movement.perspective.title"));
+    ui.add(egui::Slider::new(&mut settings.perspective_pan, 0.0..=1.0).text(get!("debug.movement.perspective.pan")));
+    ui.add(egui::Slider::new(&mut settings.perspective_rotate, 0.0..=0.1).text(get!("debug.movement.perspective.rotate")));
+    ui.add(egui::Slider::new(&mut settings.perspective_speed, 0.0..=10.0).text(get!("debug.movement.perspective.speed")));
+    ui.add(egui::Slider::new(&mut settings.perspective_scroll, 0.0..=1.0).text(get!("debug.movement.perspective.scroll")));
+
+    ui.heading(get!("debug.movement.orthographic.title"));
+    ui.add(egui::Slider::new(&mut settings.orthographic_speed, 0.0..=10.0).text(get!("debug.movement.orthographic.speed")));
+    ui.add(egui::Slider::new(&mut settings.orthographic_scroll, 0.0..=1.0).text(get!("debug.movement.orthographic.scroll")));
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
 pub struct MovementSettings {
-    debug_window: bool,
-    perspective_pan: f32,
-    perspective_rotate: f32,
-    perspective_speed: f32,
-    perspective_scroll: f32,
-    orthographic_speed: f32,
-    orthographic_scroll: f32,
+    pub debug_window: bool,
+    pub perspective_pan: f32,
+    pub perspective_rotate: f32,
+    pub perspective_speed: f32,
+    pub perspective_scroll: f32,
+    pub orthographic_speed: f32,
+    pub orthographic_scroll: f32,
 }
 
 impl Default for MovementSettings {
@@ -0,0 +1,375 @@
+use bevy::app::App;
+use bevy::prelude::*;
+use crate::common::rect_subtract::{subtract_rects, Rect2D};
+use crate::editor::editable::FeatureTimeline;
+use crate::editor::stairs::StairShape;
+use crate::tool::playtest::{box_covered, collision_boxes, PlayerPhysics, PLAYER_HALF_WIDTH, PLAYER_HEIGHT};
+use crate::tool::room::Room;
+use crate::tool::show::GizmoVisibility;
+
+const EPSILON: f32 = 1e-4;
+
+pub struct NavmeshPlugin;
+
+impl Plugin for NavmeshPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Navmesh>()
+            .add_message::<CalculateNavmesh>()
+            .add_message::<ClearNavmesh>()
+            .add_systems(Update, (
+                Self::bake,
+                Self::clear,
+                Self::draw_gizmos,
+            ).chain())
+        ;
+    }
+}
+
+#[derive(Message)]
+pub struct CalculateNavmesh;
+
+#[derive(Message)]
+pub struct ClearNavmesh;
+
+/// The player hull the navmesh is generated for.
+#[derive(Clone, Copy, Debug)]
+pub struct NavHull {
+    pub half_width: f32,
+    pub height: f32,
+    pub step_height: f32,
+}
+
+impl Default for NavHull {
+    fn default() -> Self {
+        Self {
+            half_width: PLAYER_HALF_WIDTH,
+            height: PLAYER_HEIGHT,
+            step_height: PlayerPhysics::default().step_height,
+        }
+    }
+}
+
+impl NavHull {
+    fn fits(&self, feet: Vec3, boxes: &[(Vec3, Vec3)]) -> bool {
+        let half = Vec3::new(self.half_width, 0.0, self.half_width);
+        box_covered(feet - half, feet + half + Vec3::Y * self.height, boxes)
+    }
+}
+
+/// A walkable floor rectangle; `min` and `max` are (x, z).
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavPolygon {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub height: f32,
+}
+
+impl NavPolygon {
+    pub fn center(&self) -> Vec3 {
+        let c = (self.min + self.max) * 0.5;
+        Vec3::new(c.x, self.height, c.y)
+    }
+}
+
+/// Two polygons a player can walk or step between. `portal` spans the region where they meet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavLink {
+    pub from: usize,
+    pub to: usize,
+    pub portal: [Vec3; 2],
+}
+
+#[derive(Resource, Default, Clone, Debug)]
+pub struct Navmesh {
+    pub polygons: Vec<NavPolygon>,
+    pub links: Vec<NavLink>,
+}
+
+impl Navmesh {
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+}
+
+/// Rooms as the timeline currently resolves them.
+pub fn rooms_from_timeline(timeline: &FeatureTimeline) -> Vec<Room> {
+    timeline.active_features()
+        .filter(|(_, f)| f.object().type_key() == "editor_room")
+        .filter_map(|(_, f)| Some(Room::new(f.get_point("min").ok()?, f.get_point("max").ok()?)))
+        .collect()
+}
+
+/// Stairs as the timeline currently resolves them.
+pub fn stairs_from_timeline(timeline: &FeatureTimeline) -> Vec<StairShape> {
+    timeline.active_features()
+        .filter_map(|(_, f)| StairShape::of(f))
+        .collect()
+}
+
+/// Derives walkable polygons from room floors, minus the openings other rooms carve into them
+/// and anywhere the hull would not fit, and from the tops of the stairs' steps. Polygons within a
+/// step of each other are linked.
+pub fn build_navmesh(rooms: &[Room], stairs: &[StairShape], hull: &NavHull) -> Navmesh {
+    let boxes = collision_boxes(rooms.iter(), stairs);
+    let mut polygons = Vec::new();
+
+    for (i, room) in rooms.iter().enumerate() {
+        let others: Vec<Room> = rooms.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, r)| r.clone())
+            .collect();
+        let (floor, mut holes) = room.floor_openings(&others);
+        let height = room.min.y.min(room.max.y);
+        holes.extend(blocked_cells(&floor, height, &boxes, hull));
+
+        for rect in subtract_rects(&floor, &holes) {
+            polygons.push(NavPolygon {
+                min: Vec2::new(rect.min_u, rect.min_v),
+                max: Vec2::new(rect.max_u, rect.max_v),
+                height,
+            });
+        }
+    }
+
+    // Treads are narrower than the hull is deep, so only their headroom is checked; the player
+    // crosses them one step at a time.
+    let column = NavHull { half_width: EPSILON, ..*hull };
+    for (min, max) in stairs.iter().flat_map(|shape| shape.solid_boxes()) {
+        let tread = NavPolygon {
+            min: Vec2::new(min.x, min.z),
+            max: Vec2::new(max.x, max.z),
+            height: max.y,
+        };
+        if column.fits(tread.center(), &boxes) {
+            polygons.push(tread);
+        }
+    }
+
+    let mut links = Vec::new();
+    for from in 0..polygons.len() {
+        for to in (from + 1)..polygons.len() {
+            if let Some(portal) = portal_between(&polygons[from], &polygons[to], hull) {
+                links.push(NavLink { from, to, portal });
+            }
+        }
+    }
+
+    Navmesh { polygons, links }
+}
+
+/// Cells of the floor where the hull, standing on it, would poke out of every room.
+/// Fitting only changes where a hull edge crosses a room boundary, so those lines split the floor.
+/// Only boxes the hull could overlap somewhere on this floor matter, which keeps the split lines and
+/// the fit checks local to the room.
+fn blocked_cells(floor: &Rect2D, height: f32, boxes: &[(Vec3, Vec3)], hull: &NavHull) -> Vec<Rect2D> {
+    let reach_min = Vec3::new(floor.min_u - hull.half_width, height, floor.min_v - hull.half_width);
+    let reach_max = Vec3::new(floor.max_u + hull.half_width, height + hull.height, floor.max_v + hull.half_width);
+    let boxes: Vec<(Vec3, Vec3)> = boxes.iter()
+        .filter(|(min, max)| min.cmple(reach_max).all() && max.cmpge(reach_min).all())
+        .copied()
+        .collect();
+    let splits = |lo: f32, hi: f32, axis: usize| {
+        let mut splits = vec![lo, hi];
+        for (min, max) in &boxes {
+            for bound in [min[axis], max[axis]] {
+                for v in [bound - hull.half_width, bound + hull.half_width] {
+                    if v > lo && v < hi {
+                        splits.push(v);
+                    }
+                }
+            }
+        }
+        splits.sort_by(f32::total_cmp);
+        splits.dedup();
+        splits
+    };
+    let us = splits(floor.min_u, floor.max_u, 0);
+    let vs = splits(floor.min_v, floor.max_v, 2);
+
+    let mut blocked = Vec::new();
+    for u in us.windows(2) {
+        for v in vs.windows(2) {
+            let feet = Vec3::new((u[0] + u[1]) * 0.5, height, (v[0] + v[1]) * 0.5);
+            if !hull.fits(feet, &boxes) {
+                blocked.push(Rect2D::new(u[0], v[0], u[1], v[1]));
+            }
+        }
+    }
+    blocked
+}
+
+/// Polygons on one level must share an edge; across a step the hull bridges up to its half width.
+fn portal_between(a: &NavPolygon, b: &NavPolygon, hull: &NavHull) -> Option<[Vec3; 2]> {
+    let rise = (a.height - b.height).abs();
+    if rise > hull.step_height + EPSILON {
+        return None;
+    }
+    let reach = if rise < EPSILON { EPSILON } else { hull.half_width + EPSILON };
+    let min = a.min.max(b.min - reach);
+    let max = a.max.min(b.max + reach);
+    if min.x > max.x || min.y > max.y || (max - min).max_element() <= EPSILON {
+        return None;
+    }
+    let height = a.height.max(b.height);
+    Some([Vec3::new(min.x, height, min.y), Vec3::new(max.x, height, max.y)])
+}
+
+impl NavmeshPlugin {
+    fn bake(
+        mut events: MessageReader<CalculateNavmesh>,
+        rooms: Query<&Room>,
+        stairs: Query<&StairShape>,
+        mut navmesh: ResMut<Navmesh>,
+    ) {
+        if events.read().next().is_none() { return; }
+        events.clear();
+
+        let rooms: Vec<Room> = rooms.iter().map(|r| Room::new(r.min, r.max)).collect();
+        let stairs: Vec<StairShape> = stairs.iter().cloned().collect();
+        *navmesh = build_navmesh(&rooms, &stairs, &NavHull::default());
+        info!("Baked navmesh: {} polygon(s), {} link(s)", navmesh.polygons.len(), navmesh.links.len());
+    }
+
+    fn clear(
+        mut events: MessageReader<ClearNavmesh>,
+        mut navmesh: ResMut<Navmesh>,
+    ) {
+        if events.read().next().is_none() { return; }
+        events.clear();
+
+        *navmesh = Navmesh::default();
+    }
+
+    fn draw_gizmos(navmesh: Res<Navmesh>, visibility: Res<GizmoVisibility>, mut gizmos: Gizmos) {
+        if !visibility.navmesh {
+            return;
+        }
+        let lift = Vec3::Y * 0.02;
+        let fill = Color::srgba(0.2, 0.9, 0.4, 0.8);
+        for polygon in &navmesh.polygons {
+            let y = polygon.height;
+            let corners = [
+                Vec3::new(polygon.min.x, y, polygon.min.y),
+                Vec3::new(polygon.max.x, y, polygon.min.y),
+                Vec3::new(polygon.max.x, y, polygon.max.y),
+                Vec3::new(polygon.min.x, y, polygon.max.y),
+            ];
+            for i in 0..4 {
+                gizmos.line(corners[i] + lift, corners[(i + 1) % 4] + lift, fill);
+            }
+            gizmos.line(corners[0] + lift, corners[2] + lift, fill.with_alpha(0.3));
+            gizmos.line(corners[1] + lift, corners[3] + lift, fill.with_alpha(0.3));
+        }
+
+        let link_color = Color::srgb(0.2, 0.8, 1.0);
+        for link in &navmesh.links {
+            let portal = (link.portal[0] + link.portal[1]) * 0.5 + lift;
+            gizmos.line(navmesh.polygons[link.from].center() + lift, portal, link_color);
+            gizmos.line(portal, navmesh.polygons[link.to].center() + lift, link_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polygons reachable over the links from any polygon matching `start`.
+    fn reachable(navmesh: &Navmesh, start: impl Fn(&NavPolygon) -> bool) -> Vec<usize> {
+        let mut reached: Vec<usize> = navmesh.polygons.iter().enumerate()
+            .filter(|(_, p)| start(p))
+            .map(|(i, _)| i)
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for link in &navmesh.links {
+                for (a, b) in [(link.from, link.to), (link.to, link.from)] {
+                    if reached.contains(&a) && !reached.contains(&b) {
+                        reached.push(b);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        reached
+    }
+
+    fn area(navmesh: &Navmesh) -> f32 {
+        navmesh.polygons.iter().map(|p| (p.max - p.min).element_product()).sum()
+    }
+
+    #[test]
+    fn test_single_room_keeps_clearance_from_walls() {
+        let navmesh = build_navmesh(&[Room::new(Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0))], &[], &NavHull::default());
+
+        let w = PLAYER_HALF_WIDTH;
+        assert!((area(&navmesh) - (4.0 - 2.0 * w).powi(2)).abs() < 1e-3);
+        for polygon in &navmesh.polygons {
+            assert!(polygon.min.cmpge(Vec2::splat(w - 1e-4)).all());
+            assert!(polygon.max.cmple(Vec2::splat(4.0 - w + 1e-4)).all());
+        }
+    }
+
+    #[test]
+    fn test_low_ceiling_is_not_walkable() {
+        let navmesh = build_navmesh(&[Room::new(Vec3::ZERO, Vec3::new(4.0, 1.0, 4.0))], &[], &NavHull::default());
+        assert!(navmesh.is_empty());
+    }
+
+    #[test]
+    fn test_doorway_connects_rooms() {
+        let rooms = [
+            Room::new(Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0)),
+            Room::new(Vec3::new(4.0, 0.0, 1.0), Vec3::new(5.0, 3.0, 3.0)),
+            Room::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(9.0, 3.0, 4.0)),
+        ];
+        let navmesh = build_navmesh(&rooms, &[], &NavHull::default());
+
+        // Walk the links from the first room and make sure the far room is reachable.
+        let in_room = |p: &NavPolygon, room: &Room| p.center().x > room.min.x && p.center().x < room.max.x;
+        let reached = reachable(&navmesh, |p| in_room(p, &rooms[0]));
+        assert!(reached.iter().any(|i| in_room(&navmesh.polygons[*i], &rooms[2])));
+    }
+
+    #[test]
+    fn test_stairs_connect_floors() {
+        let rooms = [
+            Room::new(Vec3::ZERO, Vec3::new(8.0, 4.0, 4.0)),
+            Room::new(Vec3::new(8.0, 2.0, 0.0), Vec3::new(12.0, 5.0, 4.0)),
+        ];
+        let stairs = [StairShape {
+            start: Vec3::new(2.0, 0.0, 2.0),
+            end: Vec3::new(8.0, 2.0, 2.0),
+            width: 2.0,
+            step_height: 0.25,
+            ramp: false,
+        }];
+        let upper = |p: &NavPolygon| p.center().x > 8.0 && (p.height - 2.0).abs() < 1e-3;
+
+        let without = build_navmesh(&rooms, &[], &NavHull::default());
+        let reached = reachable(&without, |p| p.height < 1e-3);
+        assert!(!reached.iter().any(|i| upper(&without.polygons[*i])));
+
+        let with = build_navmesh(&rooms, &stairs, &NavHull::default());
+        let reached = reachable(&with, |p| p.height < 1e-3);
+        assert!(reached.iter().any(|i| upper(&with.polygons[*i])));
+        // Nothing stands inside the steps.
+        assert!(!with.polygons.iter().any(|p| p.height < 1e-3 && p.max.x > 2.0 && p.min.x < 8.0 && p.max.y > 1.0 && p.min.y < 3.0));
+    }
+
+    #[test]
+    fn test_step_height_limits_links() {
+        let rooms = [
+            Room::new(Vec3::ZERO, Vec3::new(4.0, 3.0, 4.0)),
+            Room::new(Vec3::new(4.0, 0.3, 0.0), Vec3::new(8.0, 3.0, 4.0)),
+        ];
+        let linked = |step_height: f32| {
+            let navmesh = build_navmesh(&rooms, &[], &NavHull { step_height, ..NavHull::default() });
+            navmesh.links.iter().any(|l| (navmesh.polygons[l.from].height - navmesh.polygons[l.to].height).abs() > 0.1)
+        };
+        assert!(linked(0.45));
+        assert!(!linked(0.2));
+    }
+}
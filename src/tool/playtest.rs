@@ -0,0 +1,693 @@
+use bevy::app::App;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::gizmos::config::{DefaultGizmoConfigGroup, GizmoConfigStore};
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::render::view::Hdr;
+use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use crate::editor::action::FeatureData;
+use crate::editor::editable::{FeatureId, FeatureTimeline};
+use crate::editor::input::{CurrentKeyboardInput, CurrentMouseInput};
+use crate::editor::jump_pad::launch_velocity;
+use crate::editor::map_marker::MarkerKind;
+use crate::editor::map_metadata::MapMetadata;
+use crate::editor::multicam::{CameraAxis, Multicam};
+use crate::editor::stairs::StairShape;
+use crate::editor::teleporter::facing_direction;
+use crate::get;
+use crate::tool::room::Room;
+
+/// Half the width of the player's collision box.
+pub(crate) const PLAYER_HALF_WIDTH: f32 = 0.4;
+pub(crate) const PLAYER_HEIGHT: f32 = 1.8;
+const EYE_HEIGHT: f32 = 1.6;
+/// How far below the feet we look for a floor.
+const GROUND_PROBE: f32 = 0.05;
+/// Keeps a single frame's fall shorter than the player, so floors can't be skipped.
+const TERMINAL_VELOCITY: f32 = 30.0;
+/// Falling this far below the lowest room sends the player back to the spawn.
+const KILL_DEPTH: f32 = 50.0;
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 * 0.99;
+
+pub struct PlaytestPlugin;
+
+impl Plugin for PlaytestPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_state::<PlaytestState>()
+            .init_resource::<PlaytestSettings>()
+            .init_resource::<PlaytestRestore>()
+            .add_systems(Update, PlaytestPlugin::request_start.run_if(in_state(PlaytestState::Off)))
+            .add_systems(OnEnter(PlaytestState::Running), PlaytestPlugin::enter)
+            .add_systems(Update, (
+                PlaytestPlugin::look,
+                PlaytestPlugin::move_player,
+                PlaytestPlugin::exit_on_escape,
+            ).chain().run_if(in_state(PlaytestState::Running)))
+            .add_systems(OnExit(PlaytestState::Running), PlaytestPlugin::leave)
+            .add_systems(EguiPrimaryContextPass, PlaytestPlugin::hud.run_if(in_state(PlaytestState::Running)))
+        ;
+    }
+}
+
+/// Whether the editor is handed over to a first-person player.
+/// Editor input, tools and panels only run while this is `Off`.
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlaytestState {
+    #[default]
+    Off,
+    Running,
+}
+
+/// Quake-style movement tuning, in metres and seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerPhysics {
+    pub jump_speed: f32,
+    pub max_speed: f32,
+    pub ground_accel: f32,
+    pub air_accel: f32,
+    /// Speed along the wish direction beyond which air acceleration stops adding; strafing sidesteps it.
+    pub air_speed_cap: f32,
+    pub friction: f32,
+    pub stop_speed: f32,
+    pub step_height: f32,
+}
+
+impl Default for PlayerPhysics {
+    fn default() -> Self {
+        Self {
+            jump_speed: 7.0,
+            max_speed: 8.0,
+            ground_accel: 10.0,
+            air_accel: 10.0,
+            air_speed_cap: 0.9,
+            friction: 6.0,
+            stop_speed: 3.0,
+            step_height: 0.45,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PlaytestSettings {
+    /// Feature the player starts at; `None` starts at the free camera.
+    pub spawn: Option<FeatureId>,
+    pub start_requested: bool,
+    pub mouse_sensitivity: f32,
+    pub physics: PlayerPhysics,
+}
+
+impl Default for PlaytestSettings {
+    fn default() -> Self {
+        Self {
+            spawn: None,
+            start_requested: false,
+            mouse_sensitivity: 0.003,
+            physics: PlayerPhysics::default(),
+        }
+    }
+}
+
+/// Editor state changed on entering a playtest, put back on leaving.
+#[derive(Resource, Default)]
+struct PlaytestRestore {
+    cameras: Vec<(Entity, bool)>,
+    gizmos_enabled: bool,
+}
+
+/// A teleporter or jump pad, read from the timeline when the playtest starts.
+#[derive(Clone, Copy)]
+enum Mover {
+    Teleporter { min: Vec3, max: Vec3, destination: Vec3, yaw: f32 },
+    JumpPad { min: Vec3, max: Vec3, apex: Vec3 },
+}
+
+impl Mover {
+    fn touches(&self, feet: Vec3) -> bool {
+        let (Mover::Teleporter { min, max, .. } | Mover::JumpPad { min, max, .. }) = *self;
+        let half = Vec3::new(PLAYER_HALF_WIDTH, 0.0, PLAYER_HALF_WIDTH);
+        (feet - half).cmple(max).all() && (feet + half + Vec3::Y * PLAYER_HEIGHT).cmpge(min).all()
+    }
+}
+
+fn movers(timeline: &FeatureTimeline) -> Vec<Mover> {
+    timeline.active_features()
+        .filter_map(|(_, feature)| {
+            let (min, max) = feature.object().drag_handle_bounds()?;
+            match feature.object().snapshot() {
+                FeatureData::Teleporter { yaw, .. } => {
+                    Some(Mover::Teleporter { min, max, destination: feature.get_point("destination").ok()?, yaw })
+                }
+                FeatureData::JumpPad { .. } => Some(Mover::JumpPad { min, max, apex: feature.get_point("apex").ok()? }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[derive(Component)]
+pub struct PlaytestPlayer {
+    feet: Vec3,
+    velocity: Vec3,
+    yaw: f32,
+    pitch: f32,
+    grounded: bool,
+    spawn: Vec3,
+    spawn_inside: bool,
+    movers: Vec<Mover>,
+    /// Index of the mover the player touched last frame. Movers fire on being stepped onto, so a
+    /// pad the player lands back on doesn't relaunch them every frame they stand on it.
+    on_mover: Option<usize>,
+}
+
+impl PlaytestPlayer {
+    /// The mover `feet` have just stepped onto, if any.
+    fn mover_entered(&mut self, feet: Vec3) -> Option<Mover> {
+        let touching = self.movers.iter().position(|mover| mover.touches(feet));
+        let entered = touching.filter(|i| self.on_mover != Some(*i));
+        self.on_mover = touching;
+        entered.map(|i| self.movers[i])
+    }
+}
+
+impl PlaytestPlugin {
+    fn request_start(
+        mut settings: ResMut<PlaytestSettings>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut next_state: ResMut<NextState<PlaytestState>>,
+    ) {
+        if settings.start_requested || keys.just_pressed(KeyCode::F5) {
+            settings.start_requested = false;
+            next_state.set(PlaytestState::Running);
+        }
+    }
+
+    fn enter(
+        mut commands: Commands,
+        timeline: Res<FeatureTimeline>,
+        settings: Res<PlaytestSettings>,
+        (rooms, stairs): (Query<&Room>, Query<&StairShape>),
+        mut cameras: Query<(Entity, &mut Camera, &Multicam, &Transform)>,
+        mut cursor_options: Single<&mut CursorOptions, With<PrimaryWindow>>,
+        mut gizmo_store: ResMut<GizmoConfigStore>,
+        mut restore: ResMut<PlaytestRestore>,
+        mut mouse_input: ResMut<CurrentMouseInput>,
+        mut keyboard_input: ResMut<CurrentKeyboardInput>,
+    ) {
+        let free_camera = cameras.iter()
+            .find(|(_, _, multicam, _)| multicam.axis == CameraAxis::None)
+            .map(|(_, _, _, transform)| *transform)
+            .unwrap_or_default();
+        let spawn = settings.spawn
+            .and_then(|id| timeline.get_feature(&id))
+            .and_then(|feature| feature.get_point("").ok())
+            .unwrap_or(free_camera.translation - Vec3::Y * EYE_HEIGHT);
+        let (yaw, _, _) = free_camera.rotation.to_euler(EulerRot::YXZ);
+        let rooms = collision_boxes(rooms.iter(), stairs.iter());
+
+        restore.cameras.clear();
+        for (entity, mut camera, _, _) in &mut cameras {
+            restore.cameras.push((entity, camera.is_active));
+            camera.is_active = false;
+        }
+        let (gizmo_config, _) = gizmo_store.config_mut::<DefaultGizmoConfigGroup>();
+        restore.gizmos_enabled = gizmo_config.enabled;
+        gizmo_config.enabled = false;
+
+        // Stale editor input would otherwise keep driving tools and camera movement while frozen.
+        *mouse_input = CurrentMouseInput::default();
+        *keyboard_input = CurrentKeyboardInput::default();
+        cursor_options.grab_mode = CursorGrabMode::Locked;
+        cursor_options.visible = false;
+
+        commands.spawn((
+            Camera3d::default(),
+            Camera {
+                order: 16,
+                ..Default::default()
+            },
+            Hdr,
+            Tonemapping::TonyMcMapface,
+            Projection::Perspective(PerspectiveProjection {
+                fov: 90.0_f32.to_radians(),
+                ..Default::default()
+            }),
+            Transform::from_translation(spawn + Vec3::Y * EYE_HEIGHT)
+                .with_rotation(Quat::from_rotation_y(yaw)),
+            PlaytestPlayer {
+                feet: spawn,
+                velocity: Vec3::ZERO,
+                yaw,
+                pitch: 0.0,
+                grounded: false,
+                spawn,
+                spawn_inside: player_fits(spawn, &rooms),
+                movers: movers(&timeline),
+                on_mover: None,
+            },
+        ));
+    }
+
+    fn leave(
+        mut commands: Commands,
+        players: Query<Entity, With<PlaytestPlayer>>,
+        mut cameras: Query<&mut Camera, With<Multicam>>,
+        mut cursor_options: Single<&mut CursorOptions, With<PrimaryWindow>>,
+        mut gizmo_store: ResMut<GizmoConfigStore>,
+        restore: Res<PlaytestRestore>,
+    ) {
+        for entity in &players {
+            commands.entity(entity).despawn();
+        }
+        for (entity, was_active) in &restore.cameras {
+            if let Ok(mut camera) = cameras.get_mut(*entity) {
+                camera.is_active = *was_active;
+            }
+        }
+        gizmo_store.config_mut::<DefaultGizmoConfigGroup>().0.enabled = restore.gizmos_enabled;
+        cursor_options.grab_mode = CursorGrabMode::None;
+        cursor_options.visible = true;
+    }
+
+    fn look(
+        settings: Res<PlaytestSettings>,
+        mut evr_motion: MessageReader<MouseMotion>,
+        mut player: Single<&mut PlaytestPlayer>,
+    ) {
+        for ev in evr_motion.read() {
+            player.yaw -= ev.delta.x * settings.mouse_sensitivity;
+            player.pitch = (player.pitch - ev.delta.y * settings.mouse_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+    }
+
+    fn move_player(
+        time: Res<Time>,
+        keys: Res<ButtonInput<KeyCode>>,
+        settings: Res<PlaytestSettings>,
+        metadata: Res<MapMetadata>,
+        (rooms, stairs): (Query<&Room>, Query<&StairShape>),
+        player: Single<(&mut PlaytestPlayer, &mut Transform)>,
+    ) {
+        let (mut player, mut transform) = player.into_inner();
+        let physics = &settings.physics;
+        let dt = time.delta_secs().min(0.05);
+        let rooms = collision_boxes(rooms.iter(), stairs.iter());
+
+        let axis = |positive: KeyCode, negative: KeyCode| {
+            keys.pressed(positive) as i8 as f32 - keys.pressed(negative) as i8 as f32
+        };
+        let rotation = Quat::from_rotation_y(player.yaw);
+        let wish = (rotation * Vec3::NEG_Z) * axis(KeyCode::KeyW, KeyCode::KeyS)
+            + (rotation * Vec3::X) * axis(KeyCode::KeyD, KeyCode::KeyA);
+        let wish_dir = wish.normalize_or_zero();
+
+        let mut velocity = player.velocity;
+        if player.grounded && keys.pressed(KeyCode::Space) {
+            velocity.y = physics.jump_speed;
+            air_accelerate(&mut velocity, wish_dir, physics.max_speed, physics, dt);
+        } else if player.grounded {
+            apply_friction(&mut velocity, physics, dt);
+            accelerate(&mut velocity, wish_dir, physics.max_speed, physics.ground_accel, dt);
+        } else {
+            air_accelerate(&mut velocity, wish_dir, physics.max_speed, physics, dt);
+        }
+        velocity.y = (velocity.y - metadata.environment.gravity * dt).max(-TERMINAL_VELOCITY);
+
+        let step_height = if player.grounded { physics.step_height } else { 0.0 };
+        let mut feet = slide_move(player.feet, &mut velocity, dt, step_height, &rooms);
+
+        let floor = rooms.iter().map(|(min, _)| min.y).fold(player.spawn.y, f32::min);
+        if feet.y < floor - KILL_DEPTH {
+            feet = player.spawn;
+            velocity = Vec3::ZERO;
+        }
+
+        match player.mover_entered(feet) {
+            Some(Mover::Teleporter { destination, yaw, .. }) => {
+                feet = destination;
+                player.yaw = yaw.to_radians();
+                velocity = facing_direction(yaw) * velocity.with_y(0.0).length();
+            }
+            Some(Mover::JumpPad { apex, .. }) => {
+                velocity = launch_velocity(feet, apex, metadata.environment.gravity);
+            }
+            None => {}
+        }
+
+        player.grounded = velocity.y <= 0.0
+            && player_fits(feet, &rooms)
+            && !player_fits(feet - Vec3::Y * GROUND_PROBE, &rooms);
+        player.feet = feet;
+        player.velocity = velocity;
+
+        transform.translation = feet + Vec3::Y * EYE_HEIGHT;
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, player.yaw, player.pitch, 0.0);
+    }
+
+    fn exit_on_escape(
+        keys: Res<ButtonInput<KeyCode>>,
+        mut next_state: ResMut<NextState<PlaytestState>>,
+    ) {
+        if keys.just_pressed(KeyCode::Escape) {
+            next_state.set(PlaytestState::Off);
+        }
+    }
+
+    fn hud(
+        mut contexts: EguiContexts,
+        player: Single<&PlaytestPlayer>,
+    ) {
+        let ctx = contexts.ctx_mut();
+        if ctx.is_err() { warn!("{}", ctx.unwrap_err()); return; }
+        let ctx = ctx.unwrap();
+
+        egui::Area::new(egui::Id::new("playtest_hud"))
+            .anchor(egui::Align2::LEFT_TOP, [12.0, 12.0])
+            .show(ctx, |ui| {
+                ui.label(get!("playtest.hud.exit"));
+                let speed = format!("{:.1}", player.velocity.with_y(0.0).length());
+                ui.label(get!("playtest.hud.speed", "speed", speed));
+                if !player.spawn_inside {
+                    ui.colored_label(egui::Color32::YELLOW, get!("playtest.hud.outside"));
+                }
+            });
+    }
+}
+
+pub fn playtest_panel_ui(ui: &mut egui::Ui, settings: &mut PlaytestSettings, timeline: &FeatureTimeline) {
+    let spawn_choices: Vec<(FeatureId, String)> = timeline.active_features()
+        .filter(|(_, f)| f.object().type_key() == "global_point"
+            || matches!(f.object().snapshot(), FeatureData::Marker { kind: MarkerKind::Spawn, .. }))
+        .map(|(id, f)| (id, f.type_name_with_id()))
+        .collect();
+
+    if ui.button(get!("playtest.start")).clicked() {
+        settings.start_requested = true;
+    }
+
+    egui::Grid::new("playtest_grid")
+        .num_columns(2)
+        .spacing([10.0, 6.0])
+        .show(ui, |ui| {
+            ui.label(get!("playtest.spawn"));
+            let spawn_text = settings.spawn
+                .and_then(|id| spawn_choices.iter().find(|(c, _)| *c == id))
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| get!("playtest.free_camera"));
+            egui::ComboBox::from_id_salt("playtest_spawn")
+                .selected_text(spawn_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.spawn, None, get!("playtest.free_camera"));
+                    for (id, name) in &spawn_choices {
+                        ui.selectable_value(&mut settings.spawn, Some(*id), name);
+                    }
+                });
+            ui.end_row();
+        });
+
+    ui.collapsing(get!("playtest.movement.title"), |ui| {
+        let physics = &mut settings.physics;
+        ui.add(egui::Slider::new(&mut physics.jump_speed, 0.0..=20.0).text(get!("playtest.movement.jump_speed")));
+        ui.add(egui::Slider::new(&mut physics.max_speed, 0.0..=20.0).text(get!("playtest.movement.max_speed")));
+        ui.add(egui::Slider::new(&mut physics.ground_accel, 0.0..=20.0).text(get!("playtest.movement.ground_accel")));
+        ui.add(egui::Slider::new(&mut physics.air_accel, 0.0..=20.0).text(get!("playtest.movement.air_accel")));
+        ui.add(egui::Slider::new(&mut physics.friction, 0.0..=20.0).text(get!("playtest.movement.friction")));
+        ui.add(egui::Slider::new(&mut physics.step_height, 0.0..=1.0).text(get!("playtest.movement.step_height")));
+        ui.add(egui::Slider::new(&mut settings.mouse_sensitivity, 0.0..=0.01).text(get!("playtest.movement.sensitivity")));
+    });
+}
+
+/// Room volumes as ordered `(min, max)` corners, with the stairs' steps cut out of them.
+pub(crate) fn collision_boxes<'a>(rooms: impl Iterator<Item = &'a Room>, stairs: impl IntoIterator<Item = &'a StairShape>) -> Vec<(Vec3, Vec3)> {
+    let mut boxes: Vec<(Vec3, Vec3)> = rooms.map(|room| (room.min.min(room.max), room.min.max(room.max))).collect();
+    for solid in stairs.into_iter().flat_map(|shape| shape.solid_boxes()) {
+        boxes = boxes.into_iter().flat_map(|b| subtract_box(b, solid)).collect();
+    }
+    boxes
+}
+
+/// The parts of `(min, max)` outside `(cut_min, cut_max)`, as up to six boxes.
+fn subtract_box((min, max): (Vec3, Vec3), (cut_min, cut_max): (Vec3, Vec3)) -> Vec<(Vec3, Vec3)> {
+    if cut_min.cmpge(max).any() || cut_max.cmple(min).any() {
+        return vec![(min, max)];
+    }
+    let mut pieces = vec![];
+    let (mut lo, mut hi) = (min, max);
+    for axis in 0..3 {
+        if cut_min[axis] > lo[axis] {
+            let mut piece_hi = hi;
+            piece_hi[axis] = cut_min[axis];
+            pieces.push((lo, piece_hi));
+            lo[axis] = cut_min[axis];
+        }
+        if cut_max[axis] < hi[axis] {
+            let mut piece_lo = lo;
+            piece_lo[axis] = cut_max[axis];
+            pieces.push((piece_lo, hi));
+            hi[axis] = cut_max[axis];
+        }
+    }
+    pieces
+}
+
+/// True when every part of the box lies inside the union of the rooms.
+/// Splits the box at room boundaries and checks the centre of each cell.
+pub fn box_covered(min: Vec3, max: Vec3, rooms: &[(Vec3, Vec3)]) -> bool {
+    let overlapping: Vec<&(Vec3, Vec3)> = rooms.iter()
+        .filter(|(r_min, r_max)| r_min.cmple(max).all() && r_max.cmpge(min).all())
+        .collect();
+    if overlapping.is_empty() {
+        return false;
+    }
+
+    let cuts = |axis: usize| {
+        let mut cuts = vec![min[axis], max[axis]];
+        for (r_min, r_max) in &overlapping {
+            for v in [r_min[axis], r_max[axis]] {
+                if v > min[axis] && v < max[axis] {
+                    cuts.push(v);
+                }
+            }
+        }
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup();
+        cuts
+    };
+    let (xs, ys, zs) = (cuts(0), cuts(1), cuts(2));
+
+    for x in xs.windows(2) {
+        for y in ys.windows(2) {
+            for z in zs.windows(2) {
+                let centre = Vec3::new(x[0] + x[1], y[0] + y[1], z[0] + z[1]) * 0.5;
+                let inside = overlapping.iter()
+                    .any(|(r_min, r_max)| centre.cmpge(*r_min).all() && centre.cmple(*r_max).all());
+                if !inside {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+pub(crate) fn player_fits(feet: Vec3, rooms: &[(Vec3, Vec3)]) -> bool {
+    let half = Vec3::new(PLAYER_HALF_WIDTH, 0.0, PLAYER_HALF_WIDTH);
+    box_covered(feet - half, feet + half + Vec3::Y * PLAYER_HEIGHT, rooms)
+}
+
+/// Furthest point from `from` towards `to` where the player still fits, assuming it fits at `from`.
+fn sweep(from: Vec3, to: Vec3, rooms: &[(Vec3, Vec3)]) -> Vec3 {
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..12 {
+        let mid = (lo + hi) * 0.5;
+        if player_fits(from.lerp(to, mid), rooms) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    from.lerp(to, lo)
+}
+
+/// Moves the player one axis at a time, stopping against walls and zeroing velocity into them.
+/// Horizontal moves that hit a ledge no taller than `step_height` climb onto it.
+/// A player that starts outside every room moves freely so they can fall or walk back in.
+pub fn slide_move(feet: Vec3, velocity: &mut Vec3, dt: f32, step_height: f32, rooms: &[(Vec3, Vec3)]) -> Vec3 {
+    if !player_fits(feet, rooms) {
+        return feet + *velocity * dt;
+    }
+
+    let mut pos = feet;
+    for axis in [0, 2, 1] {
+        let delta = velocity[axis] * dt;
+        if delta == 0.0 {
+            continue;
+        }
+        let mut target = pos;
+        target[axis] += delta;
+        if player_fits(target, rooms) {
+            pos = target;
+            continue;
+        }
+
+        if axis != 1 && step_height > 0.0 {
+            let raised = pos + Vec3::Y * step_height;
+            let mut raised_target = raised;
+            raised_target[axis] += delta;
+            if player_fits(raised, rooms) && player_fits(raised_target, rooms) {
+                pos = sweep(raised_target, raised_target - Vec3::Y * step_height, rooms);
+                continue;
+            }
+        }
+
+        pos = sweep(pos, target, rooms);
+        velocity[axis] = 0.0;
+    }
+    pos
+}
+
+/// Slows horizontal movement on the ground; slow speeds stop as if moving at `stop_speed`.
+pub fn apply_friction(velocity: &mut Vec3, physics: &PlayerPhysics, dt: f32) {
+    let speed = velocity.with_y(0.0).length();
+    if speed < 1e-4 {
+        velocity.x = 0.0;
+        velocity.z = 0.0;
+        return;
+    }
+    let drop = speed.max(physics.stop_speed) * physics.friction * dt;
+    let scale = (speed - drop).max(0.0) / speed;
+    velocity.x *= scale;
+    velocity.z *= scale;
+}
+
+/// Adds speed along `wish_dir` until the projection of velocity onto it reaches `wish_speed`.
+pub fn accelerate(velocity: &mut Vec3, wish_dir: Vec3, wish_speed: f32, accel: f32, dt: f32) {
+    let add = wish_speed - velocity.dot(wish_dir);
+    if add <= 0.0 {
+        return;
+    }
+    *velocity += wish_dir * (accel * wish_speed * dt).min(add);
+}
+
+/// Air control: only the projected speed is capped, so turning while strafing gains speed.
+pub fn air_accelerate(velocity: &mut Vec3, wish_dir: Vec3, wish_speed: f32, physics: &PlayerPhysics, dt: f32) {
+    let add = wish_speed.min(physics.air_speed_cap) - velocity.dot(wish_dir);
+    if add <= 0.0 {
+        return;
+    }
+    *velocity += wish_dir * (physics.air_accel * wish_speed * dt).min(add);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(min: [f32; 3], max: [f32; 3]) -> (Vec3, Vec3) {
+        (Vec3::from(min), Vec3::from(max))
+    }
+
+    #[test]
+    fn test_box_covered_across_rooms() {
+        let rooms = vec![room([0.0, 0.0, 0.0], [4.0, 3.0, 4.0]), room([4.0, 0.0, 1.0], [8.0, 3.0, 3.0])];
+
+        assert!(box_covered(Vec3::new(1.0, 0.0, 1.0), Vec3::new(2.0, 2.0, 2.0), &rooms));
+        // Straddles the opening between the two rooms.
+        assert!(box_covered(Vec3::new(3.5, 0.0, 1.5), Vec3::new(4.5, 2.0, 2.5), &rooms));
+        // Clips the wall beside the opening.
+        assert!(!box_covered(Vec3::new(3.5, 0.0, 0.5), Vec3::new(4.5, 2.0, 1.5), &rooms));
+        assert!(!box_covered(Vec3::new(1.0, -0.1, 1.0), Vec3::new(2.0, 2.0, 2.0), &rooms));
+        assert!(!box_covered(Vec3::new(10.0, 0.0, 10.0), Vec3::new(11.0, 1.0, 11.0), &[]));
+    }
+
+    #[test]
+    fn test_slide_move_stops_at_wall_and_floor() {
+        let rooms = vec![room([0.0, 0.0, 0.0], [4.0, 3.0, 4.0])];
+        let mut velocity = Vec3::new(10.0, -10.0, 0.0);
+        let feet = slide_move(Vec3::new(3.0, 0.5, 2.0), &mut velocity, 0.1, 0.0, &rooms);
+
+        assert!((feet.x - (4.0 - PLAYER_HALF_WIDTH)).abs() < 0.01);
+        assert!(feet.y.abs() < 0.01);
+        assert_eq!(velocity.x, 0.0);
+        assert_eq!(velocity.y, 0.0);
+        assert!(player_fits(feet, &rooms));
+    }
+
+    #[test]
+    fn test_slide_move_climbs_steps() {
+        let rooms = vec![room([0.0, 0.0, 0.0], [4.0, 3.0, 4.0]), room([4.0, 0.3, 0.0], [8.0, 3.0, 4.0])];
+        let mut velocity = Vec3::new(5.0, 0.0, 0.0);
+        let feet = slide_move(Vec3::new(3.5, 0.0, 2.0), &mut velocity, 0.1, 0.45, &rooms);
+        assert!(feet.x > 3.5 && (feet.y - 0.3).abs() < 0.01);
+
+        let mut velocity = Vec3::new(5.0, 0.0, 0.0);
+        let feet = slide_move(Vec3::new(3.5, 0.0, 2.0), &mut velocity, 0.1, 0.2, &rooms);
+        assert!(feet.y.abs() < 0.01);
+        assert_eq!(velocity.x, 0.0);
+    }
+
+    #[test]
+    fn test_stairs_are_solid() {
+        let rooms = [Room::new(Vec3::ZERO, Vec3::new(8.0, 4.0, 4.0))];
+        let stairs = [StairShape { start: Vec3::new(2.0, 0.0, 2.0), end: Vec3::new(6.0, 2.0, 2.0), width: 2.0, step_height: 0.25, ramp: false }];
+        let boxes = collision_boxes(rooms.iter(), stairs.iter());
+        assert!(player_fits(Vec3::new(1.0, 0.0, 2.0), &boxes));
+        assert!(!player_fits(Vec3::new(5.0, 0.0, 2.0), &boxes));
+        // The hull spans two steps, so it stands on the higher one.
+        assert!(player_fits(Vec3::new(4.9, 1.75, 2.0), &boxes));
+        assert!(!player_fits(Vec3::new(4.9, 1.5, 2.0), &boxes));
+    }
+
+    #[test]
+    fn test_friction_and_ground_acceleration() {
+        let physics = PlayerPhysics::default();
+        let mut velocity = Vec3::ZERO;
+        for _ in 0..200 {
+            apply_friction(&mut velocity, &physics, 0.01);
+            accelerate(&mut velocity, Vec3::X, physics.max_speed, physics.ground_accel, 0.01);
+        }
+        assert!(velocity.x > 0.0 && velocity.x <= physics.max_speed);
+
+        for _ in 0..200 {
+            apply_friction(&mut velocity, &physics, 0.01);
+        }
+        assert_eq!(velocity, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_air_strafing_gains_speed() {
+        let physics = PlayerPhysics::default();
+        let mut velocity = Vec3::new(physics.max_speed, 0.0, 0.0);
+        for _ in 0..100 {
+            // Wish direction held perpendicular to the current heading, as when turning into a strafe.
+            let heading = velocity.normalize();
+            let wish_dir = Vec3::new(-heading.z, 0.0, heading.x);
+            air_accelerate(&mut velocity, wish_dir, physics.max_speed, &physics, 0.01);
+        }
+        assert!(velocity.length() > physics.max_speed);
+    }
+
+    #[test]
+    fn test_movers_fire_on_entering() {
+        let pad = Mover::JumpPad { min: Vec3::new(0.0, 0.0, 0.0), max: Vec3::new(2.0, 0.2, 2.0), apex: Vec3::new(1.0, 5.0, 1.0) };
+        let mut player = PlaytestPlayer {
+            feet: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            grounded: true,
+            spawn: Vec3::ZERO,
+            spawn_inside: true,
+            movers: vec![pad],
+            on_mover: None,
+        };
+        let on_pad = Vec3::new(1.0, 0.0, 1.0);
+        let off_pad = Vec3::new(5.0, 0.0, 1.0);
+
+        assert!(player.mover_entered(off_pad).is_none());
+        assert!(player.mover_entered(on_pad).is_some());
+        assert!(player.mover_entered(on_pad).is_none());
+        assert!(player.mover_entered(off_pad).is_none());
+        assert!(player.mover_entered(on_pad).is_some());
+    }
+}
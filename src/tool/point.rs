@@ -7,8 +7,7 @@ use crate::editor::multicam::Multicam;
 use crate::tool::room::Room;
 use crate::tool::tool_helpers::*;
 use crate::tool::Tools;
-
-const DEFAULT_SNAP_GRANULARITY: f32 = 0.1;
+use crate::tool::playtest::PlaytestState;
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum PointToolMode {
@@ -26,8 +25,6 @@ struct PointTool {
     reference_key: String,
     reference_resolved: Option<Vec3>,
     hovered_point: Option<(FeatureId, String, Vec3)>,
-    snap: bool,
-    snap_granularity: f32,
 }
 
 impl Default for PointTool {
@@ -40,8 +37,6 @@ impl Default for PointTool {
             reference_key: String::new(),
             reference_resolved: None,
             hovered_point: None,
-            snap: true,
-            snap_granularity: DEFAULT_SNAP_GRANULARITY,
         }
     }
 }
@@ -55,7 +50,7 @@ impl Plugin for PointPlugin {
             .add_systems(Update, (
                 PointTool::interface,
                 PointTool::draw_gizmos,
-            ).chain().run_if(in_state(Tools::Point)))
+            ).chain().run_if(in_state(Tools::Point)).run_if(in_state(PlaytestState::Off)))
             .add_systems(OnExit(Tools::Point), PointTool::on_exit)
         ;
     }
@@ -77,7 +72,7 @@ impl PointTool {
         mouse_input: Res<CurrentMouseInput>,
         keys: Res<ButtonInput<KeyCode>>,
         mut features: ResMut<FeatureTimeline>,
-        rooms: Query<&Room>,
+        (rooms, snap): (Query<&Room>, Res<SnapSettings>),
         mut next_tool: ResMut<NextState<Tools>>,
     ) {
         let shift_held = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
@@ -85,7 +80,7 @@ impl PointTool {
 
         tool.cursor = compute_cursor(
             &mouse_input, &cameras, tool.last_position,
-            tool.snap, tool.snap_granularity, &rooms,
+            snap.enabled, snap.granularity, &rooms,
         );
 
         match tool.mode {
@@ -3,8 +3,10 @@ use std::f32::consts::FRAC_PI_2;
 use bevy::prelude::*;
 use crate::editor::editable::{EditEvent, FeatureId, FeatureTimeline};
 use crate::editor::input::CurrentMouseInput;
+use crate::editor::render_mode::all_view_layers;
 use crate::tool::tool_helpers::closest_param_on_axis;
 use crate::tool::Tools;
+use crate::tool::playtest::PlaytestState;
 
 pub struct PointDragPlugin;
 
@@ -16,7 +18,7 @@ impl Plugin for PointDragPlugin {
                 PointDragState::spawn_arrows_system,
                 PointDragState::update_arrow_positions,
                 PointDragState::handle_arrow_drag,
-            ).chain().run_if(in_state(Tools::Select)))
+            ).chain().run_if(in_state(Tools::Select)).run_if(in_state(PlaytestState::Off)))
             .add_systems(OnExit(Tools::Select), PointDragState::despawn_arrows);
     }
 }
@@ -48,7 +50,10 @@ fn axis_rotation(axis: u8) -> Quat {
 }
 
 fn is_point_like(type_key: &str) -> bool {
-    type_key == "global_point" || type_key == "grackle_point_light"
+    matches!(
+        type_key,
+        "global_point" | "grackle_point_light" | "grackle_spot_light" | "grackle_sun" | "grackle_area_light" | "map_marker" | "logic_entity" | "pickup" | "prop"
+    )
 }
 
 impl PointDragState {
@@ -148,12 +153,14 @@ impl PointDragState {
             let shaft_entity = commands.spawn((
                 Mesh3d(shaft.clone()),
                 MeshMaterial3d(mat.clone()),
+                all_view_layers(),
                 Transform::from_translation(Vec3::new(0.0, 0.375, 0.0)),
             )).id();
 
             let head_entity = commands.spawn((
                 Mesh3d(head.clone()),
                 MeshMaterial3d(mat.clone()),
+                all_view_layers(),
                 Transform::from_translation(Vec3::new(0.0, 0.875, 0.0)),
             )).id();
 
@@ -1,14 +1,16 @@
 use bevy::app::App;
 use bevy::prelude::*;
-use crate::editor::editable::{FeatureId, FeatureTimeline, PointRef};
+use crate::editor::editable::{FeatureId, FeatureTimeline, FeatureTrait, PointRef};
+use crate::editor::grackle_area_light::GrackleAreaLight;
 use crate::editor::grackle_point_light::GracklePointLight;
+use crate::editor::grackle_spot_light::GrackleSpotLight;
+use crate::editor::grackle_sun::GrackleSun;
 use crate::editor::input::CurrentMouseInput;
 use crate::editor::multicam::Multicam;
 use crate::tool::room::Room;
 use crate::tool::tool_helpers::*;
 use crate::tool::Tools;
-
-const DEFAULT_SNAP_GRANULARITY: f32 = 0.1;
+use crate::tool::playtest::PlaytestState;
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum PointLightToolMode {
@@ -26,8 +28,6 @@ struct PointLightTool {
     reference_key: String,
     reference_resolved: Option<Vec3>,
     hovered_point: Option<(FeatureId, String, Vec3)>,
-    snap: bool,
-    snap_granularity: f32,
 }
 
 impl Default for PointLightTool {
@@ -40,8 +40,6 @@ impl Default for PointLightTool {
             reference_key: String::new(),
             reference_resolved: None,
             hovered_point: None,
-            snap: true,
-            snap_granularity: DEFAULT_SNAP_GRANULARITY,
         }
     }
 }
@@ -55,12 +53,31 @@ impl Plugin for PointLightPlugin {
             .add_systems(Update, (
                 PointLightTool::interface,
                 PointLightTool::draw_gizmos,
-            ).chain().run_if(in_state(Tools::PointLight)))
+            ).chain().run_if(
+                in_state(Tools::PointLight)
+                    .or(in_state(Tools::SpotLight))
+                    .or(in_state(Tools::Sun))
+                    .or(in_state(Tools::AreaLight))
+            ).run_if(in_state(PlaytestState::Off)))
             .add_systems(OnExit(Tools::PointLight), PointLightTool::on_exit)
+            .add_systems(OnExit(Tools::SpotLight), PointLightTool::on_exit)
+            .add_systems(OnExit(Tools::Sun), PointLightTool::on_exit)
+            .add_systems(OnExit(Tools::AreaLight), PointLightTool::on_exit)
         ;
     }
 }
 
+/// The light the current tool places. Aimed lights start out pointing straight down.
+fn light_for_tool(tool: &Tools, location: PointRef, cursor: Vec3) -> Box<dyn FeatureTrait> {
+    let below = PointRef::absolute(cursor.x, cursor.y - 1.0, cursor.z);
+    match tool {
+        Tools::SpotLight => Box::new(GrackleSpotLight::from_point_refs(location, below)),
+        Tools::Sun => Box::new(GrackleSun::from_point_ref(location)),
+        Tools::AreaLight => Box::new(GrackleAreaLight::from_point_refs(location, below)),
+        _ => Box::new(GracklePointLight::from_point_ref(location)),
+    }
+}
+
 impl PointLightTool {
     fn on_exit(mut tool: ResMut<Self>) {
         tool.mode = PointLightToolMode::Normal;
@@ -77,15 +94,29 @@ impl PointLightTool {
         mouse_input: Res<CurrentMouseInput>,
         keys: Res<ButtonInput<KeyCode>>,
         mut features: ResMut<FeatureTimeline>,
-        rooms: Query<&Room>,
+        (rooms, snap): (Query<&Room>, Res<SnapSettings>),
+        current_tool: Res<State<Tools>>,
         mut next_tool: ResMut<NextState<Tools>>,
     ) {
+        // The sun is map-wide, so the tool selects the existing one instead of adding another.
+        if *current_tool.get() == Tools::Sun {
+            let existing = features.active_features()
+                .find(|(_, f)| f.object().type_key() == "grackle_sun")
+                .map(|(id, _)| id);
+            if let Some(id) = existing {
+                info!("The map already has a sun; selecting it.");
+                features.select(Some(id));
+                next_tool.set(Tools::Select);
+                return;
+            }
+        }
+
         let shift_held = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
         let shift_just_pressed = keys.just_pressed(KeyCode::ShiftLeft) || keys.just_pressed(KeyCode::ShiftRight);
 
         tool.cursor = compute_cursor(
             &mouse_input, &cameras, tool.last_position,
-            tool.snap, tool.snap_granularity, &rooms,
+            snap.enabled, snap.granularity, &rooms,
         );
 
         match tool.mode {
@@ -95,8 +126,8 @@ impl PointLightTool {
                     tool.hovered_point = None;
                 } else if let Some(cursor) = tool.cursor {
                     if mouse_input.released == Some(MouseButton::Left) {
-                        let light = GracklePointLight::new(cursor.x, cursor.y, cursor.z);
-                        let id = features.apply_feature(Box::new(light));
+                        let location = PointRef::absolute(cursor.x, cursor.y, cursor.z);
+                        let id = features.apply_feature(light_for_tool(current_tool.get(), location, cursor));
                         features.select(Some(id));
                         tool.last_position = cursor;
                         next_tool.set(Tools::Select);
@@ -139,8 +170,7 @@ impl PointLightTool {
                             if !tool.reference_key.is_empty() {
                                 pr.point_key = tool.reference_key.clone();
                             }
-                            let light = GracklePointLight::from_point_ref(pr);
-                            let id = features.apply_feature(Box::new(light));
+                            let id = features.apply_feature(light_for_tool(current_tool.get(), pr, cursor));
                             features.select(Some(id));
                             tool.last_position = cursor;
                             next_tool.set(Tools::Select);
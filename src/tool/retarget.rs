@@ -3,6 +3,7 @@ use crate::editor::editable::{AxisRef, EditEvent, FeatureId, FeatureTimeline};
 use crate::editor::input::CurrentMouseInput;
 use crate::tool::tool_helpers::*;
 use crate::tool::Tools;
+use crate::tool::playtest::PlaytestState;
 
 pub struct RetargetPlugin;
 
@@ -13,7 +14,7 @@ impl Plugin for RetargetPlugin {
             .add_systems(Update, (
                 Self::interface,
                 Self::draw_gizmos,
-            ).chain().run_if(in_state(Tools::Retarget)))
+            ).chain().run_if(in_state(Tools::Retarget)).run_if(in_state(PlaytestState::Off)))
             .add_systems(OnExit(Tools::Retarget), Self::on_exit);
     }
 }
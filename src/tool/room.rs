@@ -4,11 +4,11 @@ use crate::editor::editable::{EditEvent, FeatureId, FeatureTimeline, PointRef};
 use crate::editor::editor_room::EditorRoom;
 use crate::editor::input::CurrentMouseInput;
 use crate::editor::multicam::Multicam;
+use crate::editor::render_mode::all_view_layers;
 use crate::get;
 use crate::tool::tool_helpers::*;
 use crate::tool::Tools;
-
-const DEFAULT_SNAP_GRANULARITY: f32 = 0.1;
+use crate::tool::playtest::PlaytestState;
 
 pub struct RoomPlugin;
 
@@ -21,13 +21,14 @@ impl Plugin for RoomPlugin {
                 RoomTool::interface,
                 RoomTool::draw_gizmos,
                 RoomTool::draw_room_bounds,
-            ).chain().run_if(in_state(Tools::Room)))
+            ).chain().run_if(in_state(Tools::Room)).run_if(in_state(PlaytestState::Off)))
             .add_systems(OnExit(Tools::Room), RoomTool::on_exit)
             .add_systems(Update, (
                 RoomDragState::spawn_handles_system,
                 RoomDragState::handle_dragging,
                 RoomDragState::update_handle_positions,
-            ).chain().run_if(in_state(Tools::Select)))
+                RoomDragState::draw_extrusion,
+            ).chain().run_if(in_state(Tools::Select)).run_if(in_state(PlaytestState::Off)))
             .add_systems(OnExit(Tools::Select), RoomDragState::despawn_handles)
         ;
     }
@@ -58,8 +59,6 @@ struct RoomTool {
     hovered_point: Option<(FeatureId, String, Vec3)>,
     min_point: Option<PointRef>,
     min_resolved: Option<Vec3>,
-    snap: bool,
-    snap_granularity: f32,
 }
 
 impl Default for RoomTool {
@@ -72,8 +71,6 @@ impl Default for RoomTool {
             hovered_point: None,
             min_point: None,
             min_resolved: None,
-            snap: true,
-            snap_granularity: DEFAULT_SNAP_GRANULARITY,
         }
     }
 }
@@ -100,7 +97,7 @@ impl RoomTool {
         mouse_input: Res<CurrentMouseInput>,
         keys: Res<ButtonInput<KeyCode>>,
         mut features: ResMut<FeatureTimeline>,
-        rooms: Query<&Room>,
+        (rooms, snap): (Query<&Room>, Res<SnapSettings>),
         mut next_tool: ResMut<NextState<Tools>>,
     ) {
         let shift_held = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
@@ -110,7 +107,7 @@ impl RoomTool {
 
         tool.cursor = compute_cursor(
             &mouse_input, &cameras, suggestion,
-            tool.snap, tool.snap_granularity, &rooms,
+            snap.enabled, snap.granularity, &rooms,
         );
 
         // ESC while placing max: discard min and reset to PlacingMin(Normal)
@@ -168,8 +165,8 @@ impl RoomTool {
                 tool.hovered_point = mouse_input.world_pos
                     .and_then(|ray| find_hovered_point(&ray, &features, PICK_RADIUS))
                     .map(|(fid, key, pos)| {
-                        let pos = if tool.snap {
-                            snap_vec3(pos, tool.snap_granularity)
+                        let pos = if snap.enabled {
+                            snap_vec3(pos, snap.granularity)
                         } else {
                             pos
                         };
@@ -323,6 +320,71 @@ impl HandleAxis {
             (Vec3::new(c.x, c.y, max.z), HandleAxis::MaxZ),
         ]
     }
+
+    /// Unit vector pointing out of the room through this face.
+    fn outward(&self) -> Vec3 {
+        let dir = match self.axis_index() {
+            0 => Vec3::X,
+            1 => Vec3::Y,
+            _ => Vec3::Z,
+        };
+        if self.is_max() { dir } else { -dir }
+    }
+}
+
+/// Room point key for the corner of a room's bounds that is at the max end of each axis set in `high`.
+fn corner_key(high: BVec3) -> &'static str {
+    match (high.x, high.y, high.z) {
+        (false, false, false) => "min",
+        (false, false, true) => "front_bottom_left_corner",
+        (false, true, false) => "front_top_right_corner",
+        (false, true, true) => "front_top_left_corner",
+        (true, false, false) => "back_bottom_right_corner",
+        (true, false, true) => "back_bottom_left_corner",
+        (true, true, false) => "back_top_right_corner",
+        (true, true, true) => "max",
+    }
+}
+
+/// Min and max of a new room `depth` deep against `face` of the room `parent`. Both are relative to
+/// corners of that face, so the new room stays attached when the parent moves or is resized.
+fn extruded_room_refs(parent: FeatureId, face: HandleAxis, depth: f32) -> (PointRef, PointRef) {
+    let on_face = BVec3::new(face.axis_index() == 0, face.axis_index() == 1, face.axis_index() == 2);
+    let (low_corner, high_corner) = if face.is_max() {
+        (on_face, BVec3::TRUE)
+    } else {
+        (BVec3::FALSE, !on_face)
+    };
+    let outward = face.outward() * depth;
+    let (min_offset, max_offset) = if face.is_max() { (Vec3::ZERO, outward) } else { (outward, Vec3::ZERO) };
+
+    let corner = |high: BVec3, offset: Vec3| {
+        let mut pr = PointRef::reference_with_offset(parent, offset.x, offset.y, offset.z);
+        pr.point_key = corner_key(high).to_owned();
+        pr
+    };
+    (corner(low_corner, min_offset), corner(high_corner, max_offset))
+}
+
+/// Bounds of the room [`extruded_room_refs`] describes, for a parent room spanning `min`..`max`.
+fn extruded_bounds(min: Vec3, max: Vec3, face: HandleAxis, depth: f32) -> (Vec3, Vec3) {
+    let outward = face.outward() * depth;
+    let i = face.axis_index() as usize;
+    if face.is_max() {
+        let mut face_min = min;
+        face_min[i] = max[i];
+        (face_min, max + outward)
+    } else {
+        let mut face_max = max;
+        face_max[i] = min[i];
+        (min + outward, face_max)
+    }
+}
+
+/// A new room being dragged out of a face of the tracked room.
+struct Extrusion {
+    face: HandleAxis,
+    depth: f32,
 }
 
 #[derive(Resource, Default)]
@@ -333,6 +395,8 @@ pub struct RoomDragState {
     tracked_feature: Option<FeatureId>,
     grabbed_handle: Option<HandleAxis>,
     grab_offset: Option<f32>,
+    /// Set while Alt-dragging a handle, which extrudes a new room instead of moving the face.
+    extrusion: Option<Extrusion>,
 }
 
 impl RoomDragState {
@@ -383,6 +447,7 @@ impl RoomDragState {
             state.tracked_feature = should_track;
             state.grabbed_handle = None;
             state.grab_offset = None;
+            state.extrusion = None;
 
             if let (Some(_feature_id), Some((min, max))) = (should_track, bounds) {
                 state.ensure_assets(&mut meshes, &mut materials);
@@ -395,6 +460,7 @@ impl RoomDragState {
                         Mesh3d(mesh.clone()),
                         MeshMaterial3d(mat.clone()),
                         Transform::from_translation(center),
+                        all_view_layers(),
                     ));
                 }
             }
@@ -424,7 +490,7 @@ impl RoomDragState {
     fn handle_dragging(
         handles: Query<(Entity, &RoomDragHandle)>,
         mut ray_cast: MeshRayCast,
-        mouse_input: Res<CurrentMouseInput>,
+        (mouse_input, keys, snap): (Res<CurrentMouseInput>, Res<ButtonInput<KeyCode>>, Res<SnapSettings>),
         mut commands: Commands,
         mut state: ResMut<Self>,
         mut features: ResMut<FeatureTimeline>,
@@ -441,7 +507,15 @@ impl RoomDragState {
         let mouse_just_pressed = mouse_input.just_pressed && mouse_input.pressed == Some(MouseButton::Left);
 
         if mouse_released || ray.is_none() {
-            if state.grabbed_handle.is_some() {
+            if let Some(extrusion) = state.extrusion.take() {
+                if extrusion.depth > snap.granularity / 2.0 {
+                    let (min, max) = extruded_room_refs(feature_id, extrusion.face, extrusion.depth);
+                    let id = features.apply_feature(Box::new(EditorRoom::from_point_refs(min, max)));
+                    features.select(Some(id));
+                }
+                state.grabbed_handle = None;
+                state.grab_offset = None;
+            } else if state.grabbed_handle.is_some() {
                 features.end_edit(feature_id);
                 state.grabbed_handle = None;
                 state.grab_offset = None;
@@ -483,8 +557,15 @@ impl RoomDragState {
                 }
             };
 
-            let g = DEFAULT_SNAP_GRANULARITY;
+            let g = snap.granularity;
             let raw = projected - offset;
+
+            if let Some(extrusion) = &mut state.extrusion {
+                let depth = (raw - face_center.dot(axis_dir)) * handle_axis.outward().dot(axis_dir);
+                extrusion.depth = ((depth / g).round() * g).max(0.0);
+                return;
+            }
+
             let new_value = match handle_axis {
                 HandleAxis::MinX | HandleAxis::MinY | HandleAxis::MinZ =>
                     f32::min((raw / g).ceil() * g, match axis_index { 0 => current_max.x, 1 => current_max.y, _ => current_max.z } - g),
@@ -528,7 +609,12 @@ impl RoomDragState {
                     }
 
                     if mouse_just_pressed {
-                        features.begin_edit(feature_id);
+                        let alt_held = keys.pressed(KeyCode::AltLeft) || keys.pressed(KeyCode::AltRight);
+                        if alt_held {
+                            state.extrusion = Some(Extrusion { face: handle.axis, depth: 0.0 });
+                        } else {
+                            features.begin_edit(feature_id);
+                        }
                         state.grabbed_handle = Some(handle.axis);
                         state.grab_offset = None;
                     }
@@ -539,6 +625,17 @@ impl RoomDragState {
         }
     }
 
+    fn draw_extrusion(
+        state: Res<Self>,
+        features: Res<FeatureTimeline>,
+        mut gizmos: Gizmos,
+    ) {
+        let (Some(feature_id), Some(extrusion)) = (state.tracked_feature, &state.extrusion) else { return; };
+        let Some((min, max)) = features.get_feature(&feature_id).and_then(|f| f.object().drag_handle_bounds()) else { return; };
+        let (new_min, new_max) = extruded_bounds(min, max, extrusion.face, extrusion.depth);
+        bounds_gizmo(&mut gizmos, new_min, new_max, Color::srgb_u8(40, 40, 200));
+    }
+
     fn restore_all_materials(
         handles: &Query<(Entity, &RoomDragHandle)>,
         idle: &Handle<StandardMaterial>,
@@ -562,6 +659,7 @@ impl RoomDragState {
         state.tracked_feature = None;
         state.grabbed_handle = None;
         state.grab_offset = None;
+        state.extrusion = None;
     }
 }
 
@@ -648,6 +746,13 @@ impl Room {
         mesh
     }
     
+    /// This room's floor in (x, z) and the openings other rooms carve into it, as `bake_faces` sees them.
+    pub fn floor_openings(&self, others: &[Room]) -> (crate::common::rect_subtract::Rect2D, Vec<crate::common::rect_subtract::Rect2D>) {
+        let [_, _, floor, _, _, _] = RoomFace::enumerate(self);
+        let holes = others.iter().filter_map(|other| floor.clip_rect(other)).collect();
+        (floor.rect, holes)
+    }
+
     pub fn messages(&self, my_entity: Entity) -> Vec<String> {
         let mut messages = Vec::new();
         if let Some(entity) = self.ghost {
@@ -905,6 +1010,31 @@ mod tests {
         assert!(!room.point_inside(Vec3::new(0.5, 0.5, -1.1)));
     }
 
+    #[test]
+    fn test_extruded_room_refs_attach_to_the_face() {
+        use crate::common::cuboid::CuboidPoint;
+
+        let parent = FeatureId::from_raw(7);
+        let (min, max) = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 3.0, 2.0));
+        let resolve = |pr: &PointRef| {
+            let base = match pr.point_key.as_str() {
+                "min" => min,
+                "max" => max,
+                key => CuboidPoint::try_from(key).ok().unwrap().resolve_in_bounds(min, max),
+            };
+            assert_eq!(pr.reference, Some(parent));
+            base + Vec3::new(pr.x.value(), pr.y.value(), pr.z.value())
+        };
+
+        for face in [HandleAxis::MinX, HandleAxis::MaxX, HandleAxis::MinY, HandleAxis::MaxY, HandleAxis::MinZ, HandleAxis::MaxZ] {
+            let (new_min, new_max) = extruded_room_refs(parent, face, 1.5);
+            assert!(new_min.x.is_relative() && new_max.z.is_relative());
+            assert_eq!((resolve(&new_min), resolve(&new_max)), extruded_bounds(min, max, face, 1.5));
+        }
+        assert_eq!(extruded_bounds(min, max, HandleAxis::MaxX, 1.5), (Vec3::new(4.0, 0.0, 0.0), Vec3::new(5.5, 3.0, 2.0)));
+        assert_eq!(extruded_bounds(min, max, HandleAxis::MinZ, 1.5), (Vec3::new(0.0, 0.0, -1.5), Vec3::new(4.0, 3.0, 0.0)));
+    }
+
     fn triangle_count(mesh: &Mesh) -> usize {
         match mesh.indices() {
             Some(bevy::mesh::Indices::U32(v)) => v.len() / 3,
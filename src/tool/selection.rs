@@ -2,10 +2,12 @@ use bevy::app::App;
 use bevy::prelude::*;
 use crate::editor::editable::{FeatureId, FeatureTimeline};
 use crate::editor::input::CurrentMouseInput;
+use crate::tool::playtest::PlaytestState;
 use crate::tool::point_drag::PointDragState;
 use crate::tool::room::RoomDragState;
 use crate::tool::show::GizmoVisibility;
 use crate::tool::tool_helpers::*;
+use crate::tool::transform_gizmo::TransformGizmoState;
 use crate::tool::Tools;
 
 pub struct SelectionPlugin;
@@ -15,8 +17,9 @@ impl Plugin for SelectionPlugin {
         app
             .init_resource::<SelectionState>()
             .add_systems(Update, (
-                Self::select.run_if(in_state(Tools::Select)),
+                Self::select.run_if(in_state(Tools::Select).or(in_state(Tools::Transform))).run_if(in_state(PlaytestState::Off)),
                 Self::draw_hover,
+                Self::draw_multi_selection,
             ).chain())
         ;
     }
@@ -26,10 +29,10 @@ impl SelectionPlugin {
     fn select(
         mut state: ResMut<SelectionState>,
         mouse_input: Res<CurrentMouseInput>,
+        keys: Res<ButtonInput<KeyCode>>,
         mut features: ResMut<FeatureTimeline>,
         visibility: Res<GizmoVisibility>,
-        point_drag: Res<PointDragState>,
-        room_drag: Res<RoomDragState>,
+        (point_drag, room_drag, transform_gizmo): (Res<PointDragState>, Res<RoomDragState>, Res<TransformGizmoState>),
     ) {
         state.hovered = None;
 
@@ -38,10 +41,14 @@ impl SelectionPlugin {
                 state.hovered = Some((feature_id, hit_pos));
             }
 
-            let any_drag = point_drag.is_dragging() || room_drag.is_dragging();
+            let any_drag = point_drag.is_dragging() || room_drag.is_dragging() || transform_gizmo.is_dragging();
             if mouse_input.released == Some(MouseButton::Left) && !any_drag {
                 let selection = state.hovered.map(|(id, _)| id);
-                features.select(selection);
+                let ctrl_held = keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight);
+                match selection {
+                    Some(id) if ctrl_held => features.toggle_selected(id),
+                    _ => features.select(selection),
+                }
             }
         }
     }
@@ -57,8 +64,8 @@ impl SelectionPlugin {
         let highlight = Color::srgb_u8(0, 230, 0);
 
         match feature.object().type_key() {
-            "editor_room" => {
-                if let Some((min, max)) = feature.object().drag_handle_bounds() {
+            "editor_room" | "trigger_volume" | "teleporter" | "jump_pad" | "prop" | "liquid_volume" | "stairs" => {
+                if let Some((min, max)) = feature.object().selection_bounds() {
                     bounds_gizmo(&mut gizmos, min, max, highlight);
                 }
             }
@@ -67,6 +74,22 @@ impl SelectionPlugin {
             }
         }
     }
+
+    /// Outline the features selected alongside the active one.
+    fn draw_multi_selection(
+        features: Res<FeatureTimeline>,
+        mut gizmos: Gizmos,
+    ) {
+        let color = Color::srgb_u8(255, 200, 0);
+        for id in features.selected_features().into_iter().skip(1) {
+            let Some(feature) = features.get_feature(&id) else { continue; };
+            if let Some((min, max)) = feature.object().selection_bounds() {
+                bounds_gizmo(&mut gizmos, min, max, color);
+            } else if let Ok(point) = feature.get_point("") {
+                gizmos.sphere(Isometry3d::from_translation(point), 0.2, color);
+            }
+        }
+    }
 }
 
 #[derive(Component)]
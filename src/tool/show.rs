@@ -2,13 +2,32 @@ use bevy::prelude::*;
 use crate::get;
 use bevy_egui::egui;
 use crate::editor::editable::FeatureTimeline;
-use crate::editor::multicam::MulticamState;
+use crate::editor::multicam::{CameraAxis, MulticamState, ViewportLayout};
+use crate::editor::render_mode::RenderMode;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
-#[derive(Resource)]
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
 pub struct GizmoVisibility {
     pub points: bool,
     pub rooms: bool,
-    pub point_lights: bool,
+    pub lights: bool,
+    pub markers: bool,
+    /// Trigger volumes, logic entities and the connection lines between features.
+    pub logic: bool,
+    /// Teleporters, jump pads and their flight arcs.
+    pub movers: bool,
+    /// Bounding boxes of props; the meshes themselves always render.
+    pub props: bool,
+    /// Outlines of liquid and hazard volumes, and warnings for ones outside the rooms.
+    pub volumes: bool,
+    /// Pinned dimension lines and their labels.
+    pub dimensions: bool,
+    /// Review notes, their pins and their text.
+    pub notes: bool,
+    /// The baked navmesh's polygons and the links between them.
+    pub navmesh: bool,
 }
 
 impl Default for GizmoVisibility {
@@ -16,7 +35,15 @@ impl Default for GizmoVisibility {
         Self {
             points: false,
             rooms: false,
-            point_lights: false,
+            lights: false,
+            markers: false,
+            logic: false,
+            movers: false,
+            props: false,
+            volumes: false,
+            dimensions: false,
+            notes: false,
+            navmesh: true,
         }
     }
 }
@@ -42,11 +69,51 @@ impl ShowPlugin {
         ui.checkbox(&mut multicam_state.draw_ortho_cameras, get!("show.ortho_cameras"));
         ui.checkbox(&mut multicam_state.draw_perspective_cameras, get!("show.perspective_cameras"));
 
+        ui.separator();
+        ui.heading(get!("show.viewports"));
+        egui::ComboBox::from_label(get!("show.layout"))
+            .selected_text(multicam_state.layout.name())
+            .show_ui(ui, |ui| {
+                for layout in ViewportLayout::iter() {
+                    ui.selectable_value(&mut multicam_state.layout, layout, layout.name());
+                }
+            });
+        let shown = multicam_state.layout.cells().len();
+        let MulticamState { views, render_modes, .. } = multicam_state;
+        for (id, (view, render_mode)) in views.iter_mut().zip(render_modes.iter_mut()).enumerate().take(shown) {
+            ui.horizontal(|ui| {
+                ui.label(get!("show.view", "number", id + 1));
+                egui::ComboBox::from_id_salt(("show_view_axis", id))
+                    .selected_text(view.name())
+                    .show_ui(ui, |ui| {
+                        for axis in CameraAxis::iter() {
+                            ui.selectable_value(view, axis, axis.name());
+                        }
+                    });
+                egui::ComboBox::from_id_salt(("show_view_render_mode", id))
+                    .selected_text(render_mode.name())
+                    .show_ui(ui, |ui| {
+                        for mode in RenderMode::iter() {
+                            ui.selectable_value(render_mode, mode, mode.name());
+                        }
+                    });
+            });
+        }
+        ui.label(get!("show.maximize_hint"));
+
         ui.separator();
         ui.heading(get!("show.gizmos_title"));
         ui.checkbox(&mut gizmo_visibility.points, get!("show.gizmos_points"));
         ui.checkbox(&mut gizmo_visibility.rooms, get!("show.gizmos_rooms"));
-        ui.checkbox(&mut gizmo_visibility.point_lights, get!("show.gizmos_point_lights"));
+        ui.checkbox(&mut gizmo_visibility.lights, get!("show.gizmos_point_lights"));
+        ui.checkbox(&mut gizmo_visibility.markers, get!("show.gizmos_markers"));
+        ui.checkbox(&mut gizmo_visibility.logic, get!("show.gizmos_logic"));
+        ui.checkbox(&mut gizmo_visibility.movers, get!("show.gizmos_movers"));
+        ui.checkbox(&mut gizmo_visibility.props, get!("show.gizmos_props"));
+        ui.checkbox(&mut gizmo_visibility.volumes, get!("show.gizmos_volumes"));
+        ui.checkbox(&mut gizmo_visibility.dimensions, get!("show.gizmos_dimensions"));
+        ui.checkbox(&mut gizmo_visibility.notes, get!("show.gizmos_notes"));
+        ui.checkbox(&mut gizmo_visibility.navmesh, get!("show.gizmos_navmesh"));
     }
 
     fn draw_visible_gizmos(
@@ -54,7 +121,7 @@ impl ShowPlugin {
         feature: Res<FeatureTimeline>,
         mut gizmos: Gizmos,
     ) {
-        if !visibility.points && !visibility.rooms && !visibility.point_lights {
+        if !visibility.points && !visibility.rooms && !visibility.lights && !visibility.markers && !visibility.logic && !visibility.movers && !visibility.props && !visibility.volumes && !visibility.dimensions && !visibility.notes {
             return;
         }
 
@@ -62,8 +129,15 @@ impl ShowPlugin {
             let key = feature.object().type_key();
             let draw = match key {
                 "global_point" => visibility.points,
-                "editor_room" => visibility.rooms,
-                "grackle_point_light" => visibility.point_lights,
+                "editor_room" | "stairs" => visibility.rooms,
+                "grackle_point_light" | "grackle_spot_light" | "grackle_sun" | "grackle_area_light" => visibility.lights,
+                "map_marker" | "pickup" => visibility.markers,
+                "trigger_volume" | "logic_entity" => visibility.logic,
+                "teleporter" | "jump_pad" => visibility.movers,
+                "prop" => visibility.props,
+                "liquid_volume" => visibility.volumes,
+                "dimension" => visibility.dimensions,
+                "note" => visibility.notes,
                 _ => false,
             };
             if draw {
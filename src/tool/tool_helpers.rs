@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
 use crate::editor::editable::{FeatureId, FeatureTimeline};
 use crate::editor::input::CurrentMouseInput;
 use crate::editor::multicam::{CameraAxis, Multicam};
@@ -6,6 +8,8 @@ use crate::tool::room::Room;
 use crate::tool::show::GizmoVisibility;
 
 pub const PICK_RADIUS: f32 = 0.1;
+/// The grid sizes the snap settings offer, in world units.
+pub const SNAP_GRANULARITY_RANGE: std::ops::RangeInclusive<f32> = 0.01..=16.0;
 const SELECT_POINT_RADIUS: f32 = 0.3;
 
 /// Given a camera ray and an infinite line through `axis_origin` in direction
@@ -28,6 +32,35 @@ pub fn closest_param_on_axis(ray: Ray3d, axis_origin: Vec3, axis_dir: Vec3) -> O
     Some((a * e - b * d) / denom)
 }
 
+/// Grid snapping for placing features: whether it is on and how fine the grid is. Shared by the
+/// room, point, light and marker tools; room drag handles always use the grid.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    pub granularity: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            granularity: 0.1,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// Keep the granularity within `SNAP_GRANULARITY_RANGE`, say after reading it from a file.
+    pub fn sanitize(&mut self) {
+        self.granularity = if self.granularity.is_finite() {
+            self.granularity.clamp(*SNAP_GRANULARITY_RANGE.start(), *SNAP_GRANULARITY_RANGE.end())
+        } else {
+            Self::default().granularity
+        };
+    }
+}
+
 /// Snap each axis to the nearest multiple of `granularity` (ties round half away from zero).
 pub fn snap_vec3(v: Vec3, granularity: f32) -> Vec3 {
     if granularity <= 0.0 {
@@ -150,6 +183,21 @@ fn cursor_from_room_faces(
     best_pos.map(|pos| if snap { snap_vec3(pos, snap_granularity) } else { pos })
 }
 
+/// Drop a cursor from [`compute_cursor`] onto the floor below it: the highest room floor at or
+/// under `point` whose footprint contains it. Points outside every room are left alone.
+pub fn snap_to_floor<'a>(point: Vec3, rooms: impl IntoIterator<Item = &'a Room>) -> Vec3 {
+    const EPSILON: f32 = 1e-3;
+    rooms.into_iter()
+        .filter(|room| {
+            point.x >= room.min.x - EPSILON && point.x <= room.max.x + EPSILON
+                && point.z >= room.min.z - EPSILON && point.z <= room.max.z + EPSILON
+                && room.min.y <= point.y + EPSILON
+        })
+        .map(|room| room.min.y)
+        .max_by(f32::total_cmp)
+        .map_or(point, |floor| point.with_y(floor))
+}
+
 /// Find the closest reference point to the mouse ray within `pick_radius`.
 pub fn find_hovered_point(
     ray: &Ray3d,
@@ -301,7 +349,7 @@ pub fn bounds_gizmo(gizmos: &mut Gizmos, min: Vec3, max: Vec3, color: Color) {
 }
 
 /// Find the nearest visible editor feature hit by a ray.
-/// For rooms, tests against all 6 AABB faces (visible ones only).
+/// For rooms and other volumes, tests against all 6 AABB faces (visible ones only).
 /// For points/lights, tests ray proximity within SELECT_POINT_RADIUS.
 /// Returns the feature ID and hit position of the closest hit across all types.
 pub fn find_nearest_feature_hit(
@@ -317,15 +365,22 @@ pub fn find_nearest_feature_hit(
         let key = feature.object().type_key();
         let visible = match key {
             "global_point" => visibility.points,
-            "editor_room" => visibility.rooms,
-            "grackle_point_light" => visibility.point_lights,
+            "editor_room" | "stairs" => visibility.rooms,
+            "grackle_point_light" | "grackle_spot_light" | "grackle_sun" | "grackle_area_light" => visibility.lights,
+            "map_marker" | "pickup" => visibility.markers,
+            "trigger_volume" | "logic_entity" => visibility.logic,
+            "teleporter" | "jump_pad" => visibility.movers,
+            "prop" => visibility.props,
+            "liquid_volume" => visibility.volumes,
+            "dimension" => visibility.dimensions,
+            "note" => visibility.notes,
             _ => false,
         };
         if !visible { continue; }
 
         match key {
-            "editor_room" => {
-                if let Some((min, max)) = feature.object().drag_handle_bounds() {
+            "editor_room" | "trigger_volume" | "teleporter" | "jump_pad" | "prop" | "liquid_volume" | "stairs" => {
+                if let Some((min, max)) = feature.object().selection_bounds() {
                     let faces: [(usize, f32, Vec3); 6] = [
                         (0, min.x, Vec3::X),
                         (0, max.x, Vec3::NEG_X),
@@ -359,7 +414,7 @@ pub fn find_nearest_feature_hit(
                     }
                 }
             }
-            "global_point" | "grackle_point_light" => {
+            "global_point" | "grackle_point_light" | "grackle_spot_light" | "grackle_sun" | "grackle_area_light" | "map_marker" | "logic_entity" | "pickup" | "note" => {
                 if let Ok(pos) = feature.object().get_point("") {
                     let dist = ray_point_distance(ray, pos);
                     if dist < SELECT_POINT_RADIUS {
@@ -377,3 +432,31 @@ pub fn find_nearest_feature_hit(
 
     best
 }
+
+/// Paint `labels` over every viewport that can see them, centred just above their world position.
+/// `scale` is the window's scale factor, which viewports are measured in.
+pub fn paint_viewport_labels<'a>(
+    ctx: &egui::Context,
+    layer: &str,
+    cameras: impl Iterator<Item = (&'a Camera, &'a GlobalTransform)>,
+    scale: f32,
+    labels: &[(Vec3, String, egui::Color32)],
+) {
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new(layer)));
+    for (camera, camera_transform) in cameras {
+        if !camera.is_active {
+            continue;
+        }
+        let Some(viewport) = &camera.viewport else { continue; };
+        let origin = viewport.physical_position.as_vec2() / scale;
+        let size = viewport.physical_size.as_vec2() / scale;
+        for (position, text, color) in labels {
+            let Ok(local) = camera.world_to_viewport(camera_transform, *position) else { continue; };
+            if local.cmplt(Vec2::ZERO).any() || local.cmpgt(size).any() {
+                continue;
+            }
+            let at = origin + local;
+            painter.text(egui::pos2(at.x, at.y), egui::Align2::CENTER_BOTTOM, text, egui::FontId::proportional(13.0), *color);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use crate::editor::editable::{EditEvent, FeatureId, FeatureTimeline};
+use crate::editor::align::{align_panel_ui, AlignState};
+use crate::editor::input::CurrentMouseInput;
+use crate::editor::room_ops::{room_ops_panel_ui, RoomOpsState};
+use crate::editor::transform::{CapturedSelection, SelectionTransform};
+use crate::get;
+use crate::tool::tool_helpers::{closest_param_on_axis, ray_point_distance, snap_vec3, SNAP_GRANULARITY_RANGE};
+use crate::tool::Tools;
+use crate::tool::playtest::PlaytestState;
+
+const DEFAULT_SNAP_GRANULARITY: f32 = 0.5;
+
+const AXIS_LENGTH: f32 = 1.5;
+const AXIS_PICK_RADIUS: f32 = 0.12;
+/// Plane handles are squares spanning this range along both of their axes.
+const PLANE_HANDLE: (f32, f32) = (0.35, 0.75);
+/// Radius of the horizontal ring that scales the selection about its center.
+const SCALE_RING: f32 = 2.0;
+const SCALE_PICK_RADIUS: f32 = 0.15;
+/// Snapped scale factors are multiples of this.
+const SCALE_SNAP: f32 = 0.25;
+const MIN_SCALE: f32 = 0.05;
+
+const AXIS_DIRS: [Vec3; 3] = [Vec3::X, Vec3::Y, Vec3::Z];
+
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TransformGizmoState>()
+            .add_systems(Update, (
+                TransformGizmoState::handle_drag,
+                TransformGizmoState::draw_gizmos,
+            ).chain().run_if(in_state(Tools::Transform)).run_if(in_state(PlaytestState::Off)))
+            .add_systems(OnExit(Tools::Transform), TransformGizmoState::on_exit)
+        ;
+    }
+}
+
+/// Part of the gizmo under the cursor or being dragged.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum GizmoHandle {
+    /// Move along one axis.
+    Axis(u8),
+    /// Move in the plane whose normal is this axis.
+    Plane(u8),
+    /// Scale about the center by dragging the ring in or out.
+    Scale,
+}
+
+struct GizmoGrab {
+    handle: GizmoHandle,
+    origin: Vec3,
+    /// Where the cursor first met the handle: the axis parameter, or the point on the plane.
+    start: Vec3,
+    transform: SelectionTransform,
+    selection: CapturedSelection,
+    root: FeatureId,
+}
+
+/// Moves the selected feature and its dependants together. Dragging an arrow or a plane square
+/// translates them and dragging the ring scales them; rotating and mirroring are one-click
+/// operations from the Tools panel.
+#[derive(Resource)]
+pub struct TransformGizmoState {
+    pub snap: bool,
+    pub snap_granularity: f32,
+    hovered: Option<GizmoHandle>,
+    grab: Option<GizmoGrab>,
+    /// Set for the frame a drag ends, so the release does not also change the selection.
+    just_released: bool,
+    pub room_ops: RoomOpsState,
+    pub align: AlignState,
+}
+
+impl Default for TransformGizmoState {
+    fn default() -> Self {
+        Self {
+            snap: true,
+            snap_granularity: DEFAULT_SNAP_GRANULARITY,
+            hovered: None,
+            grab: None,
+            just_released: false,
+            room_ops: RoomOpsState::default(),
+            align: AlignState::default(),
+        }
+    }
+}
+
+/// The two axes spanning the plane whose normal is `normal`.
+fn plane_axes(normal: u8) -> (Vec3, Vec3) {
+    match normal {
+        0 => (Vec3::Y, Vec3::Z),
+        1 => (Vec3::X, Vec3::Z),
+        _ => (Vec3::X, Vec3::Y),
+    }
+}
+
+fn ray_plane_hit(ray: Ray3d, origin: Vec3, normal: Vec3) -> Option<Vec3> {
+    let t = ray.intersect_plane(origin, InfinitePlane3d::new(normal))?;
+    Some(ray.get_point(t))
+}
+
+fn axis_color(axis: u8, highlighted: bool) -> Color {
+    let v = if highlighted { 0.6 } else { 0.0 };
+    match axis {
+        0 => Color::srgb(1.0, v, v),
+        1 => Color::srgb(v, 1.0, v),
+        _ => Color::srgb(v, v, 1.0),
+    }
+}
+
+/// The selection the gizmo acts on: the active feature, and every selected feature with
+/// everything that depends on them.
+fn selection_ids(features: &FeatureTimeline) -> Option<(FeatureId, Vec<FeatureId>)> {
+    let root = features.selected_feature()?;
+    let ids = features.selected_and_affected();
+    Some((root, ids))
+}
+
+fn selection_center(features: &FeatureTimeline) -> Option<Vec3> {
+    let (_, ids) = selection_ids(features)?;
+    CapturedSelection::capture(features, &ids).center()
+}
+
+impl TransformGizmoState {
+    pub fn is_dragging(&self) -> bool {
+        self.grab.is_some() || self.just_released
+    }
+
+    fn on_exit(mut state: ResMut<Self>, mut features: ResMut<FeatureTimeline>) {
+        if let Some(grab) = state.grab.take() {
+            grab.selection.finish(&mut features);
+        }
+        state.hovered = None;
+        state.just_released = false;
+    }
+
+    fn pick_handle(ray: Ray3d, origin: Vec3) -> Option<GizmoHandle> {
+        if let Some(hit) = ray_plane_hit(ray, origin, Vec3::Y)
+            && (hit.distance(origin) - SCALE_RING).abs() < SCALE_PICK_RADIUS
+        {
+            return Some(GizmoHandle::Scale);
+        }
+        for normal in 0u8..3 {
+            let Some(hit) = ray_plane_hit(ray, origin, AXIS_DIRS[normal as usize]) else { continue; };
+            let (a, b) = plane_axes(normal);
+            let (u, v) = ((hit - origin).dot(a), (hit - origin).dot(b));
+            let inside = |t: f32| (PLANE_HANDLE.0..=PLANE_HANDLE.1).contains(&t);
+            if inside(u) && inside(v) {
+                return Some(GizmoHandle::Plane(normal));
+            }
+        }
+        (0u8..3)
+            .filter_map(|axis| {
+                let dir = AXIS_DIRS[axis as usize];
+                let s = closest_param_on_axis(ray, origin, dir)?;
+                if !(0.0..=AXIS_LENGTH).contains(&s) {
+                    return None;
+                }
+                let distance = ray_point_distance(&ray, origin + dir * s);
+                (distance < AXIS_PICK_RADIUS).then_some((axis, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(axis, _)| GizmoHandle::Axis(axis))
+    }
+
+    /// Where the cursor is on the grabbed handle, in the same terms as `GizmoGrab::start`.
+    fn handle_position(handle: GizmoHandle, ray: Ray3d, origin: Vec3) -> Option<Vec3> {
+        match handle {
+            GizmoHandle::Axis(axis) => {
+                let dir = AXIS_DIRS[axis as usize];
+                closest_param_on_axis(ray, origin, dir).map(|s| dir * s)
+            }
+            GizmoHandle::Plane(normal) => ray_plane_hit(ray, origin, AXIS_DIRS[normal as usize]),
+            GizmoHandle::Scale => ray_plane_hit(ray, origin, Vec3::Y),
+        }
+    }
+
+    fn handle_drag(
+        mouse_input: Res<CurrentMouseInput>,
+        mut state: ResMut<Self>,
+        mut features: ResMut<FeatureTimeline>,
+        mut edit_events: MessageWriter<EditEvent>,
+    ) {
+        state.just_released = false;
+        let mouse_released = mouse_input.released == Some(MouseButton::Left);
+        let mouse_just_pressed = mouse_input.just_pressed && mouse_input.pressed == Some(MouseButton::Left);
+
+        if (mouse_released || mouse_input.world_pos.is_none())
+            && let Some(grab) = state.grab.take()
+        {
+            grab.selection.finish(&mut features);
+            state.just_released = true;
+        }
+
+        let Some(ray) = mouse_input.world_pos else {
+            state.hovered = None;
+            return;
+        };
+
+        let state = &mut *state;
+        if let Some(grab) = &mut state.grab {
+            let Some(position) = Self::handle_position(grab.handle, ray, grab.origin) else { return; };
+            let transform = if grab.handle == GizmoHandle::Scale {
+                let mut factor = position.distance(grab.origin) / grab.start.distance(grab.origin).max(f32::EPSILON);
+                if state.snap {
+                    factor = (factor / SCALE_SNAP).round() * SCALE_SNAP;
+                }
+                SelectionTransform::Scale { pivot: grab.origin, factor: factor.max(MIN_SCALE) }
+            } else {
+                let mut delta = position - grab.start;
+                if let GizmoHandle::Plane(normal) = grab.handle {
+                    let n = AXIS_DIRS[normal as usize];
+                    delta -= n * delta.dot(n);
+                }
+                if state.snap {
+                    delta = snap_vec3(delta, state.snap_granularity);
+                }
+                SelectionTransform::Translate(delta)
+            };
+            if transform == grab.transform {
+                return;
+            }
+            grab.transform = transform;
+            grab.selection.apply(&mut features, &transform);
+            if let Some(entity) = features.get_feature(&grab.root).and_then(|f| f.object().entity()) {
+                edit_events.write(EditEvent { editor_id: grab.root._id(), feature_id: grab.root, entity });
+            }
+            return;
+        }
+
+        let Some(origin) = selection_center(&features) else {
+            state.hovered = None;
+            return;
+        };
+        state.hovered = Self::pick_handle(ray, origin);
+
+        if mouse_just_pressed
+            && let Some(handle) = state.hovered
+            && let Some((root, ids)) = selection_ids(&features)
+            && let Some(start) = Self::handle_position(handle, ray, origin)
+        {
+            state.grab = Some(GizmoGrab {
+                handle,
+                origin,
+                start,
+                transform: SelectionTransform::Translate(Vec3::ZERO),
+                selection: CapturedSelection::capture(&features, &ids),
+                root,
+            });
+        }
+    }
+
+    fn draw_gizmos(
+        state: Res<Self>,
+        features: Res<FeatureTimeline>,
+        mut gizmos: Gizmos,
+    ) {
+        let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+        let (origin, active) = match &state.grab {
+            Some(grab) => match grab.transform {
+                SelectionTransform::Translate(delta) => {
+                    gizmos.line(grab.origin, grab.origin + delta, Color::WHITE);
+                    (grab.origin + delta, Some(grab.handle))
+                }
+                SelectionTransform::Scale { factor, .. } => {
+                    gizmos.circle(Isometry3d::new(grab.origin, flat), SCALE_RING * factor, Color::WHITE);
+                    (grab.origin, Some(grab.handle))
+                }
+                _ => (grab.origin, Some(grab.handle)),
+            },
+            None => match selection_center(&features) {
+                Some(center) => (center, state.hovered),
+                None => return,
+            },
+        };
+
+        for axis in 0u8..3 {
+            let highlighted = active == Some(GizmoHandle::Axis(axis));
+            gizmos.arrow(origin, origin + AXIS_DIRS[axis as usize] * AXIS_LENGTH, axis_color(axis, highlighted));
+
+            let highlighted = active == Some(GizmoHandle::Plane(axis));
+            let (a, b) = plane_axes(axis);
+            let (near, far) = PLANE_HANDLE;
+            gizmos.linestrip([
+                origin + a * near + b * near,
+                origin + a * far + b * near,
+                origin + a * far + b * far,
+                origin + a * near + b * far,
+                origin + a * near + b * near,
+            ], axis_color(axis, highlighted));
+        }
+
+        let ring_color = if active == Some(GizmoHandle::Scale) { Color::srgb(1.0, 1.0, 0.6) } else { Color::srgb(0.9, 0.8, 0.2) };
+        gizmos.circle(Isometry3d::new(origin, flat), SCALE_RING, ring_color);
+    }
+}
+
+/// Snapping settings and the one-click rotate / mirror operations, shown under the tool list.
+pub fn transform_panel_ui(ui: &mut egui::Ui, state: &mut TransformGizmoState, features: &mut FeatureTimeline, edit_events: &mut Vec<EditEvent>) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut state.snap, get!("tools.transform.snap"));
+        ui.add_enabled(state.snap, egui::DragValue::new(&mut state.snap_granularity).range(SNAP_GRANULARITY_RANGE).speed(0.05));
+    });
+
+    let selection = selection_ids(features).filter(|_| state.grab.is_none());
+    let mut requested: Option<fn(Vec3) -> SelectionTransform> = None;
+    ui.add_enabled_ui(selection.is_some(), |ui| {
+        ui.horizontal(|ui| {
+            if ui.button(get!("tools.transform.rotate_left")).clicked() {
+                requested = Some(|pivot| SelectionTransform::RotateY { pivot, quarter_turns: 1 });
+            }
+            if ui.button(get!("tools.transform.rotate_right")).clicked() {
+                requested = Some(|pivot| SelectionTransform::RotateY { pivot, quarter_turns: -1 });
+            }
+            if ui.button(get!("tools.transform.mirror_x")).clicked() {
+                requested = Some(|pivot| SelectionTransform::Mirror { pivot, flip_x: true });
+            }
+            if ui.button(get!("tools.transform.mirror_z")).clicked() {
+                requested = Some(|pivot| SelectionTransform::Mirror { pivot, flip_x: false });
+            }
+        });
+    });
+
+    if state.grab.is_none() {
+        ui.separator();
+        room_ops_panel_ui(ui, &mut state.room_ops, features);
+        ui.separator();
+        align_panel_ui(ui, &mut state.align, features);
+    }
+
+    let (Some(make_transform), Some((root, ids))) = (requested, selection) else { return; };
+    let captured = CapturedSelection::capture(features, &ids);
+    let Some(mut pivot) = captured.center() else { return; };
+    // A pivot on the grid keeps grid-aligned features on the grid after a quarter turn.
+    if state.snap {
+        pivot = snap_vec3(pivot, state.snap_granularity);
+    }
+    captured.apply(features, &make_transform(pivot));
+    captured.finish(features);
+    if let Some(entity) = features.get_feature(&root).and_then(|f| f.object().entity()) {
+        edit_events.push(EditEvent { editor_id: root._id(), feature_id: root, entity });
+    }
+}